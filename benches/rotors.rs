@@ -0,0 +1,55 @@
+//! Compares growing a level of branches in batches with growing it a node at a time.
+//!
+//! Run with `cargo bench --bench rotors`.
+
+use fractal_clock::simd::{self, Complex};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+type Grow<T> = fn(Complex<T>, &[[Complex<T>; 2]], &mut Vec<[Complex<T>; 2]>);
+
+/// Parents of a level that fits in the cache, and of the 20th level, as many as the default
+/// line budget allows, where memory is the bottleneck, with the rounds each is timed over.
+const LEVELS: [(usize, u32); 2] = [(1 << 12, 20_000), (1 << 20, 50)];
+
+fn parents<T: simd::Real>(nodes: usize) -> Vec<[Complex<T>; 2]> {
+    (0..nodes)
+        .map(|i| {
+            let angle = i as f64 * 0.001;
+            let dir = Complex::from_polar(0.7, angle);
+            [Complex::from_polar(1.0 + i as f64 * 1e-6, angle * 0.5), dir]
+        })
+        .collect()
+}
+
+fn time<T: simd::Real>(grow: Grow<T>, parents: &[[Complex<T>; 2]], rounds: u32) -> Duration {
+    let rotor = Complex::from_polar(0.71, 2.4);
+    let mut children = Vec::with_capacity(parents.len());
+    grow(rotor, parents, &mut children);
+    let start = Instant::now();
+    for _ in 0..rounds {
+        grow(black_box(rotor), black_box(parents), &mut children);
+        black_box(&children);
+    }
+    start.elapsed() / rounds
+}
+
+fn compare<T: simd::Real>(precision: &str) {
+    for (nodes, rounds) in LEVELS {
+        let parents = parents::<T>(nodes);
+        let scalar = time::<T>(simd::grow_scalar, &parents, rounds);
+        let batched = time::<T>(simd::grow, &parents, rounds);
+        println!(
+            "{precision}, {nodes} nodes: scalar {scalar:.2?}, {} batches {batched:.2?}, {:.2}x",
+            simd::instruction_set(),
+            scalar.as_secs_f64() / batched.as_secs_f64()
+        );
+    }
+}
+
+fn main() {
+    compare::<f32>("f32");
+    compare::<f64>("f64");
+}
@@ -0,0 +1,26 @@
+use std::process::Command;
+
+fn main() {
+    // Short commit hash shown in the About window, "unknown" when not building from a git checkout
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    println!("cargo:rustc-env=GIT_HASH={hash}");
+
+    // Enabled Cargo features shown in the About window, whichever are declared in Cargo.toml
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(name, _)| {
+            let feature = name.strip_prefix("CARGO_FEATURE_")?.to_lowercase();
+            (feature != "default").then(|| feature.replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=ENABLED_FEATURES={}", features.join(", "));
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
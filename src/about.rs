@@ -0,0 +1,104 @@
+use crate::toasts;
+use egui::{CollapsingHeader, Grid, RichText, ScrollArea};
+use std::sync::OnceLock;
+
+static RENDERER: OnceLock<String> = OnceLock::new();
+
+/// Records the graphics backend for the About window.
+pub fn set_renderer(cc: &eframe::CreationContext<'_>) {
+    use eframe::glow::{self, HasContext as _};
+
+    let renderer = match &cc.gl {
+        Some(gl) => unsafe {
+            format!(
+                "OpenGL {} ({})",
+                gl.get_parameter_string(glow::VERSION),
+                gl.get_parameter_string(glow::RENDERER)
+            )
+        },
+        None => "unknown".to_owned(),
+    };
+    tracing::info!("Rendering with {renderer}");
+    let _ = RENDERER.set(renderer);
+}
+
+/// Replaces a password, key or private URL before settings are shown or copied.
+pub fn redact(secret: &mut String) {
+    if !secret.is_empty() {
+        *secret = "<redacted>".to_owned();
+    }
+}
+
+/// Window with version and system details, which can be copied into bug reports.
+#[derive(Default, PartialEq)]
+pub struct AboutWindow {
+    pub open: bool,
+}
+
+impl AboutWindow {
+    /// `config` is the current configuration, included in the copied diagnostics.
+    pub fn show(&mut self, ctx: &egui::Context, config: &str) {
+        let mut open = self.open;
+        egui::Window::new("About Fractal Clock")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                Grid::new("about_grid").num_columns(2).show(ui, |ui| {
+                    for (name, value) in info() {
+                        ui.label(name);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+
+                CollapsingHeader::new("Configuration").show(ui, |ui| {
+                    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        ui.label(RichText::new(config).monospace());
+                    });
+                });
+
+                if ui.button("📋 Copy diagnostics").clicked() {
+                    ctx.copy_text(diagnostics(config));
+                    toasts::success("Copied diagnostics to the clipboard");
+                }
+            });
+        self.open = open;
+    }
+}
+
+fn info() -> [(&'static str, String); 5] {
+    [
+        ("Version", env!("CARGO_PKG_VERSION").to_owned()),
+        ("Commit", env!("GIT_HASH").to_owned()),
+        (
+            "Renderer",
+            RENDERER
+                .get()
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_owned()),
+        ),
+        (
+            "OS",
+            format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        ),
+        ("Features", features()),
+    ]
+}
+
+fn features() -> String {
+    let features = env!("ENABLED_FEATURES");
+    if features.is_empty() {
+        "none".to_owned()
+    } else {
+        features.to_owned()
+    }
+}
+
+fn diagnostics(config: &str) -> String {
+    let mut text = String::from("Fractal Clock diagnostics\n");
+    for (name, value) in info() {
+        text += &format!("{name}: {value}\n");
+    }
+    text += &format!("\nConfiguration:\n```toml\n{config}```\n");
+    text
+}
@@ -0,0 +1,234 @@
+use chrono::{DateTime, Local, NaiveTime, TimeDelta, Timelike as _};
+#[cfg(feature = "notifications")]
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+/// What the user chose for a ringing alarm, in the settings or its notification.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Response {
+    Snooze,
+    Dismiss,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct Alarm {
+    pub enabled: bool,
+    pub time: NaiveTime,
+    pub label: String,
+}
+
+// Alarm and countdown settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct AlarmsConfig {
+    pub alarms: Vec<Alarm>,
+    pub countdown_minutes: u32,
+    pub snooze_minutes: u32,
+    /// Sends a desktop notification when the window is minimized or a widget.
+    pub notify: bool,
+}
+
+impl Default for AlarmsConfig {
+    fn default() -> Self {
+        Self {
+            alarms: Vec::new(),
+            countdown_minutes: 5,
+            snooze_minutes: 9,
+            notify: true,
+        }
+    }
+}
+
+impl AlarmsConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, scheduler: &mut Scheduler, now: DateTime<Local>) {
+        let mut responses = Vec::new();
+        for ringing in &scheduler.ringing {
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("⏰ {}", ringing.label));
+                if ui.button("Snooze").clicked() {
+                    responses.push((ringing.id, Response::Snooze));
+                }
+                if ui.button("Dismiss").clicked() {
+                    responses.push((ringing.id, Response::Dismiss));
+                }
+            });
+        }
+        for (id, response) in responses {
+            scheduler.respond(id, response, now, self.snooze_minutes);
+        }
+
+        let mut removed = None;
+        for (index, alarm) in self.alarms.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut alarm.enabled, "");
+                let (mut hour, mut minute) = (alarm.time.hour(), alarm.time.minute());
+                let changed = ui
+                    .add(egui::DragValue::new(&mut hour).range(0..=23))
+                    .changed()
+                    | ui.add(egui::DragValue::new(&mut minute).range(0..=59))
+                        .changed();
+                if changed && let Some(time) = NaiveTime::from_hms_opt(hour, minute, 0) {
+                    alarm.time = time;
+                }
+                ui.text_edit_singleline(&mut alarm.label);
+                if ui.small_button("✖").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.alarms.remove(index);
+        }
+        if ui.button("＋ Add alarm").clicked() {
+            self.alarms.push(Alarm {
+                enabled: true,
+                time: now.time().with_second(0).unwrap_or_default() + TimeDelta::hours(1),
+                label: "Alarm".to_owned(),
+            });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.countdown_minutes)
+                    .range(1..=24 * 60)
+                    .suffix(" min"),
+            );
+            match scheduler.countdown {
+                Some(end) => {
+                    let left = (end - now).num_seconds().max(0);
+                    ui.monospace(format!("{}:{:02}", left / 60, left % 60));
+                    if ui.button("Cancel").clicked() {
+                        scheduler.countdown = None;
+                    }
+                }
+                None => {
+                    if ui.button("▶ Start countdown").clicked() {
+                        scheduler.countdown =
+                            Some(now + TimeDelta::minutes(self.countdown_minutes.into()));
+                    }
+                }
+            }
+        });
+        ui.add(
+            egui::Slider::new(&mut self.snooze_minutes, 1..=60)
+                .suffix(" min")
+                .text("snooze"),
+        );
+        #[cfg(feature = "notifications")]
+        ui.checkbox(&mut self.notify, "Desktop notifications")
+            .on_hover_text("When the window is minimized or a widget");
+    }
+}
+
+/// A response to the notification of the ringing alarm with the id.
+#[cfg(feature = "notifications")]
+pub type Reply = (u64, Response);
+
+/// An alarm that went off and waits for a response.
+pub struct Ringing {
+    id: u64,
+    pub label: String,
+}
+
+/// Fires the alarms, countdown and snoozed alarms as their times pass.
+#[derive(Default)]
+pub struct Scheduler {
+    last_check: Option<DateTime<Local>>,
+    countdown: Option<DateTime<Local>>,
+    snoozed: Vec<(DateTime<Local>, String)>,
+    pub ringing: Vec<Ringing>,
+    next_id: u64,
+    /// Responses from the notifications, which wait on their own threads.
+    #[cfg(feature = "notifications")]
+    responses: Option<(Sender<Reply>, Receiver<Reply>)>,
+}
+
+impl PartialEq for Scheduler {
+    fn eq(&self, other: &Self) -> bool {
+        self.countdown == other.countdown && self.snoozed == other.snoozed
+    }
+}
+
+impl Scheduler {
+    /// Fires what came due since the last check, returning the labels of the alarms that went
+    /// off. With `notify`, they are also sent as desktop notifications.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        config: &AlarmsConfig,
+        now: DateTime<Local>,
+        notify: bool,
+    ) -> Vec<String> {
+        #[cfg(feature = "notifications")]
+        if let Some((_, receiver)) = &self.responses {
+            let responses: Vec<_> = receiver.try_iter().collect();
+            for (id, response) in responses {
+                self.respond(id, response, now, config.snooze_minutes);
+            }
+        }
+
+        let mut fired = Vec::new();
+        let last = self.last_check.replace(now);
+        // Clock changes and suspends would fire a whole day of alarms at once
+        if let Some(last) = last.filter(|&last| last <= now && now - last <= TimeDelta::minutes(1))
+        {
+            let passed = |time: DateTime<Local>| last < time && time <= now;
+            fired.extend(
+                config
+                    .alarms
+                    .iter()
+                    .filter(|alarm| alarm.enabled)
+                    .filter(|alarm| {
+                        // Both days when the check spans midnight
+                        [now, last]
+                            .iter()
+                            .any(|day| day.with_time(alarm.time).earliest().is_some_and(passed))
+                    })
+                    .map(|alarm| alarm.label.clone()),
+            );
+        }
+        // Timers still go off late after a suspend
+        if self.countdown.is_some_and(|end| end <= now) {
+            self.countdown = None;
+            fired.push(format!("Countdown of {} min", config.countdown_minutes));
+        }
+        self.snoozed.retain(|(time, label)| {
+            let due = *time <= now;
+            if due {
+                fired.push(label.clone());
+            }
+            !due
+        });
+
+        for label in &fired {
+            let id = self.next_id;
+            self.next_id += 1;
+            #[cfg(feature = "notifications")]
+            if notify && config.notify {
+                let (sender, _) = self.responses.get_or_insert_with(channel);
+                crate::notifications::alarm(ctx, label, id, sender.clone());
+            }
+            #[cfg(not(feature = "notifications"))]
+            let _ = (ctx, notify);
+            self.ringing.push(Ringing {
+                id,
+                label: label.clone(),
+            });
+        }
+        if self.countdown.is_some() || !self.snoozed.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+        fired
+    }
+
+    pub fn respond(&mut self, id: u64, response: Response, now: DateTime<Local>, snooze: u32) {
+        let Some(index) = self.ringing.iter().position(|ringing| ringing.id == id) else {
+            return;
+        };
+        let ringing = self.ringing.remove(index);
+        if response == Response::Snooze {
+            self.snoozed
+                .push((now + TimeDelta::minutes(snooze.into()), ringing.label));
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+thread_local! {
+    // Initialized in place, so counting never allocates itself
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Whether the global allocator counts allocations, which only the app's own binary sets up.
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+/// Wraps the global allocator to count the allocations of every thread, which the stats show
+/// for the paint of a frame.
+pub struct CountingAllocator<A>(pub A);
+
+impl<A> CountingAllocator<A> {
+    fn count(&self) {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        if !COUNTING.load(Ordering::Relaxed) {
+            COUNTING.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.count();
+        unsafe { self.0.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.count();
+        unsafe { self.0.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.count();
+        unsafe { self.0.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.0.dealloc(ptr, layout) }
+    }
+}
+
+/// Allocations and reallocations of the current thread so far, or `None` if the global
+/// allocator does not count them, e.g. when the clock is embedded as a widget.
+pub fn count() -> Option<u64> {
+    COUNTING
+        .load(Ordering::Relaxed)
+        .then(|| ALLOCATIONS.with(Cell::get))
+}
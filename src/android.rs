@@ -0,0 +1,34 @@
+use crate::{WrapApp, cli::Args, logging, monitors::MonitorLayout, storage};
+use tracing::level_filters::LevelFilter;
+use winit::platform::android::activity::AndroidApp;
+
+/// Entry point of the native activity.
+#[unsafe(no_mangle)]
+fn android_main(app: AndroidApp) {
+    // Without platform directories, everything is kept in the app's internal storage
+    let data_dir = app.internal_data_path();
+    if let Some(dir) = &data_dir {
+        storage::set_app_dir(dir.clone());
+    }
+    logging::init(LevelFilter::INFO);
+
+    let args = Args {
+        // eframe has no storage on Android, so the config file is written back instead
+        config: data_dir.map(|dir| dir.join("fractal_clock.toml")),
+        start_fullscreen: true,
+        ..Default::default()
+    };
+    let options = eframe::NativeOptions {
+        android_app: Some(app),
+        ..Default::default()
+    };
+
+    let result = eframe::run_native(
+        "Fractal Clock",
+        options,
+        Box::new(move |cc| Ok(Box::new(WrapApp::new(cc, &args, MonitorLayout::default())))),
+    );
+    if let Err(e) = result {
+        tracing::error!("Failed to run the app: {e}");
+    }
+}
@@ -0,0 +1,140 @@
+use egui::{Color32, Shape};
+use std::{fmt::Write as _, path::PathBuf};
+
+/// Characters are about twice as high as wide, so a cell covers two units of height.
+const CELL_HEIGHT: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Destination {
+    Clipboard,
+    File,
+}
+
+// ASCII art export settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct AsciiArtConfig {
+    pub columns: usize,
+    /// Colors the characters with ANSI escape codes.
+    pub ansi: bool,
+}
+
+impl Default for AsciiArtConfig {
+    fn default() -> Self {
+        Self {
+            columns: 80,
+            ansi: true,
+        }
+    }
+}
+
+impl AsciiArtConfig {
+    /// Size of the square drawn into, where each character is a unit wide.
+    pub fn size(&self) -> f32 {
+        self.columns as f32
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<Destination> {
+        ui.add(egui::Slider::new(&mut self.columns, 20..=300).text("columns"));
+        ui.checkbox(&mut self.ansi, "ANSI colors");
+
+        let mut destination = None;
+        ui.horizontal(|ui| {
+            if ui.button("📋 Copy").clicked() {
+                destination = Some(Destination::Clipboard);
+            }
+            if ui.button("💾 Save").clicked() {
+                destination = Some(Destination::File);
+            }
+        });
+        destination
+    }
+}
+
+/// Rasterizes line segments in a square of `columns` units into text, picking the characters by
+/// the direction of the lines.
+pub fn render(shapes: impl Iterator<Item = Shape>, columns: usize, ansi: bool) -> String {
+    let rows = (columns as f32 / CELL_HEIGHT).ceil() as usize;
+    let mut cells: Vec<Option<(char, Color32)>> = vec![None; columns * rows];
+
+    for shape in shapes {
+        let Shape::LineSegment {
+            points: [from, to],
+            stroke,
+        } = shape
+        else {
+            continue;
+        };
+        let (from, to) = (
+            egui::vec2(from.x, from.y / CELL_HEIGHT),
+            egui::vec2(to.x, to.y / CELL_HEIGHT),
+        );
+        let delta = to - from;
+        let character = line_char(delta);
+        // Two samples per cell, so no cell along the line is skipped
+        let steps = (delta.abs().max_elem() * 2.0).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let pos = from + delta * (step as f32 / steps as f32);
+            if pos.x < 0.0 || pos.y < 0.0 {
+                continue;
+            }
+            let (column, row) = (pos.x as usize, pos.y as usize);
+            if column < columns && row < rows {
+                cells[row * columns + column] = Some((character, stroke.color));
+            }
+        }
+    }
+
+    let mut text = String::with_capacity(cells.len() * if ansi { 20 } else { 1 } + rows);
+    for row in cells.chunks(columns) {
+        let mut current_color = None;
+        // Trailing spaces are dropped
+        let end = row
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |end| end + 1);
+        for cell in &row[..end] {
+            match cell {
+                Some((character, color)) => {
+                    if ansi && current_color != Some(*color) {
+                        let [r, g, b, _] = color.to_srgba_unmultiplied();
+                        let _ = write!(text, "\x1b[38;2;{r};{g};{b}m");
+                        current_color = Some(*color);
+                    }
+                    text.push(*character);
+                }
+                None => text.push(' '),
+            }
+        }
+        if ansi && current_color.is_some() {
+            text.push_str("\x1b[0m");
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Character drawing a line going along `delta`, where y points down.
+fn line_char(delta: egui::Vec2) -> char {
+    // Fold into half a turn, as a line looks the same in both directions
+    let angle = (-delta.y).atan2(delta.x).rem_euclid(std::f32::consts::PI);
+    let eighths = (angle / std::f32::consts::FRAC_PI_8).round() as usize;
+    match eighths {
+        0 | 8 => '-',
+        1..=3 => '/',
+        4 => '|',
+        _ => '\\',
+    }
+}
+
+/// Saves the text in the downloads directory, returning its path.
+pub fn save(text: &str, ansi: bool) -> std::io::Result<PathBuf> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| std::io::Error::other("no downloads or home directory"))?;
+    let extension = if ansi { "ans" } else { "txt" };
+    let name = chrono::Local::now().format("fractal_clock_%Y-%m-%d_%H-%M-%S");
+    let path = dir.join(format!("{name}.{extension}"));
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
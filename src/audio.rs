@@ -0,0 +1,190 @@
+use chrono::{DateTime, Local, Timelike as _};
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink, Source as _, source::SineWave};
+use std::{fs::File, io::BufReader, time::Duration};
+
+const TICK_FREQUENCY: f32 = 2000.0;
+const TICK_DURATION: Duration = Duration::from_millis(12);
+const CHIME_FREQUENCY: f32 = 880.0;
+const CHIME_DURATION: Duration = Duration::from_millis(1500);
+/// Time between the strikes of the hourly chime.
+const CHIME_INTERVAL: Duration = Duration::from_millis(1200);
+
+// Audio settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub volume: f32,
+    pub tick: bool,
+    pub tick_volume: f32,
+    /// Strikes the hour at the top of every hour.
+    pub chime: bool,
+    pub chime_volume: f32,
+    /// Loops a sound file in the background.
+    pub ambience: bool,
+    pub ambience_volume: f32,
+    pub ambience_path: String,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            tick: false,
+            tick_volume: 0.3,
+            chime: false,
+            chime_volume: 0.6,
+            ambience: false,
+            ambience_volume: 0.5,
+            ambience_path: String::new(),
+        }
+    }
+}
+
+impl AudioConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, audio: &Audio) {
+        ui.add(egui::Slider::new(&mut self.volume, 0.0..=1.0).text("volume"));
+        for (enabled, volume, label) in [
+            (&mut self.tick, &mut self.tick_volume, "Tick every second"),
+            (&mut self.chime, &mut self.chime_volume, "Chime the hours"),
+            (&mut self.ambience, &mut self.ambience_volume, "Ambience"),
+        ] {
+            ui.horizontal(|ui| {
+                ui.checkbox(enabled, label);
+                ui.add_enabled(*enabled, egui::Slider::new(volume, 0.0..=1.0));
+            });
+        }
+        ui.add_enabled_ui(self.ambience, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Sound file:");
+                ui.text_edit_singleline(&mut self.ambience_path)
+                    .on_hover_text("WAV, FLAC, MP3 or Ogg Vorbis, looped");
+            });
+        });
+        if let Some(error) = &audio.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn any_enabled(&self) -> bool {
+        self.tick || self.chime || self.ambience
+    }
+}
+
+/// Plays the sounds of the clock on the default output device, which is opened once a sound is
+/// enabled.
+#[derive(Default)]
+pub struct Audio {
+    stream: Option<OutputStream>,
+    /// Whether opening the output device failed, which is not retried.
+    failed: bool,
+    error: Option<String>,
+    last_second: Option<i64>,
+    last_hour: Option<u32>,
+    /// The looping ambience with the file it plays.
+    ambience: Option<(String, Sink)>,
+}
+
+impl PartialEq for Audio {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl Audio {
+    /// Plays the sounds due at the real time `now`.
+    pub fn update(&mut self, config: &AudioConfig, now: &DateTime<Local>) {
+        let second = now.timestamp();
+        let hour = now.hour();
+        let new_second = self.last_second.is_some_and(|last| last != second);
+        let new_hour = self.last_hour.is_some_and(|last| last != hour);
+        self.last_second = Some(second);
+        self.last_hour = Some(hour);
+
+        if !config.any_enabled() {
+            self.stream = None;
+            self.ambience = None;
+            return;
+        }
+        self.open();
+        let Some(stream) = &self.stream else {
+            return;
+        };
+        let mixer = stream.mixer();
+
+        if config.tick && new_second {
+            let volume = config.volume * config.tick_volume;
+            mixer.add(
+                SineWave::new(TICK_FREQUENCY)
+                    .take_duration(TICK_DURATION)
+                    .fade_out(TICK_DURATION)
+                    .amplify(volume),
+            );
+        }
+        if config.chime && new_hour {
+            let volume = config.volume * config.chime_volume;
+            let strikes = match hour % 12 {
+                0 => 12,
+                strikes => strikes,
+            };
+            for strike in 0..strikes {
+                mixer.add(
+                    SineWave::new(CHIME_FREQUENCY)
+                        .take_duration(CHIME_DURATION)
+                        .fade_out(CHIME_DURATION)
+                        .amplify(volume)
+                        .delay(CHIME_INTERVAL * strike),
+                );
+            }
+        }
+
+        let path = &config.ambience_path;
+        if !config.ambience || path.is_empty() {
+            self.ambience = None;
+        } else if self
+            .ambience
+            .as_ref()
+            .is_none_or(|(playing, _)| playing != path)
+        {
+            self.ambience = None;
+            match play_looped(stream, path) {
+                Ok(sink) => {
+                    self.ambience = Some((path.clone(), sink));
+                    self.error = None;
+                }
+                Err(e) => {
+                    // Not retried until the path changes
+                    tracing::error!("Failed to play {path}: {e}");
+                    self.error = Some(format!("Failed to play {path}: {e}"));
+                    self.ambience = Some((path.clone(), Sink::connect_new(mixer)));
+                }
+            }
+        }
+        if let Some((_, sink)) = &self.ambience {
+            sink.set_volume(config.volume * config.ambience_volume);
+        }
+    }
+
+    fn open(&mut self) {
+        if self.stream.is_none() && !self.failed {
+            match OutputStreamBuilder::open_default_stream() {
+                Ok(mut stream) => {
+                    stream.log_on_drop(false);
+                    self.stream = Some(stream);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to open the audio output: {e}");
+                    self.error = Some(format!("Failed to open the audio output: {e}"));
+                    self.failed = true;
+                }
+            }
+        }
+    }
+}
+
+fn play_looped(stream: &OutputStream, path: &str) -> Result<Sink, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let source = Decoder::new_looped(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let sink = Sink::connect_new(stream.mixer());
+    sink.append(source);
+    Ok(sink)
+}
@@ -0,0 +1,122 @@
+use crate::{fractal_clock::FractalClock, migration, storage, toasts};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Keeps a copy of the settings on disk while the app runs and removes it on a clean exit, so a
+/// leftover copy at startup means the previous session crashed.
+pub struct Autosave {
+    path: Option<PathBuf>,
+    last_saved: String,
+    last_check: Instant,
+    pending_restore: Option<FractalClock>,
+}
+
+impl Autosave {
+    pub fn start() -> Self {
+        let path = storage::data_dir().map(|dir| dir.join("autosave.toml"));
+        let pending_restore = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| contents.parse().ok())
+            .and_then(|table| {
+                let mut value = toml::Value::Table(table);
+                migration::migrate(&mut value);
+                value.try_into().ok()
+            });
+
+        Self {
+            path,
+            last_saved: String::new(),
+            last_check: Instant::now(),
+            pending_restore,
+        }
+    }
+
+    /// Autosave that never writes, for when the settings are not edited in the app.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            last_saved: String::new(),
+            last_check: Instant::now(),
+            pending_restore: None,
+        }
+    }
+
+    /// Writes the settings if they changed since the last autosave.
+    pub fn update(&mut self, clock: &FractalClock) {
+        if self.pending_restore.is_some() || self.last_check.elapsed() < AUTOSAVE_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        let Ok(mut value) = toml::Value::try_from(clock) else {
+            return;
+        };
+        migration::stamp(&mut value);
+        let Ok(contents) = toml::to_string(&value) else {
+            return;
+        };
+        if contents == self.last_saved {
+            return;
+        }
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, &contents));
+        if let Err(e) = result {
+            tracing::warn!("Failed to autosave settings to {}: {e}", path.display());
+            toasts::error(format!("Failed to autosave settings: {e}"));
+        }
+        // Also after a failure, so it is retried on the next change rather than every interval
+        self.last_saved = contents;
+    }
+
+    /// Asks whether to restore the settings autosaved by a crashed session, returning them if accepted.
+    pub fn restore_ui(&mut self, ctx: &egui::Context) -> Option<FractalClock> {
+        self.pending_restore.as_ref()?;
+
+        let mut restore = None;
+        egui::Window::new("Restore settings?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("The clock did not shut down cleanly last time.");
+                ui.label("Restore the settings that were autosaved before it stopped?");
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        restore = Some(false);
+                    }
+                });
+            });
+
+        match restore? {
+            true => {
+                tracing::info!("Restoring autosaved settings");
+                self.pending_restore.take()
+            }
+            false => {
+                self.pending_restore = None;
+                None
+            }
+        }
+    }
+
+    /// Removes the autosave after the settings were saved normally.
+    pub fn finish(&self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::toasts;
+use std::io;
+#[cfg(not(target_os = "windows"))]
+use std::path::{Path, PathBuf};
+
+#[cfg(target_os = "windows")]
+const REGISTRY_RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+#[cfg(target_os = "windows")]
+const REGISTRY_VALUE: &str = "FractalClock";
+#[cfg(target_os = "macos")]
+const LAUNCH_AGENT_LABEL: &str = "io.github.lumarans30.fractal_clock";
+
+/// The "launch at login" toggle, mirroring the autostart entry installed on the system.
+#[derive(Default, PartialEq)]
+pub struct LaunchAtLogin {
+    enabled: Option<bool>,
+    error: Option<String>,
+}
+
+impl LaunchAtLogin {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let enabled = self.enabled.get_or_insert_with(is_enabled);
+        if ui.checkbox(enabled, "Launch at login").changed() {
+            self.error = set_enabled(*enabled)
+                .inspect_err(|e| {
+                    tracing::error!("Failed to update launch at login: {e}");
+                    toasts::error(format!("Failed to update launch at login: {e}"));
+                })
+                .err()
+                .map(|e| e.to_string());
+            self.enabled = Some(is_enabled());
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn home_dir() -> io::Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remove_file(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn entry_path() -> io::Result<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => home_dir()?.join(".config"),
+    };
+    Ok(config_dir.join("autostart").join("fractal_clock.desktop"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn is_enabled() -> bool {
+    entry_path().is_ok_and(|path| path.exists())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn set_enabled(enabled: bool) -> io::Result<()> {
+    let path = entry_path()?;
+    if !enabled {
+        return remove_file(&path);
+    }
+
+    let exe = std::env::current_exe()?;
+    write_file(
+        &path,
+        &format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Fractal Clock\n\
+             Exec=\"{}\"\n\
+             X-GNOME-Autostart-enabled=true\n",
+            exe.display()
+        ),
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn entry_path() -> io::Result<PathBuf> {
+    Ok(home_dir()?
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCH_AGENT_LABEL}.plist")))
+}
+
+#[cfg(target_os = "macos")]
+fn is_enabled() -> bool {
+    entry_path().is_ok_and(|path| path.exists())
+}
+
+#[cfg(target_os = "macos")]
+fn set_enabled(enabled: bool) -> io::Result<()> {
+    let path = entry_path()?;
+    if !enabled {
+        return remove_file(&path);
+    }
+
+    let exe = std::env::current_exe()?;
+    write_file(
+        &path,
+        &format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+             \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n\
+             <dict>\n\
+             \t<key>Label</key>\n\
+             \t<string>{LAUNCH_AGENT_LABEL}</string>\n\
+             \t<key>ProgramArguments</key>\n\
+             \t<array>\n\
+             \t\t<string>{}</string>\n\
+             \t</array>\n\
+             \t<key>RunAtLoad</key>\n\
+             \t<true/>\n\
+             </dict>\n\
+             </plist>\n",
+            exe.display()
+        ),
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> io::Result<bool> {
+    Ok(std::process::Command::new("reg")
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?
+        .success())
+}
+
+#[cfg(target_os = "windows")]
+fn is_enabled() -> bool {
+    reg(&["query", REGISTRY_RUN_KEY, "/v", REGISTRY_VALUE]).unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn set_enabled(enabled: bool) -> io::Result<()> {
+    let succeeded = if enabled {
+        let exe = std::env::current_exe()?;
+        let command = format!("\"{}\"", exe.display());
+        reg(&[
+            "add",
+            REGISTRY_RUN_KEY,
+            "/v",
+            REGISTRY_VALUE,
+            "/t",
+            "REG_SZ",
+            "/d",
+            &command,
+            "/f",
+        ])?
+    } else {
+        !is_enabled() || reg(&["delete", REGISTRY_RUN_KEY, "/v", REGISTRY_VALUE, "/f"])?
+    };
+
+    if succeeded {
+        Ok(())
+    } else {
+        Err(io::Error::other("failed to update the registry Run key"))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::toasts;
+use egui::{Color32, ColorImage, Painter, Rect, TextureHandle, TextureOptions, Vec2};
+use std::path::{Path, PathBuf};
+
+/// Image painted behind the clock, loaded again when its path changes.
+#[derive(Default)]
+pub struct Background {
+    /// Path of the loaded image, or of the one that failed to load, so it is not retried every
+    /// frame.
+    path: Option<PathBuf>,
+    texture: Option<TextureHandle>,
+}
+
+impl PartialEq for Background {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Background {
+    /// Paints the image at `path` over all of `rect`, cropping it to keep its aspect ratio.
+    pub fn paint(&mut self, painter: &Painter, rect: Rect, path: &Path) {
+        if self.path.as_deref() != Some(path) {
+            self.path = Some(path.to_owned());
+            self.texture = match load(path) {
+                Ok(image) => Some(painter.ctx().load_texture(
+                    "background",
+                    image,
+                    TextureOptions::LINEAR,
+                )),
+                Err(e) => {
+                    tracing::error!("Failed to load the background {}: {e}", path.display());
+                    toasts::error(format!("Failed to load the background: {e}"));
+                    None
+                }
+            };
+        }
+        let Some(texture) = &self.texture else {
+            return;
+        };
+        let size = texture.size_vec2();
+        // The shown part of the image, with the longer side cropped evenly
+        let scale = (rect.width() / size.x).max(rect.height() / size.y);
+        let visible = rect.size() / (size * scale);
+        let uv = Rect::from_min_size(((Vec2::splat(1.0) - visible) / 2.0).to_pos2(), visible);
+        painter.image(texture.id(), rect, uv, Color32::WHITE);
+    }
+}
+
+fn load(path: &Path) -> Result<ColorImage, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?.into_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
@@ -0,0 +1,96 @@
+use chrono::{NaiveTime, Timelike as _};
+
+/// A time of day whose shape is worth coming back to.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct Bookmark {
+    pub time: NaiveTime,
+    pub name: String,
+}
+
+// Bookmarked times
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct Bookmarks {
+    /// Sorted by time.
+    pub list: Vec<Bookmark>,
+}
+
+impl Default for Bookmarks {
+    fn default() -> Self {
+        Self {
+            list: vec![Bookmark {
+                time: NaiveTime::from_hms_opt(10, 9, 36).unwrap_or_default(),
+                name: "Watch advert".to_owned(),
+            }],
+        }
+    }
+}
+
+impl Bookmarks {
+    /// Lists the bookmarks, returning the time of the one clicked to jump to.
+    pub fn ui(&mut self, ui: &mut egui::Ui, shown: NaiveTime) -> Option<NaiveTime> {
+        let mut jump = None;
+        let mut removed = None;
+        for (index, bookmark) in self.list.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let label = bookmark.time.format("%H:%M:%S").to_string();
+                if ui
+                    .selectable_label(same_second(bookmark.time, shown), label)
+                    .clicked()
+                {
+                    jump = Some(bookmark.time);
+                }
+                ui.add(egui::TextEdit::singleline(&mut bookmark.name).desired_width(110.0));
+                if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.list.remove(index);
+        }
+        if ui.button("＋ Bookmark this time").clicked() {
+            self.add(shown);
+        }
+        ui.weak("[ and ] jump to the previous and next bookmark");
+        jump
+    }
+
+    pub fn add(&mut self, time: NaiveTime) {
+        let time = time.with_nanosecond(0).unwrap_or(time);
+        if self.list.iter().any(|bookmark| bookmark.time == time) {
+            return;
+        }
+        let index = self.list.partition_point(|bookmark| bookmark.time < time);
+        self.list.insert(
+            index,
+            Bookmark {
+                time,
+                name: String::new(),
+            },
+        );
+    }
+
+    /// First bookmark after `time`, wrapping around midnight.
+    pub fn next(&self, time: NaiveTime) -> Option<NaiveTime> {
+        self.list
+            .iter()
+            .map(|bookmark| bookmark.time)
+            .find(|&bookmark| bookmark > time && !same_second(bookmark, time))
+            .or_else(|| self.list.first().map(|bookmark| bookmark.time))
+    }
+
+    /// Last bookmark before `time`, wrapping around midnight.
+    pub fn previous(&self, time: NaiveTime) -> Option<NaiveTime> {
+        self.list
+            .iter()
+            .map(|bookmark| bookmark.time)
+            .rfind(|&bookmark| bookmark < time && !same_second(bookmark, time))
+            .or_else(|| self.list.last().map(|bookmark| bookmark.time))
+    }
+}
+
+/// Bookmarks are stored to the second, while the clock runs with fractions.
+fn same_second(a: NaiveTime, b: NaiveTime) -> bool {
+    a.num_seconds_from_midnight() == b.num_seconds_from_midnight()
+}
@@ -0,0 +1,84 @@
+use crate::poster::{Poster, PosterConfig};
+
+/// Frames of a burst at most, which keeps the contact sheet to a reasonable size.
+const MAX_FRAMES: u32 = 200;
+
+// Burst capture settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct BurstConfig {
+    /// Milliseconds between the frames.
+    pub interval_ms: u32,
+    pub duration_seconds: f32,
+    pub columns: u32,
+    /// Width and height of every frame in pixels.
+    pub cell_size: u32,
+    /// Writes the time of every frame under it, to the millisecond.
+    pub captions: bool,
+}
+
+impl Default for BurstConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 100,
+            duration_seconds: 2.0,
+            columns: 5,
+            cell_size: 300,
+            captions: true,
+        }
+    }
+}
+
+impl BurstConfig {
+    /// Returns whether the capture was requested.
+    pub fn ui(&mut self, ui: &mut egui::Ui, sheet: &Poster) -> bool {
+        ui.add(
+            egui::Slider::new(&mut self.interval_ms, 20..=1000)
+                .logarithmic(true)
+                .suffix(" ms")
+                .text("interval"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.duration_seconds, 0.5..=10.0)
+                .suffix(" s")
+                .text("duration"),
+        );
+        ui.add(egui::Slider::new(&mut self.columns, 1..=12).text("columns"));
+        ui.add(egui::Slider::new(&mut self.cell_size, 100..=600).text("frame size"));
+        ui.checkbox(&mut self.captions, "Captions");
+        let size = self.sheet().image_size();
+        ui.weak(format!(
+            "{} frames from the shown time in a {}×{} contact sheet",
+            self.count(),
+            size[0],
+            size[1]
+        ));
+        if sheet.is_exporting() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Rendering the contact sheet…");
+            });
+            false
+        } else {
+            ui.button("📷 Capture burst")
+                .on_hover_text("Renders the frames and saves them side by side in one image")
+                .clicked()
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        let frames = (self.duration_seconds * 1000.0 / self.interval_ms.max(1) as f32).ceil();
+        (frames as u32).clamp(1, MAX_FRAMES)
+    }
+
+    /// Layout of the contact sheet, a grid of the frames.
+    pub fn sheet(&self) -> PosterConfig {
+        let columns = self.columns.clamp(1, self.count());
+        PosterConfig {
+            columns,
+            rows: self.count().div_ceil(columns),
+            cell_size: self.cell_size,
+            captions: self.captions,
+        }
+    }
+}
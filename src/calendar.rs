@@ -0,0 +1,387 @@
+use crate::{about, location::Location, time_system::TimeSystem};
+use chrono::{
+    DateTime, Datelike as _, Days, Local, Months, NaiveDate, NaiveDateTime, TimeDelta,
+    TimeZone as _, Utc,
+};
+use egui::{Color32, Painter, Stroke, emath::RectTransform, pos2};
+use std::{
+    sync::mpsc::{Receiver, TryRecvError, channel},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Failed reads are retried sooner than the refresh interval, but not too often.
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+/// The marker is drawn for events within one turn of the hour hand.
+const MARKER_HORIZON: TimeDelta = TimeDelta::hours(12);
+const MARKER_RADIUS: f32 = 5.0;
+/// Occurrences of a recurring event searched for the next one at most, from about a period
+/// before `now`.
+const MAX_OCCURRENCES: u32 = 1000;
+
+// Calendar settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Path of an ICS file, or an http(s) or webcal URL of one.
+    pub source: String,
+    pub refresh_minutes: u32,
+    /// Marks the next event on the circle of the hour hand's tip.
+    pub marker: bool,
+    pub marker_color: Color32,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            source: String::new(),
+            refresh_minutes: 15,
+            marker: true,
+            marker_color: Color32::from_rgb(120, 200, 255),
+        }
+    }
+}
+
+impl CalendarConfig {
+    /// Copy without the source, which may be a private calendar URL.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        about::redact(&mut config.source);
+        config
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, calendar: &Calendar) {
+        ui.horizontal(|ui| {
+            ui.label("ICS file or URL:");
+            ui.text_edit_singleline(&mut self.source)
+                .on_hover_text("e.g. the secret iCal address of a calendar");
+        });
+        ui.add(egui::Slider::new(&mut self.refresh_minutes, 1..=180).text("refresh minutes"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.marker, "Marker on the dial");
+            ui.color_edit_button_srgba(&mut self.marker_color);
+        });
+        if let Some(error) = &calendar.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if !self.source.is_empty() {
+            ui.weak(format!("{} events", calendar.events.len()));
+        }
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.refresh_minutes.max(1)) * 60)
+    }
+
+    /// Paints a dot on the circle of the hour hand's tip where it points at `start`.
+    pub fn paint(
+        &self,
+        painter: &Painter,
+        to_screen: &RectTransform,
+        start: DateTime<Local>,
+        hand: f32,
+        system: TimeSystem,
+        location: Location,
+    ) {
+        let [_, _, turns] = system.hand_turns(&start, location);
+        let angle = std::f32::consts::TAU * turns as f32 - std::f32::consts::FRAC_PI_2;
+        let center = to_screen * (pos2(0.0, 0.0) + egui::Vec2::angled(angle) * hand);
+        painter.circle(
+            center,
+            MARKER_RADIUS,
+            self.marker_color,
+            Stroke::new(1.0, Color32::BLACK),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The subset of an RRULE that repeats on the weekday and day of the start.
+#[derive(Clone, Copy, PartialEq)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    until: Option<DateTime<Local>>,
+    count: Option<u32>,
+}
+
+impl Recurrence {
+    fn parse(value: &str) -> Option<Self> {
+        let mut recurrence = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            until: None,
+            count: None,
+        };
+        let mut frequency = None;
+        for part in value.split(';') {
+            let (name, value) = part.split_once('=')?;
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    });
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|&n| n > 0)?,
+                "UNTIL" => recurrence.until = parse_time(value, false),
+                "COUNT" => recurrence.count = value.parse().ok(),
+                _ => {}
+            }
+        }
+        recurrence.frequency = frequency?;
+        Some(recurrence)
+    }
+
+    /// The `index`th occurrence of an event starting at `start`.
+    fn occurrence(self, start: DateTime<Local>, index: u32) -> Option<DateTime<Local>> {
+        let naive = start.naive_local();
+        let steps = self.interval.checked_mul(index)?;
+        let naive = match self.frequency {
+            Frequency::Daily => naive.checked_add_days(Days::new(steps.into()))?,
+            Frequency::Weekly => naive.checked_add_days(Days::new(u64::from(steps) * 7))?,
+            Frequency::Monthly => naive.checked_add_months(Months::new(steps))?,
+            Frequency::Yearly => naive.checked_add_months(Months::new(steps.checked_mul(12)?))?,
+        };
+        Local.from_local_datetime(&naive).earliest()
+    }
+
+    /// Index of an occurrence shortly before `now`, to skip the earlier ones.
+    fn index_before(self, start: DateTime<Local>, now: &DateTime<Local>) -> u32 {
+        let (start, now) = (start.date_naive(), now.date_naive());
+        let periods = match self.frequency {
+            Frequency::Daily => (now - start).num_days(),
+            Frequency::Weekly => (now - start).num_weeks(),
+            Frequency::Monthly | Frequency::Yearly => {
+                let months = i64::from(now.year() - start.year()) * 12 + i64::from(now.month0())
+                    - i64::from(start.month0());
+                if self.frequency == Frequency::Yearly {
+                    months / 12
+                } else {
+                    months
+                }
+            }
+        };
+        let index = periods / i64::from(self.interval) - 1;
+        u32::try_from(index.max(0)).unwrap_or(u32::MAX)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Event {
+    start: DateTime<Local>,
+    recurrence: Option<Recurrence>,
+    pub summary: String,
+}
+
+impl Event {
+    /// Start of the first occurrence after `now`.
+    fn next_start(&self, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let Some(recurrence) = self.recurrence else {
+            return (self.start > *now).then_some(self.start);
+        };
+        let first = recurrence.index_before(self.start, now);
+        let end = first.saturating_add(MAX_OCCURRENCES);
+        (first..recurrence.count.map_or(end, |count| count.min(end)))
+            .map_while(|index| recurrence.occurrence(self.start, index))
+            .take_while(|start| recurrence.until.is_none_or(|until| *start <= until))
+            .find(|start| start > now)
+    }
+}
+
+/// Upcoming events of the calendar, read on a background thread.
+#[derive(Default)]
+pub struct Calendar {
+    events: Vec<Event>,
+    error: Option<String>,
+    pending: Option<Receiver<Result<Vec<Event>, String>>>,
+    next_read: Option<Instant>,
+    /// Settings of the latest read, which is repeated right away when they change.
+    requested: Option<CalendarConfig>,
+}
+
+impl PartialEq for Calendar {
+    fn eq(&self, other: &Self) -> bool {
+        self.events == other.events && self.error == other.error
+    }
+}
+
+impl Calendar {
+    /// The next event after `now` with its start.
+    pub fn next(&self, now: &DateTime<Local>) -> Option<(DateTime<Local>, &Event)> {
+        self.events
+            .iter()
+            .filter_map(|event| Some((event.next_start(now)?, event)))
+            .min_by_key(|(start, _)| *start)
+    }
+
+    /// The next event if it is close enough to mark on the dial.
+    pub fn marked(&self, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+        self.next(now)
+            .map(|(start, _)| start)
+            .filter(|start| *start - *now <= MARKER_HORIZON)
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context, config: &CalendarConfig) {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(result) => {
+                    self.pending = None;
+                    let delay = match result {
+                        Ok(events) => {
+                            self.events = events;
+                            self.error = None;
+                            config.refresh_interval()
+                        }
+                        Err(error) => {
+                            tracing::warn!("Failed to read the calendar: {error}");
+                            self.error = Some(error);
+                            RETRY_DELAY.min(config.refresh_interval())
+                        }
+                    };
+                    self.next_read = Some(Instant::now() + delay);
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        if config.source.is_empty() {
+            self.events.clear();
+            self.error = None;
+            self.requested = None;
+            return;
+        }
+        let changed = self.requested.as_ref().is_none_or(|requested| {
+            requested.source != config.source || requested.refresh_minutes != config.refresh_minutes
+        });
+        let due = self.next_read.is_none_or(|next| Instant::now() >= next);
+        if changed || due {
+            self.read(ctx, config);
+        }
+    }
+
+    fn read(&mut self, ctx: &egui::Context, config: &CalendarConfig) {
+        let (sender, receiver) = channel();
+        let source = config.source.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(load(&source).map(|text| parse(&text)));
+            ctx.request_repaint();
+        });
+        self.pending = Some(receiver);
+        self.requested = Some(config.clone());
+    }
+}
+
+fn load(source: &str) -> Result<String, String> {
+    if let Some(rest) = source.strip_prefix("webcal://") {
+        download(&format!("https://{rest}"))
+    } else if source.starts_with("http://") || source.starts_with("https://") {
+        download(source)
+    } else {
+        std::fs::read_to_string(source).map_err(|e| format!("{source}: {e}"))
+    }
+}
+
+#[cfg(feature = "network")]
+fn download(url: &str) -> Result<String, String> {
+    ureq::get(url)
+        .config()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .call()
+        .map_err(|e| e.to_string())?
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "network"))]
+fn download(_url: &str) -> Result<String, String> {
+    Err("Built without the network feature".to_owned())
+}
+
+/// The events of an ICS calendar with a start and a summary. Times with a TZID are taken as
+/// local time, and all-day events start at midnight.
+fn parse(text: &str) -> Vec<Event> {
+    // Long lines continue on lines starting with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<(Option<DateTime<Local>>, Option<Recurrence>, String)> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, parameters) = name.split_once(';').unwrap_or((name, ""));
+        match (name, &mut current) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some((None, None, String::new())),
+            ("END", Some(_)) if value == "VEVENT" => {
+                if let Some((Some(start), recurrence, summary)) = current.take() {
+                    events.push(Event {
+                        start,
+                        recurrence,
+                        summary,
+                    });
+                }
+            }
+            ("DTSTART", Some((start, _, _))) => {
+                *start = parse_time(value, parameters.contains("VALUE=DATE"));
+            }
+            ("RRULE", Some((_, recurrence, _))) => *recurrence = Recurrence::parse(value),
+            ("SUMMARY", Some((_, _, summary))) => *summary = unescape(value),
+            _ => {}
+        }
+    }
+    events
+}
+
+/// A DATE-TIME in UTC with a trailing `Z` or in local time, or a DATE at local midnight.
+fn parse_time(value: &str, date: bool) -> Option<DateTime<Local>> {
+    if date || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest();
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&time).with_timezone(&Local));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Local.from_local_datetime(&time).earliest()
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text
+}
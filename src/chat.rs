@@ -0,0 +1,568 @@
+use crate::{fractal_clock::FractalClockConfig, timeline::Parameter};
+use egui::{Color32, epaint::Hsva};
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead as _, BufReader, Write as _},
+    net::{Shutdown, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    time::{Duration, Instant},
+};
+
+/// Twitch chat over plain IRC, read anonymously.
+const SERVER: &str = "irc.chat.twitch.tv:6667";
+const TIMEOUT: Duration = Duration::from_secs(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+/// How often the reconnect delay checks whether the chat was left.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Applied commands listed in the settings.
+const RECENT_COMMANDS: usize = 5;
+
+/// A chat command viewers can send, like `!depth 18`.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ChatCommand {
+    Depth,
+    Zoom,
+    Spiral,
+    Gravity,
+    /// Turns the colors to a hue in degrees.
+    Hue,
+    /// Toggles rainbow mode, or turns it on or off.
+    Rainbow,
+}
+
+impl ChatCommand {
+    const ALL: [Self; 6] = [
+        Self::Depth,
+        Self::Zoom,
+        Self::Spiral,
+        Self::Gravity,
+        Self::Hue,
+        Self::Rainbow,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Depth => "depth",
+            Self::Zoom => "zoom",
+            Self::Spiral => "spiral",
+            Self::Gravity => "gravity",
+            Self::Hue => "hue",
+            Self::Rainbow => "rainbow",
+        }
+    }
+
+    fn usage(self) -> &'static str {
+        match self {
+            Self::Depth => "!depth 18",
+            Self::Zoom => "!zoom 2",
+            Self::Spiral => "!spiral 10",
+            Self::Gravity => "!gravity -5",
+            Self::Hue => "!hue 200",
+            Self::Rainbow => "!rainbow, !rainbow on or !rainbow off",
+        }
+    }
+}
+
+/// A command parsed from a chat message.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ChatAction {
+    command: ChatCommand,
+    value: Option<f32>,
+}
+
+impl ChatAction {
+    /// Reads a message like `!depth 18`, returning nothing for anything else.
+    fn parse(message: &str) -> Option<Self> {
+        let mut words = message.strip_prefix('!')?.split_whitespace();
+        let name = words.next()?.to_lowercase();
+        let command = ChatCommand::ALL
+            .into_iter()
+            .find(|command| command.name() == name)?;
+        let argument = words.next();
+        let value = match (command, argument) {
+            (ChatCommand::Rainbow, None) => None,
+            (ChatCommand::Rainbow, Some("on")) => Some(1.0),
+            (ChatCommand::Rainbow, Some("off")) => Some(0.0),
+            (_, argument) => Some(argument?.parse::<f32>().ok().filter(|v| v.is_finite())?),
+        };
+        Some(Self { command, value })
+    }
+
+    pub fn apply(self, config: &mut FractalClockConfig) {
+        let parameter = match self.command {
+            ChatCommand::Depth => Parameter::Depth,
+            ChatCommand::Zoom => Parameter::Zoom,
+            ChatCommand::Spiral => Parameter::Spiral,
+            ChatCommand::Gravity => Parameter::Gravity,
+            ChatCommand::Hue => {
+                let hue = self.value.unwrap_or_default().rem_euclid(360.0) / 360.0;
+                // Turns the whole gradient, keeping its span
+                let shift = hue - config.start_hsv().h;
+                let turn = |mut color: Hsva| {
+                    color.h = (color.h + shift).rem_euclid(1.0);
+                    color
+                };
+                config.set_start_hsv(turn(config.start_hsv()));
+                config.set_end_hsv(turn(config.end_hsv()));
+                let mut branch = Hsva::from(config.branch_color());
+                branch.h = hue;
+                config.set_branch_color(Color32::from(branch));
+                return;
+            }
+            ChatCommand::Rainbow => {
+                let enabled = self.value.map_or(!config.rainbow_mode(), |v| v > 0.0);
+                config.set_rainbow_mode(enabled);
+                return;
+            }
+        };
+        if let Some(value) = self.value {
+            parameter.set(config, value);
+        }
+    }
+}
+
+// Twitch chat settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ChatConfig {
+    pub enabled: bool,
+    /// Twitch channel whose chat is read, without the `#`.
+    pub channel: String,
+    /// Commands viewers may use.
+    pub commands: Vec<ChatCommand>,
+    /// Viewers whose commands are applied, separated by commas, everyone's when empty.
+    pub allowed_users: String,
+    /// Seconds after a command before the next one of anyone is applied.
+    pub cooldown_seconds: f32,
+    /// Seconds after a command before the next one of the same viewer is applied.
+    pub user_cooldown_seconds: f32,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channel: String::new(),
+            commands: ChatCommand::ALL.to_vec(),
+            allowed_users: String::new(),
+            cooldown_seconds: 2.0,
+            user_cooldown_seconds: 10.0,
+        }
+    }
+}
+
+impl ChatConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, chat: &Chat) {
+        ui.checkbox(&mut self.enabled, "React to Twitch chat");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Channel:");
+                ui.text_edit_singleline(&mut self.channel);
+            });
+            ui.label("Commands:");
+            for command in ChatCommand::ALL {
+                let mut allowed = self.commands.contains(&command);
+                if ui
+                    .checkbox(&mut allowed, format!("!{}", command.name()))
+                    .on_hover_text(command.usage())
+                    .changed()
+                {
+                    self.commands.retain(|other| *other != command);
+                    if allowed {
+                        self.commands.push(command);
+                    }
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label("Allowed viewers:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.allowed_users)
+                        .hint_text("everyone")
+                        .desired_width(160.0),
+                )
+                .on_hover_text("Twitch names separated by commas");
+            });
+            ui.add(
+                egui::Slider::new(&mut self.cooldown_seconds, 0.0..=60.0)
+                    .suffix(" s")
+                    .text("cooldown"),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.user_cooldown_seconds, 0.0..=300.0)
+                    .suffix(" s")
+                    .text("cooldown per viewer"),
+            );
+        });
+        if let Some(error) = &chat.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if chat.connected {
+            ui.weak(format!("Reading #{}", self.channel()));
+        }
+        for line in &chat.recent {
+            ui.weak(line);
+        }
+    }
+
+    fn channel(&self) -> String {
+        self.channel.trim().trim_start_matches('#').to_lowercase()
+    }
+
+    fn allows(&self, user: &str) -> bool {
+        let mut users = self
+            .allowed_users
+            .split(',')
+            .map(str::trim)
+            .filter(|allowed| !allowed.is_empty())
+            .peekable();
+        users.peek().is_none() || users.any(|allowed| allowed.eq_ignore_ascii_case(user))
+    }
+}
+
+enum Event {
+    Connected,
+    Disconnected(String),
+    Message { user: String, text: String },
+}
+
+struct Running {
+    events: Receiver<Event>,
+    /// Connection to the chat, shut down when leaving it.
+    stream: Arc<Mutex<Option<TcpStream>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Ok(stream) = self.stream.lock()
+            && let Some(stream) = stream.as_ref()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Reads a Twitch channel's chat on a background thread, so viewers of a stream showing the
+/// clock can change it with commands. Commands are only applied when allowed in the settings
+/// and outside the cooldowns.
+#[derive(Default)]
+pub struct Chat {
+    running: Option<Running>,
+    connected: bool,
+    error: Option<String>,
+    /// Channel of the latest attempt to join, which is not retried until it changes.
+    requested: Option<String>,
+    last_command: Option<Instant>,
+    user_commands: HashMap<String, Instant>,
+    /// Latest applied commands with their viewers.
+    recent: VecDeque<String>,
+}
+
+impl PartialEq for Chat {
+    fn eq(&self, other: &Self) -> bool {
+        self.requested == other.requested
+            && self.connected == other.connected
+            && self.error == other.error
+    }
+}
+
+impl Chat {
+    /// Joins or leaves the chat to match `config`, returning the commands to apply.
+    pub fn update(&mut self, ctx: &egui::Context, config: &ChatConfig) -> Vec<ChatAction> {
+        let channel = config.channel();
+        if !config.enabled || channel.is_empty() {
+            self.running = None;
+            self.connected = false;
+            self.error = None;
+            self.requested = None;
+            return Vec::new();
+        }
+        if self.requested.as_ref() != Some(&channel) {
+            self.running = Some(join(ctx, channel.clone()));
+            self.connected = false;
+            self.error = None;
+            self.requested = Some(channel);
+        }
+        let Some(running) = &self.running else {
+            return Vec::new();
+        };
+
+        let mut actions = Vec::new();
+        for event in running.events.try_iter() {
+            match event {
+                Event::Connected => {
+                    self.connected = true;
+                    self.error = None;
+                }
+                Event::Disconnected(error) => {
+                    self.connected = false;
+                    self.error = Some(error);
+                }
+                Event::Message { user, text } => {
+                    let Some(action) = ChatAction::parse(&text) else {
+                        continue;
+                    };
+                    if !config.commands.contains(&action.command) || !config.allows(&user) {
+                        continue;
+                    }
+                    let now = Instant::now();
+                    let cooling = |last: Option<&Instant>, seconds: f32| {
+                        last.is_some_and(|last| now.duration_since(*last).as_secs_f32() < seconds)
+                    };
+                    if cooling(self.last_command.as_ref(), config.cooldown_seconds)
+                        || cooling(self.user_commands.get(&user), config.user_cooldown_seconds)
+                    {
+                        continue;
+                    }
+                    self.last_command = Some(now);
+                    self.user_commands.insert(user.clone(), now);
+                    self.recent.push_front(format!("{user}: {}", text.trim()));
+                    self.recent.truncate(RECENT_COMMANDS);
+                    actions.push(action);
+                }
+            }
+        }
+        // Forgets the viewers whose cooldown is over
+        let cooldown = config.user_cooldown_seconds;
+        self.user_commands
+            .retain(|_, last| last.elapsed().as_secs_f32() < cooldown);
+        actions
+    }
+}
+
+fn join(ctx: &egui::Context, channel: String) -> Running {
+    let (sender, events) = mpsc::channel();
+    let stream = Arc::new(Mutex::new(None));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let ctx = ctx.clone();
+    let thread_stream = stream.clone();
+    let thread_stop = stop.clone();
+    std::thread::spawn(move || {
+        while !thread_stop.load(Ordering::Relaxed) {
+            let error = match read_chat(&channel, &sender, &ctx, &thread_stream) {
+                Ok(()) => "The chat closed the connection".to_owned(),
+                Err(e) => format!("Failed to read the chat: {e}"),
+            };
+            if thread_stop.load(Ordering::Relaxed) {
+                return;
+            }
+            tracing::warn!("{error}, reconnecting to #{channel}");
+            if sender.send(Event::Disconnected(error)).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+            let reconnect = Instant::now() + RECONNECT_DELAY;
+            while Instant::now() < reconnect && !thread_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    });
+
+    Running {
+        events,
+        stream,
+        stop,
+    }
+}
+
+/// Reads the chat of `channel` until the connection closes.
+fn read_chat(
+    channel: &str,
+    events: &Sender<Event>,
+    ctx: &egui::Context,
+    slot: &Mutex<Option<TcpStream>>,
+) -> std::io::Result<()> {
+    let address = std::net::ToSocketAddrs::to_socket_addrs(SERVER)?
+        .next()
+        .ok_or_else(|| std::io::Error::other("Twitch chat not found"))?;
+    let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    if let Ok(mut slot) = slot.lock() {
+        *slot = Some(stream.try_clone()?);
+    }
+    let mut writer = &stream;
+    // Anonymous viewers only read the chat, which is all the clock needs
+    let nick = format!("justinfan{}", std::process::id() % 100_000);
+    write!(writer, "NICK {nick}\r\nJOIN #{channel}\r\n")?;
+
+    let mut line = String::new();
+    let mut reader = BufReader::new(&stream);
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let line = line.trim_end();
+        if let Some(server) = line.strip_prefix("PING ") {
+            write!(writer, "PONG {server}\r\n")?;
+            continue;
+        }
+        let event = match parse_line(line) {
+            Some(Line::Welcome) => {
+                tracing::info!("Reading the chat of #{channel}");
+                Event::Connected
+            }
+            Some(Line::Message { user, text }) => Event::Message {
+                user: user.to_owned(),
+                text: text.to_owned(),
+            },
+            None => continue,
+        };
+        if events.send(event).is_err() {
+            return Ok(());
+        }
+        ctx.request_repaint();
+    }
+}
+
+enum Line<'a> {
+    Welcome,
+    Message { user: &'a str, text: &'a str },
+}
+
+/// Reads the IRC lines the clock reacts to, like
+/// `:viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :!depth 18`.
+fn parse_line(line: &str) -> Option<Line<'_>> {
+    let (prefix, rest) = line.strip_prefix(':')?.split_once(' ')?;
+    let (command, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    match command {
+        "001" => Some(Line::Welcome),
+        "PRIVMSG" => {
+            let (_, text) = rest.split_once(" :")?;
+            let user = prefix.split_once('!').map_or(prefix, |(user, _)| user);
+            Some(Line::Message { user, text })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(line: &str) -> Option<(&str, &str)> {
+        match parse_line(line)? {
+            Line::Message { user, text } => Some((user, text)),
+            Line::Welcome => None,
+        }
+    }
+
+    #[test]
+    fn reads_chat_messages() {
+        assert_eq!(
+            message(":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :!depth 18"),
+            Some(("viewer", "!depth 18"))
+        );
+        // The text keeps its own colons
+        assert_eq!(
+            message(":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :hi :) !hue 3"),
+            Some(("viewer", "hi :) !hue 3"))
+        );
+        // A prefix without a user is the server's name
+        assert_eq!(
+            message(":tmi.twitch.tv PRIVMSG #channel :!rainbow"),
+            Some(("tmi.twitch.tv", "!rainbow"))
+        );
+        assert!(matches!(
+            parse_line(":tmi.twitch.tv 001 justinfan1 :Welcome, GLHF!"),
+            Some(Line::Welcome)
+        ));
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        for line in [
+            "",
+            "PING :tmi.twitch.tv",
+            "PRIVMSG #channel :!depth 18",
+            ":viewer!viewer@viewer.tmi.twitch.tv JOIN #channel",
+            ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel",
+            ":tmi.twitch.tv NOTICE * :Login unsuccessful",
+        ] {
+            assert!(parse_line(line).is_none(), "{line}");
+        }
+    }
+
+    fn action(message: &str) -> Option<(ChatCommand, Option<f32>)> {
+        ChatAction::parse(message).map(|action| (action.command, action.value))
+    }
+
+    #[test]
+    fn reads_commands() {
+        assert_eq!(action("!depth 18"), Some((ChatCommand::Depth, Some(18.0))));
+        assert_eq!(
+            action("!DEPTH 18 please"),
+            Some((ChatCommand::Depth, Some(18.0)))
+        );
+        assert_eq!(action("!hue 200"), Some((ChatCommand::Hue, Some(200.0))));
+        assert_eq!(
+            action("!gravity -5"),
+            Some((ChatCommand::Gravity, Some(-5.0)))
+        );
+        assert_eq!(action("!rainbow"), Some((ChatCommand::Rainbow, None)));
+        assert_eq!(
+            action("!rainbow on"),
+            Some((ChatCommand::Rainbow, Some(1.0)))
+        );
+        assert_eq!(
+            action("!rainbow off"),
+            Some((ChatCommand::Rainbow, Some(0.0)))
+        );
+    }
+
+    #[test]
+    fn ignores_other_messages() {
+        for message in [
+            "depth 18",
+            "!",
+            "!depth",
+            "!depth deep",
+            "!depth NaN",
+            "!zoom inf",
+            "!spiral -infinity",
+            "!rainbow maybe",
+            "!quit",
+        ] {
+            assert!(action(message).is_none(), "{message}");
+        }
+    }
+
+    #[test]
+    fn applies_rainbow() {
+        let mut config = FractalClockConfig::builder().rainbow_mode(false).build();
+        ChatAction::parse("!rainbow").unwrap().apply(&mut config);
+        assert!(config.rainbow_mode());
+        ChatAction::parse("!rainbow on").unwrap().apply(&mut config);
+        assert!(config.rainbow_mode());
+        ChatAction::parse("!rainbow off")
+            .unwrap()
+            .apply(&mut config);
+        assert!(!config.rainbow_mode());
+    }
+
+    #[test]
+    fn clamps_values() {
+        let mut config = FractalClockConfig::default();
+        ChatAction::parse("!depth 1000").unwrap().apply(&mut config);
+        assert_eq!(config.depth(), *crate::fractal_clock::DEPTH_RANGE.end());
+    }
+
+    #[test]
+    fn allows_listed_viewers() {
+        let everyone = ChatConfig::default();
+        assert!(everyone.allows("anyone"));
+        let listed = ChatConfig {
+            allowed_users: " Streamer, moderator ,,".to_owned(),
+            ..ChatConfig::default()
+        };
+        assert!(listed.allows("streamer"));
+        assert!(listed.allows("Moderator"));
+        assert!(!listed.allows("viewer"));
+        assert!(!listed.allows("stream"));
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+use tracing::level_filters::LevelFilter;
+
+const USAGE: &str = "\
+Usage: fractal_clock [OPTIONS]
+
+Options:
+      --start-minimized     Start with the window minimized
+      --start-fullscreen    Start in fullscreen mode
+      --monitor <N>         Open the window on monitor N (0 is the first monitor)
+      --config <PATH>       Load and save settings in this TOML file
+      --log-level <LEVEL>   Log verbosity: off, error, warn, info, debug or trace [default: info]
+      --kiosk               Run fullscreen without settings, with the config file as the only
+                            settings source, restarting the clock if it crashes
+      --lock                Start locked, without settings, input or saving until unlocked with
+                            Ctrl+Alt+Shift+L
+      --tui                 Draw the clock in the terminal (requires the `tui` feature)
+      --metrics-out <PATH>  Record per-frame metrics of the session to this CSV file
+  -h, --help                Print this help";
+
+// Command line arguments
+#[derive(Default)]
+pub struct Args {
+    pub config: Option<PathBuf>,
+    pub start_minimized: bool,
+    pub start_fullscreen: bool,
+    pub monitor: Option<usize>,
+    pub log_level: Option<LevelFilter>,
+    pub kiosk: bool,
+    pub lock: bool,
+    pub tui: bool,
+    pub metrics_out: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn parse() -> Self {
+        match Self::try_parse(std::env::args().skip(1)) {
+            Ok(args) => args,
+            Err(error) => {
+                eprintln!("{error}\n\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    fn try_parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--start-minimized" => parsed.start_minimized = true,
+                "--start-fullscreen" => parsed.start_fullscreen = true,
+                "--kiosk" => parsed.kiosk = true,
+                "--lock" => parsed.lock = true,
+                "--tui" => parsed.tui = true,
+                "--monitor" => {
+                    let value = args.next().ok_or("--monitor requires a value")?;
+                    let index = value
+                        .parse()
+                        .map_err(|_| format!("invalid monitor index: {value}"))?;
+                    parsed.monitor = Some(index);
+                }
+                "--config" => {
+                    let path = args.next().ok_or("--config requires a path")?;
+                    parsed.config = Some(PathBuf::from(path));
+                }
+                "--metrics-out" => {
+                    let path = args.next().ok_or("--metrics-out requires a path")?;
+                    parsed.metrics_out = Some(PathBuf::from(path));
+                }
+                "--log-level" => {
+                    let value = args.next().ok_or("--log-level requires a value")?;
+                    let level = value
+                        .parse()
+                        .map_err(|_| format!("invalid log level: {value}"))?;
+                    parsed.log_level = Some(level);
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ => return Err(format!("unknown argument: {arg}")),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,39 @@
+use image::RgbaImage;
+
+/// Places rendered frames on the system clipboard as images.
+#[derive(Default)]
+pub struct Clipboard {
+    /// Kept open, as on X11 the copied image is only served while the clipboard is.
+    #[cfg(not(target_os = "android"))]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl PartialEq for Clipboard {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Clipboard {
+    #[cfg(not(target_os = "android"))]
+    pub fn copy_image(&mut self, image: &RgbaImage) -> Result<(), String> {
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self
+                .clipboard
+                .insert(arboard::Clipboard::new().map_err(|e| e.to_string())?),
+        };
+        clipboard
+            .set_image(arboard::ImageData {
+                width: image.width() as usize,
+                height: image.height() as usize,
+                bytes: image.as_raw().into(),
+            })
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(target_os = "android")]
+    pub fn copy_image(&mut self, _: &RgbaImage) -> Result<(), String> {
+        Err("Copying images is not supported on Android".to_owned())
+    }
+}
@@ -0,0 +1,108 @@
+use egui::{Color32, Rgba, ecolor::Hsva, lerp};
+
+/// Space the branch colors are blended and dimmed in.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum ColorSpace {
+    /// Hue, saturation and value are blended and sRGB values are dimmed, as originally.
+    Hsv,
+    /// Perceptually even steps, without muddy or overly bright mid-tones.
+    Oklab,
+    /// Blended and dimmed like light, which keeps the brightness of mixed colors.
+    LinearRgb,
+}
+
+impl ColorSpace {
+    pub const ALL: [Self; 3] = [Self::Hsv, Self::Oklab, Self::LinearRgb];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Hsv => "HSV",
+            Self::Oklab => "Oklab",
+            Self::LinearRgb => "Linear RGB",
+        }
+    }
+
+    /// The color `t` of the way from `start` to `end`.
+    pub fn gradient(self, start: Hsva, end: Hsva, t: f32) -> Color32 {
+        match self {
+            Self::Hsv => {
+                let [h, s, v, a] = [
+                    (start.h, end.h),
+                    (start.s, end.s),
+                    (start.v, end.v),
+                    (start.a, end.a),
+                ]
+                .map(|(start, end)| lerp(start..=end, t));
+                Hsva::new(h, s, v, a).into()
+            }
+            Self::Oklab => {
+                let [start_lab, end_lab] = [start, end].map(|color| oklab(linear(color.into())));
+                let lab = [0, 1, 2].map(|i| lerp(start_lab[i]..=end_lab[i], t));
+                let [r, g, b] = linear_from_oklab(lab);
+                Rgba::from_rgba_unmultiplied(r, g, b, lerp(start.a..=end.a, t)).into()
+            }
+            Self::LinearRgb => {
+                let [start, end] = [start, end].map(|color| {
+                    let [r, g, b] = linear(color.into());
+                    [r, g, b, color.a]
+                });
+                let [r, g, b, a] = [0, 1, 2, 3].map(|i| lerp(start[i]..=end[i], t));
+                Rgba::from_rgba_unmultiplied(r, g, b, a).into()
+            }
+        }
+    }
+
+    /// The color `t` of the way along evenly spaced `stops`, of which there are at least two.
+    pub fn gradient_through(self, stops: &[Hsva], t: f32) -> Color32 {
+        let segments = stops.len() - 1;
+        let position = t.clamp(0.0, 1.0) * segments as f32;
+        let index = (position as usize).min(segments - 1);
+        self.gradient(stops[index], stops[index + 1], position - index as f32)
+    }
+
+    /// `color` dimmed to `factor` of its brightness, keeping its alpha.
+    pub fn dim(self, color: Color32, factor: f32) -> Color32 {
+        let [r, g, b, a] = color.to_array();
+        let factor = match self {
+            Self::Hsv => {
+                let dim = |c: u8| (c as f32 * factor).round() as u8;
+                return Color32::from_rgba_premultiplied(dim(r), dim(g), dim(b), a);
+            }
+            // Scaling all of Oklab scales the lightness evenly and keeps the hue
+            Self::Oklab => factor.powi(3),
+            Self::LinearRgb => factor,
+        };
+        let [r, g, b] = linear(Rgba::from(Color32::from_rgb(r, g, b))).map(|c| c * factor);
+        let dimmed = Color32::from(Rgba::from_rgb(r, g, b));
+        Color32::from_rgba_premultiplied(dimmed.r(), dimmed.g(), dimmed.b(), a)
+    }
+}
+
+fn linear(color: Rgba) -> [f32; 3] {
+    [color.r(), color.g(), color.b()]
+}
+
+/// Converts linear RGB to Oklab.
+pub fn oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.41222147 * r + 0.53633254 * g + 0.051445995 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+    [
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+/// Converts Oklab to linear RGB, clamped to the displayable colors.
+fn linear_from_oklab([lightness, a, b]: [f32; 3]) -> [f32; 3] {
+    let l = (lightness + 0.39633778 * a + 0.21580376 * b).powi(3);
+    let m = (lightness - 0.105561346 * a - 0.06385417 * b).powi(3);
+    let s = (lightness - 0.08948418 * a - 1.2914855 * b).powi(3);
+    [
+        4.0767417 * l - 3.3077116 * m + 0.23096994 * s,
+        -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+        -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s,
+    ]
+    .map(|c| c.clamp(0.0, 1.0))
+}
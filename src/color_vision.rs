@@ -0,0 +1,210 @@
+use crate::{
+    color_space::oklab,
+    effects::{Effect, Language},
+};
+use egui::{Rgba, Ui, ecolor::Hsva};
+use std::sync::Arc;
+
+/// Oklab distance below which the ends of the gradient are hard to tell apart.
+const MIN_DIFFERENCE: f32 = 0.08;
+/// Hue steps searched for a suggestion, one per degree.
+const HUE_STEPS: usize = 360;
+
+/// Simulates the deficiency given by `params.x` on the unpremultiplied, linear colors.
+const SIMULATION: &str = "
+vec3 to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 to_gamma(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+void main() {
+    vec4 color = texture(u_frame, v_uv);
+    if (color.a <= 0.0) {
+        out_color = color;
+        return;
+    }
+    // Filled with the rows of the matrices, so the color multiplies from the left
+    mat3 matrix;
+    if (params.x < 0.5) {
+        matrix = mat3(0.152286, 1.052583, -0.204868,
+                      0.114503, 0.786281, 0.099216,
+                      -0.003882, -0.048116, 1.051998);
+    } else if (params.x < 1.5) {
+        matrix = mat3(0.367322, 0.860646, -0.227968,
+                      0.280085, 0.672501, 0.047413,
+                      -0.011820, 0.042940, 0.968881);
+    } else {
+        matrix = mat3(1.255528, -0.076749, -0.178779,
+                      -0.078411, 0.930809, 0.147602,
+                      0.004733, 0.691367, 0.303900);
+    }
+    vec3 rgb = to_linear(clamp(color.rgb / color.a, 0.0, 1.0)) * matrix;
+    out_color = vec4(to_gamma(clamp(rgb, 0.0, 1.0)) * color.a, color.a);
+}
+";
+
+/// A color vision deficiency, with full severity.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum Deficiency {
+    /// No red cones.
+    Protanopia,
+    /// No green cones, the most common.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+impl Deficiency {
+    const ALL: [Self; 3] = [Self::Protanopia, Self::Deuteranopia, Self::Tritanopia];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Protanopia => "Protanopia",
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// Simulation matrix on linear RGB, from Machado, Oliveira and Fernandes (2009).
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// `color` as seen with the deficiency, in linear RGB.
+    fn simulate(self, color: Hsva) -> [f32; 3] {
+        let rgb = Rgba::from(color);
+        let rgb = [rgb.r(), rgb.g(), rgb.b()];
+        self.matrix().map(|row| {
+            let channel: f32 = row.iter().zip(rgb).map(|(weight, c)| weight * c).sum();
+            channel.clamp(0.0, 1.0)
+        })
+    }
+
+    /// How different `a` and `b` look with the deficiency, as a distance in Oklab.
+    fn difference(self, a: Hsva, b: Hsva) -> f32 {
+        let [a, b] = [a, b].map(|color| oklab(self.simulate(color)));
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/// Distance between two hues going around the color wheel.
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let distance = (a - b).rem_euclid(1.0);
+    distance.min(1.0 - distance)
+}
+
+/// The gradient ends with the hue of one of them turned the least to be told apart with
+/// `deficiency`, or `None` if no hue is far enough.
+fn suggest(start: Hsva, end: Hsva, deficiency: Deficiency) -> Option<(Hsva, Hsva)> {
+    let hues = (0..HUE_STEPS).map(|step| step as f32 / HUE_STEPS as f32);
+    let with_hue = |color: Hsva, h: f32| Hsva { h, ..color };
+    let ends = hues
+        .clone()
+        .map(|h| (start, with_hue(end, h), hue_distance(h, end.h)));
+    let starts = hues.map(|h| (with_hue(start, h), end, hue_distance(h, start.h)));
+    ends.chain(starts)
+        // Clearly apart, rather than only just
+        .filter(|&(start, end, _)| deficiency.difference(start, end) >= 1.5 * MIN_DIFFERENCE)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(start, end, _)| (start, end))
+}
+
+// Color vision settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ColorVisionConfig {
+    /// Shows the output as seen with `deficiency`.
+    pub simulate: bool,
+    /// Deficiency simulated and checked by the palette assist.
+    pub deficiency: Deficiency,
+    /// Warns when the ends of the rainbow gradient look alike with `deficiency`.
+    pub assist: bool,
+}
+
+impl Default for ColorVisionConfig {
+    fn default() -> Self {
+        Self {
+            simulate: false,
+            deficiency: Deficiency::Deuteranopia,
+            assist: false,
+        }
+    }
+}
+
+impl ColorVisionConfig {
+    /// The simulation, drawn after every other effect.
+    pub fn effect(&self) -> Option<Arc<Effect>> {
+        self.simulate.then(|| {
+            Arc::new(Effect {
+                name: format!("{} simulation", self.deficiency.label()),
+                language: Language::Glsl,
+                source: SIMULATION.into(),
+                params: [self.deficiency as u8 as f32, 0.0, 0.0, 0.0],
+            })
+        })
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        egui::ComboBox::from_label("Deficiency")
+            .selected_text(self.deficiency.label())
+            .show_ui(ui, |ui| {
+                for deficiency in Deficiency::ALL {
+                    ui.selectable_value(&mut self.deficiency, deficiency, deficiency.label());
+                }
+            });
+        ui.checkbox(&mut self.simulate, "Simulate on the output")
+            .on_hover_text("Shows the clock as seen with the deficiency, to check its colors");
+        ui.checkbox(&mut self.assist, "Palette assist")
+            .on_hover_text("Warns when the ends of the rainbow gradient look alike");
+    }
+
+    /// Warns when the gradient ends look alike with the deficiency, offering turned hues.
+    pub fn assist_ui(&self, ui: &mut Ui, start: &mut Hsva, end: &mut Hsva) {
+        let deficiency = self.deficiency;
+        if !self.assist || deficiency.difference(*start, *end) >= MIN_DIFFERENCE {
+            return;
+        }
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "The gradient ends look alike with {}",
+                deficiency.label().to_lowercase()
+            ),
+        );
+        if let Some((suggested_start, suggested_end)) = suggest(*start, *end, deficiency) {
+            let degrees = |color: Hsva| (color.h * 360.0).round();
+            let text = format!(
+                "Use hues {}° and {}°",
+                degrees(suggested_start),
+                degrees(suggested_end)
+            );
+            if ui.button(text).clicked() {
+                *start = suggested_start;
+                *end = suggested_end;
+            }
+        }
+    }
+}
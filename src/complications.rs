@@ -0,0 +1,281 @@
+use crate::{
+    calendar::{Calendar, CalendarConfig},
+    location::Location,
+    sun::{Sun, SunMarkers},
+    weather::{Weather, WeatherConfig},
+};
+use chrono::{DateTime, Datelike, Local, Utc};
+use egui::{Align2, Color32, FontId, Mesh, Painter, Pos2, Rect, Ui, Vec2, vec2};
+use std::f32::consts::TAU;
+
+/// Distance of the complications from the window edges.
+const MARGIN: f32 = 16.0;
+const MOON_RADIUS: f32 = 12.0;
+const FONT_SIZE: f32 = 16.0;
+/// Average length of a lunar month in days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+/// Segments along the moon's outline.
+const MOON_SEGMENTS: usize = 32;
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum Position {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Position {
+    const ALL: [Self; 8] = [
+        Self::TopLeft,
+        Self::Top,
+        Self::TopRight,
+        Self::Left,
+        Self::Right,
+        Self::BottomLeft,
+        Self::Bottom,
+        Self::BottomRight,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::TopLeft => "Top left",
+            Self::Top => "Top",
+            Self::TopRight => "Top right",
+            Self::Left => "Left",
+            Self::Right => "Right",
+            Self::BottomLeft => "Bottom left",
+            Self::Bottom => "Bottom",
+            Self::BottomRight => "Bottom right",
+        }
+    }
+
+    fn align(self) -> Align2 {
+        match self {
+            Self::TopLeft => Align2::LEFT_TOP,
+            Self::Top => Align2::CENTER_TOP,
+            Self::TopRight => Align2::RIGHT_TOP,
+            Self::Left => Align2::LEFT_CENTER,
+            Self::Right => Align2::RIGHT_CENTER,
+            Self::BottomLeft => Align2::LEFT_BOTTOM,
+            Self::Bottom => Align2::CENTER_BOTTOM,
+            Self::BottomRight => Align2::RIGHT_BOTTOM,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct Complication {
+    pub enabled: bool,
+    pub position: Position,
+    pub color: Color32,
+}
+
+impl Complication {
+    fn new(position: Position) -> Self {
+        Self {
+            enabled: false,
+            position,
+            color: Color32::from_gray(200),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, label: &str) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, label);
+            egui::ComboBox::from_id_salt(label)
+                .selected_text(self.position.label())
+                .show_ui(ui, |ui| {
+                    for position in Position::ALL {
+                        ui.selectable_value(&mut self.position, position, position.label());
+                    }
+                });
+            ui.color_edit_button_srgba(&mut self.color);
+        });
+    }
+}
+
+// Complication settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct Complications {
+    pub moon_phase: Complication,
+    /// Weekday and date.
+    pub date: Complication,
+    /// ISO week number.
+    pub week: Complication,
+    /// Temperature and condition icon.
+    pub weather: Complication,
+    pub weather_config: WeatherConfig,
+    /// Start and title of the next calendar event.
+    pub calendar: Complication,
+    pub calendar_config: CalendarConfig,
+    /// Markers on the hour dial.
+    pub sun: SunMarkers,
+    pub location: Location,
+}
+
+impl Default for Complications {
+    fn default() -> Self {
+        Self {
+            moon_phase: Complication::new(Position::TopRight),
+            date: Complication::new(Position::BottomLeft),
+            week: Complication::new(Position::BottomRight),
+            weather: Complication::new(Position::TopLeft),
+            weather_config: WeatherConfig::default(),
+            calendar: Complication::new(Position::Bottom),
+            calendar_config: CalendarConfig::default(),
+            sun: SunMarkers::default(),
+            location: Location::default(),
+        }
+    }
+}
+
+impl Complications {
+    /// Copy without the weather API key and calendar source.
+    pub fn redacted(&self) -> Self {
+        Self {
+            weather_config: self.weather_config.redacted(),
+            calendar_config: self.calendar_config.redacted(),
+            ..self.clone()
+        }
+    }
+
+    /// Hides every complication, keeping their settings.
+    pub fn disable(&mut self) {
+        for complication in [
+            &mut self.moon_phase,
+            &mut self.date,
+            &mut self.week,
+            &mut self.weather,
+            &mut self.calendar,
+        ] {
+            complication.enabled = false;
+        }
+        self.sun.enabled = false;
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, weather: &Weather, calendar: &Calendar, sun: &Sun) {
+        self.moon_phase.ui(ui, "Moon phase");
+        self.date.ui(ui, "Date");
+        self.week.ui(ui, "Week number");
+        self.weather.ui(ui, "Weather");
+        self.calendar.ui(ui, "Next event");
+        self.sun.ui(ui, sun);
+        self.location.ui(ui);
+        egui::CollapsingHeader::new("Weather provider")
+            .show(ui, |ui| self.weather_config.ui(ui, weather));
+        egui::CollapsingHeader::new("Calendar")
+            .show(ui, |ui| self.calendar_config.ui(ui, calendar));
+    }
+
+    pub fn paint(
+        &self,
+        painter: &Painter,
+        window: Rect,
+        time: &DateTime<Local>,
+        weather: &Weather,
+        calendar: &Calendar,
+    ) {
+        let window = window.shrink(MARGIN);
+        if self.moon_phase.enabled {
+            let align = self.moon_phase.position.align();
+            let rect = align.align_size_within_rect(Vec2::splat(MOON_RADIUS * 2.0), window);
+            paint_moon(
+                painter,
+                rect.center(),
+                moon_phase(time.with_timezone(&Utc)),
+                self.moon_phase.color,
+            );
+        }
+        // Without a reading yet, e.g. while offline, a placeholder is shown
+        let (weather_text, stale) = match weather.reading(&self.weather_config) {
+            Some((reading, stale)) => (
+                format!(
+                    "{} {}",
+                    reading.condition.icon(),
+                    self.weather_config.format(reading)
+                ),
+                stale,
+            ),
+            None => ("–".to_owned(), true),
+        };
+        let (event_text, no_event) = match calendar.next(time) {
+            Some((start, event)) => {
+                let format = if start.date_naive() == time.date_naive() {
+                    "%H:%M"
+                } else {
+                    "%a %H:%M"
+                };
+                (
+                    format!("📅 {} {}", start.format(format), event.summary),
+                    false,
+                )
+            }
+            None => ("📅 –".to_owned(), true),
+        };
+        for (complication, text, dimmed) in [
+            (&self.date, time.format("%a %-d %b").to_string(), false),
+            (
+                &self.week,
+                format!("Week {}", time.iso_week().week()),
+                false,
+            ),
+            (&self.weather, weather_text, stale),
+            (&self.calendar, event_text, no_event),
+        ] {
+            if complication.enabled {
+                let align = complication.position.align();
+                let color = if dimmed {
+                    complication.color.gamma_multiply(0.5)
+                } else {
+                    complication.color
+                };
+                painter.text(
+                    align.pos_in_rect(&window),
+                    align,
+                    text,
+                    FontId::proportional(FONT_SIZE),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Age of the moon as a fraction of the lunar month, where 0 is new moon and 0.5 full moon.
+fn moon_phase(time: DateTime<Utc>) -> f32 {
+    // New moon of 2000-01-06 18:14 UTC
+    const NEW_MOON: i64 = 947_182_440;
+    let days = (time.timestamp() - NEW_MOON) as f64 / 86_400.0;
+    (days / SYNODIC_MONTH).rem_euclid(1.0) as f32
+}
+
+/// Paints the lit part of the moon over a faint disc, as seen from the northern hemisphere.
+fn paint_moon(painter: &Painter, center: Pos2, phase: f32, color: Color32) {
+    painter.circle_filled(center, MOON_RADIUS, color.gamma_multiply(0.15));
+
+    // The lit part lies between the limb and the terminator, which is half an ellipse. Waxing
+    // moons are lit on the right and waning ones on the left.
+    let side = if phase < 0.5 { 1.0 } else { -1.0 };
+    let terminator = (phase * TAU).cos();
+    let mut mesh = Mesh::default();
+    for i in 0..=MOON_SEGMENTS {
+        let angle = (i as f32 / MOON_SEGMENTS as f32 - 0.5) * TAU / 2.0;
+        let (y, x) = angle.sin_cos();
+        let limb = center + vec2(side * x, y) * MOON_RADIUS;
+        let inner = center + vec2(side * terminator * x, y) * MOON_RADIUS;
+        mesh.colored_vertex(limb, color);
+        mesh.colored_vertex(inner, color);
+        if i > 0 {
+            let index = 2 * i as u32;
+            mesh.add_triangle(index - 2, index - 1, index);
+            mesh.add_triangle(index - 1, index, index + 1);
+        }
+    }
+    painter.add(mesh);
+}
@@ -0,0 +1,293 @@
+use crate::instanced::{INSTANCE_FLOATS, Renderer};
+use eframe::{
+    egui_glow,
+    glow::{self, HasContext as _},
+};
+use egui::{Color32, Context, Id, PaintCallback, Painter, emath::RectTransform};
+use std::sync::{Arc, Mutex};
+
+/// Invocations per work group, which is also the minimum guaranteed number.
+const WORK_GROUP_SIZE: u32 = 64;
+/// Work groups per dispatch at most that every implementation supports.
+const MAX_WORK_GROUPS: u32 = 65_535;
+/// Bytes of a node, its position and direction.
+const NODE_SIZE: usize = 4 * size_of::<f32>();
+
+const COMPUTE_SHADER: &str = r#"
+layout(local_size_x = 64) in;
+
+struct Node {
+    vec2 pos;
+    vec2 dir;
+};
+
+layout(std430, binding = 0) readonly buffer Current { Node current[]; };
+layout(std430, binding = 1) writeonly buffer Next { Node next[]; };
+layout(std430, binding = 2) writeonly buffer Segments { float segments[]; };
+
+// The rotors of the second and minute branches as complex numbers
+uniform vec4 u_rotors;
+// Scale and offset from the fractal space to the screen
+uniform vec4 u_to_screen;
+// Children in this level, and the instance of the first one
+uniform uint u_count;
+uniform uint u_offset;
+uniform float u_width;
+uniform uint u_color;
+
+vec2 mul(vec2 a, vec2 b) {
+    return vec2(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+void main() {
+    uint index = gl_GlobalInvocationID.x;
+    if (index >= u_count) {
+        return;
+    }
+    Node node = current[index / 2u];
+    vec2 rotor = index % 2u == 0u ? u_rotors.xy : u_rotors.zw;
+    vec2 dir = mul(rotor, node.dir);
+    vec2 pos = node.pos + dir;
+    next[index] = Node(pos, dir);
+
+    vec2 from = node.pos * u_to_screen.xy + u_to_screen.zw;
+    vec2 to = pos * u_to_screen.xy + u_to_screen.zw;
+    uint base = (u_offset + index) * 6u;
+    segments[base] = from.x;
+    segments[base + 1u] = from.y;
+    segments[base + 2u] = to.x;
+    segments[base + 3u] = to.y;
+    segments[base + 4u] = u_width;
+    segments[base + 5u] = uintBitsToFloat(u_color);
+}
+"#;
+
+/// The branches of one frame to grow on the GPU.
+pub struct Job {
+    /// Position and direction of the second and minute hands, which the branches grow from.
+    pub hands: [[f32; 4]; 2],
+    pub rotors: [f32; 4],
+    /// Width and color of every level.
+    pub levels: Vec<(f32, Color32)>,
+    pub to_screen: [f32; 4],
+}
+
+impl Job {
+    /// Scale and offset of `to_screen` for the shader.
+    pub fn transform(to_screen: &RectTransform) -> [f32; 4] {
+        let scale = to_screen.scale();
+        let offset = to_screen.to().min.to_vec2() - to_screen.from().min.to_vec2() * scale;
+        [scale.x, scale.y, offset.x, offset.y]
+    }
+
+    /// Lines of the `level`th level, counted from 0.
+    pub fn level_count(level: usize) -> usize {
+        4 << level
+    }
+
+    /// Whether a level of `count` lines fits in one dispatch.
+    pub fn dispatchable(count: usize) -> bool {
+        count <= (WORK_GROUP_SIZE * MAX_WORK_GROUPS) as usize
+    }
+
+    fn line_count(&self) -> usize {
+        (0..self.levels.len()).map(Self::level_count).sum()
+    }
+}
+
+/// The renderers, created on the first frame, and whether creating them failed.
+#[derive(Default)]
+struct Shared {
+    renderers: Option<(Expansion, Renderer)>,
+    failed: bool,
+}
+
+type SharedExpansion = Arc<Mutex<Shared>>;
+
+fn shared(ctx: &Context) -> SharedExpansion {
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<SharedExpansion>(Id::new("compute"))
+            .clone()
+    })
+}
+
+/// Whether compute shaders can be used, which is only known to fail after they were tried.
+pub fn available(ctx: &Context) -> bool {
+    shared(ctx).lock().is_ok_and(|shared| !shared.failed)
+}
+
+/// Grows the branches of `job` in a compute shader and draws them as instances, without them
+/// passing through the CPU.
+pub fn paint(painter: &Painter, job: Job) {
+    if job.levels.is_empty() {
+        return;
+    }
+    let pixels_per_point = painter.ctx().pixels_per_point();
+    let shared = shared(painter.ctx());
+    let callback = egui_glow::CallbackFn::new(move |info, painter| {
+        let Ok(mut shared) = shared.lock() else {
+            return;
+        };
+        let gl = painter.gl();
+        if shared.renderers.is_none() && !shared.failed {
+            match unsafe { Expansion::new(gl).and_then(|e| Ok((e, Renderer::new(gl)?))) } {
+                Ok(renderers) => shared.renderers = Some(renderers),
+                Err(e) => {
+                    tracing::error!("Compute shaders unavailable: {e}");
+                    shared.failed = true;
+                }
+            }
+        }
+        if let Some((expansion, renderer)) = &mut shared.renderers {
+            unsafe {
+                expansion.run(gl, &job, renderer.instance_buffer);
+                renderer.draw(gl, &info, job.line_count(), pixels_per_point);
+            }
+        }
+    });
+    painter.add(PaintCallback {
+        rect: painter.clip_rect(),
+        callback: Arc::new(callback),
+    });
+}
+
+/// The compute shader with the nodes of the current and next level, which swap every level.
+struct Expansion {
+    program: glow::Program,
+    nodes: [glow::Buffer; 2],
+    /// Nodes every node buffer holds.
+    node_capacity: usize,
+    /// Instances the instance buffer holds.
+    instance_capacity: usize,
+}
+
+impl Expansion {
+    unsafe fn new(gl: &glow::Context) -> Result<Self, String> {
+        let version = gl.version();
+        // Compute shaders arrived in OpenGL 4.3 and OpenGL ES 3.1
+        let (declaration, supported) = if version.is_embedded {
+            (
+                "#version 310 es\nprecision highp float;\nprecision highp int;\n",
+                (version.major, version.minor) >= (3, 1),
+            )
+        } else {
+            ("#version 430\n", (version.major, version.minor) >= (4, 3))
+        };
+        if !supported {
+            return Err("OpenGL 4.3 or OpenGL ES 3.1 is required".to_owned());
+        }
+        unsafe {
+            let program = link_compute_program(gl, &format!("{declaration}{COMPUTE_SHADER}"))?;
+            match [gl.create_buffer(), gl.create_buffer()] {
+                [Ok(current), Ok(next)] => Ok(Self {
+                    program,
+                    nodes: [current, next],
+                    node_capacity: 0,
+                    instance_capacity: 0,
+                }),
+                [current, next] => {
+                    for buffer in [current, next].into_iter().flatten() {
+                        gl.delete_buffer(buffer);
+                    }
+                    gl.delete_program(program);
+                    Err("Failed to create the node buffers".to_owned())
+                }
+            }
+        }
+    }
+
+    /// Grows the levels of `job` a dispatch each, writing their lines to `instances`.
+    unsafe fn run(&mut self, gl: &glow::Context, job: &Job, instances: glow::Buffer) {
+        let deepest = Job::level_count(job.levels.len() - 1);
+        let line_count = job.line_count();
+        unsafe {
+            if self.node_capacity < deepest {
+                self.node_capacity = deepest;
+                for buffer in self.nodes {
+                    gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(buffer));
+                    gl.buffer_data_size(
+                        glow::SHADER_STORAGE_BUFFER,
+                        (deepest * NODE_SIZE) as i32,
+                        glow::DYNAMIC_COPY,
+                    );
+                }
+            }
+            if self.instance_capacity < line_count {
+                self.instance_capacity = line_count;
+                gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(instances));
+                gl.buffer_data_size(
+                    glow::SHADER_STORAGE_BUFFER,
+                    (line_count * INSTANCE_FLOATS * size_of::<f32>()) as i32,
+                    glow::DYNAMIC_COPY,
+                );
+            }
+            // The CPU only uploads the hands, the rest are uniforms
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(self.nodes[0]));
+            gl.buffer_sub_data_u8_slice(
+                glow::SHADER_STORAGE_BUFFER,
+                0,
+                bytemuck::cast_slice(&job.hands),
+            );
+
+            gl.use_program(Some(self.program));
+            let uniform = |name| gl.get_uniform_location(self.program, name);
+            let [a, b, c, d] = job.rotors;
+            gl.uniform_4_f32(uniform("u_rotors").as_ref(), a, b, c, d);
+            let [a, b, c, d] = job.to_screen;
+            gl.uniform_4_f32(uniform("u_to_screen").as_ref(), a, b, c, d);
+            let (count, offset) = (uniform("u_count"), uniform("u_offset"));
+            let (width, color) = (uniform("u_width"), uniform("u_color"));
+            gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, 2, Some(instances));
+
+            let mut first = 0;
+            for (level, &(line_width, line_color)) in job.levels.iter().enumerate() {
+                let lines = Job::level_count(level);
+                gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, 0, Some(self.nodes[level % 2]));
+                gl.bind_buffer_base(
+                    glow::SHADER_STORAGE_BUFFER,
+                    1,
+                    Some(self.nodes[(level + 1) % 2]),
+                );
+                gl.uniform_1_u32(count.as_ref(), lines as u32);
+                gl.uniform_1_u32(offset.as_ref(), first as u32);
+                gl.uniform_1_f32(width.as_ref(), line_width);
+                gl.uniform_1_u32(color.as_ref(), u32::from_ne_bytes(line_color.to_array()));
+                gl.dispatch_compute((lines as u32).div_ceil(WORK_GROUP_SIZE), 1, 1);
+                // The next level reads the nodes this one wrote
+                gl.memory_barrier(glow::SHADER_STORAGE_BARRIER_BIT);
+                first += lines;
+            }
+            gl.memory_barrier(glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+            for index in 0..3 {
+                gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, index, None);
+            }
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, None);
+        }
+    }
+}
+
+unsafe fn link_compute_program(gl: &glow::Context, source: &str) -> Result<glow::Program, String> {
+    unsafe {
+        let program = gl.create_program()?;
+        let shader = gl.create_shader(glow::COMPUTE_SHADER)?;
+        gl.shader_source(shader, source);
+        gl.compile_shader(shader);
+        let result = if gl.get_shader_compile_status(shader) {
+            gl.attach_shader(program, shader);
+            gl.link_program(program);
+            gl.detach_shader(program, shader);
+            if gl.get_program_link_status(program) {
+                Ok(program)
+            } else {
+                Err(gl.get_program_info_log(program))
+            }
+        } else {
+            Err(gl.get_shader_info_log(shader))
+        };
+        gl.delete_shader(shader);
+        if result.is_err() {
+            gl.delete_program(program);
+        }
+        result
+    }
+}
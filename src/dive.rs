@@ -0,0 +1,146 @@
+use egui::{Pos2, Vec2, emath::Rot2, vec2};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Levels between the branch drawn as the root and the branch the dive is heading into, so the
+/// view is covered by the root's descendants when it re-roots.
+pub const CONTEXT_LEVELS: usize = 3;
+/// Levels summed to locate the tip of the dive, enough for the remainder to be sub-pixel.
+const TIP_LEVELS: usize = 64;
+
+// Dive animation settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct DiveConfig {
+    pub enabled: bool,
+    pub seconds_per_level: f32,
+    /// Selects the branches followed into the tree.
+    pub seed: u64,
+}
+
+impl Default for DiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seconds_per_level: 3.0,
+            seed: 0,
+        }
+    }
+}
+
+impl DiveConfig {
+    /// Index of the rotor followed at `level` below the root of the dive.
+    fn branch(&self, level: u64) -> usize {
+        (splitmix64(self.seed.wrapping_add(level)) & 1) as usize
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Endless dive");
+        ui.add(
+            egui::Slider::new(&mut self.seconds_per_level, 0.5..=30.0)
+                .logarithmic(true)
+                .text("seconds per level"),
+        );
+        if ui.button("🎲 Another branch").clicked() {
+            self.reroll();
+        }
+    }
+
+    /// Picks a new seed, so the dive follows other branches.
+    pub fn reroll(&mut self) {
+        // TOML integers are signed
+        self.seed = splitmix64(self.seed ^ random_seed()) & i64::MAX as u64;
+    }
+}
+
+/// What to draw for the current frame of the dive, in coordinates where the root branch has length 1.
+pub struct DiveFrame {
+    /// Direction of the root branch, whose end is at the origin.
+    pub root_dir: Vec2,
+    /// Point the dive is heading into.
+    pub tip: Pos2,
+    /// Length of the branch the view has zoomed into, relative to the root.
+    pub scale: f32,
+}
+
+/// Progress of the dive.
+///
+/// Instead of zooming into ever smaller coordinates, the dive re-roots the tree at the next branch
+/// on its path every level, keeping coordinates around 1 however deep it goes.
+#[derive(Default, PartialEq)]
+pub struct Dive {
+    depth: u64,
+    progress: f32,
+    last_update: Option<Instant>,
+}
+
+impl Dive {
+    pub fn update(&mut self, ctx: &egui::Context, config: &DiveConfig) {
+        let now = Instant::now();
+        if let Some(last_update) = self.last_update {
+            let elapsed = (now - last_update).as_secs_f32();
+            self.progress += elapsed / config.seconds_per_level.max(0.01);
+            while self.progress >= 1.0 {
+                self.progress -= 1.0;
+                self.depth += 1;
+            }
+        }
+        self.last_update = Some(now);
+        ctx.request_repaint();
+    }
+
+    /// Holds the dive while it is not shown, so it continues where it stopped.
+    pub fn stop(&mut self) {
+        self.last_update = None;
+    }
+
+    /// Lays out the frame for the branch `rotors`, which scale and rotate a branch into its children.
+    pub fn frame(&self, config: &DiveConfig, rotors: &[Rot2; 2]) -> DiveFrame {
+        // Turn with the next branch while approaching it, so re-rooting does not rotate the view
+        let next = rotors[config.branch(self.depth + 1)];
+        let next_angle = next.angle();
+        let root_dir = Rot2::from_angle(-self.progress * next_angle) * vec2(1.0, 0.0);
+
+        let mut tip = Pos2::ZERO;
+        let mut dir = root_dir;
+        for level in 1..=TIP_LEVELS as u64 {
+            dir = rotors[config.branch(self.depth + level)] * dir;
+            tip += dir;
+            if dir.length_sq() < 1e-12 {
+                break;
+            }
+        }
+
+        // The branches followed may differ in length, so their lengths are multiplied up
+        let scale = (1..=CONTEXT_LEVELS as u64)
+            .map(|level| rotors[config.branch(self.depth + level)].length())
+            .product::<f32>()
+            * rotors[config.branch(self.depth + CONTEXT_LEVELS as u64 + 1)]
+                .length()
+                .powf(self.progress);
+
+        DiveFrame {
+            root_dir,
+            tip,
+            scale,
+        }
+    }
+
+    /// Fraction of the way to the next level, used to grow line widths continuously.
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+}
+
+/// Seed differing between calls, for picking random settings.
+pub fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
+
+pub fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,378 @@
+use eframe::glow::{self, HasContext as _};
+use egui::{Context, Id};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+/// Bytes of the globals every effect gets: the resolution, the time padded to 16 bytes and the
+/// parameters of the effect.
+const GLOBALS_SIZE: usize = 8 * size_of::<f32>();
+
+/// Declarations the built-in GLSL effects are compiled with.
+const GLSL_HEADER: &str = "layout(std140) uniform Globals {
+    vec2 resolution;
+    float time;
+    vec4 params;
+};
+uniform sampler2D u_frame;
+in vec2 v_uv;
+out vec4 out_color;
+";
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Language {
+    /// User shaders with a fragment entry point, translated to GLSL.
+    Wgsl,
+    /// Built-in effects, a `main` function using the declarations of `GLSL_HEADER`.
+    Glsl,
+}
+
+/// A post-processing effect the frame is drawn through on the GPU.
+#[derive(PartialEq)]
+pub struct Effect {
+    pub name: String,
+    pub language: Language,
+    /// Fragment shader source, compiled when first drawn.
+    pub source: Arc<str>,
+    /// Given to the shader after the globals.
+    pub params: [f32; 4],
+}
+
+/// Errors of the effects by name, written when they are compiled for drawing.
+pub type SharedErrors = Arc<Mutex<BTreeMap<String, String>>>;
+
+pub fn errors(ctx: &Context) -> SharedErrors {
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<SharedErrors>(Id::new("effect_errors"))
+            .clone()
+    })
+}
+
+/// An effect compiled into a program, with the names it binds the frame and the globals by.
+struct Program {
+    program: glow::Program,
+    samplers: Vec<String>,
+    blocks: Vec<String>,
+}
+
+/// The compiled effects and the textures the frame passes through between them.
+pub struct Chain {
+    vertex_array: glow::VertexArray,
+    globals: glow::Buffer,
+    /// Programs by source, or the error they failed with.
+    programs: HashMap<Arc<str>, Result<Program, String>>,
+    errors: SharedErrors,
+    targets: [(glow::Framebuffer, glow::Texture); 2],
+    size: [i32; 2],
+}
+
+impl Chain {
+    pub unsafe fn new(gl: &glow::Context, errors: SharedErrors) -> Result<Self, String> {
+        unsafe {
+            let vertex_array = gl.create_vertex_array()?;
+            let globals = gl.create_buffer()?;
+            gl.bind_buffer(glow::UNIFORM_BUFFER, Some(globals));
+            gl.buffer_data_size(
+                glow::UNIFORM_BUFFER,
+                GLOBALS_SIZE as i32,
+                glow::DYNAMIC_DRAW,
+            );
+            gl.bind_buffer(glow::UNIFORM_BUFFER, None);
+            let target =
+                || -> Result<_, String> { Ok((gl.create_framebuffer()?, gl.create_texture()?)) };
+            let targets = [target()?, target()?];
+            Ok(Self {
+                vertex_array,
+                globals,
+                programs: HashMap::new(),
+                errors,
+                targets,
+                size: [0, 0],
+            })
+        }
+    }
+
+    /// Draws the `frame` texture of `size` pixels through `effects`, each into the input of the
+    /// next, with the last one drawn by `to_screen` once it bound the window. Returns `false`
+    /// without drawing if none of the effects compiled.
+    pub unsafe fn apply(
+        &mut self,
+        gl: &glow::Context,
+        effects: &[Arc<Effect>],
+        frame: glow::Texture,
+        size: [i32; 2],
+        time: f32,
+        to_screen: impl FnOnce(),
+    ) -> bool {
+        // Programs of shaders that were edited or removed are deleted
+        self.programs.retain(|source, program| {
+            let used = effects.iter().any(|effect| effect.source == *source);
+            if !used && let Ok(program) = program {
+                unsafe { gl.delete_program(program.program) };
+            }
+            used
+        });
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.clear();
+            for effect in effects {
+                let program = self
+                    .programs
+                    .entry(effect.source.clone())
+                    .or_insert_with(|| {
+                        let program = unsafe { compile(gl, effect.language, &effect.source) };
+                        if let Err(e) = &program {
+                            tracing::warn!("Failed to compile effect {}: {e}", effect.name);
+                        }
+                        program
+                    });
+                if let Err(e) = program {
+                    errors.insert(effect.name.clone(), e.clone());
+                }
+            }
+        }
+        let compiled =
+            |effect: &Arc<Effect>| matches!(self.programs.get(&effect.source), Some(Ok(_)));
+        if !effects.iter().any(compiled) {
+            return false;
+        }
+
+        unsafe {
+            self.resize(gl, size);
+            let programs: Vec<_> = effects
+                .iter()
+                .filter_map(|effect| {
+                    let program = self.programs.get(&effect.source)?.as_ref().ok()?;
+                    Some((program, effect.params))
+                })
+                .collect();
+            gl.bind_buffer(glow::UNIFORM_BUFFER, Some(self.globals));
+            let globals = [size[0] as f32, size[1] as f32, time, 0.0];
+            gl.buffer_sub_data_u8_slice(glow::UNIFORM_BUFFER, 0, bytemuck::cast_slice(&globals));
+            gl.bind_buffer_base(glow::UNIFORM_BUFFER, 0, Some(self.globals));
+            gl.bind_vertex_array(Some(self.vertex_array));
+            gl.active_texture(glow::TEXTURE0);
+
+            let mut input = frame;
+            let last = programs.len() - 1;
+            let mut to_screen = Some(to_screen);
+            for (index, (program, params)) in programs.into_iter().enumerate() {
+                let (framebuffer, texture) = self.targets[index % 2];
+                if index == last {
+                    if let Some(to_screen) = to_screen.take() {
+                        to_screen();
+                    }
+                } else {
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+                    gl.viewport(0, 0, size[0], size[1]);
+                    gl.disable(glow::BLEND);
+                }
+                let offset = size_of_val(&globals) as i32;
+                gl.buffer_sub_data_u8_slice(
+                    glow::UNIFORM_BUFFER,
+                    offset,
+                    bytemuck::cast_slice(&params),
+                );
+                gl.use_program(Some(program.program));
+                for sampler in &program.samplers {
+                    let location = gl.get_uniform_location(program.program, sampler);
+                    gl.uniform_1_i32(location.as_ref(), 0);
+                }
+                for block in &program.blocks {
+                    if let Some(index) = gl.get_uniform_block_index(program.program, block) {
+                        gl.uniform_block_binding(program.program, index, 0);
+                    }
+                }
+                gl.bind_texture(glow::TEXTURE_2D, Some(input));
+                gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+                gl.enable(glow::BLEND);
+                input = texture;
+            }
+
+            gl.bind_buffer_base(glow::UNIFORM_BUFFER, 0, None);
+            gl.bind_buffer(glow::UNIFORM_BUFFER, None);
+            gl.bind_vertex_array(None);
+        }
+        true
+    }
+
+    unsafe fn resize(&mut self, gl: &glow::Context, size: [i32; 2]) {
+        if self.size == size {
+            return;
+        }
+        self.size = size;
+        for (framebuffer, texture) in self.targets {
+            unsafe {
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                gl.tex_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    glow::RGBA8 as i32,
+                    size[0],
+                    size[1],
+                    0,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                    glow::PixelUnpackData::Slice(None),
+                );
+                for (parameter, value) in [
+                    (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+                    (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+                    (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                    (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+                ] {
+                    gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+                }
+                gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+                gl.framebuffer_texture_2d(
+                    glow::FRAMEBUFFER,
+                    glow::COLOR_ATTACHMENT0,
+                    glow::TEXTURE_2D,
+                    Some(texture),
+                    0,
+                );
+            }
+        }
+    }
+}
+
+/// GLSL of an effect, with the names of its samplers and uniform blocks.
+struct Translated {
+    fragment: String,
+    samplers: Vec<String>,
+    blocks: Vec<String>,
+}
+
+/// Translates `wgsl` to GLSL 3.30, or GLSL ES 3.00 if `embedded`.
+#[cfg(feature = "shaders")]
+fn translate(wgsl: &str, embedded: bool) -> Result<Translated, String> {
+    use naga::back::glsl;
+
+    let module = naga::front::wgsl::parse_str(wgsl).map_err(|e| e.emit_to_string(wgsl))?;
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|e| e.emit_to_string(wgsl))?;
+    let entry_point = module
+        .entry_points
+        .iter()
+        .find(|entry_point| entry_point.stage == naga::ShaderStage::Fragment)
+        .ok_or("The shader has no @fragment entry point")?
+        .name
+        .clone();
+    let options = glsl::Options {
+        version: if embedded {
+            glsl::Version::new_gles(300)
+        } else {
+            glsl::Version::Desktop(330)
+        },
+        writer_flags: glsl::WriterFlags::empty(),
+        ..Default::default()
+    };
+    let pipeline_options = glsl::PipelineOptions {
+        shader_stage: naga::ShaderStage::Fragment,
+        entry_point,
+        multiview: None,
+    };
+    let mut fragment = String::new();
+    let reflection = glsl::Writer::new(
+        &mut fragment,
+        &module,
+        &info,
+        &options,
+        &pipeline_options,
+        naga::proc::BoundsCheckPolicies::default(),
+    )
+    .and_then(|mut writer| writer.write())
+    .map_err(|e| e.to_string())?;
+    Ok(Translated {
+        fragment,
+        samplers: reflection.texture_mapping.into_keys().collect(),
+        blocks: reflection.uniforms.into_values().collect(),
+    })
+}
+
+#[cfg(not(feature = "shaders"))]
+fn translate(_wgsl: &str, _embedded: bool) -> Result<Translated, String> {
+    Err("Shader effects require building with `--features shaders`".to_owned())
+}
+
+/// Compiles and links `source` with a vertex shader covering the window, which hands the
+/// fragment shader its coordinates, at `@location(0)` in WGSL.
+unsafe fn compile(gl: &glow::Context, language: Language, source: &str) -> Result<Program, String> {
+    let embedded = gl.version().is_embedded;
+    let declaration = if embedded {
+        "#version 300 es"
+    } else {
+        "#version 330 core"
+    };
+    let (translated, coordinates) = match language {
+        // Named like the fragment inputs of the translated shader
+        Language::Wgsl => (translate(source, embedded)?, "_vs2fs_location0"),
+        Language::Glsl => {
+            let precision = if embedded {
+                "precision highp float;\n"
+            } else {
+                ""
+            };
+            let translated = Translated {
+                fragment: format!("{declaration}\n{precision}{GLSL_HEADER}{source}"),
+                samplers: vec!["u_frame".to_owned()],
+                blocks: vec!["Globals".to_owned()],
+            };
+            (translated, "v_uv")
+        }
+    };
+    let vertex = format!(
+        "{declaration}
+out vec2 {coordinates};
+
+void main() {{
+    vec2 corner = vec2(float(gl_VertexID % 2), float(gl_VertexID / 2));
+    {coordinates} = corner;
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}}
+"
+    );
+    unsafe {
+        let program = gl.create_program()?;
+        let mut shaders = Vec::new();
+        let mut result = Ok(());
+        for (kind, source) in [
+            (glow::VERTEX_SHADER, vertex.as_str()),
+            (glow::FRAGMENT_SHADER, translated.fragment.as_str()),
+        ] {
+            let shader = gl.create_shader(kind)?;
+            gl.shader_source(shader, source);
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                result = Err(gl.get_shader_info_log(shader));
+            }
+            gl.attach_shader(program, shader);
+            shaders.push(shader);
+        }
+        if result.is_ok() {
+            gl.link_program(program);
+            if !gl.get_program_link_status(program) {
+                result = Err(gl.get_program_info_log(program));
+            }
+        }
+        for shader in shaders {
+            gl.detach_shader(program, shader);
+            gl.delete_shader(shader);
+        }
+        match result {
+            Ok(()) => Ok(Program {
+                program,
+                samplers: translated.samplers,
+                blocks: translated.blocks,
+            }),
+            Err(e) => {
+                gl.delete_program(program);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{FractalClock, fractal_clock::FractalClockConfig, migration, palette};
+use egui::{Align2, Color32, DroppedFile, FontId, Id, LayerId, Order};
+use std::path::Path;
+#[cfg(feature = "export")]
+use std::path::PathBuf;
+
+/// A file dropped onto the window.
+pub enum Dropped {
+    /// Settings to apply, from a config file or a file of the clock's settings alone.
+    Settings(Box<FractalClock>),
+    /// The clock's look alone, from a saved preset.
+    Preset(Box<FractalClockConfig>),
+    /// Colors for the gradient.
+    Palette(Vec<Color32>),
+    /// Image to paint behind the clock.
+    #[cfg(feature = "export")]
+    Background(PathBuf),
+}
+
+/// Reads a dropped file by its extension.
+pub fn read(file: &DroppedFile) -> Result<Dropped, String> {
+    let name = file.path.as_deref().unwrap_or(Path::new(&file.name));
+    let extension = name
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "json" | "toml" => parse_settings(&contents(file)?, extension == "json"),
+        "gpl" | "hex" | "txt" => palette::parse(&contents(file)?).map(Dropped::Palette),
+        #[cfg(feature = "export")]
+        "png" | "jpg" | "jpeg" | "webp" | "bmp" | "gif" => file
+            .path
+            .clone()
+            .map(Dropped::Background)
+            .ok_or_else(|| "Dropped images need a path".to_owned()),
+        #[cfg(not(feature = "export"))]
+        "png" | "jpg" | "jpeg" | "webp" | "bmp" | "gif" => {
+            Err("Background images need the export feature".to_owned())
+        }
+        _ => Err(format!("Unknown file type: {}", name.display())),
+    }
+}
+
+fn contents(file: &DroppedFile) -> Result<String, String> {
+    match (&file.bytes, &file.path) {
+        (Some(bytes), _) => String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string()),
+        (None, Some(path)) => std::fs::read_to_string(path).map_err(|e| e.to_string()),
+        (None, None) => Err("The file is empty".to_owned()),
+    }
+}
+
+fn parse_settings(text: &str, json: bool) -> Result<Dropped, String> {
+    let value = parse_value(text, json)?;
+    // Presets are the look alone, which the clock's settings keep under `config`. As every
+    // setting of the clock has a default, reading a preset as them would reset all the rest.
+    if value.get("config").is_none() {
+        return value
+            .try_into()
+            .map(|config| Dropped::Preset(Box::new(config)))
+            .map_err(|e: toml::de::Error| e.to_string());
+    }
+    value
+        .try_into()
+        .map(|clock| Dropped::Settings(Box::new(clock)))
+        .map_err(|e: toml::de::Error| e.to_string())
+}
+
+/// The clock's settings in a JSON or TOML file, migrated to the current layout.
+pub fn parse_value(text: &str, json: bool) -> Result<toml::Value, String> {
+    let mut value: toml::Value = if json {
+        serde_json::from_str(text).map_err(|e| e.to_string())?
+    } else {
+        toml::from_str(text).map_err(|e| e.to_string())?
+    };
+    // Config files keep the clock's settings next to the window geometry
+    if let Some(settings) = value.get("fractal_clock") {
+        value = settings.clone();
+    }
+    migration::migrate(&mut value);
+    Ok(value)
+}
+
+/// Shades the window while files are dragged over it, naming what can be dropped.
+pub fn paint_overlay(ctx: &egui::Context) {
+    if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+        return;
+    }
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("file_drop")));
+    let rect = ctx.screen_rect();
+    painter.rect_filled(rect, 0.0, Color32::from_black_alpha(180));
+    painter.text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        "Drop settings, a palette or an image",
+        FontId::proportional(24.0),
+        Color32::WHITE,
+    );
+}
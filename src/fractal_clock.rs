@@ -1,6 +1,88 @@
-use chrono::{DateTime, Local, Timelike};
+#[cfg(feature = "audio")]
+use crate::audio::{Audio, AudioConfig};
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{Action, Gamepad, GamepadConfig};
+#[cfg(feature = "hotkey")]
+use crate::hotkey::HotkeyConfig;
+use crate::{
+    about::AboutWindow,
+    alarms::{AlarmsConfig, Scheduler},
+    allocations,
+    autostart::LaunchAtLogin,
+    bookmarks::Bookmarks,
+    calendar::Calendar,
+    chat::{Chat, ChatConfig},
+    color_space::ColorSpace,
+    color_vision::ColorVisionConfig,
+    complications::Complications,
+    compute,
+    dive::{self, Dive, DiveConfig},
+    effects::Effect,
+    heatmap::HeatmapConfig,
+    history::{History, HistoryConfig},
+    idle::{IdleWatcher, ScreensaverConfig},
+    instanced::{self, LineBackend},
+    jitter::{self, JitterConfig},
+    joints::JointsConfig,
+    location::Location,
+    lock::LockButton,
+    logging::LogViewer,
+    metrics::{self, FrameMetrics, MetricsLog},
+    migration,
+    morph::{self, MorphAction, MorphConfig},
+    night_mode::{NightMode, dim},
+    ntp::{Ntp, NtpConfig},
+    oled::OledProtection,
+    palette::{PaletteAction, PaletteImport},
+    playback::PlaybackConfig,
+    post_processing::PostProcessingConfig,
+    power::{BatteryProfile, PowerMonitor},
+    precision::{Complex, Real, ScreenTransform},
+    profiler::{self, ProfilerWindow},
+    profiles::{PROFILE_KEYS, ProfileAction, ProfileState, SessionProfiles},
+    prometheus::{PrometheusConfig, PrometheusServer},
+    readout::{self, ReadoutConfig},
+    refinement::{Governor, RefinementConfig},
+    remote::{RemoteConfig, RemoteServer},
+    render_scale::{self, BlendMode, RENDER_SCALE_RANGE},
+    settings_sync::{self, SettingsSync, SyncConfig},
+    shaders::{ShaderEffectsConfig, ShaderLibrary},
+    shadow::ShadowConfig,
+    shape_pool::{self, ShapePool},
+    simd,
+    slideshow::{Slideshow, SlideshowConfig},
+    stereo::{Eye, StereoConfig, StereoMode},
+    stopwatch::{self, Stopwatch, StopwatchConfig},
+    sun::Sun,
+    three_d::{Branch, Quat, ThreeDConfig, Vec3},
+    time_system::TimeSystem,
+    timeline::{Timeline, TimelineConfig},
+    toasts, watchdog,
+    weather::Weather,
+    widget_window::WidgetWindow,
+    wind::WindConfig,
+    window_geometry::DEFAULT_WINDOW_SIZE,
+    window_stacking::WindowStacking,
+    worker::{self, Interpolation, Worker},
+    world_map::WorldMapConfig,
+    x11_hints::X11Hints,
+};
+#[cfg(feature = "export")]
+use crate::{
+    ascii_art::{self, AsciiArtConfig, Destination},
+    background::Background,
+    burst::BurstConfig,
+    clipboard::Clipboard,
+    headless,
+    output::{FrameOutput, OutputConfig},
+    poster::{Poster, PosterConfig},
+    search::{self, Candidate, Search, SearchAction, SearchConfig, SearchMessage},
+    timelapse::{self, ClipRender, Timelapse, TimelapseAction, TimelapseConfig},
+    timeline::TimelineAction,
+};
+use chrono::{DateTime, Local, NaiveTime, TimeZone as _, Timelike};
 use egui::{
-    Color32, Painter, Pos2, Rect, Shape, Stroke, Ui, Vec2,
+    Color32, Key, Painter, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2,
     containers::{CollapsingHeader, Frame},
     emath,
     epaint::Hsva,
@@ -9,39 +91,409 @@ use egui::{
 };
 use std::{
     f32::consts::TAU,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
+/// Length of the hour hand, which does not branch.
+const HOUR_HAND_LENGTH: f32 = 0.5;
+/// Branch depth drawn in the overview shown while zoomed in.
+const MINIMAP_DEPTH: usize = 8;
+pub(crate) const ZOOM_RANGE: RangeInclusive<f32> = 0.01..=1000.0;
+/// Zoom factor of one `+` or `-` key press.
+const ZOOM_STEP: f32 = 1.25;
+/// Limit of the branch depth when it grows with the zoom.
+const MAX_ADAPTIVE_DEPTH: usize = 48;
+/// Narrowest line drawn for branches added by the adaptive depth, so they stay visible.
+const MIN_ADAPTIVE_LINE_WIDTH: f32 = 0.5;
+/// Narrowest line drawn on e-ink displays, where thin lines are lost to dithering.
+const EINK_MIN_LINE_WIDTH: f32 = 2.0;
+const DEFAULT_LINE_CAP: usize = 2_000_000;
+/// Room in the shape buffer besides the branches, for the hands, the face and the complications.
+const EXTRA_SHAPES: usize = 256;
+/// Buffers are sized for this many times the lines drawn in the last frame, for the view moving.
+const CAPACITY_HEADROOM: f32 = 1.25;
+const LINE_CAP_RANGE: RangeInclusive<usize> = 10_000..=50_000_000;
+pub(crate) const DEPTH_RANGE: RangeInclusive<usize> = 0..=20;
+pub(crate) const FACTOR_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub(crate) const BRANCH_SCALE_RANGE: RangeInclusive<f32> = 0.5..=1.5;
+pub(crate) const START_LINE_WIDTH_RANGE: RangeInclusive<f32> = 0.0..=5.0;
+pub(crate) const SPIRAL_RANGE: RangeInclusive<f32> = -45.0..=45.0;
+pub(crate) const GRAVITY_RANGE: RangeInclusive<f32> = -5.0..=5.0;
+
+/// How the zoom follows the size of the fractal.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+enum AutoZoom {
+    /// Use the zoom slider.
+    Off,
+    /// The whole tree stays inside the window.
+    Fit,
+    /// The tree covers the whole window, cropping it along the shorter side.
+    Fill,
+}
+
+/// Period over which the branches grow from the hands to the full depth.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+enum Growth {
+    Off,
+    Minute,
+    Hour,
+}
+
+/// Rendering tuned for a kind of display.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+enum RenderProfile {
+    Standard,
+    /// Thick black lines on white without gradients or animation, redrawn every `eink_interval`.
+    EInk,
+}
+
+impl RenderProfile {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::EInk => "E-ink",
+        }
+    }
+}
+
+/// How often the e-ink profile redraws the clock, as every refresh flashes the display.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+enum EInkInterval {
+    Second,
+    Minute,
+}
+
+/// How the square fractal space is mapped to the window.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+enum ScalingMode {
+    /// The square fits the shorter side of the window.
+    Fit,
+    /// The square covers the longer side of the window.
+    Fill,
+    /// The square is stretched to the window, distorting the fractal.
+    Stretch,
+    /// The square keeps the size it has in a default sized window.
+    Native,
+}
+
+impl ScalingMode {
+    const ALL: [Self; 4] = [Self::Fit, Self::Fill, Self::Stretch, Self::Native];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Fit => "Fit",
+            Self::Fill => "Fill",
+            Self::Stretch => "Stretch",
+            Self::Native => "1:1",
+        }
+    }
+
+    /// Part of the window the fractal space is mapped to.
+    fn viewport(self, rect: Rect) -> Rect {
+        let side = match self {
+            Self::Fit => rect.size().min_elem(),
+            Self::Fill => rect.size().max_elem(),
+            Self::Stretch => return rect,
+            Self::Native => DEFAULT_WINDOW_SIZE.min_elem(),
+        };
+        Rect::from_center_size(rect.center(), Vec2::splat(side))
+    }
+}
+
 // Configuration parameters
 #[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
 pub struct FractalClockConfig {
+    profile: RenderProfile,
+    eink_interval: EInkInterval,
     zoom: f32,
+    auto_zoom: AutoZoom,
+    scaling_mode: ScalingMode,
+    letterbox: bool,
+    letterbox_color: Color32,
+    minimap: bool,
+    render_scale: f32,
+    blend_mode: BlendMode,
+    /// How the lines are sent to the GPU at a render scale of 1 with the normal blend mode.
+    line_backend: LineBackend,
+    adaptive_depth: bool,
+    double_precision: bool,
+    /// Generates the branches on a background thread, drawing the latest finished frame.
+    async_generation: bool,
+    /// Eases between the two latest frames of the background thread.
+    interpolate: bool,
     start_line_width: f32,
+    /// Line widths in points, which look the same at any DPI, instead of physical pixels.
+    dpi_aware_widths: bool,
+    time_system: TimeSystem,
+    jitter: JitterConfig,
+    joints: JointsConfig,
+    wind: WindConfig,
+    /// Degrees every level of branches droops by, or rises by if negative.
+    gravity: f32,
+    growth: Growth,
+    /// Degrees every generation of branches is turned by on top of the hand angles.
+    spiral: f32,
     depth: usize,
+    /// Branches generated per frame at most, beyond which the deepest levels are dropped.
+    line_cap: usize,
     length_factor: f32,
+    /// Multiplies `length_factor` for the branches turned like the second hand.
+    second_scale: f32,
+    /// Multiplies `length_factor` for the branches turned like the minute hand.
+    minute_scale: f32,
     luminance_factor: f32,
+    /// Space the rainbow gradient is blended and the branch color dimmed in.
+    color_space: ColorSpace,
     width_factor: f32,
+    /// Draws the hands and branches as solid limbs tapering to the width of their children.
+    fill: bool,
+    shadow: ShadowConfig,
+    post_processing: PostProcessingConfig,
     branch_color: Color32,
     hand_color: Color32,
     rainbow_mode: bool,
+    heatmap: HeatmapConfig,
     start_hsv: Hsva,
     end_hsv: Hsva,
+    /// Colors the gradient runs through between `start_hsv` and `end_hsv`, evenly spaced.
+    gradient_stops: Vec<Hsva>,
 }
 
 impl Default for FractalClockConfig {
     fn default() -> Self {
         Self {
+            profile: RenderProfile::Standard,
+            eink_interval: EInkInterval::Minute,
             zoom: 0.5,
+            auto_zoom: AutoZoom::Off,
+            scaling_mode: ScalingMode::Fit,
+            letterbox: false,
+            letterbox_color: Color32::BLACK,
+            minimap: true,
+            render_scale: 1.0,
+            blend_mode: BlendMode::Normal,
+            line_backend: LineBackend::Shapes,
+            adaptive_depth: true,
+            double_precision: false,
+            async_generation: false,
+            interpolate: true,
             start_line_width: 5.0,
+            dpi_aware_widths: true,
+            time_system: TimeSystem::Standard,
+            jitter: JitterConfig::default(),
+            joints: JointsConfig::default(),
+            wind: WindConfig::default(),
+            gravity: 0.0,
+            growth: Growth::Off,
+            spiral: 0.0,
             depth: 15,
+            line_cap: DEFAULT_LINE_CAP,
             length_factor: 0.75,
+            second_scale: 1.0,
+            minute_scale: 1.0,
             luminance_factor: 1.0,
+            color_space: ColorSpace::Hsv,
             width_factor: 0.75,
+            fill: false,
+            shadow: ShadowConfig::default(),
+            post_processing: PostProcessingConfig::default(),
             branch_color: Color32::from_rgb(115, 186, 37),
             hand_color: Color32::WHITE,
             rainbow_mode: true,
+            heatmap: HeatmapConfig::default(),
             start_hsv: Hsva::from_rgb([255.0, 0.0, 0.0]),
             end_hsv: Hsva::from_rgb([0.0, 0.0, 255.0]),
+            gradient_stops: Vec::new(),
+        }
+    }
+}
+
+impl FractalClockConfig {
+    pub fn builder() -> FractalClockConfigBuilder {
+        FractalClockConfigBuilder::default()
+    }
+
+    /// Settings `t` of the way from `a` to `b`. Numbers and colors are blended, while switches
+    /// and choices flip halfway.
+    pub(crate) fn morph(a: &Self, b: &Self, t: f32) -> Self {
+        let mut config = if t < 0.5 { a.clone() } else { b.clone() };
+        config.zoom = morph::lerp(a.zoom, b.zoom, t);
+        config.letterbox_color = morph::lerp_color(a.letterbox_color, b.letterbox_color, t);
+        config.render_scale = morph::lerp(a.render_scale, b.render_scale, t);
+        config.start_line_width = morph::lerp(a.start_line_width, b.start_line_width, t);
+        config.jitter.amount = morph::lerp(a.jitter.amount, b.jitter.amount, t);
+        config.joints.segments = morph::lerp_usize(a.joints.segments, b.joints.segments, t);
+        config.joints.curvature = morph::lerp(a.joints.curvature, b.joints.curvature, t);
+        config.wind.strength = morph::lerp(a.wind.strength, b.wind.strength, t);
+        config.wind.gustiness = morph::lerp(a.wind.gustiness, b.wind.gustiness, t);
+        config.gravity = morph::lerp(a.gravity, b.gravity, t);
+        config.spiral = morph::lerp(a.spiral, b.spiral, t);
+        config.depth = morph::lerp_usize(a.depth, b.depth, t);
+        config.line_cap = morph::lerp_usize(a.line_cap, b.line_cap, t);
+        config.length_factor = morph::lerp(a.length_factor, b.length_factor, t);
+        config.second_scale = morph::lerp(a.second_scale, b.second_scale, t);
+        config.minute_scale = morph::lerp(a.minute_scale, b.minute_scale, t);
+        config.luminance_factor = morph::lerp(a.luminance_factor, b.luminance_factor, t);
+        config.width_factor = morph::lerp(a.width_factor, b.width_factor, t);
+        config.shadow.offset = a.shadow.offset + (b.shadow.offset - a.shadow.offset) * t;
+        config.shadow.color = morph::lerp_color(a.shadow.color, b.shadow.color, t);
+        config.shadow.extra_width = morph::lerp(a.shadow.extra_width, b.shadow.extra_width, t);
+        config.branch_color = morph::lerp_color(a.branch_color, b.branch_color, t);
+        config.hand_color = morph::lerp_color(a.hand_color, b.hand_color, t);
+        config.heatmap.cell_size = morph::lerp(a.heatmap.cell_size, b.heatmap.cell_size, t);
+        config.start_hsv = morph::lerp_hsva(a.start_hsv, b.start_hsv, t);
+        config.end_hsv = morph::lerp_hsva(a.end_hsv, b.end_hsv, t);
+        config
+    }
+}
+
+/// Builds a [`FractalClockConfig`] from the defaults, clamping every value like the settings do.
+///
+/// ```
+/// let config = fractal_clock::FractalClockConfig::builder()
+///     .depth(12)
+///     .rainbow_mode(false)
+///     .branch_color(egui::Color32::GOLD)
+///     .build();
+/// assert_eq!(config.depth(), 12);
+/// ```
+#[derive(Default)]
+pub struct FractalClockConfigBuilder {
+    config: FractalClockConfig,
+}
+
+impl FractalClockConfigBuilder {
+    pub fn build(self) -> FractalClockConfig {
+        self.config
+    }
+}
+
+/// Adds a getter and a setter to the config, and a method to the builder, for every plain
+/// setting. Setters clamp the value to the range if given.
+macro_rules! accessors {
+    ($($(#[$doc:meta])* $field:ident, $setter:ident: $ty:ty $(=> $range:expr)?;)*) => {
+        impl FractalClockConfig {
+            $(
+                $(#[$doc])*
+                pub fn $field(&self) -> $ty {
+                    self.$field
+                }
+
+                $(#[$doc])*
+                pub fn $setter(&mut self, value: $ty) {
+                    $(let value = value.clamp(*$range.start(), *$range.end());)?
+                    self.$field = value;
+                }
+            )*
+        }
+
+        impl FractalClockConfigBuilder {
+            $(
+                $(#[$doc])*
+                pub fn $field(mut self, value: $ty) -> Self {
+                    self.config.$setter(value);
+                    self
+                }
+            )*
+        }
+    };
+}
+
+accessors! {
+    /// Magnification of the clock, which fits the window at 1.
+    zoom, set_zoom: f32 => ZOOM_RANGE;
+    /// Hides what falls outside the viewport of the scaling mode behind bars.
+    letterbox, set_letterbox: bool;
+    letterbox_color, set_letterbox_color: Color32;
+    /// Shows an overview of the whole clock while zoomed in.
+    minimap, set_minimap: bool;
+    /// Resolution the lines are drawn at relative to the window.
+    render_scale, set_render_scale: f32 => RENDER_SCALE_RANGE;
+    /// Adds branch levels as the zoom magnifies the smallest branches.
+    adaptive_depth, set_adaptive_depth: bool;
+    /// Computes the branches in `f64`, which stay precise when zoomed in far.
+    double_precision, set_double_precision: bool;
+    /// Generates the branches on a background thread, drawing the latest finished frame.
+    async_generation, set_async_generation: bool;
+    /// Eases between the two latest frames of the background thread.
+    interpolate, set_interpolate: bool;
+    /// Width of the hands in points.
+    start_line_width, set_start_line_width: f32 => START_LINE_WIDTH_RANGE;
+    /// Line widths in points, which look the same at any DPI, instead of physical pixels.
+    dpi_aware_widths, set_dpi_aware_widths: bool;
+    /// Degrees every level of branches droops by, or rises by if negative.
+    gravity, set_gravity: f32 => GRAVITY_RANGE;
+    /// Degrees every generation of branches is turned by on top of the hand angles.
+    spiral, set_spiral: f32 => SPIRAL_RANGE;
+    /// Levels of branches.
+    depth, set_depth: usize => DEPTH_RANGE;
+    /// Branches generated per frame at most, beyond which the deepest levels are dropped.
+    line_cap, set_line_cap: usize => LINE_CAP_RANGE;
+    /// Length of every branch relative to its parent.
+    length_factor, set_length_factor: f32 => FACTOR_RANGE;
+    /// Multiplies `length_factor` for the branches turned like the second hand.
+    second_scale, set_second_scale: f32 => BRANCH_SCALE_RANGE;
+    /// Multiplies `length_factor` for the branches turned like the minute hand.
+    minute_scale, set_minute_scale: f32 => BRANCH_SCALE_RANGE;
+    /// Brightness of every level of branches relative to the previous one.
+    luminance_factor, set_luminance_factor: f32 => FACTOR_RANGE;
+    /// Width of every branch relative to its parent.
+    width_factor, set_width_factor: f32 => FACTOR_RANGE;
+    /// Draws the hands and branches as solid limbs tapering to the width of their children.
+    fill, set_fill: bool;
+    /// Color of the branches when not in rainbow mode.
+    branch_color, set_branch_color: Color32;
+    hand_color, set_hand_color: Color32;
+    /// Colors the levels of branches from `start_hsv` to `end_hsv`.
+    rainbow_mode, set_rainbow_mode: bool;
+    start_hsv, set_start_hsv: Hsva;
+    end_hsv, set_end_hsv: Hsva;
+}
+
+/// Settings the branch colors depend on, compared every frame to recompute the colors when any
+/// changed, whether from the settings, a preset or the command line.
+#[derive(Clone, Copy, PartialEq)]
+struct ColorKey {
+    /// Branch levels to color, which grows with the zoom.
+    depth: usize,
+    config_depth: usize,
+    eink: bool,
+    rainbow_mode: bool,
+    color_space: ColorSpace,
+    luminance_factor: f32,
+    branch_color: Color32,
+    start_hsv: Hsva,
+    end_hsv: Hsva,
+    /// Hash of the gradient stops, which keeps the key free of allocations.
+    gradient_stops: u64,
+}
+
+impl ColorKey {
+    fn new(config: &FractalClockConfig, depth: usize) -> Self {
+        Self {
+            depth,
+            config_depth: config.depth,
+            eink: config.profile == RenderProfile::EInk,
+            rainbow_mode: config.rainbow_mode,
+            color_space: config.color_space,
+            luminance_factor: config.luminance_factor,
+            branch_color: config.branch_color,
+            start_hsv: config.start_hsv,
+            end_hsv: config.end_hsv,
+            gradient_stops: {
+                let mut hasher = DefaultHasher::new();
+                for stop in &config.gradient_stops {
+                    [stop.h, stop.s, stop.v, stop.a]
+                        .map(f32::to_bits)
+                        .hash(&mut hasher);
+                }
+                hasher.finish()
+            },
         }
     }
 }
@@ -49,51 +501,89 @@ impl Default for FractalClockConfig {
 #[derive(Default, PartialEq)]
 struct FractalClockRendering {
     depth_colors: Vec<Color32>,
-    nodes_buf1: Vec<Node>,
-    nodes_buf2: Vec<Node>,
+    /// Settings `depth_colors` was computed from.
+    colors_key: Option<ColorKey>,
+    /// Zoom of the last painted frame.
+    zoom: f32,
+    nodes: [Vec<Node<f32>>; 2],
+    nodes_f64: [Vec<Node<f64>>; 2],
+    branches_3d: [Vec<Branch>; 2],
+    /// Lines of the 3D mode with their depth, sorted to draw far lines first.
+    segments_3d: Vec<(f32, Shape)>,
     shapes: Vec<Shape>,
+    /// Line widths are multiplied by this, which turns them into physical pixels if they are not
+    /// DPI aware.
+    width_scale: f32,
+    /// Lines of the last painted frame skipped for being outside the window.
+    culled_count: usize,
+    /// Part of the frame being painted, which is disabled if it panics.
+    stage: RenderStage,
+    /// Branches left to generate in this frame before reaching the line cap.
+    line_budget: usize,
+    /// Levels were dropped at the line cap in the last frame.
+    truncated: bool,
+    /// Levels of branches generated in full in the last frame, as the deepest ones are dropped
+    /// at the line cap.
+    levels_drawn: usize,
+    /// Part of the lines of the last frame drawn rather than culled, which the buffers of the
+    /// next frame are sized by.
+    drawn_fraction: f32,
 }
 
 impl FractalClockRendering {
-    fn update_colors(&mut self, config: &FractalClockConfig) {
+    /// Remembers the part of the lines of the frame, of which `line_count` were drawn, that were
+    /// not culled.
+    fn measure_culling(&mut self, line_count: usize) {
+        let total = line_count + self.culled_count;
+        self.drawn_fraction = if total == 0 {
+            1.0
+        } else {
+            line_count as f32 / total as f32
+        };
+    }
+
+    /// Items of a buffer for `count` lines or nodes, of which as many are expected to be culled
+    /// as in the last frame, with headroom for the view moving.
+    fn predicted(&self, count: usize) -> usize {
+        let fraction = (self.drawn_fraction * CAPACITY_HEADROOM).min(1.0);
+        (count as f64 * f64::from(fraction)).ceil() as usize
+    }
+
+    /// Computes the colors of `depth` branch levels, which may exceed `config.depth` when zoomed in.
+    fn update_colors(&mut self, config: &FractalClockConfig, depth: usize) {
         const MIN_LUMINANCE: f32 = 0.5 / 255.0;
         self.depth_colors.clear();
+        self.colors_key = Some(ColorKey::new(config, depth));
         let mut luminance = 0.7;
 
+        if config.profile == RenderProfile::EInk {
+            self.depth_colors.resize(depth, Color32::BLACK);
+            return;
+        }
+
         if config.rainbow_mode {
-            for depth_index in 0..config.depth {
+            let stops: Vec<Hsva> = std::iter::once(config.start_hsv)
+                .chain(config.gradient_stops.iter().copied())
+                .chain([config.end_hsv])
+                .collect();
+            for depth_index in 0..depth {
                 luminance *= config.luminance_factor;
                 if luminance < MIN_LUMINANCE {
                     break;
                 }
 
-                let t = depth_index as f32 / config.depth.max(1) as f32;
-
-                let [h, s, v, a] = [
-                    (config.start_hsv.h, config.end_hsv.h),
-                    (config.start_hsv.s, config.end_hsv.s),
-                    (config.start_hsv.v, config.end_hsv.v),
-                    (config.start_hsv.a, config.end_hsv.a),
-                ]
-                .map(|(start, end)| egui::lerp(start..=end, t));
-
-                self.depth_colors.push(Hsva::new(h, s, v, a).into());
+                let t = (depth_index as f32 / config.depth.max(1) as f32).min(1.0);
+                let color = config.color_space.gradient_through(&stops, t);
+                self.depth_colors.push(color);
             }
         } else {
-            let [r, g, b, a] = config.branch_color.to_array().map(|c| c as f32 / 255.0);
-            let multiply_color = |color: f32, factor: f32| (color * factor * 255.0).round() as u8;
-
-            for _ in 0..config.depth {
+            for _ in 0..depth {
                 luminance *= config.luminance_factor;
                 if luminance < MIN_LUMINANCE {
                     break;
                 }
                 let factor = luminance.min(1.0);
-
-                let [r_new, g_new, b_new] = [r, g, b].map(|c| multiply_color(c, factor));
-                let a_new = (a * 255.0).round() as u8;
-
-                let color = Color32::from_rgba_premultiplied(r_new, g_new, b_new, a_new);
+                let color = config.color_space.dim(config.branch_color, factor);
                 self.depth_colors.push(color);
             }
         }
@@ -101,9 +591,21 @@ impl FractalClockRendering {
 }
 
 #[derive(Clone, Copy, PartialEq)]
-struct Node {
-    pos: Pos2,
-    dir: Vec2,
+struct Node<T> {
+    pos: Complex<T>,
+    dir: Complex<T>,
+    /// Identifies the branch across frames, see `jitter::child_id`.
+    id: u64,
+}
+
+impl<T: Copy> simd::Parent<T> for Node<T> {
+    fn pos(&self) -> Complex<T> {
+        self.pos
+    }
+
+    fn dir(&self) -> Complex<T> {
+        self.dir
+    }
 }
 
 struct Hand {
@@ -122,9 +624,113 @@ impl Hand {
     }
 }
 
+/// Mapping from the fractal space to the window.
+struct View {
+    to_screen: emath::RectTransform,
+    /// Part of the window showing the fractal, excluding letterbox bars.
+    visible: Rect,
+    zoom: f32,
+}
+
+/// Settings and views of a frame generated on the background thread.
+struct GenerationJob {
+    time: DateTime<Local>,
+    config: FractalClockConfig,
+    stereo: StereoConfig,
+    night_mode: NightMode,
+    location: Location,
+    depth_colors: Vec<Color32>,
+    width_scale: f32,
+    /// Transform to the screen and culling rectangle of each eye.
+    passes: Vec<(emath::RectTransform, Rect, Option<Eye>)>,
+    start_width: f32,
+    max_depth: usize,
+    root: Option<Vec2>,
+}
+
+/// Lines generated on the background thread.
+struct Generated {
+    shapes: Vec<Shape>,
+    line_count: usize,
+    culled_count: usize,
+    truncated: bool,
+    levels_drawn: usize,
+}
+
+/// Background thread generating the branches, started when first enabled.
+#[derive(Default)]
+struct AsyncGeneration {
+    worker: Option<Worker<GenerationJob, Generated>>,
+    frames: Interpolation<Generated>,
+    /// Shapes of replaced frames, which the thread generates the next ones into.
+    shape_pool: ShapePool,
+}
+
+impl PartialEq for AsyncGeneration {
+    fn eq(&self, other: &Self) -> bool {
+        self.worker.is_some() == other.worker.is_some()
+    }
+}
+
+/// Parts of painting a frame that can be turned off when they panic.
+#[derive(Clone, Copy, PartialEq, Default)]
+enum RenderStage {
+    Dive,
+    #[default]
+    Branches,
+    ThreeD,
+    Heatmap,
+    Shadow,
+    Complications,
+    Minimap,
+    WorldMap,
+}
+
+impl RenderStage {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Dive => "the dive",
+            Self::Branches => "the branch effects",
+            Self::ThreeD => "3D mode",
+            Self::Heatmap => "the heatmap",
+            Self::Shadow => "the shadow",
+            Self::Complications => "the complications",
+            Self::Minimap => "the minimap",
+            Self::WorldMap => "the world map",
+        }
+    }
+}
+
+/// Where and how a render pass draws the clock.
+struct Pass<'a> {
+    to_screen: &'a emath::RectTransform,
+    /// Part of the screen lines are culled to.
+    rect: Rect,
+    start_width: f32,
+    max_depth: usize,
+    /// Direction of a branch ending at the origin, drawn from instead of the hands.
+    root: Option<Vec2>,
+    /// Eye drawn for in stereo mode.
+    eye: Option<Eye>,
+    /// Draws filled polygons instead of lines.
+    fill: bool,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, PartialEq)]
+#[serde(default)]
 pub struct FractalClock {
     paused: bool,
+    /// Hides the settings and ignores input, for unattended displays.
+    #[serde(skip)]
+    pub kiosk: bool,
+    /// Hides the settings and ignores input, set with `--lock` or the lock button.
+    #[serde(skip)]
+    pub locked: bool,
+    #[serde(skip)]
+    lock_button: LockButton,
+    /// Point of the fractal space shown at the center of the window, moved with the minimap.
+    #[serde(skip)]
+    view_center: Pos2,
     #[serde(skip)]
     time: DateTime<Local>,
     config: FractalClockConfig,
@@ -132,257 +738,2969 @@ pub struct FractalClock {
     line_count: usize,
     #[serde(skip)]
     paint_time: Duration,
+    /// Allocations of the last paint, if the global allocator counts them.
+    #[serde(skip)]
+    paint_allocations: Option<u64>,
+    /// Depth chosen with the slider, waiting for confirmation as it exceeds the line cap.
+    #[serde(skip)]
+    pending_depth: Option<usize>,
+    /// Whether a morph, slideshow or timeline set the settings this frame.
+    #[serde(skip)]
+    settings_driven: bool,
+    #[serde(skip)]
+    metrics: MetricsLog,
+    prometheus: PrometheusConfig,
+    #[serde(skip)]
+    prometheus_server: PrometheusServer,
+    remote: RemoteConfig,
+    #[serde(skip)]
+    remote_server: RemoteServer,
+    chat_config: ChatConfig,
+    #[serde(skip)]
+    chat: Chat,
     #[serde(skip)]
     rendering: FractalClockRendering,
+    #[serde(skip)]
+    async_generation: AsyncGeneration,
     pub fullscreen: bool,
     pub transparent_background: bool,
+    /// Image painted behind the clock, set by dropping it onto the window.
+    #[cfg(feature = "export")]
+    pub background_image: Option<PathBuf>,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    background: Background,
+    /// Opacity of the clock and its background, which needs a compositor to show through.
+    pub window_opacity: f32,
+    /// Frames per second the clock is redrawn at most.
+    fps_cap: Option<u32>,
+    pub profiles: SessionProfiles,
+    #[serde(alias = "window_layer")]
+    pub window_stacking: WindowStacking,
+    pub widget_window: WidgetWindow,
+    complications: Complications,
+    #[serde(skip)]
+    weather: Weather,
+    #[serde(skip)]
+    calendar: Calendar,
+    #[serde(skip)]
+    sun: Sun,
+    ntp_config: NtpConfig,
+    #[serde(skip)]
+    ntp: Ntp,
+    sync_config: SyncConfig,
+    #[serde(skip)]
+    sync: SettingsSync,
+    pub x11_hints: X11Hints,
+    pub minimize_to_tray: bool,
+    #[cfg(feature = "hotkey")]
+    pub hotkey: HotkeyConfig,
+    #[cfg(feature = "export")]
+    output: OutputConfig,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    frame_output: FrameOutput,
+    /// Stream name being typed, applied when the edit loses focus so the stream isn't restarted
+    /// on every key.
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    stream_name_draft: Option<String>,
+    #[serde(skip)]
+    launch_at_login: LaunchAtLogin,
+    screensaver: ScreensaverConfig,
+    #[serde(skip)]
+    idle_watcher: IdleWatcher,
+    battery_profile: BatteryProfile,
+    #[serde(skip)]
+    power: PowerMonitor,
+    night_mode: NightMode,
+    oled: OledProtection,
+    playback: PlaybackConfig,
+    stopwatch_config: StopwatchConfig,
+    refinement: RefinementConfig,
+    #[serde(skip)]
+    governor: Governor,
+    history_config: HistoryConfig,
+    #[serde(skip)]
+    history: History,
+    world_map: WorldMapConfig,
+    shader_effects: ShaderEffectsConfig,
+    color_vision: ColorVisionConfig,
+    #[serde(skip)]
+    shader_library: ShaderLibrary,
+    #[serde(skip)]
+    stopwatch: Stopwatch,
+    alarms: AlarmsConfig,
+    #[serde(skip)]
+    scheduler: Scheduler,
+    readout: ReadoutConfig,
+    bookmarks: Bookmarks,
+    morph: MorphConfig,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    morph_render: ClipRender,
+    slideshow_config: SlideshowConfig,
+    #[serde(skip)]
+    slideshow: Slideshow,
+    timeline_config: TimelineConfig,
+    #[serde(skip)]
+    timeline: Timeline,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    timeline_render: ClipRender,
+    dive: DiveConfig,
+    #[serde(skip)]
+    dive_progress: Dive,
+    stereo: StereoConfig,
+    three_d: ThreeDConfig,
+    #[cfg(feature = "export")]
+    ascii_art: AsciiArtConfig,
+    #[cfg(feature = "export")]
+    timelapse_config: TimelapseConfig,
+    #[cfg(feature = "export")]
+    search_config: SearchConfig,
+    #[cfg(feature = "export")]
+    poster_config: PosterConfig,
+    #[cfg(feature = "export")]
+    burst_config: BurstConfig,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    search: Search,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    timelapse: Timelapse,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    poster: Poster,
+    /// Renders the contact sheets of bursts.
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    burst: Poster,
+    #[cfg(feature = "export")]
+    #[serde(skip)]
+    clipboard: Clipboard,
+    #[cfg(feature = "audio")]
+    audio_config: AudioConfig,
+    #[cfg(feature = "audio")]
+    #[serde(skip)]
+    audio: Audio,
+    #[cfg(feature = "gamepad")]
+    gamepad_config: GamepadConfig,
+    #[cfg(feature = "gamepad")]
+    #[serde(skip)]
+    gamepad: Gamepad,
+    #[serde(skip)]
+    log_viewer: LogViewer,
+    #[serde(skip)]
+    palette_import: PaletteImport,
+    #[serde(skip)]
+    profiler: ProfilerWindow,
+    #[serde(skip)]
+    about: AboutWindow,
 }
 
 impl Default for FractalClock {
     fn default() -> Self {
         Self {
             paused: false,
+            kiosk: false,
+            locked: false,
+            lock_button: LockButton::default(),
+            view_center: Pos2::ZERO,
             time: Local::now(),
             config: FractalClockConfig::default(),
             line_count: 0,
             paint_time: Duration::ZERO,
+            paint_allocations: None,
+            pending_depth: None,
+            settings_driven: false,
+            metrics: MetricsLog::default(),
+            prometheus: PrometheusConfig::default(),
+            prometheus_server: PrometheusServer::default(),
+            remote: RemoteConfig::default(),
+            remote_server: RemoteServer::default(),
+            chat_config: ChatConfig::default(),
+            chat: Chat::default(),
             rendering: FractalClockRendering {
                 depth_colors: Vec::with_capacity(16),
-                nodes_buf1: Vec::with_capacity(1 << 16),
-                nodes_buf2: Vec::with_capacity(1 << 16),
+                colors_key: None,
+                zoom: 1.0,
+                nodes: [Vec::with_capacity(1 << 16), Vec::with_capacity(1 << 16)],
+                nodes_f64: [Vec::new(), Vec::new()],
+                branches_3d: [Vec::new(), Vec::new()],
+                segments_3d: Vec::new(),
                 shapes: Vec::with_capacity(1 << 18),
+                width_scale: 1.0,
+                culled_count: 0,
+                stage: RenderStage::Branches,
+                line_budget: DEFAULT_LINE_CAP,
+                truncated: false,
+                levels_drawn: 0,
+                drawn_fraction: 1.0,
             },
+            async_generation: AsyncGeneration::default(),
             fullscreen: false,
             transparent_background: true,
+            #[cfg(feature = "export")]
+            background_image: None,
+            #[cfg(feature = "export")]
+            background: Background::default(),
+            window_opacity: 1.0,
+            fps_cap: None,
+            profiles: SessionProfiles::default(),
+            window_stacking: WindowStacking::Normal,
+            widget_window: WidgetWindow::default(),
+            complications: Complications::default(),
+            weather: Weather::default(),
+            calendar: Calendar::default(),
+            sun: Sun::default(),
+            ntp_config: NtpConfig::default(),
+            ntp: Ntp::default(),
+            sync_config: SyncConfig::default(),
+            sync: SettingsSync::default(),
+            x11_hints: X11Hints::default(),
+            minimize_to_tray: false,
+            #[cfg(feature = "hotkey")]
+            hotkey: HotkeyConfig::default(),
+            #[cfg(feature = "export")]
+            output: OutputConfig::default(),
+            #[cfg(feature = "export")]
+            frame_output: FrameOutput::default(),
+            #[cfg(feature = "export")]
+            stream_name_draft: None,
+            launch_at_login: LaunchAtLogin::default(),
+            screensaver: ScreensaverConfig::default(),
+            idle_watcher: IdleWatcher::default(),
+            battery_profile: BatteryProfile::default(),
+            power: PowerMonitor::default(),
+            night_mode: NightMode::default(),
+            oled: OledProtection::default(),
+            playback: PlaybackConfig::default(),
+            stopwatch_config: StopwatchConfig::default(),
+            refinement: RefinementConfig::default(),
+            governor: Governor::default(),
+            history_config: HistoryConfig::default(),
+            history: History::default(),
+            world_map: WorldMapConfig::default(),
+            shader_effects: ShaderEffectsConfig::default(),
+            color_vision: ColorVisionConfig::default(),
+            shader_library: ShaderLibrary::default(),
+            stopwatch: Stopwatch::default(),
+            alarms: AlarmsConfig::default(),
+            scheduler: Scheduler::default(),
+            readout: ReadoutConfig::default(),
+            bookmarks: Bookmarks::default(),
+            morph: MorphConfig::default(),
+            #[cfg(feature = "export")]
+            morph_render: ClipRender::default(),
+            slideshow_config: SlideshowConfig::default(),
+            slideshow: Slideshow::default(),
+            timeline_config: TimelineConfig::default(),
+            timeline: Timeline::default(),
+            #[cfg(feature = "export")]
+            timeline_render: ClipRender::default(),
+            dive: DiveConfig::default(),
+            dive_progress: Dive::default(),
+            stereo: StereoConfig::default(),
+            three_d: ThreeDConfig::default(),
+            #[cfg(feature = "export")]
+            ascii_art: AsciiArtConfig::default(),
+            #[cfg(feature = "export")]
+            timelapse_config: TimelapseConfig::default(),
+            #[cfg(feature = "export")]
+            search_config: SearchConfig::default(),
+            #[cfg(feature = "export")]
+            poster_config: PosterConfig::default(),
+            #[cfg(feature = "export")]
+            burst_config: BurstConfig::default(),
+            #[cfg(feature = "export")]
+            search: Search::default(),
+            #[cfg(feature = "export")]
+            timelapse: Timelapse::default(),
+            #[cfg(feature = "export")]
+            poster: Poster::default(),
+            #[cfg(feature = "export")]
+            burst: Poster::default(),
+            #[cfg(feature = "export")]
+            clipboard: Clipboard::default(),
+            #[cfg(feature = "audio")]
+            audio_config: AudioConfig::default(),
+            #[cfg(feature = "audio")]
+            audio: Audio::default(),
+            #[cfg(feature = "gamepad")]
+            gamepad_config: GamepadConfig::default(),
+            #[cfg(feature = "gamepad")]
+            gamepad: Gamepad::default(),
+            log_viewer: LogViewer::default(),
+            palette_import: PaletteImport::default(),
+            profiler: ProfilerWindow::default(),
+            about: AboutWindow::default(),
         }
     }
 }
 
 impl FractalClock {
+    /// Starts logging the metrics of every frame to a CSV file at `path`.
+    pub fn start_metrics(&mut self, path: PathBuf) {
+        if let Err(e) = self.metrics.start(path.clone()) {
+            tracing::error!("Failed to record metrics to {}: {e}", path.display());
+            toasts::error(format!("Failed to record metrics: {e}"));
+        }
+    }
+
     pub fn update(&mut self, ctx: &egui::Context) {
-        if !self.paused {
-            self.time = Local::now();
+        self.power.update();
+        self.shader_library.update(ctx);
+        self.ntp.update(ctx, &self.ntp_config);
+        self.prometheus_server.update(&self.prometheus);
+        self.remote_server.update(ctx, &self.remote);
+        self.slideshow.update(ctx, &self.slideshow_config);
+        let seconds = ctx.input(|i| i.time);
+        let config = self
+            .morph
+            .config(seconds)
+            .or_else(|| self.slideshow.config(&self.slideshow_config, seconds));
+        self.settings_driven = config.is_some();
+        if let Some(config) = config {
+            self.config = config;
             ctx.request_repaint();
         }
+        if self.timeline.advance(ctx, &self.timeline_config) {
+            self.timeline_config
+                .apply(self.timeline.playhead, &mut self.config);
+            self.settings_driven = true;
+        }
+        // A locked clock ignores remotes like any other input
+        if !self.locked {
+            for change in self.remote_server.changes() {
+                change.apply(&mut self.config);
+            }
+        }
+        for action in self.chat.update(ctx, &self.chat_config) {
+            if !self.locked {
+                action.apply(&mut self.config);
+            }
+        }
+        self.remote_server.publish(&self.config);
+        if !self.paused {
+            self.time = self.shown_time();
+            if self.eink() {
+                self.time = self.eink_time(ctx);
+            } else if let Some(fps) = self.frame_rate_cap() {
+                let fps = fps.max(1) as f32;
+                ctx.request_repaint_after(Duration::from_secs_f32(1.0 / fps));
+            } else {
+                ctx.request_repaint();
+            }
+        }
+        #[cfg(feature = "gamepad")]
+        if !self.locked {
+            self.handle_gamepad(ctx);
+        }
+        #[cfg(feature = "audio")]
+        self.audio.update(&self.audio_config, &self.now());
+        // Away from the window, alarms also go to the desktop notifications
+        let minimized = ctx.input(|i| i.viewport().minimized).unwrap_or(false);
+        let away = minimized || self.widget_window.enabled;
+        for label in self.scheduler.update(ctx, &self.alarms, self.now(), away) {
+            toasts::warning(format!("⏰ {label}"));
+        }
+        if self.complications.weather.enabled {
+            self.weather.update(
+                ctx,
+                &self.complications.weather_config,
+                self.complications.location,
+            );
+        }
+        if self.complications.calendar.enabled || self.complications.calendar_config.marker {
+            self.calendar
+                .update(ctx, &self.complications.calendar_config);
+        }
+        if self.diving() {
+            self.dive_progress.update(ctx, &self.dive);
+        } else {
+            self.dive_progress.stop();
+        }
+        #[cfg(feature = "export")]
+        self.update_timelapse(ctx);
+        #[cfg(feature = "export")]
+        self.search.update(ctx);
+        #[cfg(feature = "export")]
+        match self.morph_render.poll() {
+            Some(Ok(path)) => toasts::success(format!("Saved morph to {}", path.display())),
+            Some(Err(e)) => {
+                tracing::error!("Failed to render the morph: {e}");
+                toasts::error(format!("Failed to render the morph: {e}"));
+            }
+            None => {}
+        }
+        #[cfg(feature = "export")]
+        match self.timeline_render.poll() {
+            Some(Ok(path)) => toasts::success(format!("Saved timeline to {}", path.display())),
+            Some(Err(e)) => {
+                tracing::error!("Failed to render the timeline: {e}");
+                toasts::error(format!("Failed to render the timeline: {e}"));
+            }
+            None => {}
+        }
+        #[cfg(feature = "export")]
+        match self.poster.poll() {
+            Some(Ok(path)) => toasts::success(format!("Saved poster to {}", path.display())),
+            Some(Err(e)) => {
+                tracing::error!("Failed to export the poster: {e}");
+                toasts::error(format!("Failed to export the poster: {e}"));
+            }
+            None => {}
+        }
+        #[cfg(feature = "export")]
+        match self.burst.poll() {
+            Some(Ok(path)) => toasts::success(format!("Saved burst to {}", path.display())),
+            Some(Err(e)) => {
+                tracing::error!("Failed to capture the burst: {e}");
+                toasts::error(format!("Failed to capture the burst: {e}"));
+            }
+            None => {}
+        }
+        #[cfg(feature = "export")]
+        if let Err(message) = watchdog::catch(|| self.frame_output.update(ctx, &self.output)) {
+            tracing::error!("NDI output panicked: {message}");
+            toasts::error(format!("Turned off NDI output after an error: {message}"));
+            self.output.ndi_enabled = false;
+        }
+        self.idle_watcher
+            .update(ctx, &self.screensaver, &mut self.fullscreen);
+    }
+
+    /// Paints the clock at `time` with `config` into `painter`, for embedding it in other apps.
+    /// Features disabled after a panic are turned off in `config` as well.
+    pub(crate) fn paint_with(
+        &mut self,
+        painter: &Painter,
+        config: &mut FractalClockConfig,
+        time: DateTime<Local>,
+    ) {
+        self.config.clone_from(config);
+        self.time = time;
+        if let Err(message) = watchdog::catch(|| self.paint(painter)) {
+            self.recover(message);
+            config.clone_from(&self.config);
+        }
     }
 
     pub fn ui(&mut self, ui: &mut Ui) {
-        let painter = Painter::new(
+        profiler::new_frame();
+        let _scope = profiler::scope("ui");
+        let mut painter = Painter::new(
             ui.ctx().clone(),
             ui.layer_id(),
             ui.available_rect_before_wrap(),
         );
+        painter.set_opacity(self.window_opacity);
 
         let now = Instant::now();
-        self.paint(&painter);
+        let allocations = allocations::count();
+        if let Err(message) = watchdog::catch(|| self.paint(&painter)) {
+            self.recover(message);
+        }
         self.paint_time = now.elapsed();
+        self.paint_allocations = allocations
+            .zip(allocations::count())
+            .map(|(before, after)| after - before);
+        if self.metrics.path().is_some() || self.prometheus.enabled {
+            let dt = ui.input(|i| i.unstable_dt);
+            let frame = FrameMetrics {
+                depth: self.branch_depth(),
+                line_count: self.line_count,
+                paint_time: self.paint_time,
+                culled_lines: self.rendering.culled_count,
+                fps: if dt > 0.0 { 1.0 / dt } else { 0.0 },
+                allocations: self.paint_allocations,
+            };
+            self.metrics.record(&frame);
+            self.prometheus_server.record(&frame);
+        }
 
         ui.expand_to_include_rect(painter.clip_rect());
 
-        Frame::popup(ui.style())
-            .stroke(Stroke::NONE)
-            .show(ui, |ui| {
-                ui.set_max_width(270.0);
-                CollapsingHeader::new("Settings").show(ui, |ui| self.options_ui(ui));
-            });
-    }
-
-    fn compute_colors(&mut self) {
-        self.rendering.update_colors(&self.config);
-    }
-
-    fn options_ui(&mut self, ui: &mut Ui) {
-        ui.label(self.time.format("%H:%M:%S:%S%.3f").to_string());
-        ui.label(format!("Painted line count: {}", self.line_count));
-        ui.label(format!("{:.2?} / paint", self.paint_time));
+        if self.idle_watcher.is_active() || self.kiosk || self.locked {
+            return;
+        }
 
-        ui.checkbox(&mut self.paused, "Paused");
-        ui.add(Slider::new(&mut self.config.zoom, 0.0..=1.0).text("zoom"));
-        ui.add(Slider::new(&mut self.config.start_line_width, 0.0..=5.0).text("Start line width"));
+        let window = painter.clip_rect();
+        let canvas = ui.interact(window, ui.id().with("canvas"), Sense::click_and_drag());
+        // Two fingers pan instead of orbiting
+        if self.three_d.enabled && ui.input(|i| i.multi_touch()).is_none() {
+            self.three_d.orbit(&canvas);
+        }
+        if !self.diving() {
+            self.navigate(ui, &canvas);
+        }
+        if !ui.ctx().wants_keyboard_input() {
+            let time = self.time.time();
+            let jump = ui.input(|i| {
+                if i.key_pressed(Key::OpenBracket) {
+                    self.bookmarks.previous(time)
+                } else if i.key_pressed(Key::CloseBracket) {
+                    self.bookmarks.next(time)
+                } else {
+                    None
+                }
+            });
+            if let Some(time) = jump {
+                self.jump_to(time);
+            }
+            #[cfg(feature = "export")]
+            if ui.input(|i| i.events.contains(&egui::Event::Copy)) {
+                self.copy_image(ui.ctx());
+            }
+            let profile = ui.input_mut(|i| {
+                PROFILE_KEYS
+                    .iter()
+                    .position(|&key| i.consume_key(egui::Modifiers::COMMAND, key))
+            });
+            if let Some(index) = profile {
+                self.switch_profile(index);
+            }
+        }
+        // Dragging pans the view while zoomed in, and orbits in 3D mode
+        let can_move = !self.three_d.enabled && self.view(window, Vec2::ZERO).zoom <= 1.0;
+        self.widget_window.interact(ui, &canvas, can_move);
 
-        if ui
-            .add(Slider::new(&mut self.config.depth, 0..=20).text("depth"))
-            .changed()
-        {
-            self.compute_colors();
+        if let Some(minimap) = self.minimap(window) {
+            let response = ui.interact(
+                *minimap.to(),
+                ui.id().with("minimap"),
+                Sense::click_and_drag(),
+            );
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.view_center = minimap.inverse() * pos;
+            }
         }
-        if ui
-            .add(Slider::new(&mut self.config.length_factor, 0.0..=1.0).text("length factor"))
-            .changed()
-        {
-            self.compute_colors();
+        // Recenter once the whole clock fits again
+        if self.view(window, Vec2::ZERO).zoom <= 1.0 {
+            self.view_center = Pos2::ZERO;
         }
-        if ui
-            .add(Slider::new(&mut self.config.luminance_factor, 0.0..=1.0).text("luminance factor"))
-            .changed()
-        {
-            self.compute_colors();
+
+        self.log_viewer.show(ui.ctx());
+        self.profiler.show(ui.ctx());
+        if self.about.open {
+            let config = self.redacted_settings();
+            self.about.show(ui.ctx(), &config);
         }
 
-        ui.add(Slider::new(&mut self.config.width_factor, 0.0..=1.0).text("width factor"));
+        let time = ui.input(|i| i.time);
+        ui.scope(|ui| {
+            ui.multiply_opacity(self.oled.overlay_opacity(time));
+            Frame::popup(ui.style())
+                .stroke(Stroke::NONE)
+                .show(ui, |ui| {
+                    ui.set_max_width(270.0);
+                    // Long-pressing a touchscreen or right-clicking opens the settings
+                    CollapsingHeader::new("Settings")
+                        .open(canvas.secondary_clicked().then_some(true))
+                        .show(ui, |ui| self.options_ui(ui));
+                });
+        });
+    }
 
-        egui::Grid::new("color_settings_grid").show(ui, |ui| {
-            ui.label("Branch color:");
-            if ui
-                .color_edit_button_srgba(&mut self.config.branch_color)
-                .changed()
-            {
-                self.compute_colors();
+    #[cfg(any(feature = "tray", feature = "tui"))]
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn handle_gamepad(&mut self, ctx: &egui::Context) {
+        let input = self.gamepad.update(ctx, &mut self.gamepad_config);
+        for action in input.actions {
+            match action {
+                Action::Pause => self.paused = !self.paused,
+                Action::Fullscreen => self.fullscreen = !self.fullscreen,
+                Action::Randomize => self.randomize(),
+                Action::DepthUp => self.config.set_depth(self.config.depth + 1),
+                Action::DepthDown => self.config.depth = self.config.depth.saturating_sub(1),
+                Action::ResetView => {
+                    self.config.auto_zoom = AutoZoom::Off;
+                    self.config.zoom = FractalClockConfig::default().zoom;
+                    self.view_center = Pos2::ZERO;
+                }
             }
-            ui.end_row();
-            ui.label("Hand color:");
-            ui.color_edit_button_srgba(&mut self.config.hand_color);
-            ui.end_row();
-        });
+        }
 
-        if ui
-            .checkbox(&mut self.config.rainbow_mode, "Rainbow")
-            .changed()
-        {
-            self.compute_colors();
+        let zoom = self.rendering.zoom;
+        if zoom > 1.0 {
+            self.view_center += input.pan / zoom;
         }
+        if input.zoom != 0.0 {
+            self.config.auto_zoom = AutoZoom::Off;
+            self.config.zoom =
+                (zoom * input.zoom.exp()).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+        }
+    }
 
-        if self.config.rainbow_mode {
-            if ui
-                .color_edit_button_hsva(&mut self.config.start_hsv)
-                .changed()
-            {
-                self.compute_colors();
-            }
-            if ui
-                .color_edit_button_hsva(&mut self.config.end_hsv)
-                .changed()
-            {
-                self.compute_colors();
-            }
+    /// Settings shown in the About window and copied into bug reports, without passwords, keys and
+    /// private URLs.
+    fn redacted_settings(&self) -> String {
+        let Ok(toml::Value::Table(mut settings)) = toml::Value::try_from(self) else {
+            return String::new();
+        };
+        let redacted = [
+            ("remote", toml::Value::try_from(self.remote.redacted())),
+            (
+                "sync_config",
+                toml::Value::try_from(self.sync_config.redacted()),
+            ),
+            (
+                "complications",
+                toml::Value::try_from(self.complications.redacted()),
+            ),
+            ("profiles", toml::Value::try_from(self.profiles.redacted())),
+        ];
+        for (key, value) in redacted {
+            match value {
+                Ok(value) => settings.insert(key.to_owned(), value),
+                Err(_) => settings.remove(key),
+            };
         }
+        toml::to_string_pretty(&settings).unwrap_or_default()
+    }
 
-        ui.checkbox(&mut self.fullscreen, "Fullscreen mode");
-        ui.checkbox(&mut self.transparent_background, "Transparent background");
+    /// Picks random colors and branch proportions.
+    fn randomize(&mut self) {
+        let mut seed = dive::random_seed();
+        let mut random = || {
+            seed = dive::splitmix64(seed);
+            (seed >> 40) as f32 / (1 << 24) as f32
+        };
+        self.config.length_factor = egui::lerp(0.6..=0.8, random());
+        self.config.width_factor = egui::lerp(0.6..=0.85, random());
+        // Mostly small turns, which keep the tree recognizable
+        self.config.spiral = 15.0 * (2.0 * random() - 1.0).powi(3);
+        self.config.start_hsv.h = random();
+        self.config.end_hsv.h = random();
+        self.config.branch_color = Hsva::new(random(), 0.8, 0.8, 1.0).into();
+    }
 
-        egui::reset_button(ui, self, "🔁 Reset");
+    /// Frames per second the clock is limited to, by the FPS cap or the battery profile.
+    fn frame_rate_cap(&self) -> Option<u32> {
+        let battery = self
+            .battery_saving()
+            .then_some(self.battery_profile.fps_cap);
+        battery.into_iter().chain(self.fps_cap).min()
+    }
 
-        ui.hyperlink_to(
-            "Standalone version of this code",
-            "https://github.com/emilk/egui/blob/main/crates/egui_demo_app/src/apps/fractal_clock.rs",
-        );
+    fn battery_saving(&self) -> bool {
+        self.battery_profile.enabled && self.power.on_battery()
     }
 
-    fn paint(&mut self, painter: &Painter) {
-        if self.rendering.depth_colors.is_empty() {
-            self.compute_colors();
+    /// Whether the effects are turned off to save battery.
+    fn effects_off_on_battery(&self) -> bool {
+        self.battery_saving() && self.battery_profile.effects_off
+    }
+
+    fn eink(&self) -> bool {
+        self.config.profile == RenderProfile::EInk
+    }
+
+    /// System time, corrected to the network time if enabled.
+    fn now(&self) -> DateTime<Local> {
+        Local::now() + self.ntp.correction(&self.ntp_config)
+    }
+
+    /// Pauses the clock at `time` of the current day.
+    fn jump_to(&mut self, time: NaiveTime) {
+        self.paused = true;
+        if let Some(time) = Local
+            .from_local_datetime(&self.time.date_naive().and_time(time))
+            .earliest()
+        {
+            self.time = time;
         }
+    }
 
-        let rect = painter.clip_rect();
-        let to_screen = emath::RectTransform::from_to(
-            Rect::from_center_size(Pos2::ZERO, rect.square_proportions() / self.config.zoom),
-            rect,
+    /// Time the hands show, which runs faster during playback or is the elapsed time of the
+    /// stopwatch.
+    fn shown_time(&self) -> DateTime<Local> {
+        if self.stopwatch_config.enabled {
+            return Stopwatch::time(self.stopwatch.elapsed(), self.now());
+        }
+        self.playback.time(self.now())
+    }
+
+    /// Current time rounded down to the e-ink update interval, scheduling the next update.
+    fn eink_time(&self, ctx: &egui::Context) -> DateTime<Local> {
+        let time = self.time.with_nanosecond(0).unwrap_or(self.time);
+        let (time, period) = match self.config.eink_interval {
+            EInkInterval::Second => (time, 1),
+            EInkInterval::Minute => (time.with_second(0).unwrap_or(time), 60),
+        };
+        let elapsed = (self.time - time).to_std().unwrap_or_default();
+        ctx.request_repaint_after(Duration::from_secs(period).saturating_sub(elapsed));
+        time
+    }
+
+    /// Brightness of the colors, dimmed by the night mode.
+    fn brightness(&self) -> f32 {
+        if self.eink() {
+            1.0
+        } else {
+            self.night_mode.brightness_at(self.time.time())
+        }
+    }
+
+    fn hand_color(&self) -> Color32 {
+        if self.eink() {
+            Color32::BLACK
+        } else {
+            dim(self.config.hand_color, self.brightness())
+        }
+    }
+
+    /// Narrowest line drawn, keeping lines visible on e-ink displays.
+    fn min_line_width(&self) -> f32 {
+        if self.eink() {
+            EINK_MIN_LINE_WIDTH * self.rendering.width_scale
+        } else {
+            0.0
+        }
+    }
+
+    fn eyes(&self) -> &'static [Option<Eye>] {
+        // Anaglyph colors add up to nothing on black lines
+        if self.eink() {
+            &[None]
+        } else {
+            self.stereo.eyes()
+        }
+    }
+
+    /// Recomputes the branch colors if the settings they depend on changed since the last
+    /// frame, however they were changed.
+    fn update_colors(&mut self) {
+        let key = ColorKey::new(&self.config, self.branch_depth());
+        if self.rendering.colors_key != Some(key) {
+            let _scope = profiler::scope("colors");
+            self.rendering.update_colors(&self.config, key.depth);
+        }
+    }
+
+    fn reset_line_budget(&mut self) {
+        self.rendering.line_budget = self.config.line_cap;
+        self.rendering.truncated = false;
+        self.rendering.levels_drawn = 0;
+    }
+
+    /// Shapes of a frame with `max_depth` levels of branches, which the shape buffer is grown to
+    /// before the frame rather than as it fills.
+    fn shape_capacity(&self, max_depth: usize) -> usize {
+        let lines = self
+            .rendering
+            .predicted(estimated_line_count(max_depth).min(self.config.line_cap));
+        // The shadow adds a shape under every line
+        let layers = if self.config.shadow.enabled { 2 } else { 1 };
+        lines * layers + EXTRA_SHAPES
+    }
+
+    /// Asks whether to apply the `pending` depth that exceeds the line cap.
+    fn confirm_depth_ui(&mut self, ui: &mut Ui, pending: usize) {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "Depth {pending} draws up to {} lines, above the maximum of {} per frame.",
+                estimated_line_count(pending),
+                self.config.line_cap
+            ),
+        );
+        ui.horizontal(|ui| {
+            let mut apply = false;
+            if ui
+                .button("Apply")
+                .on_hover_text("The deepest levels that do not fit are dropped")
+                .clicked()
+            {
+                apply = true;
+            }
+            if ui.button("Raise the maximum").clicked() {
+                self.config.line_cap = self.config.line_cap.max(estimated_line_count(pending));
+                apply = true;
+            }
+            if ui.button("Cancel").clicked() {
+                self.pending_depth = None;
+            }
+            if apply {
+                self.config.depth = pending;
+                self.pending_depth = None;
+            }
+        });
+    }
+
+    /// Number of branch levels, growing as the zoom magnifies the smallest branches.
+    fn branch_depth(&self) -> usize {
+        if self.diving() {
+            // The root is the branch a few levels above the one being zoomed into
+            return self.config.depth + dive::CONTEXT_LEVELS + 1;
+        }
+        // The longer branches need the most levels to shrink below a pixel
+        let length = self.max_branch_length();
+        let zoom = self.rendering.zoom;
+        if !self.config.adaptive_depth || zoom <= 1.0 || !(0.0..1.0).contains(&length) {
+            return self.config.depth;
+        }
+        // Each level is `length` times smaller, so zooming by its inverse reveals one more
+        let extra = (zoom.ln() / -length.ln()).ceil() as usize;
+        (self.config.depth + extra).min(MAX_ADAPTIVE_DEPTH.max(self.config.depth))
+    }
+
+    fn diving(&self) -> bool {
+        // The dive needs branches to shrink, otherwise there is no tip to head into
+        self.dive.enabled
+            && !self.three_d.enabled
+            && !self.eink()
+            && self
+                .branch_lengths()
+                .iter()
+                .all(|&length| length > 0.0 && length < 1.0)
+    }
+
+    /// Zooms with the scroll wheel, pinch and `+`/`-`/`0` keys, and pans by dragging with one or two
+    /// fingers while zoomed in.
+    fn navigate(&mut self, ui: &Ui, canvas: &egui::Response) {
+        let window = canvas.rect;
+        let mut factor = 1.0;
+        if canvas.hovered() {
+            factor *= ui.input(|i| (i.smooth_scroll_delta.y / 200.0).exp() * i.zoom_delta());
+        }
+        if !ui.ctx().wants_keyboard_input() {
+            ui.input(|i| {
+                if i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals) {
+                    factor *= ZOOM_STEP;
+                }
+                if i.key_pressed(Key::Minus) {
+                    factor /= ZOOM_STEP;
+                }
+            });
+            if ui.input(|i| i.key_pressed(Key::Num0)) {
+                self.config.auto_zoom = AutoZoom::Off;
+                self.config.zoom = FractalClockConfig::default().zoom;
+                self.view_center = Pos2::ZERO;
+            }
+        }
+
+        let view = self.view(window, Vec2::ZERO);
+        let touch = ui.input(|i| i.multi_touch());
+        let pan = match &touch {
+            Some(touch) => touch.translation_delta,
+            // Dragging orbits the camera in 3D mode instead
+            None if canvas.dragged() && !self.three_d.enabled => canvas.drag_delta(),
+            None => Vec2::ZERO,
+        };
+        if view.zoom > 1.0 {
+            self.view_center -= view.to_screen.inverse().scale() * pan;
+        }
+
+        if factor != 1.0 {
+            let zoom = (view.zoom * factor).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+            // Keep the point under the pointer or between the fingers in place
+            let anchor = touch.map(|touch| touch.center_pos);
+            if let Some(pointer) = anchor.or_else(|| canvas.hover_pos()) {
+                let anchor = view.to_screen.inverse() * pointer;
+                self.view_center = anchor + (self.view_center - anchor) * (view.zoom / zoom);
+            }
+            self.config.auto_zoom = AutoZoom::Off;
+            self.config.zoom = zoom;
+        }
+    }
+
+    fn options_ui(&mut self, ui: &mut Ui) {
+        let values = readout::Values {
+            time: &self.time,
+            system_time: self
+                .config
+                .time_system
+                .format(&self.time, self.complications.location),
+            line_count: self.line_count,
+            fps: 1.0 / ui.input(|i| i.stable_dt).max(f32::EPSILON),
+            preset: self.config.profile.label(),
+            depth: self.branch_depth(),
+            zoom: self.rendering.zoom,
+        };
+        match readout::render(&self.readout.template, &values) {
+            Ok(text) => ui.label(text),
+            Err(e) => ui.colored_label(ui.visuals().error_fg_color, e),
+        };
+        CollapsingHeader::new("Readout").show(ui, |ui| self.readout.ui(ui));
+        ui.label(format!("Painted line count: {}", self.line_count));
+        let estimate = estimated_line_count(self.branch_depth());
+        if estimate > self.config.line_cap {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("Estimated lines: {estimate}, above the maximum"),
+            );
+        } else {
+            ui.label(format!("Estimated lines: {estimate}"));
+        }
+        let levels = self.branch_depth();
+        if self.rendering.truncated {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "Generations drawn: {} of {levels}, the deepest are dropped",
+                    self.rendering.levels_drawn
+                ),
+            );
+        } else {
+            ui.label(format!(
+                "Generations drawn: {} of {levels}",
+                self.rendering.levels_drawn
+            ));
+        }
+        ui.label(format!("{:.2?} / paint", self.paint_time));
+        if let Some(allocations) = self.paint_allocations {
+            ui.label(format!("{allocations} allocations / paint"))
+                .on_hover_text(
+                    "Buffers are sized before the frame from the depth and the lines culled in \
+                     the last frame, so drawing the branches allocates nothing once they are",
+                );
+        }
+        ui.label(format!("Branch batches: {}", simd::instruction_set()));
+        ui.horizontal(|ui| match self.metrics.path() {
+            Some(path) => {
+                ui.label(format!("Recording metrics to {}", path.display()));
+                if ui.button("⏹ Stop").clicked()
+                    && let Some(path) = self.metrics.stop()
+                {
+                    toasts::success(format!("Saved metrics to {}", path.display()));
+                }
+            }
+            None => {
+                if ui
+                    .button("⏺ Record metrics")
+                    .on_hover_text("Log every frame to a CSV file")
+                    .clicked()
+                {
+                    match metrics::default_path() {
+                        Some(path) => self.start_metrics(path),
+                        None => toasts::error("No downloads or home directory for the metrics"),
+                    }
+                }
+            }
+        });
+        if let Some(offset) = self.ntp.offset() {
+            let millis = offset.num_microseconds().unwrap_or_default() as f64 / 1000.0;
+            ui.label(format!("Clock drift: {millis:+.1} ms"));
+        }
+
+        ui.checkbox(&mut self.paused, "Paused");
+        egui::ComboBox::from_label("Time system")
+            .selected_text(self.config.time_system.label())
+            .show_ui(ui, |ui| {
+                for system in TimeSystem::ALL {
+                    ui.selectable_value(&mut self.config.time_system, system, system.label());
+                }
+            });
+        if self.config.time_system == TimeSystem::Sidereal {
+            ui.weak("Uses the longitude of the location in the complications");
+        }
+        ui.add_enabled(
+            self.config.auto_zoom == AutoZoom::Off,
+            Slider::new(&mut self.config.zoom, ZOOM_RANGE)
+                .logarithmic(true)
+                .text("zoom"),
+        );
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("scaling")
+                .selected_text(self.config.scaling_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in ScalingMode::ALL {
+                        ui.selectable_value(&mut self.config.scaling_mode, mode, mode.label());
+                    }
+                });
+            ui.checkbox(&mut self.config.letterbox, "Letterbox");
+            if self.config.letterbox {
+                ui.color_edit_button_srgba(&mut self.config.letterbox_color);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Auto zoom:");
+            ui.radio_value(&mut self.config.auto_zoom, AutoZoom::Off, "Off");
+            ui.radio_value(&mut self.config.auto_zoom, AutoZoom::Fit, "Fit");
+            ui.radio_value(&mut self.config.auto_zoom, AutoZoom::Fill, "Fill");
+        });
+        ui.checkbox(&mut self.config.minimap, "Minimap when zoomed in");
+        ui.checkbox(
+            &mut self.config.adaptive_depth,
+            "More branches when zoomed in",
+        );
+        self.refinement.ui(ui);
+        ui.checkbox(
+            &mut self.config.async_generation,
+            "Generate in the background",
+        )
+        .on_hover_text(
+            "Keeps the settings responsive with huge trees, drawing frames as they finish",
+        );
+        ui.add_enabled(
+            self.config.async_generation,
+            egui::Checkbox::new(&mut self.config.interpolate, "Ease between frames"),
+        );
+        ui.add(
+            Slider::new(&mut self.config.render_scale, RENDER_SCALE_RANGE)
+                .logarithmic(true)
+                .text("render scale"),
+        )
+        .on_hover_text("Above 1 supersamples the lines, below 1 renders fewer pixels");
+        egui::ComboBox::from_label("Blend mode")
+            .selected_text(self.config.blend_mode.label())
+            .show_ui(ui, |ui| {
+                for mode in BlendMode::ALL {
+                    ui.selectable_value(&mut self.config.blend_mode, mode, mode.label());
+                }
+            });
+        let scaled = self.config.render_scale != 1.0 || self.config.blend_mode != BlendMode::Normal;
+        ui.add_enabled_ui(!scaled, |ui| {
+            egui::ComboBox::from_label("Line renderer")
+                .selected_text(self.config.line_backend.label())
+                .show_ui(ui, |ui| {
+                    for backend in LineBackend::ALL {
+                        ui.selectable_value(
+                            &mut self.config.line_backend,
+                            backend,
+                            backend.label(),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Instanced sends every line as one quad instance, for huge trees. It needs \
+                     OpenGL 3.3 and a render scale of 1 with the normal blend mode. GPU compute \
+                     also grows the branches on the GPU, with OpenGL 4.3, unless 3D, stereo, \
+                     async generation or an effect that moves or fills the lines is on.",
+                );
+        });
+        if self.config.line_backend.instanced() && !instanced::available(ui.ctx()) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Instanced lines are unavailable, drawing egui shapes",
+            );
+        } else if self.config.line_backend == LineBackend::Compute && !compute::available(ui.ctx())
+        {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Compute shaders are unavailable, growing the branches on the CPU",
+            );
+        }
+        CollapsingHeader::new("Shader effects")
+            .show(ui, |ui| self.shader_effects.ui(ui, &self.shader_library));
+        CollapsingHeader::new("Post-processing").show(ui, |ui| self.config.post_processing.ui(ui));
+        CollapsingHeader::new("Color vision").show(ui, |ui| self.color_vision.ui(ui));
+        ui.add(
+            Slider::new(&mut self.config.start_line_width, START_LINE_WIDTH_RANGE)
+                .text("Start line width"),
+        );
+        ui.checkbox(&mut self.config.dpi_aware_widths, "DPI aware line widths")
+            .on_hover_text("Off draws widths in physical pixels, for pixel-exact lines");
+
+        ui.horizontal(|ui| {
+            ui.label("Profile:");
+            ui.radio_value(
+                &mut self.config.profile,
+                RenderProfile::Standard,
+                RenderProfile::Standard.label(),
+            );
+            ui.radio_value(
+                &mut self.config.profile,
+                RenderProfile::EInk,
+                RenderProfile::EInk.label(),
+            )
+            .on_hover_text("Black on white without gradients or animation");
+        });
+        if self.eink() {
+            ui.horizontal(|ui| {
+                ui.label("Update every");
+                ui.radio_value(
+                    &mut self.config.eink_interval,
+                    EInkInterval::Second,
+                    "second",
+                );
+                ui.radio_value(
+                    &mut self.config.eink_interval,
+                    EInkInterval::Minute,
+                    "minute",
+                );
+            });
+        }
+
+        let depth = self.config.depth;
+        // Ask before a depth that would exceed the cap, unless it already does
+        if ui
+            .add(Slider::new(&mut self.config.depth, DEPTH_RANGE).text("depth"))
+            .changed()
+            && estimated_line_count(self.config.depth) > self.config.line_cap
+            && estimated_line_count(depth) <= self.config.line_cap
+        {
+            self.pending_depth = Some(self.config.depth);
+            self.config.depth = depth;
+        }
+        if let Some(pending) = self.pending_depth {
+            self.confirm_depth_ui(ui, pending);
+        }
+        ui.add(
+            Slider::new(&mut self.config.line_cap, LINE_CAP_RANGE)
+                .logarithmic(true)
+                .text("max lines per frame"),
+        )
+        .on_hover_text(
+            "Branches generated per frame at most, so huge trees cannot freeze the clock. The \
+             deepest levels, which are the thinnest, are dropped first.",
+        );
+        ui.add(Slider::new(&mut self.config.length_factor, FACTOR_RANGE).text("length factor"));
+        ui.add(
+            Slider::new(&mut self.config.second_scale, BRANCH_SCALE_RANGE)
+                .text("second branch scale"),
+        );
+        ui.add(
+            Slider::new(&mut self.config.minute_scale, BRANCH_SCALE_RANGE)
+                .text("minute branch scale"),
+        );
+        ui.add(
+            Slider::new(&mut self.config.luminance_factor, FACTOR_RANGE).text("luminance factor"),
         );
+        egui::ComboBox::from_label("Color space")
+            .selected_text(self.config.color_space.label())
+            .show_ui(ui, |ui| {
+                for space in ColorSpace::ALL {
+                    ui.selectable_value(&mut self.config.color_space, space, space.label());
+                }
+            })
+            .response
+            .on_hover_text("Space the rainbow gradient is blended and the branch color dimmed in");
+
+        ui.add(Slider::new(&mut self.config.width_factor, FACTOR_RANGE).text("width factor"));
+        ui.checkbox(&mut self.config.fill, "Fill limbs")
+            .on_hover_text("Solid tapering limbs instead of overlapping lines");
+        self.config.shadow.ui(ui);
+        ui.add(Slider::new(&mut self.config.spiral, SPIRAL_RANGE).text("spiral °"));
+        self.config.jitter.ui(ui);
+        self.config.wind.ui(ui);
+        self.config.joints.ui(ui);
+        ui.add(Slider::new(&mut self.config.gravity, GRAVITY_RANGE).text("gravity °"))
+            .on_hover_text("Bends deeper branches down, or up if negative");
+        ui.horizontal(|ui| {
+            ui.label("Grow every:");
+            ui.radio_value(&mut self.config.growth, Growth::Off, "Off");
+            ui.radio_value(&mut self.config.growth, Growth::Minute, "Minute");
+            ui.radio_value(&mut self.config.growth, Growth::Hour, "Hour");
+        });
+
+        egui::Grid::new("color_settings_grid").show(ui, |ui| {
+            ui.label("Branch color:");
+            ui.color_edit_button_srgba(&mut self.config.branch_color);
+            ui.end_row();
+            ui.label("Hand color:");
+            ui.color_edit_button_srgba(&mut self.config.hand_color);
+            ui.end_row();
+        });
+
+        ui.checkbox(&mut self.config.rainbow_mode, "Rainbow");
+        if self.config.rainbow_mode {
+            ui.horizontal_wrapped(|ui| {
+                ui.color_edit_button_hsva(&mut self.config.start_hsv);
+                let mut removed = None;
+                for (index, stop) in self.config.gradient_stops.iter_mut().enumerate() {
+                    let response = ui.color_edit_button_hsva(stop);
+                    if response
+                        .on_hover_text("Right-click to remove")
+                        .secondary_clicked()
+                    {
+                        removed = Some(index);
+                    }
+                }
+                if let Some(index) = removed {
+                    self.config.gradient_stops.remove(index);
+                }
+                ui.color_edit_button_hsva(&mut self.config.end_hsv);
+                if ui
+                    .small_button("＋")
+                    .on_hover_text("Adds a color the gradient runs through")
+                    .clicked()
+                {
+                    let last = self.config.gradient_stops.last();
+                    let stop = last.copied().unwrap_or(self.config.start_hsv);
+                    self.config.gradient_stops.push(stop);
+                }
+            });
+            self.color_vision
+                .assist_ui(ui, &mut self.config.start_hsv, &mut self.config.end_hsv);
+        }
+        self.config.heatmap.ui(ui);
+        let action = CollapsingHeader::new("Import palette")
+            .show(ui, |ui| self.palette_import.ui(ui))
+            .body_returned
+            .flatten();
+        if let Some(action) = action {
+            self.apply_palette(action);
+        }
+
+        ui.checkbox(&mut self.fullscreen, "Fullscreen mode");
+        ui.checkbox(&mut self.transparent_background, "Transparent background");
+        #[cfg(feature = "export")]
+        if let Some(path) = &self.background_image {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let remove = ui
+                .horizontal(|ui| {
+                    ui.label(format!("Background: {name}"));
+                    ui.small_button("✖")
+                        .on_hover_text("Remove the background image")
+                        .clicked()
+                })
+                .inner;
+            if remove {
+                self.background_image = None;
+            }
+        } else {
+            ui.weak("Drop an image onto the window for a background");
+        }
+        ui.add(Slider::new(&mut self.window_opacity, 0.1..=1.0).text("window opacity"));
+        ui.horizontal(|ui| {
+            let mut capped = self.fps_cap.is_some();
+            ui.checkbox(&mut capped, "Cap FPS");
+            let mut fps = self.fps_cap.unwrap_or(30);
+            ui.add_enabled(capped, Slider::new(&mut fps, 1..=120));
+            self.fps_cap = capped.then_some(fps);
+        });
+        let action = CollapsingHeader::new("Session profiles")
+            .show(ui, |ui| self.profiles.ui(ui))
+            .body_returned
+            .flatten();
+        match action {
+            Some(ProfileAction::Save(index)) => self.save_profile(index),
+            Some(ProfileAction::Switch(index)) => self.switch_profile(index),
+            None => {}
+        }
+        self.window_stacking.ui(ui);
+        self.widget_window.ui(ui);
+        #[cfg(target_os = "linux")]
+        CollapsingHeader::new("X11 window hints").show(ui, |ui| self.x11_hints.ui(ui));
+        #[cfg(feature = "tray")]
+        ui.checkbox(&mut self.minimize_to_tray, "Minimize to tray");
+        #[cfg(feature = "hotkey")]
+        CollapsingHeader::new("Global hotkey").show(ui, |ui| self.hotkey.ui(ui));
+        self.launch_at_login.ui(ui);
+        if self.lock_button.ui(ui) {
+            self.locked = true;
+        }
+
+        CollapsingHeader::new("Complications").show(ui, |ui| {
+            self.complications
+                .ui(ui, &self.weather, &self.calendar, &self.sun)
+        });
+        CollapsingHeader::new("Time synchronization")
+            .show(ui, |ui| self.ntp_config.ui(ui, &self.ntp));
+        CollapsingHeader::new("Sync between machines")
+            .show(ui, |ui| self.sync_config.ui(ui, &mut self.sync));
+        CollapsingHeader::new("Prometheus metrics")
+            .show(ui, |ui| self.prometheus.ui(ui, &self.prometheus_server));
+        CollapsingHeader::new("Remote control")
+            .show(ui, |ui| self.remote.ui(ui, &self.remote_server));
+        CollapsingHeader::new("Twitch chat").show(ui, |ui| self.chat_config.ui(ui, &self.chat));
+        CollapsingHeader::new("OLED protection").show(ui, |ui| self.oled.ui(ui));
+        CollapsingHeader::new("Night mode").show(ui, |ui| self.night_mode.ui(ui));
+        CollapsingHeader::new("Screensaver").show(ui, |ui| {
+            ui.checkbox(&mut self.screensaver.enabled, "Enter fullscreen when idle");
+            ui.add(Slider::new(&mut self.screensaver.idle_minutes, 1..=120).text("idle minutes"));
+        });
+        CollapsingHeader::new("Battery saver").show(ui, |ui| self.battery_ui(ui));
+        #[cfg(feature = "audio")]
+        CollapsingHeader::new("Audio").show(ui, |ui| self.audio_config.ui(ui, &self.audio));
+        CollapsingHeader::new("Playback").show(ui, |ui| self.playback.ui(ui, &self.time));
+        CollapsingHeader::new("Stopwatch")
+            .show(ui, |ui| self.stopwatch_config.ui(ui, &mut self.stopwatch));
+        CollapsingHeader::new("History").show(ui, |ui| self.history_config.ui(ui));
+        let now = self.now();
+        CollapsingHeader::new("World map").show(ui, |ui| self.world_map.ui(ui, &now));
+        CollapsingHeader::new("Alarms").show(ui, |ui| {
+            self.alarms.ui(ui, &mut self.scheduler, now);
+        });
+        let jump = CollapsingHeader::new("Bookmarks")
+            .show(ui, |ui| self.bookmarks.ui(ui, self.time.time()))
+            .body_returned
+            .flatten();
+        if let Some(time) = jump {
+            self.jump_to(time);
+        }
+        #[cfg(feature = "export")]
+        {
+            let action = CollapsingHeader::new("Find beautiful times")
+                .show(ui, |ui| self.search_config.ui(ui, &self.search))
+                .body_returned
+                .flatten();
+            match action {
+                Some(SearchAction::Start) => self.start_search(ui.ctx()),
+                Some(SearchAction::Jump(time)) => self.jump_to(time),
+                Some(SearchAction::Bookmark(time)) => self.bookmarks.add(time),
+                None => {}
+            }
+        }
+        let action = CollapsingHeader::new("Morph")
+            .show(ui, |ui| {
+                let action = self.morph.ui(ui);
+                #[cfg(feature = "export")]
+                let action = action.or(self.morph.render_ui(ui, &self.morph_render));
+                action
+            })
+            .body_returned
+            .flatten();
+        match action {
+            Some(MorphAction::SetA) => self.morph.a = Some(self.config.clone()),
+            Some(MorphAction::SetB) => self.morph.b = Some(self.config.clone()),
+            Some(MorphAction::Swap) => std::mem::swap(&mut self.morph.a, &mut self.morph.b),
+            #[cfg(feature = "export")]
+            Some(MorphAction::RenderGif) => self.render_morph(ui.ctx(), false),
+            #[cfg(feature = "export")]
+            Some(MorphAction::RenderVideo) => self.render_morph(ui.ctx(), true),
+            None => {}
+        }
+        let save = CollapsingHeader::new("Slideshow")
+            .show(ui, |ui| self.slideshow_config.ui(ui, &mut self.slideshow))
+            .body_returned
+            .unwrap_or_default();
+        if save {
+            self.slideshow.save_preset(&self.config);
+        }
+        CollapsingHeader::new("Timeline").show(ui, |ui| self.timeline_ui(ui));
+        CollapsingHeader::new("Dive").show(ui, |ui| self.dive.ui(ui));
+        CollapsingHeader::new("Stereo 3D").show(ui, |ui| self.stereo.ui(ui));
+        CollapsingHeader::new("3D").show(ui, |ui| self.three_d.ui(ui));
+        #[cfg(feature = "gamepad")]
+        CollapsingHeader::new("Gamepad")
+            .show(ui, |ui| self.gamepad.ui(ui, &mut self.gamepad_config));
+        #[cfg(feature = "export")]
+        self.export_ui(ui);
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.log_viewer.open, "📜 Log");
+            ui.toggle_value(&mut self.profiler.open, "⏱ Profiler")
+                .on_hover_text("Times the parts of every frame, to find or report slowness");
+            ui.toggle_value(&mut self.about.open, "ℹ About");
+        });
+
+        ui.horizontal(|ui| {
+            egui::reset_button(ui, self, "🔁 Reset");
+            if ui.button("🎲 Randomize").clicked() {
+                self.randomize();
+            }
+        });
+
+        ui.hyperlink_to(
+            "Standalone version of this code",
+            "https://github.com/emilk/egui/blob/main/crates/egui_demo_app/src/apps/fractal_clock.rs",
+        );
+    }
+
+    fn timeline_ui(&mut self, ui: &mut Ui) {
+        self.timeline_config
+            .ui(ui, &mut self.timeline, &self.config);
+        #[cfg(feature = "export")]
+        match self.timeline_config.render_ui(ui, &self.timeline_render) {
+            Some(TimelineAction::RenderGif) => self.render_timeline(ui.ctx(), false),
+            Some(TimelineAction::RenderVideo) => self.render_timeline(ui.ctx(), true),
+            None => {}
+        }
+    }
+
+    fn battery_ui(&mut self, ui: &mut Ui) {
+        ui.label(if self.power.on_battery() {
+            "Running on battery"
+        } else {
+            "Running on AC power"
+        });
+        ui.checkbox(
+            &mut self.battery_profile.enabled,
+            "Reduce quality on battery",
+        );
+        ui.add(Slider::new(&mut self.battery_profile.max_depth, 0..=20).text("max depth"));
+        ui.add(Slider::new(&mut self.battery_profile.fps_cap, 1..=60).text("FPS cap"));
+        ui.checkbox(&mut self.battery_profile.effects_off, "Effects off")
+            .on_hover_text(
+                "Turns off the shadow, heatmap, blend modes, shader effects and post-processing",
+            );
+    }
+
+    #[cfg(feature = "export")]
+    fn export_ui(&mut self, ui: &mut Ui) {
+        if ui
+            .button("📋 Copy image")
+            .on_hover_text("Copies the shown frame to the clipboard, also with Ctrl+C on the clock")
+            .clicked()
+        {
+            self.copy_image(ui.ctx());
+        }
+        CollapsingHeader::new("Output").show(ui, |ui| self.output_ui(ui));
+        let destination = CollapsingHeader::new("ASCII art")
+            .show(ui, |ui| self.ascii_art.ui(ui))
+            .body_returned
+            .flatten();
+        if let Some(destination) = destination {
+            let ctx = ui.ctx().clone();
+            if let Err(message) = watchdog::catch(|| self.export_ascii_art(&ctx, destination)) {
+                tracing::error!("ASCII art export panicked: {message}");
+                toasts::error(format!("Failed to export ASCII art: {message}"));
+                self.rendering.shapes.clear();
+            }
+        }
+        let action = CollapsingHeader::new("Timelapse")
+            .show(ui, |ui| self.timelapse_config.ui(ui, &self.timelapse))
+            .body_returned
+            .flatten();
+        match action {
+            Some(TimelapseAction::Start) => {
+                if let Err(e) = self.timelapse.start() {
+                    tracing::error!("Failed to start a timelapse: {e}");
+                    toasts::error(format!("Failed to start a timelapse: {e}"));
+                }
+            }
+            Some(TimelapseAction::Stop) => self.timelapse.stop(),
+            Some(TimelapseAction::AssembleGif) => {
+                self.timelapse
+                    .assemble(ui.ctx(), false, self.timelapse_config.fps);
+            }
+            Some(TimelapseAction::AssembleVideo) => {
+                self.timelapse
+                    .assemble(ui.ctx(), true, self.timelapse_config.fps);
+            }
+            None => {}
+        }
+        let export = CollapsingHeader::new("Poster")
+            .show(ui, |ui| self.poster_config.ui(ui, &self.poster))
+            .body_returned
+            .unwrap_or_default();
+        if export {
+            self.export_poster(ui.ctx());
+        }
+        let capture = CollapsingHeader::new("Burst")
+            .show(ui, |ui| self.burst_config.ui(ui, &self.burst))
+            .body_returned
+            .unwrap_or_default();
+        if capture {
+            self.capture_burst(ui.ctx());
+        }
+    }
+
+    /// Renders the shown frame at the size of the window, without the settings, and places it
+    /// on the clipboard.
+    #[cfg(feature = "export")]
+    fn copy_image(&mut self, ctx: &egui::Context) {
+        let size = (ctx.screen_rect().size() * ctx.pixels_per_point()).round();
+        let size = [size.x as u32, size.y as u32];
+        let result = watchdog::catch(|| self.render_image(size, self.time))
+            .and_then(|image| self.clipboard.copy_image(&image));
+        match result {
+            Ok(()) => toasts::success("Copied the frame to the clipboard"),
+            Err(e) => {
+                tracing::error!("Failed to copy the frame: {e}");
+                toasts::error(format!("Failed to copy the frame: {e}"));
+                self.rendering.shapes.clear();
+            }
+        }
+    }
+
+    /// Syncs the settings when due, returning a clock with the settings of a newer copy from
+    /// another machine.
+    pub(crate) fn update_sync(&mut self, ctx: &egui::Context) -> Option<Self> {
+        if !self.sync_config.enabled {
+            return None;
+        }
+        if self.settings_driven {
+            // Not changes made on this machine, which would win every sync
+            self.sync.pause();
+        } else if self.sync.check_due() {
+            // Through TOML like the stored settings, which leaves out the empty options
+            let settings = toml::Value::try_from(&*self)
+                .map_err(|e| e.to_string())
+                .and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string()));
+            match settings {
+                Ok(settings) => {
+                    let settings = settings_sync::synced(&settings);
+                    self.sync.observe(&mut self.sync_config, settings);
+                }
+                Err(e) => tracing::warn!("Failed to serialize the synced settings: {e}"),
+            }
+        }
+        let settings = self.sync.update(ctx, &mut self.sync_config)?;
+        // Only the synced settings replace the ones of this machine
+        let clock = toml::Value::try_from(&*self)
+            .and_then(|mut value| {
+                let synced = toml::Value::try_from(settings_sync::synced(&settings))?;
+                if let (Some(table), Some(synced)) = (value.as_table_mut(), synced.as_table()) {
+                    table.extend(synced.clone());
+                }
+                Ok(value)
+            })
+            .map_err(|e| e.to_string())
+            .and_then(|mut value| {
+                migration::migrate(&mut value);
+                value.try_into::<Self>().map_err(|e| e.to_string())
+            });
+        match clock {
+            Ok(mut clock) => {
+                clock.sync_config = self.sync_config.clone();
+                clock.sync = std::mem::take(&mut self.sync);
+                Some(clock)
+            }
+            Err(e) => {
+                tracing::error!("Failed to apply the synced settings: {e}");
+                toasts::error(format!("Failed to apply the synced settings: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Keeps the current settings, window mode, FPS cap and overlays in session profile `index`.
+    fn save_profile(&mut self, index: usize) {
+        self.profiles.profiles[index].state = Some(ProfileState {
+            config: self.config.clone(),
+            fullscreen: self.fullscreen,
+            widget_window: self.widget_window.enabled,
+            window_stacking: self.window_stacking,
+            fps_cap: self.fps_cap,
+            readout: self.readout.clone(),
+            complications: self.complications.clone(),
+        });
+        self.profiles.active = Some(index);
+        toasts::success(format!(
+            "Saved profile {}",
+            self.profiles.profiles[index].name
+        ));
+    }
+
+    /// Switches to session profile `index`, if it was saved.
+    pub fn switch_profile(&mut self, index: usize) {
+        let Some(profile) = self.profiles.profiles.get(index) else {
+            return;
+        };
+        let Some(state) = profile.state.clone() else {
+            toasts::warning(format!("Profile {} was not saved yet", profile.name));
+            return;
+        };
+        toasts::info(format!("Switched to profile {}", profile.name));
+        self.config = state.config;
+        self.fullscreen = state.fullscreen;
+        self.widget_window.enabled = state.widget_window;
+        self.window_stacking = state.window_stacking;
+        self.fps_cap = state.fps_cap;
+        self.readout = state.readout;
+        // The location belongs to the place, not the profile
+        let location = self.complications.location;
+        self.complications = state.complications;
+        self.complications.location = location;
+        self.profiles.active = Some(index);
+    }
+
+    /// Uses the look of a preset, keeping the other settings.
+    pub(crate) fn apply_preset(&mut self, config: FractalClockConfig) {
+        self.config = config;
+    }
+
+    /// Uses imported colors for the gradient or the hands and branches.
+    pub(crate) fn apply_palette(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::Gradient(colors) => {
+                let stops: Vec<Hsva> = colors.into_iter().map(Hsva::from).collect();
+                if let [start, middle @ .., end] = &stops[..] {
+                    self.config.start_hsv = *start;
+                    self.config.end_hsv = *end;
+                    self.config.gradient_stops = middle.to_vec();
+                    self.config.rainbow_mode = true;
+                }
+            }
+            PaletteAction::Theme { hand, branch } => {
+                self.config.hand_color = hand;
+                self.config.branch_color = branch;
+                self.config.rainbow_mode = false;
+            }
+        }
+    }
+
+    /// A clock with the settings of this one, for rendering on a background thread.
+    #[cfg(feature = "export")]
+    fn offscreen(&self) -> impl FnOnce() -> Self + Send + 'static {
+        let config = self.config.clone();
+        let night_mode = self.night_mode.clone();
+        let location = self.complications.location;
+        move || {
+            let mut clock = Self {
+                config,
+                night_mode,
+                ..Self::default()
+            };
+            clock.complications.location = location;
+            clock
+        }
+    }
+
+    /// Renders the clock at evenly spaced times of the shown day into one grid on a background
+    /// thread.
+    #[cfg(feature = "export")]
+    fn export_poster(&mut self, ctx: &egui::Context) {
+        let make_clock = self.offscreen();
+        let settings = self.poster_config.clone();
+        let day = self.time;
+        self.poster.export(ctx, "poster", move || {
+            let mut clock = make_clock();
+            let [width, height] = settings.image_size();
+            // Matches the background of the rendered clocks
+            let (background, caption) = if clock.eink() {
+                (Color32::WHITE, Color32::from_gray(80))
+            } else {
+                (Color32::BLACK, Color32::from_gray(200))
+            };
+            let mut poster =
+                image::RgbaImage::from_pixel(width, height, image::Rgba(background.to_array()));
+            for index in 0..settings.count() {
+                let Some(time) = NaiveTime::from_num_seconds_from_midnight_opt(
+                    index * settings.interval_seconds(),
+                    0,
+                ) else {
+                    continue;
+                };
+                let at = Local
+                    .from_local_datetime(&day.date_naive().and_time(time))
+                    .earliest()
+                    .unwrap_or(day);
+                let cell = clock.render_image([settings.cell_size; 2], at);
+                let label = time.format("%H:%M").to_string();
+                settings.place(&mut poster, index, &cell, &label, caption);
+            }
+            poster
+        });
+    }
+
+    /// Renders frames from the shown time a burst interval apart into a contact sheet on a
+    /// background thread, to pick the best instant of the sweep of the second hand.
+    #[cfg(feature = "export")]
+    fn capture_burst(&mut self, ctx: &egui::Context) {
+        let make_clock = self.offscreen();
+        let settings = self.burst_config.clone();
+        let start = self.time;
+        self.burst.export(ctx, "burst", move || {
+            let mut clock = make_clock();
+            let sheet = settings.sheet();
+            let [width, height] = sheet.image_size();
+            // Matches the background of the rendered clocks
+            let (background, caption) = if clock.eink() {
+                (Color32::WHITE, Color32::from_gray(80))
+            } else {
+                (Color32::BLACK, Color32::from_gray(200))
+            };
+            let mut image =
+                image::RgbaImage::from_pixel(width, height, image::Rgba(background.to_array()));
+            for index in 0..settings.count() {
+                let offset =
+                    chrono::Duration::milliseconds(i64::from(index * settings.interval_ms));
+                let at = start + offset;
+                let cell = clock.render_image([settings.cell_size; 2], at);
+                let label = at.format("%H:%M:%S%.3f").to_string();
+                sheet.place(&mut image, index, &cell, &label, caption);
+            }
+            image
+        });
+    }
+
+    /// Renders a loop of the morph at the shown time into frames and assembles them on a
+    /// background thread. The hands stand still, so the loop has no seam.
+    #[cfg(feature = "export")]
+    fn render_morph(&mut self, ctx: &egui::Context, video: bool) {
+        let make_clock = self.offscreen();
+        let morph = self.morph.clone();
+        let time = self.time;
+        self.morph_render.start(ctx, move || {
+            let dir = timelapse::frames_dir("morph").map_err(|e| e.to_string())?;
+            let mut clock = make_clock();
+            let frames = morph.frame_count();
+            for index in 0..frames {
+                let Some(config) = morph.at(index as f32 / frames as f32) else {
+                    break;
+                };
+                clock.config = config;
+                let image = clock.render_image([morph.size; 2], time);
+                let path = timelapse::frame_path(&dir, index as usize);
+                image.save(&path).map_err(|e| e.to_string())?;
+            }
+            if video {
+                timelapse::assemble_video(&dir, morph.fps)
+            } else {
+                timelapse::assemble_gif(&dir, morph.fps)
+            }
+        });
+    }
+
+    /// Renders the timeline from the shown time into frames and assembles them on a background
+    /// thread.
+    #[cfg(feature = "export")]
+    fn render_timeline(&mut self, ctx: &egui::Context, video: bool) {
+        let make_clock = self.offscreen();
+        let timeline = self.timeline_config.clone();
+        let start = self.time;
+        self.timeline_render.start(ctx, move || {
+            let dir = timelapse::frames_dir("timeline").map_err(|e| e.to_string())?;
+            let mut clock = make_clock();
+            for index in 0..timeline.frame_count() {
+                let seconds = index as f32 / timeline.fps.max(1) as f32;
+                timeline.apply(seconds, &mut clock.config);
+                let time = start + Duration::from_secs_f32(seconds);
+                let image = clock.render_image([timeline.size; 2], time);
+                let path = timelapse::frame_path(&dir, index as usize);
+                image.save(&path).map_err(|e| e.to_string())?;
+            }
+            if video {
+                timelapse::assemble_video(&dir, timeline.fps)
+            } else {
+                timelapse::assemble_gif(&dir, timeline.fps)
+            }
+        });
+    }
+
+    /// Searches the shown day for beautiful times on a background thread, with a clock of its own.
+    #[cfg(feature = "export")]
+    fn start_search(&mut self, ctx: &egui::Context) {
+        let make_clock = self.offscreen();
+        let settings = self.search_config.clone();
+        let day = self.time;
+        self.search.start(ctx, move |report| {
+            let mut clock = make_clock();
+            let candidates = clock.find_beautiful_times(day, &settings, &|progress| {
+                report(SearchMessage::Progress(progress));
+            });
+            report(SearchMessage::Done(candidates));
+        });
+    }
+
+    /// Scores the times of the day of `day` every `step_minutes`, returning the best with their
+    /// thumbnails.
+    #[cfg(feature = "export")]
+    fn find_beautiful_times(
+        &mut self,
+        day: DateTime<Local>,
+        settings: &SearchConfig,
+        progress: &dyn Fn(f32),
+    ) -> Vec<Candidate> {
+        const SCORE_SIZE: f32 = 256.0;
+        // The overall shape is set by the first levels, which keeps scoring a whole day fast
+        const SCORE_DEPTH: usize = 10;
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(SCORE_SIZE));
+        let step = settings.step_minutes.max(1);
+        let steps = 24 * 60 / step;
+        let times: Vec<_> = (0..steps)
+            .filter_map(|index| NaiveTime::from_num_seconds_from_midnight_opt(index * step * 60, 0))
+            .collect();
+
+        let at = |time| {
+            Local
+                .from_local_datetime(&day.date_naive().and_time(time))
+                .earliest()
+                .unwrap_or(day)
+        };
+        let depth = self.config.depth;
+        self.config.depth = depth.min(SCORE_DEPTH);
+        let mut scored = Vec::with_capacity(times.len());
+        for (index, &time) in times.iter().enumerate() {
+            self.time = at(time);
+            let [second, minute, hour] = self.hand_angles();
+            // The tree mirrors itself when the hour hand halves the angle between the others
+            let spiral = f64::from(self.config.spiral.to_radians());
+            let symmetry = 0.5 + 0.5 * (second + minute - 2.0 * hour + 2.0 * spiral).cos();
+            self.layout(rect, 1.0, false);
+            let spread = search::spread(&self.rendering.shapes, rect);
+            scored.push((time, symmetry as f32 + spread));
+            if index % 16 == 0 {
+                progress(index as f32 / times.len() as f32);
+            }
+        }
+
+        self.config.depth = depth;
+        let separation = i64::from(settings.separation_minutes) * 60;
+        search::best(scored, settings.count, separation)
+            .into_iter()
+            .map(|(time, score)| {
+                let size = search::THUMBNAIL_SIZE;
+                let image = self.render_image([size; 2], at(time));
+                Candidate {
+                    time,
+                    score,
+                    thumbnail: search::thumbnail(&image),
+                }
+            })
+            .collect()
+    }
+
+    /// Captures the next timelapse frame when it is due.
+    #[cfg(feature = "export")]
+    fn update_timelapse(&mut self, ctx: &egui::Context) {
+        match self.timelapse.poll() {
+            Some(Ok(path)) => toasts::success(format!("Saved timelapse to {}", path.display())),
+            Some(Err(e)) => {
+                tracing::error!("Failed to assemble the timelapse: {e}");
+                toasts::error(format!("Failed to assemble the timelapse: {e}"));
+            }
+            None => {}
+        }
+        if !self.timelapse.due(ctx) {
+            return;
+        }
+        let size = self.timelapse_config.size;
+        match watchdog::catch(|| self.render_image([size; 2], self.now())) {
+            Ok(image) => self.timelapse.save(image, &self.timelapse_config),
+            Err(message) => {
+                tracing::error!("Timelapse capture panicked: {message}");
+                toasts::error(format!("Stopped the timelapse after an error: {message}"));
+                self.timelapse.stop();
+                self.rendering.shapes.clear();
+            }
+        }
+    }
+
+    #[cfg(feature = "export")]
+    fn output_ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.output.ndi_enabled, "NDI stream");
+        ui.horizontal(|ui| {
+            ui.label("Stream name:");
+            let draft = self
+                .stream_name_draft
+                .get_or_insert_with(|| self.output.stream_name.clone());
+            let response = ui.text_edit_singleline(draft);
+            if response.lost_focus() {
+                self.output.stream_name = draft.clone();
+            }
+            if !response.has_focus() {
+                self.stream_name_draft = None;
+            }
+        });
+
+        if let Some(error) = &self.frame_output.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if self.frame_output.is_streaming() {
+            ui.label(format!("Streaming as \"{}\"", self.output.stream_name));
+        }
+    }
+
+    #[cfg(feature = "export")]
+    fn export_ascii_art(&mut self, ctx: &egui::Context, destination: Destination) {
+        let size = self.ascii_art.size();
+        let (columns, ansi) = (self.ascii_art.columns, self.ascii_art.ansi);
+        let lines = self.lines(Rect::from_min_size(Pos2::ZERO, Vec2::splat(size)));
+        let text = ascii_art::render(lines, columns, ansi);
 
+        match destination {
+            Destination::Clipboard => {
+                ctx.copy_text(text);
+                toasts::success("Copied ASCII art to the clipboard");
+            }
+            Destination::File => match ascii_art::save(&text, ansi) {
+                Ok(path) => toasts::success(format!("Saved ASCII art to {}", path.display())),
+                Err(e) => {
+                    tracing::error!("Failed to save ASCII art: {e}");
+                    toasts::error(format!("Failed to save ASCII art: {e}"));
+                }
+            },
+        }
+    }
+
+    /// Turns off the part of the frame that panicked with `message`, so the clock keeps running.
+    fn recover(&mut self, message: String) {
+        let stage = self.rendering.stage;
+        tracing::error!("Painting {} panicked: {message}", stage.label());
+        toasts::error(format!(
+            "Turned off {} after an error: {message}",
+            stage.label()
+        ));
+        match stage {
+            RenderStage::Dive => self.dive.enabled = false,
+            RenderStage::Branches => {
+                self.config.jitter.enabled = false;
+                self.config.wind.enabled = false;
+                self.config.joints.enabled = false;
+                self.config.fill = false;
+                self.config.growth = Growth::Off;
+                self.stereo.mode = StereoMode::Off;
+            }
+            RenderStage::ThreeD => self.three_d.enabled = false,
+            RenderStage::Heatmap => self.config.heatmap.enabled = false,
+            RenderStage::Shadow => self.config.shadow.enabled = false,
+            RenderStage::Complications => self.complications.disable(),
+            RenderStage::Minimap => self.config.minimap = false,
+            RenderStage::WorldMap => self.world_map.enabled = false,
+        }
+        // Buffers may be left half filled
         self.rendering.shapes.clear();
-        self.rendering.nodes_buf1.clear();
-        self.rendering.nodes_buf2.clear();
+        self.rendering.segments_3d.clear();
+        self.rendering.depth_colors.clear();
+        self.rendering.colors_key = None;
+    }
+
+    fn paint(&mut self, painter: &Painter) {
+        let _scope = profiler::scope("paint");
+        self.rendering.stage = RenderStage::Branches;
+        let window = painter.clip_rect();
+        let time = painter.ctx().input(|i| i.time);
+        let center_offset = if self.eink() {
+            Vec2::ZERO
+        } else {
+            self.oled.center_offset(time)
+        };
+        if self.eink() {
+            self.widget_window
+                .paint_background(painter, window, Color32::WHITE);
+        } else if self.widget_window.enabled && !self.transparent_background {
+            let fill = painter.ctx().style().visuals.extreme_bg_color;
+            self.widget_window.paint_background(painter, window, fill);
+        }
+        #[cfg(feature = "export")]
+        if !self.eink()
+            && let Some(path) = &self.background_image
+        {
+            self.background.paint(painter, window, path);
+        }
+        let (view, root, start_width) = if self.diving() {
+            self.rendering.stage = RenderStage::Dive;
+            let hand_rotors = self.calculate_hand_rotors(&self.create_hands());
+            let frame = self.dive_progress.frame(&self.dive, &hand_rotors);
+            // Widths grow towards those of the parent level, which the next root is drawn with
+            let width = self.config.start_line_width
+                / self
+                    .config
+                    .width_factor
+                    .max(f32::EPSILON)
+                    .powf(self.dive_progress.progress());
+            (
+                self.dive_view(window, center_offset, &frame),
+                Some(frame.root_dir),
+                width,
+            )
+        } else {
+            let view = self.view(window, center_offset);
+            (view, None, self.config.start_line_width)
+        };
 
+        self.rendering.zoom = view.zoom;
+        self.rendering.width_scale = if self.config.dpi_aware_widths {
+            1.0
+        } else {
+            1.0 / painter.ctx().pixels_per_point()
+        };
+        let start_width = start_width * self.rendering.width_scale;
+        self.update_colors();
+        if self.world_map.enabled && !self.three_d.enabled && root.is_none() {
+            self.rendering.stage = RenderStage::WorldMap;
+            self.line_count = self.paint_world_map(painter, window, start_width);
+            return;
+        }
+        if self.stopwatch_config.enabled && !self.three_d.enabled && root.is_none() {
+            self.paint_ghosts(painter, &view, start_width);
+        }
+        if self.history_config.enabled && !self.three_d.enabled && root.is_none() {
+            let angles = self.hand_angles().map(|angle| angle as f32);
+            self.history.record(&self.history_config, self.time, angles);
+            let length = self.config.length_factor;
+            let width = start_width.max(self.min_line_width());
+            self.history.paint(
+                &self.history_config,
+                painter,
+                &view.to_screen,
+                self.time,
+                [length, length, HOUR_HAND_LENGTH],
+                Stroke::new(width, self.hand_color()),
+            );
+        }
+
+        self.rendering.shapes.clear();
+        let max_depth = self.max_depth().min(self.grown_depth());
+        let max_depth = self.governor.update(
+            painter.ctx(),
+            &self.refinement,
+            max_depth.min(self.branch_depth()),
+        );
+        let capacity = self.shape_capacity(max_depth);
+        shape_pool::reserve(&mut self.rendering.shapes, capacity);
+        self.line_count = 0;
+        self.rendering.culled_count = 0;
+        self.reset_line_budget();
+        let mut gpu_job = None;
+        if self.three_d.enabled {
+            self.rendering.stage = RenderStage::ThreeD;
+            self.line_count = self.render_3d(&view, start_width, max_depth);
+        } else if root.is_none() && self.gpu_expansion(painter.ctx()) {
+            let job = self.gpu_job(&view, start_width, max_depth);
+            self.line_count = job.1;
+            gpu_job = Some(job.0);
+        } else if self.config.async_generation {
+            self.line_count =
+                self.generate_async(painter.ctx(), &view, window, start_width, max_depth, root);
+        } else {
+            for &eye in self.eyes() {
+                let to_screen = self.stereo.eye_transform(eye, &view.to_screen, window);
+                self.line_count += self.render_pass(&Pass {
+                    to_screen: &to_screen,
+                    rect: self.stereo.eye_rect(eye, view.visible, window),
+                    start_width,
+                    max_depth,
+                    root,
+                    eye,
+                    fill: self.config.fill,
+                });
+            }
+        }
+        self.rendering.measure_culling(self.line_count);
+        if !self.eink() && !self.effects_off_on_battery() {
+            let brightness = self.brightness();
+            self.rendering.stage = RenderStage::Heatmap;
+            self.config
+                .heatmap
+                .apply(&mut self.rendering.shapes, view.visible, brightness);
+            self.rendering.stage = RenderStage::Shadow;
+            self.config.shadow.apply(&mut self.rendering.shapes);
+        }
+        self.widget_window.mask(window, &mut self.rendering.shapes);
+        let blend = if self.eink() || self.effects_off_on_battery() {
+            BlendMode::Normal
+        } else {
+            self.config.blend_mode
+        };
+        let _scope = profiler::scope("submit");
+        let effects = self.effects();
+        // Other blend modes and effects need the offscreen pass even at the window's resolution
+        if self.config.render_scale == 1.0 && blend == BlendMode::Normal && effects.is_empty() {
+            if self.config.line_backend.instanced() && instanced::available(painter.ctx()) {
+                instanced::paint(painter, self.rendering.shapes.drain(..));
+            } else {
+                painter.extend(self.rendering.shapes.drain(..));
+            }
+            if let Some(job) = gpu_job {
+                compute::paint(painter, job);
+            }
+        } else {
+            render_scale::paint_scaled(
+                painter,
+                self.rendering.shapes.drain(..),
+                self.config.render_scale,
+                blend,
+                effects,
+                painter.ctx().input(|i| i.time) as f32,
+            );
+        }
+
+        if self.config.letterbox {
+            for &eye in self.eyes() {
+                let eye_window = self.stereo.eye_window(eye, window);
+                let visible = self.stereo.eye_rect(eye, view.visible, window);
+                for bar in letterbox_bars(eye_window, visible) {
+                    painter.rect_filled(bar, 0.0, self.config.letterbox_color);
+                }
+            }
+        }
+
+        self.rendering.stage = RenderStage::Complications;
+        // The dial turns with the root while diving
+        if self.complications.sun.enabled && !self.three_d.enabled && !self.diving() {
+            let times = self.sun.times(&self.time, self.complications.location);
+            self.complications.sun.paint(
+                painter,
+                &view.to_screen,
+                times,
+                HOUR_HAND_LENGTH,
+                self.config.time_system,
+                self.complications.location,
+            );
+        }
+        if self.complications.calendar_config.marker
+            && !self.three_d.enabled
+            && !self.diving()
+            && let Some(start) = self.calendar.marked(&self.time)
+        {
+            self.complications.calendar_config.paint(
+                painter,
+                &view.to_screen,
+                start,
+                HOUR_HAND_LENGTH,
+                self.config.time_system,
+                self.complications.location,
+            );
+        }
+        self.complications
+            .paint(painter, window, &self.time, &self.weather, &self.calendar);
+
+        if let Some(minimap) = self.minimap(window) {
+            self.rendering.stage = RenderStage::Minimap;
+            let mut painter = painter.with_clip_rect(*minimap.to());
+            painter.multiply_opacity(self.oled.overlay_opacity(time));
+            self.paint_minimap(&painter, &minimap, &view);
+        }
+    }
+
+    /// Draws the hands frozen at the latest laps of the stopwatch, faintly behind the clock.
+    fn paint_ghosts(&mut self, painter: &Painter, view: &View, start_width: f32) {
+        let settings = &self.stopwatch_config;
+        let mut ghost_painter = painter.clone();
+        ghost_painter.multiply_opacity(settings.ghost_opacity);
+        let max_depth = if settings.ghost_fractal {
+            settings.ghost_depth.min(self.max_depth())
+        } else {
+            0
+        };
+        let skipped = self
+            .stopwatch
+            .laps
+            .len()
+            .saturating_sub(stopwatch::MAX_GHOSTS);
+        let now = self.time;
+        for index in skipped..self.stopwatch.laps.len() {
+            self.time = Stopwatch::time(self.stopwatch.laps[index], now);
+            self.rendering.shapes.clear();
+            self.reset_line_budget();
+            self.render_pass(&Pass {
+                to_screen: &view.to_screen,
+                rect: view.visible,
+                start_width,
+                max_depth,
+                root: None,
+                eye: None,
+                fill: self.config.fill,
+            });
+            ghost_painter.extend(self.rendering.shapes.drain(..));
+        }
+        self.time = now;
+    }
+
+    /// Whether the branches can grow in a compute shader, which only does the plain fractal
+    /// without effects that change the lines on the CPU.
+    fn gpu_expansion(&self, ctx: &egui::Context) -> bool {
+        self.config.line_backend == LineBackend::Compute
+            && compute::available(ctx)
+            && !self.config.async_generation
+            && self.config.render_scale == 1.0
+            && self.config.blend_mode == BlendMode::Normal
+            && self.effects().is_empty()
+            && self.stereo.mode == StereoMode::Off
+            && !self.config.jitter.enabled
+            && !self.config.wind.enabled
+            && self.config.gravity == 0.0
+            && !self.config.joints.bends()
+            && !self.config.fill
+            && !self.config.heatmap.enabled
+            && !self.config.shadow.enabled
+            && !self.widget_window.enabled
+    }
+
+    /// Shader effects and then post-processing effects to draw the frame through, none on e-ink.
+    /// Only the color vision filter is kept to save battery.
+    fn effects(&self) -> Vec<Arc<Effect>> {
+        if self.eink() {
+            return Vec::new();
+        }
+        let mut effects = Vec::new();
+        if !self.effects_off_on_battery() {
+            effects.extend(self.shader_effects.active(&self.shader_library));
+            effects.extend(self.config.post_processing.active());
+        }
+        effects.extend(self.color_vision.effect());
+        effects
+    }
+
+    /// Draws the hands and sets up the levels of branches for the compute shader, within the
+    /// line budget, returning them with the number of lines.
+    fn gpu_job(
+        &mut self,
+        view: &View,
+        start_width: f32,
+        max_depth: usize,
+    ) -> (compute::Job, usize) {
         let mut line_count = 0;
         let hands = self.create_hands();
-        let hand_rotors = self.calculate_hand_rotors(&hands);
+        self.draw_hands(
+            &hands,
+            &Pass {
+                to_screen: &view.to_screen,
+                rect: view.visible,
+                start_width,
+                max_depth,
+                root: None,
+                eye: None,
+                fill: false,
+            },
+            &mut line_count,
+        );
+
+        let brightness = self.brightness();
+        let mut width = start_width;
+        let mut levels = Vec::new();
+        let depth = self.rendering.depth_colors.len().min(max_depth);
+        for (level, &color) in self.rendering.depth_colors[..depth].iter().enumerate() {
+            let lines = compute::Job::level_count(level);
+            if lines > self.rendering.line_budget || !compute::Job::dispatchable(lines) {
+                self.rendering.truncated = true;
+                break;
+            }
+            self.rendering.line_budget -= lines;
+            width *= self.config.width_factor;
+            let line_width = if level >= self.config.depth {
+                width.max(MIN_ADAPTIVE_LINE_WIDTH * self.rendering.width_scale)
+            } else {
+                width
+            }
+            .max(self.min_line_width());
+            levels.push((line_width, dim(color, brightness)));
+            line_count += lines;
+        }
+        self.rendering.levels_drawn = levels.len();
+
+        let [second, minute, _] = self.hand_angles();
+        let length = f64::from(self.config.length_factor);
+        let [second, minute] = [second, minute].map(|angle| {
+            let hand = Complex::<f32>::from_polar(length, angle);
+            [hand.re, hand.im, hand.re, hand.im]
+        });
+        let [a, b] = self.branch_rotors::<f32>();
+        let job = compute::Job {
+            hands: [second, minute],
+            rotors: [a.re, a.im, b.re, b.im],
+            levels,
+            to_screen: compute::Job::transform(&view.to_screen),
+        };
+        (job, line_count)
+    }
+
+    /// Paints the world map with a small clock at every city, in its time zone and at the map's
+    /// depth, returning the number of lines.
+    fn paint_world_map(&mut self, painter: &Painter, window: Rect, start_width: f32) -> usize {
+        let map = WorldMapConfig::map_rect(window);
+        self.world_map.paint_map(painter, map);
+        let size = map.height() * self.world_map.clock_size;
+        let radius = self.bounding_radius();
+        let max_depth = self.world_map.depth.min(self.max_depth());
+        let start_width = start_width * (size / window.size().min_elem()).max(0.25);
+        let now = self.time;
+        let mut line_count = 0;
+        for city in self.world_map.cities.clone() {
+            self.time = city.time(&now);
+            let rect = Rect::from_center_size(
+                WorldMapConfig::project(map, city.longitude as f32, city.latitude as f32),
+                Vec2::splat(size),
+            );
+            let to_screen = emath::RectTransform::from_to(
+                Rect::from_center_size(Pos2::ZERO, Vec2::splat(2.0 * radius)),
+                rect,
+            );
+            self.rendering.shapes.clear();
+            self.reset_line_budget();
+            line_count += self.render_pass(&Pass {
+                to_screen: &to_screen,
+                rect,
+                start_width,
+                max_depth,
+                root: None,
+                eye: None,
+                fill: self.config.fill,
+            });
+            painter.extend(self.rendering.shapes.drain(..));
+            self.world_map.paint_label(painter, rect, &city, &self.time);
+        }
+        self.time = now;
+        line_count
+    }
+
+    /// Lays out the hands and branches in `rect` without egui, for frontends drawing the lines
+    /// themselves, like the terminal.
+    pub fn lines(&mut self, rect: Rect) -> std::vec::Drain<'_, Shape> {
+        if !self.paused {
+            self.time = self.shown_time();
+        }
+        self.line_count = self.layout(rect, 1.0, false);
+        self.rendering.shapes.drain(..)
+    }
+
+    /// Renders the clock at `time` into an image of `size` pixels without a window, for exports.
+    #[cfg(feature = "export")]
+    fn render_image(&mut self, size: [u32; 2], time: DateTime<Local>) -> image::RgbaImage {
+        let now = std::mem::replace(&mut self.time, time);
+        let rect = Rect::from_min_size(Pos2::ZERO, egui::vec2(size[0] as f32, size[1] as f32));
+        self.layout(rect, self.config.start_line_width, self.config.fill);
+        let background = if self.eink() {
+            Color32::WHITE
+        } else {
+            let brightness = self.brightness();
+            self.config
+                .heatmap
+                .apply(&mut self.rendering.shapes, rect, brightness);
+            self.config.shadow.apply(&mut self.rendering.shapes);
+            Color32::BLACK
+        };
+        self.time = now;
+        headless::rasterize(&self.rendering.shapes, size, background)
+    }
+
+    /// Lays out the hands and branches in `rect` into `rendering.shapes`, returning the number of
+    /// lines.
+    fn layout(&mut self, rect: Rect, start_width: f32, fill: bool) -> usize {
+        let view = self.view(rect, Vec2::ZERO);
+        self.rendering.zoom = view.zoom;
+        self.rendering.width_scale = 1.0;
+        self.update_colors();
+
+        self.rendering.shapes.clear();
+        self.rendering.culled_count = 0;
+        self.reset_line_budget();
+        self.render_pass(&Pass {
+            to_screen: &view.to_screen,
+            rect: view.visible,
+            start_width,
+            max_depth: self.max_depth(),
+            root: None,
+            eye: None,
+            fill,
+        })
+    }
 
-        self.draw_hands(&hands, &to_screen, rect, &mut line_count);
-        self.draw_fractal_branches(&hand_rotors, &to_screen, rect, &mut line_count);
+    /// Draws the hands and branches into `rendering.shapes`, returning the number of lines.
+    ///
+    /// With a `root` direction, only the branches growing from a branch ending at the origin are
+    /// drawn instead of the whole clock.
+    fn render_pass(&mut self, pass: &Pass) -> usize {
+        let mut line_count = 0;
+        if pass.root.is_none() {
+            let hands = self.create_hands();
+            self.draw_hands(&hands, pass, &mut line_count);
+        }
+
+        // The buffers are taken out so the branches can be drawn while borrowing `self`
+        if self.config.double_precision {
+            let mut nodes = std::mem::take(&mut self.rendering.nodes_f64);
+            line_count += self.draw_fractal_branches(&mut nodes, pass);
+            self.rendering.nodes_f64 = nodes;
+        } else {
+            let mut nodes = std::mem::take(&mut self.rendering.nodes);
+            line_count += self.draw_fractal_branches(&mut nodes, pass);
+            self.rendering.nodes = nodes;
+        }
+        line_count
+    }
+
+    /// Hands the frame to the background thread and draws the latest frame it finished into
+    /// `rendering.shapes`, returning the number of lines.
+    fn generate_async(
+        &mut self,
+        ctx: &egui::Context,
+        view: &View,
+        window: Rect,
+        start_width: f32,
+        max_depth: usize,
+        root: Option<Vec2>,
+    ) -> usize {
+        let passes: Vec<_> = self
+            .eyes()
+            .iter()
+            .map(|&eye| {
+                (
+                    self.stereo.eye_transform(eye, &view.to_screen, window),
+                    self.stereo.eye_rect(eye, view.visible, window),
+                    eye,
+                )
+            })
+            .collect();
+        let generation = &mut self.async_generation;
+        let pool = generation.shape_pool.clone();
+        let worker = generation.worker.get_or_insert_with(|| {
+            Worker::spawn(ctx, || {
+                // A clock of its own, whose settings are replaced by those of every job
+                let mut clock = Self::default();
+                move |job| clock.generate(job, &pool)
+            })
+        });
+        while let Some(generated) = worker.try_recv() {
+            if let Some(replaced) = generation.frames.push(generated) {
+                generation.shape_pool.give(replaced.shapes);
+            }
+        }
+        let running = worker.offer(|| GenerationJob {
+            time: self.time,
+            config: self.config.clone(),
+            stereo: self.stereo.clone(),
+            night_mode: self.night_mode.clone(),
+            location: self.complications.location,
+            depth_colors: self.rendering.depth_colors.clone(),
+            width_scale: self.rendering.width_scale,
+            passes,
+            start_width,
+            max_depth,
+            root,
+        });
+        if !running {
+            tracing::error!("The background generation thread stopped");
+            toasts::error("Generating on the UI thread after the background thread stopped");
+            self.config.async_generation = false;
+            *generation = AsyncGeneration::default();
+            return 0;
+        }
 
-        self.line_count = line_count;
+        let Some(latest) = generation.frames.latest() else {
+            return 0;
+        };
+        let eased = self.config.interpolate
+            && generation.frames.progress().is_some_and(|(previous, t)| {
+                worker::interpolate_shapes(
+                    &previous.shapes,
+                    &latest.shapes,
+                    t,
+                    &mut self.rendering.shapes,
+                )
+            });
+        if !eased {
+            self.rendering.shapes.extend(latest.shapes.iter().cloned());
+        }
+        self.rendering.culled_count = latest.culled_count;
+        self.rendering.truncated = latest.truncated;
+        self.rendering.levels_drawn = latest.levels_drawn;
+        latest.line_count
+    }
+
+    /// Generates the lines of `job` on the background thread, into a buffer of `pool`.
+    fn generate(&mut self, job: GenerationJob, pool: &ShapePool) -> Generated {
+        let _scope = profiler::scope("generate");
+        self.time = job.time;
+        self.config = job.config;
+        self.stereo = job.stereo;
+        self.night_mode = job.night_mode;
+        self.complications.location = job.location;
+        self.rendering.depth_colors = job.depth_colors;
+        self.rendering.width_scale = job.width_scale;
+
+        self.rendering.shapes = pool.take(self.shape_capacity(job.max_depth));
+        self.rendering.culled_count = 0;
+        self.reset_line_budget();
+        let mut line_count = 0;
+        for (to_screen, rect, eye) in &job.passes {
+            line_count += self.render_pass(&Pass {
+                to_screen,
+                rect: *rect,
+                start_width: job.start_width,
+                max_depth: job.max_depth,
+                root: job.root,
+                eye: *eye,
+                fill: self.config.fill,
+            });
+        }
+        self.rendering.measure_culling(line_count);
+        Generated {
+            shapes: std::mem::take(&mut self.rendering.shapes),
+            line_count,
+            culled_count: self.rendering.culled_count,
+            truncated: self.rendering.truncated,
+            levels_drawn: self.rendering.levels_drawn,
+        }
+    }
+
+    /// Draws the clock with branches rolling out of its plane, as seen by the orbiting camera.
+    fn render_3d(&mut self, view: &View, start_width: f32, max_depth: usize) -> usize {
+        let _scope = profiler::scope("3D");
+        let camera = self.three_d.camera(self.bounding_radius());
+        let brightness = self.brightness();
+        let [second, minute, hour] = self.hand_angles().map(|angle| angle as f32);
+        let spiral = self.config.spiral.to_radians();
+        let rotors = self
+            .three_d
+            .rotors([second, minute].map(|angle| angle - hour + TAU / 2.0 + spiral));
+        let length = self.config.length_factor;
+        let branch_lengths = self.branch_lengths();
+        let hand_color = self.hand_color();
+        let min_width = self.min_line_width();
+        let segments = &mut self.rendering.segments_3d;
+        segments.clear();
+        let mut culled = 0;
+        let budget = &mut self.rendering.line_budget;
+        let truncated = &mut self.rendering.truncated;
+        let levels_drawn = &mut self.rendering.levels_drawn;
+
+        let mut push_line = |from: Vec3, to: Vec3, width: f32, color: Color32| {
+            let (Some(from), Some(to)) = (camera.project(from), camera.project(to)) else {
+                return;
+            };
+            let line = [view.to_screen * from.pos, view.to_screen * to.pos];
+            if view
+                .visible
+                .intersects(Rect::from_two_pos(line[0], line[1]))
+            {
+                let width = width * 0.5 * (from.scale + to.scale);
+                let depth = 0.5 * (from.depth + to.depth);
+                segments.push((depth, Shape::line_segment(line, (width, color))));
+            } else {
+                culled += 1;
+            }
+        };
+
+        let [mut current, mut next] = self.rendering.branches_3d.each_mut();
+        current.clear();
+        for (angle, hand_length) in [(second, length), (minute, length), (hour, HOUR_HAND_LENGTH)] {
+            let orientation = Quat::from_axis_angle(Vec3::Z, angle);
+            let hand = Branch {
+                end: Vec3::ZERO,
+                orientation,
+                length: hand_length,
+            };
+            let end = hand.dir();
+            push_line(Vec3::ZERO, end, start_width.max(min_width), hand_color);
+            if hand_length == length {
+                current.push(Branch { end, ..hand });
+            }
+        }
+
+        // Without pruning to the window, the tree is limited to the configured depth
+        let levels = max_depth.min(self.config.depth);
+        let mut width = start_width;
+        for (depth, &color) in self.rendering.depth_colors.iter().take(levels).enumerate() {
+            // A level that does not fit is dropped with the deeper ones, instead of drawn in part
+            let needed = current.len() * rotors.len();
+            if needed > *budget {
+                *truncated = true;
+                break;
+            }
+            *budget -= needed;
+            let color = dim(color, brightness);
+            width *= self.config.width_factor;
+            next.clear();
+            for (&rotor, &scale) in rotors.iter().zip(&branch_lengths) {
+                for branch in current.iter() {
+                    let child = Branch {
+                        end: branch.end,
+                        orientation: branch.orientation * rotor,
+                        length: branch.length * scale,
+                    };
+                    let end = child.end + child.dir();
+                    push_line(child.end, end, width.max(min_width), color);
+                    next.push(Branch { end, ..child });
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            *levels_drawn = (*levels_drawn).max(depth + 1);
+        }
+
+        self.rendering.culled_count += culled;
+        segments.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+        let line_count = segments.len();
+        self.rendering
+            .shapes
+            .extend(segments.drain(..).map(|(_, shape)| shape));
+        line_count
+    }
+
+    fn paint_minimap(&mut self, painter: &Painter, minimap: &emath::RectTransform, view: &View) {
+        let rect = *minimap.to();
+        let visuals = &painter.ctx().style().visuals;
+        painter.rect_filled(rect, 4.0, visuals.extreme_bg_color);
+
+        let depth = self.max_depth().min(MINIMAP_DEPTH);
+        let (truncated, levels_drawn) = (self.rendering.truncated, self.rendering.levels_drawn);
+        self.reset_line_budget();
+        self.render_pass(&Pass {
+            to_screen: minimap,
+            rect,
+            start_width: 1.0,
+            max_depth: depth,
+            root: None,
+            eye: None,
+            fill: self.config.fill,
+        });
+        // The stats are about the main view
+        self.rendering.truncated = truncated;
+        self.rendering.levels_drawn = levels_drawn;
         painter.extend(self.rendering.shapes.drain(..));
+
+        let shown = view.to_screen.inverse().transform_rect(view.visible);
+        painter.rect_stroke(
+            minimap.transform_rect(shown),
+            0.0,
+            visuals.selection.stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.rect_stroke(rect, 4.0, visuals.window_stroke, egui::StrokeKind::Inside);
+    }
+
+    fn view(&self, window: Rect, center_offset: Vec2) -> View {
+        let viewport = self.config.scaling_mode.viewport(window);
+        let visible = if self.config.letterbox {
+            window.intersect(viewport)
+        } else {
+            window
+        };
+        let zoom = self.zoom(viewport, visible);
+        let to_screen = emath::RectTransform::from_to(
+            Rect::from_center_size(self.view_center, Vec2::splat(1.0 / zoom)),
+            viewport.translate(center_offset),
+        );
+        View {
+            to_screen,
+            visible,
+            zoom,
+        }
+    }
+
+    /// View following the dive, in its coordinates where the root branch has length 1.
+    fn dive_view(&self, window: Rect, center_offset: Vec2, frame: &dive::DiveFrame) -> View {
+        let viewport = self.config.scaling_mode.viewport(window);
+        let visible = if self.config.letterbox {
+            window.intersect(viewport)
+        } else {
+            window
+        };
+        // Fit the descendants of the root, then zoom in by a branch length per level
+        let length = self.max_branch_length();
+        let zoom = 0.5 * (1.0 - length) / length / frame.scale;
+        let to_screen = emath::RectTransform::from_to(
+            Rect::from_center_size(frame.tip, Vec2::splat(1.0 / zoom)),
+            viewport.translate(center_offset),
+        );
+        View {
+            to_screen,
+            visible,
+            zoom,
+        }
+    }
+
+    /// Overview of the whole fractal in the top right corner, shown while zoomed in.
+    fn minimap(&self, window: Rect) -> Option<emath::RectTransform> {
+        if !self.config.minimap || self.diving() || self.view(window, Vec2::ZERO).zoom <= 1.0 {
+            return None;
+        }
+        let side = (window.size().min_elem() * 0.2).clamp(80.0, 200.0);
+        let rect = Rect::from_min_size(
+            pos2(window.right() - side - 10.0, window.top() + 10.0),
+            Vec2::splat(side),
+        );
+        let radius = self.bounding_radius();
+        Some(emath::RectTransform::from_to(
+            Rect::from_center_size(Pos2::ZERO, Vec2::splat(2.0 * radius)),
+            rect,
+        ))
+    }
+
+    fn max_depth(&self) -> usize {
+        if self.battery_saving() {
+            self.battery_profile.max_depth
+        } else {
+            usize::MAX
+        }
+    }
+
+    /// Branch levels grown so far in the current minute or hour, which leaves the bounding radius
+    /// and thus the auto zoom alone.
+    fn grown_depth(&self) -> usize {
+        let (seconds, period) = match self.config.growth {
+            Growth::Off => return usize::MAX,
+            Growth::Minute => (self.time.second(), 60.0),
+            Growth::Hour => (self.time.minute() * 60 + self.time.second(), 3600.0),
+        };
+        let sub_second = f64::from(self.time.nanosecond().min(999_999_999)) / 1e9;
+        let progress = (f64::from(seconds) + sub_second) / period;
+        1 + (progress * self.branch_depth() as f64) as usize
+    }
+
+    /// Upper bound of the distance from the center reached by the hands and branches.
+    fn bounding_radius(&self) -> f32 {
+        let length = self.max_branch_length();
+        let depth = self.rendering.depth_colors.len().min(self.max_depth());
+        // Every branch is at most `length` times as long as its parent
+        let branches: f32 = (0..=depth).map(|level| length.powi(level as i32)).sum();
+        (self.config.length_factor * branches).max(HOUR_HAND_LENGTH)
+    }
+
+    /// Zoom for the fractal space mapped to `viewport`, of which `visible` is shown.
+    fn zoom(&self, viewport: Rect, visible: Rect) -> f32 {
+        // Fraction of the fractal space visible along each axis at zoom 1
+        let visible_fraction = visible.size() / viewport.size().max(Vec2::splat(1.0));
+        let fraction = match self.config.auto_zoom {
+            AutoZoom::Off => return self.config.zoom,
+            AutoZoom::Fit => visible_fraction.min_elem(),
+            AutoZoom::Fill => visible_fraction.max_elem(),
+        };
+        0.5 * fraction / self.bounding_radius().max(f32::EPSILON)
+    }
+
+    /// Angles of the second, minute and hour hands, in `f64` for the double precision branches.
+    fn hand_angles(&self) -> [f64; 3] {
+        self.config
+            .time_system
+            .hand_turns(&self.time, self.complications.location)
+            .map(|turns| std::f64::consts::TAU * turns - std::f64::consts::FRAC_PI_2)
     }
 
     fn create_hands(&self) -> [Hand; 3] {
-        let seconds = self.time.second() as f32 + self.time.nanosecond() as f32 / 1e9;
-        let minutes = self.time.minute() as f32 + seconds / 60.0;
-        let hours = self.time.hour() as f32 + minutes / 60.0;
+        let [second, minute, hour] = self.hand_angles().map(|angle| angle as f32);
 
         [
-            Hand::from_length_angle(self.config.length_factor, TAU * seconds / 60.0 - TAU / 4.0),
-            Hand::from_length_angle(self.config.length_factor, TAU * minutes / 60.0 - TAU / 4.0),
-            Hand::from_length_angle(0.5, TAU * hours / 12.0 - TAU / 4.0),
+            Hand::from_length_angle(self.config.length_factor, second),
+            Hand::from_length_angle(self.config.length_factor, minute),
+            Hand::from_length_angle(HOUR_HAND_LENGTH, hour),
         ]
     }
 
+    /// Lengths of the two children of a branch of length 1.
+    fn branch_lengths(&self) -> [f32; 2] {
+        [self.config.second_scale, self.config.minute_scale]
+            .map(|scale| self.config.length_factor * scale)
+    }
+
+    fn max_branch_length(&self) -> f32 {
+        let [second, minute] = self.branch_lengths();
+        second.max(minute)
+    }
+
+    /// Rotations scaling a branch into its two children, like `calculate_hand_rotors`.
+    fn branch_rotors<T: Real>(&self) -> [Complex<T>; 2] {
+        let [second, minute, hour] = self.hand_angles();
+        let spiral = f64::from(self.config.spiral).to_radians();
+        let [second_length, minute_length] = self.branch_lengths().map(f64::from);
+        [(second, second_length), (minute, minute_length)].map(|(angle, length)| {
+            Complex::from_polar(length, angle - hour + std::f64::consts::PI + spiral)
+        })
+    }
+
     fn calculate_hand_rotors(&self, hands: &[Hand; 3]) -> [emath::Rot2; 2] {
         let [second, minute, hour] = hands;
-        let base_rotation = |hand: &Hand| {
-            hand.length * emath::Rot2::from_angle(hand.angle - hour.angle + TAU / 2.0)
+        let spiral = self.config.spiral.to_radians();
+        let base_rotation = |hand: &Hand, scale: f32| {
+            hand.length
+                * scale
+                * emath::Rot2::from_angle(hand.angle - hour.angle + TAU / 2.0 + spiral)
         };
 
-        [base_rotation(second), base_rotation(minute)]
+        [
+            base_rotation(second, self.config.second_scale),
+            base_rotation(minute, self.config.minute_scale),
+        ]
     }
 
-    fn draw_hands(
-        &mut self,
-        hands: &[Hand; 3],
-        to_screen: &emath::RectTransform,
-        rect: Rect,
-        line_count: &mut usize,
-    ) {
+    fn draw_hands(&mut self, hands: &[Hand; 3], pass: &Pass, line_count: &mut usize) {
         let center = pos2(0.0, 0.0);
-        let screen_center = to_screen * center;
-        let width = self.config.start_line_width;
+        let screen_center = pass.to_screen * center;
+        let hand_color = self.stereo.tint(pass.eye, self.hand_color());
+        let width = pass.start_width.max(self.min_line_width());
 
-        for (i, hand) in hands.iter().enumerate() {
+        for hand in hands {
             let end = center + hand.vec;
-            let screen_end = to_screen * end;
+            let screen_end = pass.to_screen * end;
 
-            if rect.intersects(Rect::from_two_pos(screen_center, screen_end)) {
-                self.rendering.shapes.push(Shape::line_segment(
-                    [screen_center, screen_end],
-                    (width, self.config.hand_color),
-                ));
+            let line = [screen_center, screen_end];
+            if pass.rect.intersects(self.config.joints.bounds(line)) {
+                self.config.joints.emit(
+                    &mut self.rendering.shapes,
+                    line,
+                    [width; 2],
+                    hand_color,
+                    pass.fill,
+                );
                 *line_count += 1;
-            }
-
-            if i < 2 {
-                self.rendering.nodes_buf1.push(Node {
-                    pos: end,
-                    dir: hand.vec,
-                });
+            } else {
+                self.rendering.culled_count += 1;
             }
         }
     }
 
-    fn draw_fractal_branches(
+    /// Grows the branches from the second and minute hands, or from `root`, in `T` precision.
+    fn draw_fractal_branches<T: Real>(
         &mut self,
-        hand_rotors: &[emath::Rot2; 2],
-        to_screen: &emath::RectTransform,
-        rect: Rect,
-        line_count: &mut usize,
-    ) {
-        let mut current_nodes = &mut self.rendering.nodes_buf1;
-        let mut next_nodes = &mut self.rendering.nodes_buf2;
-        let mut width = self.config.start_line_width;
+        nodes: &mut [Vec<Node<T>>; 2],
+        pass: &Pass,
+    ) -> usize {
+        let _scope = profiler::scope("branches");
+        let levels = self.rendering.depth_colors.len().min(pass.max_depth);
+        // Sized for the deepest level, which holds the most nodes
+        let capacity = self
+            .rendering
+            .predicted(estimated_line_count(levels).min(self.config.line_cap));
+        for buffer in nodes.iter_mut() {
+            buffer.clear();
+            shape_pool::reserve(buffer, capacity);
+        }
+        let [mut current_nodes, mut next_nodes] = nodes.each_mut();
+        match pass.root {
+            Some(dir) => current_nodes.push(Node {
+                pos: Complex::ZERO,
+                dir: Complex::from_vec2(dir),
+                id: jitter::ROOT_ID,
+            }),
+            None => {
+                let [second, minute, _] = self.hand_angles();
+                let length = f64::from(self.config.length_factor);
+                current_nodes.extend([second, minute].into_iter().enumerate().map(
+                    |(index, angle)| {
+                        let hand = Complex::from_polar(length, angle);
+                        Node {
+                            pos: hand,
+                            dir: hand,
+                            id: jitter::child_id(jitter::ROOT_ID, index),
+                        }
+                    },
+                ));
+            }
+        }
 
-        for &color in self.rendering.depth_colors.iter() {
+        let rotors = self.branch_rotors::<T>();
+        let transform = ScreenTransform::<T>::new(pass.to_screen, pass.rect);
+        let brightness = self.brightness();
+        // Bound of the distance a node's descendants reach, relative to the node's branch length
+        let length = self.max_branch_length();
+        let descendant_reach = if length < 1.0 {
+            T::from_f64(f64::from(length / (1.0 - length)))
+        } else {
+            T::INFINITY
+        };
+        let descendant_reach_sq = descendant_reach * descendant_reach;
+        let mut width = pass.start_width;
+        // Filled branches taper from the width of their parent
+        let mut parent_width = pass.start_width.max(self.min_line_width());
+        let mut line_count = 0;
+        let wind_time = self.time.timestamp_millis() as f64 / 1000.0;
+        let gravity = f64::from(self.config.gravity).to_radians();
+        let bends = self.config.jitter.enabled || self.config.wind.enabled || gravity != 0.0;
+        let mut grown = Vec::new();
+
+        for (depth, &color) in self.rendering.depth_colors[..levels].iter().enumerate() {
+            // A level that does not fit is dropped with the deeper ones, instead of drawn in part
+            let needed = current_nodes.len() * rotors.len();
+            if needed > self.rendering.line_budget {
+                self.rendering.truncated = true;
+                break;
+            }
+            self.rendering.line_budget -= needed;
+            let color = self.stereo.tint(pass.eye, dim(color, brightness));
+            // Deeper branches appear further behind the screen
+            let parallax = [depth, depth + 1]
+                .map(|depth| Vec2::new(self.stereo.offset(pass.eye, depth, levels), 0.0));
             next_nodes.clear();
             width *= self.config.width_factor;
+            let line_width = if depth >= self.config.depth {
+                width.max(MIN_ADAPTIVE_LINE_WIDTH * self.rendering.width_scale)
+            } else {
+                width
+            }
+            .max(self.min_line_width());
 
-            for &rotor in hand_rotors {
-                for &node in current_nodes.iter() {
-                    let new_dir = rotor * node.dir;
-                    let new_node = Node {
-                        pos: node.pos + new_dir,
-                        dir: new_dir,
+            for (index, &rotor) in rotors.iter().enumerate() {
+                // Without bends a child only depends on its parent and the rotor, so the
+                // children grow in batches
+                if !bends {
+                    simd::grow(rotor, current_nodes, &mut grown);
+                }
+                for (node_index, &node) in current_nodes.iter().enumerate() {
+                    let id = jitter::child_id(node.id, index);
+                    let new_node = if bends {
+                        let mut new_dir = rotor * node.dir;
+                        let mut bend = self.config.jitter.angle(id)
+                            + self.config.wind.angle(id, depth, wind_time);
+                        if gravity != 0.0 {
+                            // Turns towards the bottom, most for horizontal branches
+                            let cos = new_dir.to_vec2().normalized().x;
+                            bend += gravity * (depth + 1) as f64 * f64::from(cos);
+                        }
+                        if bend != 0.0 {
+                            new_dir = Complex::from_polar(1.0, bend) * new_dir;
+                        }
+                        Node {
+                            pos: node.pos + new_dir,
+                            dir: new_dir,
+                            id,
+                        }
+                    } else {
+                        let [pos, dir] = grown[node_index];
+                        Node { pos, dir, id }
                     };
+                    let new_dir = new_node.dir;
 
-                    let line = [to_screen * node.pos, to_screen * new_node.pos];
-                    if rect.intersects(Rect::from_two_pos(line[0], line[1])) {
-                        self.rendering
-                            .shapes
-                            .push(Shape::line_segment(line, (width, color)));
-                        *line_count += 1;
+                    let line = [
+                        transform.to_screen(node.pos) + parallax[0],
+                        transform.to_screen(new_node.pos) + parallax[1],
+                    ];
+                    if pass.rect.intersects(self.config.joints.bounds(line)) {
+                        self.config.joints.emit(
+                            &mut self.rendering.shapes,
+                            line,
+                            [parent_width, line_width],
+                            color,
+                            pass.fill,
+                        );
+                        line_count += 1;
+                    } else {
+                        self.rendering.culled_count += 1;
                     }
 
-                    next_nodes.push(new_node);
+                    // Branches that cannot reach the window are not grown further
+                    if transform.distance_sq_to_visible(new_node.pos)
+                        <= new_dir.length_sq() * descendant_reach_sq
+                    {
+                        next_nodes.push(new_node);
+                    }
                 }
             }
 
             std::mem::swap(&mut current_nodes, &mut next_nodes);
+            parent_width = line_width;
+            self.rendering.levels_drawn = self.rendering.levels_drawn.max(depth + 1);
         }
+
+        line_count
+    }
+}
+
+/// Lines of the hands and `levels` levels of branches, before any are culled.
+fn estimated_line_count(levels: usize) -> usize {
+    // Each level doubles the branches, from the two of the second and minute hands
+    u32::try_from(levels + 1)
+        .ok()
+        .and_then(|shift| 1usize.checked_shl(shift))
+        .map_or(usize::MAX, |branches| branches.saturating_add(1))
+}
+
+/// Parts of `window` outside of `inner`.
+fn letterbox_bars(window: Rect, inner: Rect) -> impl Iterator<Item = Rect> {
+    [
+        Rect::from_x_y_ranges(window.x_range(), window.top()..=inner.top()),
+        Rect::from_x_y_ranges(window.x_range(), inner.bottom()..=window.bottom()),
+        Rect::from_x_y_ranges(window.left()..=inner.left(), inner.y_range()),
+        Rect::from_x_y_ranges(inner.right()..=window.right(), inner.y_range()),
+    ]
+    .into_iter()
+    .filter(|bar| bar.is_positive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_dive_seeds_round_trip() {
+        let mut clock = FractalClock::default();
+        clock.dive.seed = i64::MAX as u64;
+        for _ in 0..64 {
+            let value = toml::Value::try_from(&clock).unwrap();
+            let read: FractalClock = value.try_into().unwrap();
+            assert!(read.dive == clock.dive);
+            clock.dive.reroll();
+        }
+    }
+
+    #[test]
+    fn secrets_are_redacted() {
+        let mut clock = FractalClock::default();
+        clock.remote.pin = "secret-pin".to_owned();
+        clock.sync_config.token = "secret-token".to_owned();
+        clock.complications.weather_config.api_key = "secret-key".to_owned();
+        clock.complications.calendar_config.source = "https://example.com/secret.ics".to_owned();
+        clock.profiles.profiles[0].state = Some(ProfileState {
+            config: clock.config.clone(),
+            fullscreen: false,
+            widget_window: false,
+            window_stacking: clock.window_stacking,
+            fps_cap: None,
+            readout: clock.readout.clone(),
+            complications: clock.complications.clone(),
+        });
+        let settings = clock.redacted_settings();
+        assert!(settings.contains("<redacted>"));
+        assert!(!settings.contains("secret"), "{settings}");
     }
 }
@@ -0,0 +1,232 @@
+use egui::Vec2;
+use gilrs::{Axis, Button, EventType, Gilrs};
+use std::time::Instant;
+
+/// Stick deflection ignored, so worn sticks do not drift.
+const DEAD_ZONE: f32 = 0.15;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Pause,
+    Fullscreen,
+    Randomize,
+    DepthUp,
+    DepthDown,
+    ResetView,
+}
+
+impl Action {
+    const ALL: [Self; 6] = [
+        Self::Pause,
+        Self::Fullscreen,
+        Self::Randomize,
+        Self::DepthUp,
+        Self::DepthDown,
+        Self::ResetView,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Pause => "Pause",
+            Self::Fullscreen => "Fullscreen",
+            Self::Randomize => "Randomize",
+            Self::DepthUp => "Depth +",
+            Self::DepthDown => "Depth −",
+            Self::ResetView => "Reset view",
+        }
+    }
+}
+
+/// Buttons bound to each action.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct Bindings {
+    pub pause: Button,
+    pub fullscreen: Button,
+    pub randomize: Button,
+    pub depth_up: Button,
+    pub depth_down: Button,
+    pub reset_view: Button,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            pause: Button::South,
+            fullscreen: Button::North,
+            randomize: Button::West,
+            depth_up: Button::RightTrigger2,
+            depth_down: Button::LeftTrigger2,
+            reset_view: Button::Select,
+        }
+    }
+}
+
+impl Bindings {
+    fn button_mut(&mut self, action: Action) -> &mut Button {
+        match action {
+            Action::Pause => &mut self.pause,
+            Action::Fullscreen => &mut self.fullscreen,
+            Action::Randomize => &mut self.randomize,
+            Action::DepthUp => &mut self.depth_up,
+            Action::DepthDown => &mut self.depth_down,
+            Action::ResetView => &mut self.reset_view,
+        }
+    }
+
+    fn action(&mut self, button: Button) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|&action| *self.button_mut(action) == button)
+    }
+}
+
+// Gamepad settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct GamepadConfig {
+    pub enabled: bool,
+    /// Panning speed of a fully deflected left stick, in window sizes per second.
+    pub pan_speed: f32,
+    /// Zoom speed of a fully deflected right stick, in factors of e per second.
+    pub zoom_speed: f32,
+    pub bindings: Bindings,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pan_speed: 0.5,
+            zoom_speed: 1.5,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+/// Gamepad input of one frame.
+#[derive(Default)]
+pub struct GamepadInput {
+    pub actions: Vec<Action>,
+    /// Movement of the view center, in window sizes.
+    pub pan: Vec2,
+    /// Natural logarithm of the zoom factor.
+    pub zoom: f32,
+}
+
+/// Connection to the gamepads, opened once enabled.
+#[derive(Default)]
+pub struct Gamepad {
+    gilrs: Option<Gilrs>,
+    error: Option<String>,
+    /// Action waiting for a button press to be bound to.
+    rebinding: Option<Action>,
+    last_update: Option<Instant>,
+}
+
+impl PartialEq for Gamepad {
+    fn eq(&self, other: &Self) -> bool {
+        self.gilrs.is_some() == other.gilrs.is_some()
+            && self.error == other.error
+            && self.rebinding == other.rebinding
+    }
+}
+
+impl Gamepad {
+    pub fn update(&mut self, ctx: &egui::Context, config: &mut GamepadConfig) -> GamepadInput {
+        let mut input = GamepadInput::default();
+        if !config.enabled {
+            self.last_update = None;
+            return input;
+        }
+        if self.gilrs.is_none() && self.error.is_none() {
+            match Gilrs::new() {
+                Ok(gilrs) => self.gilrs = Some(gilrs),
+                Err(err) => {
+                    tracing::warn!("Failed to open gamepads: {err}");
+                    self.error = Some(format!("Failed to open gamepads: {err}"));
+                }
+            }
+        }
+        let Some(gilrs) = &mut self.gilrs else {
+            return input;
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            let EventType::ButtonPressed(button, _) = event.event else {
+                continue;
+            };
+            if let Some(action) = self.rebinding.take() {
+                *config.bindings.button_mut(action) = button;
+            } else if let Some(action) = config.bindings.action(button) {
+                input.actions.push(action);
+            }
+        }
+
+        let now = Instant::now();
+        let elapsed = self
+            .last_update
+            .map_or(0.0, |last_update| (now - last_update).as_secs_f32());
+        self.last_update = Some(now);
+        let stick = |value: f32| {
+            if value.abs() < DEAD_ZONE { 0.0 } else { value }
+        };
+        for (_, gamepad) in gilrs.gamepads() {
+            // Stick axes point up, while the screen's y axis points down
+            let left = Vec2::new(
+                stick(gamepad.value(Axis::LeftStickX)),
+                -stick(gamepad.value(Axis::LeftStickY)),
+            );
+            input.pan += left * config.pan_speed * elapsed;
+            input.zoom += stick(gamepad.value(Axis::RightStickY)) * config.zoom_speed * elapsed;
+        }
+
+        // Sticks are polled rather than sending events
+        ctx.request_repaint();
+        input
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, config: &mut GamepadConfig) {
+        ui.checkbox(&mut config.enabled, "Gamepad control");
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if let Some(gilrs) = &self.gilrs {
+            let names: Vec<_> = gilrs
+                .gamepads()
+                .map(|(_, gamepad)| gamepad.name().to_owned())
+                .collect();
+            if names.is_empty() {
+                ui.label("No gamepad connected");
+            } else {
+                ui.label(format!("Connected: {}", names.join(", ")));
+            }
+        }
+
+        ui.add(egui::Slider::new(&mut config.pan_speed, 0.1..=2.0).text("pan speed"));
+        ui.add(egui::Slider::new(&mut config.zoom_speed, 0.1..=5.0).text("zoom speed"));
+        ui.label("Left stick pans and right stick zooms.");
+
+        egui::Grid::new("gamepad_bindings").show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.label());
+                let text = if self.rebinding == Some(action) {
+                    "Press a button…".to_owned()
+                } else {
+                    format!("{:?}", config.bindings.button_mut(action))
+                };
+                if ui
+                    .button(text)
+                    .on_hover_text("Click, then press a gamepad button to bind it")
+                    .clicked()
+                {
+                    self.rebinding = Some(action);
+                }
+                ui.end_row();
+            }
+        });
+        if ui.button("Default bindings").clicked() {
+            config.bindings = Bindings::default();
+            self.rebinding = None;
+        }
+    }
+}
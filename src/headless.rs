@@ -0,0 +1,145 @@
+use ab_glyph::{Font as _, FontRef, PxScale, ScaleFont as _};
+use egui::{Color32, Pos2, Shape, epaint::ColorMode};
+use image::{Rgba, RgbaImage};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+/// Rasterizes the lines and polygons laid out by the clock into an image of `size` pixels,
+/// for exports that do not go through a window.
+pub fn rasterize(shapes: &[Shape], [width, height]: [u32; 2], background: Color32) -> RgbaImage {
+    let Some(mut pixmap) = Pixmap::new(width.max(1), height.max(1)) else {
+        return RgbaImage::new(width, height);
+    };
+    pixmap.fill(color(background));
+
+    for shape in shapes {
+        match shape {
+            Shape::LineSegment { points, stroke } => {
+                stroke_polyline(&mut pixmap, points, false, stroke.width, stroke.color);
+            }
+            Shape::Path(path) => {
+                if path.fill.a() > 0
+                    && let Some(outline) = polyline(&path.points, true)
+                {
+                    pixmap.fill_path(
+                        &outline,
+                        &paint(path.fill),
+                        FillRule::Winding,
+                        Transform::identity(),
+                        None,
+                    );
+                }
+                if let ColorMode::Solid(stroke_color) = path.stroke.color {
+                    stroke_polyline(
+                        &mut pixmap,
+                        &path.points,
+                        path.closed,
+                        path.stroke.width,
+                        stroke_color,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let pixel = pixel.demultiply();
+            [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixels)
+        .unwrap_or_else(|| RgbaImage::new(width, height))
+}
+
+fn stroke_polyline(pixmap: &mut Pixmap, points: &[Pos2], closed: bool, width: f32, color: Color32) {
+    if width <= 0.0 || color.a() == 0 {
+        return;
+    }
+    if let Some(path) = polyline(points, closed) {
+        let stroke = Stroke {
+            width,
+            ..Stroke::default()
+        };
+        pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+    }
+}
+
+fn polyline(points: &[Pos2], closed: bool) -> Option<tiny_skia::Path> {
+    let (first, rest) = points.split_first()?;
+    let mut builder = PathBuilder::new();
+    builder.move_to(first.x, first.y);
+    for point in rest {
+        builder.line_to(point.x, point.y);
+    }
+    if closed {
+        builder.close();
+    }
+    builder.finish()
+}
+
+fn paint(color32: Color32) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color(color32));
+    paint.anti_alias = true;
+    paint
+}
+
+/// The colors of the clock are premultiplied, while tiny-skia takes them straight.
+fn color(color: Color32) -> tiny_skia::Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    tiny_skia::Color::from_rgba8(r, g, b, a)
+}
+
+/// Draws `text` centered on `center` with the default egui font, `size` pixels high.
+pub fn draw_text(image: &mut RgbaImage, text: &str, center: Pos2, size: f32, color: Color32) {
+    let fonts = egui::FontDefinitions::default();
+    let Some(data) = fonts.font_data.get("Ubuntu-Light") else {
+        return;
+    };
+    let Ok(font) = FontRef::try_from_slice(&data.font[..]) else {
+        return;
+    };
+    let font = font.as_scaled(PxScale::from(size));
+
+    let glyphs: Vec<_> = text.chars().map(|c| font.glyph_id(c)).collect();
+    let width: f32 = glyphs.iter().map(|&glyph| font.h_advance(glyph)).sum();
+    let baseline = center.y + (font.ascent() + font.descent()) / 2.0;
+    let mut x = center.x - width / 2.0;
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    for id in glyphs {
+        let glyph = id.with_scale_and_position(font.scale(), ab_glyph::point(x, baseline));
+        x += font.h_advance(id);
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|dx, dy, coverage| {
+            let px = bounds.min.x as i64 + i64::from(dx);
+            let py = bounds.min.y as i64 + i64::from(dy);
+            let (Ok(px), Ok(py)) = (u32::try_from(px), u32::try_from(py)) else {
+                return;
+            };
+            if px >= image.width() || py >= image.height() {
+                return;
+            }
+            let alpha = coverage.clamp(0.0, 1.0) * f32::from(a) / 255.0;
+            let Rgba(below) = *image.get_pixel(px, py);
+            let blend = |over: u8, under: u8| {
+                (f32::from(over) * alpha + f32::from(under) * (1.0 - alpha)).round() as u8
+            };
+            image.put_pixel(
+                px,
+                py,
+                Rgba([
+                    blend(r, below[0]),
+                    blend(g, below[1]),
+                    blend(b, below[2]),
+                    below[3].max((alpha * 255.0) as u8),
+                ]),
+            );
+        });
+    }
+}
@@ -0,0 +1,109 @@
+use crate::night_mode::dim;
+use egui::{Color32, Pos2, Rect, Shape};
+
+/// Colors from sparse to dense.
+const GRADIENT: [Color32; 5] = [
+    Color32::from_rgb(30, 50, 190),
+    Color32::from_rgb(0, 170, 220),
+    Color32::from_rgb(90, 200, 80),
+    Color32::from_rgb(250, 200, 40),
+    Color32::from_rgb(240, 60, 30),
+];
+
+// Density heatmap settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct HeatmapConfig {
+    pub enabled: bool,
+    /// Side of the squares the lines are counted in, in points.
+    pub cell_size: f32,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cell_size: 16.0,
+        }
+    }
+}
+
+impl HeatmapConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Density heatmap")
+            .on_hover_text("Colors the lines by how many others are around them");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.cell_size, 4.0..=64.0).text("heatmap cell size"));
+        });
+    }
+
+    /// Recolors the lines and polygons in `shapes` by the number of them centered in the same
+    /// cell of `rect`, from cold sparse tips to the hot dense core.
+    pub fn apply(&self, shapes: &mut [Shape], rect: Rect, brightness: f32) {
+        if !self.enabled || !rect.is_positive() {
+            return;
+        }
+        let cell_size = self.cell_size.max(1.0);
+        let [columns, rows] =
+            [rect.width(), rect.height()].map(|side| (side / cell_size).ceil() as usize);
+        let cell = |pos: Pos2| {
+            let offset = (pos - rect.min) / cell_size;
+            (offset.x >= 0.0 && offset.y >= 0.0)
+                .then_some((offset.x as usize, offset.y as usize))
+                .filter(|&(column, row)| column < columns && row < rows)
+                .map(|(column, row)| row * columns + column)
+        };
+
+        // Counted in a pre-pass, so every line sees the final density
+        let mut counts = vec![0u32; columns * rows];
+        for index in shapes.iter().filter_map(center).filter_map(cell) {
+            counts[index] += 1;
+        }
+        let max = counts.iter().copied().max().unwrap_or(0);
+        if max == 0 {
+            return;
+        }
+        // Logarithmic, as the density grows exponentially towards the core
+        let scale = 1.0 / (max as f32).ln_1p();
+
+        for shape in shapes.iter_mut() {
+            let Some(index) = center(shape).and_then(cell) else {
+                continue;
+            };
+            let heat = (counts[index] as f32).ln_1p() * scale;
+            match shape {
+                Shape::LineSegment { stroke, .. } => {
+                    stroke.color = heat_color(heat, stroke.color.a(), brightness);
+                }
+                Shape::Path(path) => path.fill = heat_color(heat, path.fill.a(), brightness),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn center(shape: &Shape) -> Option<Pos2> {
+    match shape {
+        Shape::LineSegment { points, .. } => Some(points[0].lerp(points[1], 0.5)),
+        Shape::Path(path) if !path.points.is_empty() => {
+            let sum = path
+                .points
+                .iter()
+                .fold(egui::Vec2::ZERO, |sum, point| sum + point.to_vec2());
+            Some((sum / path.points.len() as f32).to_pos2())
+        }
+        _ => None,
+    }
+}
+
+/// Color of `heat` in 0..=1 along the gradient, keeping the line's alpha.
+fn heat_color(heat: f32, alpha: u8, brightness: f32) -> Color32 {
+    let position = heat.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f32;
+    let index = (position as usize).min(GRADIENT.len() - 2);
+    let color = GRADIENT[index].lerp_to_gamma(GRADIENT[index + 1], position - index as f32);
+    let [r, g, b, _] = color.to_array();
+    // Premultiplied like the colors of the branches
+    let factor = f32::from(alpha) / 255.0;
+    let [r, g, b] = [r, g, b].map(|c| (f32::from(c) * factor).round() as u8);
+    dim(Color32::from_rgba_premultiplied(r, g, b, alpha), brightness)
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Local, TimeDelta};
+use egui::{Painter, Stroke, emath::RectTransform, pos2};
+use std::collections::VecDeque;
+
+// History skeleton settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Draws the hands of the last minutes, without branches, behind the clock.
+    pub enabled: bool,
+    pub minutes: u32,
+    /// Time between the kept hand positions.
+    pub interval_seconds: u32,
+    /// Opacity of the newest kept hands.
+    pub opacity: f32,
+    /// Exponent of the fade with age, where 1 fades linearly and larger values faster.
+    pub decay: f32,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            minutes: 5,
+            interval_seconds: 10,
+            opacity: 0.4,
+            decay: 1.5,
+        }
+    }
+}
+
+impl HistoryConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "History skeleton")
+            .on_hover_text("A fading fan of the hands of the last minutes");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.minutes, 1..=60).text("minutes"));
+            ui.add(
+                egui::Slider::new(&mut self.interval_seconds, 1..=60)
+                    .suffix(" s")
+                    .text("interval"),
+            );
+            ui.add(egui::Slider::new(&mut self.opacity, 0.0..=1.0).text("opacity"));
+            ui.add(
+                egui::Slider::new(&mut self.decay, 0.2..=5.0)
+                    .logarithmic(true)
+                    .text("decay"),
+            );
+        });
+    }
+
+    fn window(&self) -> TimeDelta {
+        TimeDelta::minutes(self.minutes.into())
+    }
+}
+
+/// Hand angles of the shown time, kept every interval for the history skeleton.
+#[derive(Default, PartialEq)]
+pub struct History {
+    samples: VecDeque<(DateTime<Local>, [f32; 3])>,
+}
+
+impl History {
+    /// Keeps the hand `angles` at `time` if an interval passed since the last ones. Jumps back
+    /// in time, e.g. when scrubbing, start over.
+    pub fn record(&mut self, config: &HistoryConfig, time: DateTime<Local>, angles: [f32; 3]) {
+        let window = config.window();
+        if self
+            .samples
+            .back()
+            .is_some_and(|(last, _)| *last > time || time - *last > window)
+        {
+            self.samples.clear();
+        }
+        let interval = TimeDelta::seconds(config.interval_seconds.max(1).into());
+        if self
+            .samples
+            .back()
+            .is_none_or(|(last, _)| time - *last >= interval)
+        {
+            self.samples.push_back((time, angles));
+        }
+        while self
+            .samples
+            .front()
+            .is_some_and(|(first, _)| time - *first > window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Paints the kept hands with `lengths`, fading with their age at `now`, oldest first.
+    pub fn paint(
+        &self,
+        config: &HistoryConfig,
+        painter: &Painter,
+        to_screen: &RectTransform,
+        now: DateTime<Local>,
+        lengths: [f32; 3],
+        stroke: Stroke,
+    ) {
+        let window = config.window().as_seconds_f32().max(1.0);
+        let center = to_screen * pos2(0.0, 0.0);
+        for (time, angles) in &self.samples {
+            let age = ((now - *time).as_seconds_f32() / window).clamp(0.0, 1.0);
+            let opacity = config.opacity * (1.0 - age).powf(config.decay);
+            if opacity <= 0.0 {
+                continue;
+            }
+            let stroke = Stroke::new(stroke.width, stroke.color.gamma_multiply(opacity));
+            for (&angle, length) in angles.iter().zip(lengths) {
+                let end = to_screen * (pos2(0.0, 0.0) + egui::Vec2::angled(angle) * length);
+                painter.line_segment([center, end], stroke);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::toasts;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState, hotkey::HotKey};
+use std::sync::mpsc::{Receiver, channel};
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum HotkeyAction {
+    ToggleWindow,
+    ToggleFullscreen,
+}
+
+impl HotkeyAction {
+    const ALL: [Self; 2] = [Self::ToggleWindow, Self::ToggleFullscreen];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::ToggleWindow => "Show / Hide",
+            Self::ToggleFullscreen => "Fullscreen",
+        }
+    }
+}
+
+// Global hotkey settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub enabled: bool,
+    /// Shortcut such as `Ctrl+Alt+C`, with the key last.
+    pub shortcut: String,
+    pub action: HotkeyAction,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shortcut: "Ctrl+Alt+C".to_owned(),
+            action: HotkeyAction::ToggleWindow,
+        }
+    }
+}
+
+impl HotkeyConfig {
+    fn hotkey(&self) -> Option<HotKey> {
+        HotKey::try_from(self.shortcut.as_str()).ok()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Enabled");
+        ui.horizontal(|ui| {
+            ui.label("Shortcut:");
+            ui.text_edit_singleline(&mut self.shortcut)
+                .on_hover_text("Modifiers (Ctrl, Alt, Shift, Super) and a key, joined by +");
+        });
+        if self.enabled && self.hotkey().is_none() {
+            ui.colored_label(ui.visuals().error_fg_color, "Invalid shortcut");
+        }
+        ui.horizontal(|ui| {
+            ui.label("Action:");
+            for action in HotkeyAction::ALL {
+                ui.radio_value(&mut self.action, action, action.label());
+            }
+        });
+    }
+}
+
+/// System-wide shortcut, registered while it is enabled.
+pub struct GlobalHotkey {
+    manager: Option<GlobalHotKeyManager>,
+    registered: Option<HotKey>,
+    /// Shortcut last asked for, which may have failed to register.
+    applied: Option<Option<HotKey>>,
+    events: Receiver<GlobalHotKeyEvent>,
+}
+
+impl GlobalHotkey {
+    pub fn new(ctx: &egui::Context) -> Self {
+        let (sender, events) = channel();
+        let ctx = ctx.clone();
+        // Events arrive on another thread, and the app must wake up even while it is hidden
+        GlobalHotKeyEvent::set_event_handler(Some(move |event| {
+            if sender.send(event).is_ok() {
+                ctx.request_repaint();
+            }
+        }));
+
+        Self {
+            manager: None,
+            registered: None,
+            applied: None,
+            events,
+        }
+    }
+
+    /// Registers the shortcut if the config changed, and returns the action if it was pressed.
+    pub fn update(&mut self, config: &HotkeyConfig) -> Option<HotkeyAction> {
+        // A shortcut being typed is only registered once it is complete
+        let hotkey = config.hotkey().filter(|_| config.enabled);
+        if self.applied != Some(hotkey) && (hotkey.is_some() || !config.enabled) {
+            if let Err(err) = self.register(hotkey) {
+                tracing::warn!("Failed to register the global hotkey: {err}");
+                toasts::error(format!("Failed to register the global hotkey: {err}"));
+            }
+            self.applied = Some(hotkey);
+        }
+
+        let registered = self.registered?;
+        self.events
+            .try_iter()
+            .filter(|event| event.id == registered.id() && event.state == HotKeyState::Pressed)
+            .last()
+            .map(|_| config.action)
+    }
+
+    fn register(&mut self, hotkey: Option<HotKey>) -> Result<(), String> {
+        if let (Some(manager), Some(registered)) = (&self.manager, self.registered.take()) {
+            manager.unregister(registered).map_err(|e| e.to_string())?;
+        }
+        let Some(hotkey) = hotkey else {
+            return Ok(());
+        };
+        let manager = match &mut self.manager {
+            Some(manager) => manager,
+            manager => manager.insert(GlobalHotKeyManager::new().map_err(|e| e.to_string())?),
+        };
+        manager.register(hotkey).map_err(|e| e.to_string())?;
+        self.registered = Some(hotkey);
+        Ok(())
+    }
+}
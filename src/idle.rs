@@ -0,0 +1,208 @@
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Screensaver settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ScreensaverConfig {
+    pub enabled: bool,
+    pub idle_minutes: u32,
+}
+
+impl Default for ScreensaverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: 5,
+        }
+    }
+}
+
+/// Switches to fullscreen after a period of user inactivity and back on input.
+pub struct IdleWatcher {
+    source: Option<SystemIdle>,
+    last_input: Instant,
+    last_poll: Instant,
+    restore_fullscreen: Option<bool>,
+}
+
+impl Default for IdleWatcher {
+    fn default() -> Self {
+        Self {
+            source: None,
+            last_input: Instant::now(),
+            last_poll: Instant::now(),
+            restore_fullscreen: None,
+        }
+    }
+}
+
+impl PartialEq for IdleWatcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.restore_fullscreen == other.restore_fullscreen
+    }
+}
+
+impl IdleWatcher {
+    pub fn is_active(&self) -> bool {
+        self.restore_fullscreen.is_some()
+    }
+
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        config: &ScreensaverConfig,
+        fullscreen: &mut bool,
+    ) {
+        let had_input = ctx.input(|i| {
+            i.pointer.delta() != egui::Vec2::ZERO
+                || i.events.iter().any(|event| {
+                    matches!(
+                        event,
+                        egui::Event::Key { .. }
+                            | egui::Event::PointerButton { .. }
+                            | egui::Event::MouseWheel { .. }
+                            | egui::Event::Touch { .. }
+                    )
+                })
+        });
+        if had_input {
+            self.last_input = Instant::now();
+        }
+
+        if !config.enabled {
+            if let Some(previous) = self.restore_fullscreen.take() {
+                *fullscreen = previous;
+            }
+            return;
+        }
+
+        if self.last_poll.elapsed() >= POLL_INTERVAL {
+            self.last_poll = Instant::now();
+
+            let idle = self
+                .source
+                .get_or_insert_with(SystemIdle::new)
+                .idle_time()
+                .unwrap_or_else(|| self.last_input.elapsed());
+            let threshold = Duration::from_secs(u64::from(config.idle_minutes) * 60);
+
+            match self.restore_fullscreen {
+                None if idle >= threshold => {
+                    tracing::info!("Idle for {idle:?}, entering screensaver");
+                    self.restore_fullscreen = Some(*fullscreen);
+                    *fullscreen = true;
+                }
+                Some(previous) if idle < threshold => {
+                    self.restore_fullscreen = None;
+                    *fullscreen = previous;
+                }
+                _ => {}
+            }
+        }
+
+        ctx.request_repaint_after(POLL_INTERVAL);
+    }
+}
+
+/// Time since the last keyboard or mouse input anywhere on the system, where the platform exposes it.
+#[cfg(target_os = "linux")]
+struct SystemIdle {
+    connection: Option<(x11rb::rust_connection::RustConnection, u32)>,
+}
+
+#[cfg(target_os = "linux")]
+impl SystemIdle {
+    fn new() -> Self {
+        use x11rb::connection::Connection as _;
+
+        let connection = x11rb::connect(None)
+            .inspect_err(|e| tracing::debug!("System idle time unavailable: {e}"))
+            .ok()
+            .map(|(connection, screen)| {
+                let root = connection.setup().roots[screen].root;
+                (connection, root)
+            });
+        Self { connection }
+    }
+
+    fn idle_time(&self) -> Option<Duration> {
+        use x11rb::protocol::screensaver::ConnectionExt as _;
+
+        let (connection, root) = self.connection.as_ref()?;
+        let info = connection
+            .screensaver_query_info(*root)
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(Duration::from_millis(info.ms_since_user_input.into()))
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct SystemIdle;
+
+#[cfg(target_os = "windows")]
+impl SystemIdle {
+    fn new() -> Self {
+        Self
+    }
+
+    fn idle_time(&self) -> Option<Duration> {
+        use windows_sys::Win32::{
+            System::SystemInformation::GetTickCount,
+            UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+        };
+
+        let mut info = LASTINPUTINFO {
+            cbSize: size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        unsafe {
+            if GetLastInputInfo(&mut info) == 0 {
+                return None;
+            }
+            Some(Duration::from_millis(
+                GetTickCount().wrapping_sub(info.dwTime).into(),
+            ))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+struct SystemIdle;
+
+#[cfg(target_os = "macos")]
+impl SystemIdle {
+    fn new() -> Self {
+        Self
+    }
+
+    fn idle_time(&self) -> Option<Duration> {
+        let output = std::process::Command::new("ioreg")
+            .args(["-c", "IOHIDSystem", "-d", "4"])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout
+            .lines()
+            .find(|line| line.contains("\"HIDIdleTime\""))?;
+        let nanos = line.rsplit('=').next()?.trim().parse().ok()?;
+        Some(Duration::from_nanos(nanos))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+struct SystemIdle;
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+impl SystemIdle {
+    fn new() -> Self {
+        Self
+    }
+
+    fn idle_time(&self) -> Option<Duration> {
+        None
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{profiler, render_scale::link_program};
+use eframe::{
+    egui_glow::{self, ShaderVersion},
+    glow::{self, HasContext as _},
+};
+use egui::{Context, Id, PaintCallback, PaintCallbackInfo, Painter, Shape};
+use std::sync::{Arc, Mutex};
+
+/// Floats per instance: the two endpoints, the width and the color's four bytes.
+pub const INSTANCE_FLOATS: usize = 6;
+
+/// How the lines reach the GPU.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum LineBackend {
+    /// egui tessellates every line into a mesh on the CPU.
+    Shapes,
+    /// Every line is an instance of one quad, with its endpoints, width and color.
+    Instanced,
+    /// A compute shader grows the branches from the hands into the instances.
+    Compute,
+}
+
+impl LineBackend {
+    pub const ALL: [Self; 3] = [Self::Shapes, Self::Instanced, Self::Compute];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Shapes => "egui shapes",
+            Self::Instanced => "Instanced",
+            Self::Compute => "GPU compute",
+        }
+    }
+
+    /// Whether the lines are drawn as instances, which the compute backend falls back to.
+    pub fn instanced(self) -> bool {
+        self != Self::Shapes
+    }
+}
+
+const VERTEX_SHADER: &str = r#"
+uniform vec2 u_screen_size;
+uniform vec2 u_origin;
+uniform float u_pixels_per_point;
+I vec2 a_from;
+I vec2 a_to;
+I float a_width;
+I vec4 a_srgba;
+O vec4 v_rgba;
+O float v_offset;
+O float v_half_width;
+
+void main() {
+    // Corners of the quad along the line and across it
+    vec2 corner = vec2(float(gl_VertexID % 2), float(gl_VertexID / 2));
+    vec2 dir = a_to - a_from;
+    float len = length(dir);
+    vec2 along = len > 0.0 ? dir / len : vec2(1.0, 0.0);
+    vec2 across = vec2(-along.y, along.x);
+    // Thinner lines are drawn a pixel wide and fainter, like egui does
+    float width = a_width * u_pixels_per_point;
+    v_half_width = 0.5 * max(width, 1.0);
+    v_offset = (corner.y * 2.0 - 1.0) * (v_half_width + 0.5);
+    vec2 pos = mix(a_from, a_to, corner.x) + across * v_offset / u_pixels_per_point - u_origin;
+    gl_Position = vec4(2.0 * pos.x / u_screen_size.x - 1.0, 1.0 - 2.0 * pos.y / u_screen_size.y, 0.0, 1.0);
+    v_rgba = a_srgba * min(width, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+I vec4 v_rgba;
+I float v_offset;
+I float v_half_width;
+
+void main() {
+    // Antialiased edges over the outer half pixel on either side
+    float coverage = clamp(v_half_width + 0.5 - abs(v_offset), 0.0, 1.0);
+    FRAG_COLOR = v_rgba * coverage;
+}
+"#;
+
+/// The renderer, created on the first frame, and whether creating it failed.
+#[derive(Default)]
+struct Shared {
+    renderer: Option<Renderer>,
+    failed: bool,
+}
+
+type SharedRenderer = Arc<Mutex<Shared>>;
+
+fn shared(ctx: &Context) -> SharedRenderer {
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<SharedRenderer>(Id::new("instanced"))
+            .clone()
+    })
+}
+
+/// Whether the instanced renderer can be used, which is only known to fail after it was tried.
+pub fn available(ctx: &Context) -> bool {
+    shared(ctx).lock().is_ok_and(|shared| !shared.failed)
+}
+
+/// Paints the line segments of `shapes` as instances in one draw call, over the other shapes,
+/// which egui paints as usual.
+pub fn paint(painter: &Painter, shapes: impl Iterator<Item = Shape>) {
+    let _scope = profiler::scope("pack instances");
+    let mut instances: Vec<f32> = Vec::new();
+    let mut others = Vec::new();
+    for shape in shapes {
+        match shape {
+            Shape::LineSegment { points, stroke } => {
+                if stroke.width > 0.0 && stroke.color.a() > 0 {
+                    let color = f32::from_bits(u32::from_ne_bytes(stroke.color.to_array()));
+                    instances.extend([
+                        points[0].x,
+                        points[0].y,
+                        points[1].x,
+                        points[1].y,
+                        stroke.width,
+                        color,
+                    ]);
+                }
+            }
+            shape => others.push(shape),
+        }
+    }
+    painter.extend(others);
+    if instances.is_empty() {
+        return;
+    }
+
+    let ctx = painter.ctx();
+    let pixels_per_point = ctx.pixels_per_point();
+    let shared = shared(ctx);
+    let callback = egui_glow::CallbackFn::new(move |info, painter| {
+        let Ok(mut shared) = shared.lock() else {
+            return;
+        };
+        let gl = painter.gl();
+        if shared.renderer.is_none() && !shared.failed {
+            match unsafe { Renderer::new(gl) } {
+                Ok(renderer) => shared.renderer = Some(renderer),
+                Err(e) => {
+                    tracing::error!("Instanced lines unavailable: {e}");
+                    shared.failed = true;
+                }
+            }
+        }
+        if let Some(renderer) = &shared.renderer {
+            unsafe {
+                renderer.upload(gl, &instances);
+                renderer.draw(
+                    gl,
+                    &info,
+                    instances.len() / INSTANCE_FLOATS,
+                    pixels_per_point,
+                );
+            }
+        }
+    });
+    painter.add(PaintCallback {
+        rect: painter.clip_rect(),
+        callback: Arc::new(callback),
+    });
+}
+
+/// Draws the instances in its buffer, which the compute backend also writes to.
+pub struct Renderer {
+    program: glow::Program,
+    vertex_array: glow::VertexArray,
+    pub instance_buffer: glow::Buffer,
+}
+
+impl Renderer {
+    pub unsafe fn new(gl: &glow::Context) -> Result<Self, String> {
+        let version = ShaderVersion::get(gl);
+        let gl_version = gl.version();
+        // Attribute divisors arrived in OpenGL 3.3 and OpenGL ES 3.0
+        let supported = if gl_version.is_embedded {
+            gl_version.major >= 3
+        } else {
+            (gl_version.major, gl_version.minor) >= (3, 3)
+        };
+        if !supported || !version.is_new_shader_interface() {
+            return Err("OpenGL 3.3 or OpenGL ES 3.0 is required".to_owned());
+        }
+        unsafe {
+            let program = link_program(gl, version, VERTEX_SHADER, FRAGMENT_SHADER)?;
+            let vertex_array = gl.create_vertex_array()?;
+            let instance_buffer = gl.create_buffer()?;
+
+            gl.bind_vertex_array(Some(vertex_array));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(instance_buffer));
+            let stride = (INSTANCE_FLOATS * size_of::<f32>()) as i32;
+            for (name, size, kind, offset) in [
+                ("a_from", 2, glow::FLOAT, 0),
+                ("a_to", 2, glow::FLOAT, 2),
+                ("a_width", 1, glow::FLOAT, 4),
+                ("a_srgba", 4, glow::UNSIGNED_BYTE, 5),
+            ] {
+                if let Some(location) = gl.get_attrib_location(program, name) {
+                    gl.enable_vertex_attrib_array(location);
+                    gl.vertex_attrib_pointer_f32(
+                        location,
+                        size,
+                        kind,
+                        kind == glow::UNSIGNED_BYTE,
+                        stride,
+                        offset * size_of::<f32>() as i32,
+                    );
+                    gl.vertex_attrib_divisor(location, 1);
+                }
+            }
+            gl.bind_vertex_array(None);
+
+            Ok(Self {
+                program,
+                vertex_array,
+                instance_buffer,
+            })
+        }
+    }
+
+    unsafe fn upload(&self, gl: &glow::Context, instances: &[f32]) {
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.instance_buffer));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(instances),
+                glow::STREAM_DRAW,
+            );
+        }
+    }
+
+    /// Draws the first `count` instances of the buffer.
+    pub unsafe fn draw(
+        &self,
+        gl: &glow::Context,
+        info: &PaintCallbackInfo,
+        count: usize,
+        pixels_per_point: f32,
+    ) {
+        let rect = info.viewport;
+        unsafe {
+            gl.use_program(Some(self.program));
+            let screen_size = gl.get_uniform_location(self.program, "u_screen_size");
+            gl.uniform_2_f32(screen_size.as_ref(), rect.width(), rect.height());
+            let origin = gl.get_uniform_location(self.program, "u_origin");
+            gl.uniform_2_f32(origin.as_ref(), rect.left(), rect.top());
+            let scale = gl.get_uniform_location(self.program, "u_pixels_per_point");
+            gl.uniform_1_f32(scale.as_ref(), pixels_per_point);
+
+            gl.bind_vertex_array(Some(self.vertex_array));
+            gl.draw_arrays_instanced(glow::TRIANGLE_STRIP, 0, 4, count as i32);
+            gl.bind_vertex_array(None);
+        }
+    }
+}
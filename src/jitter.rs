@@ -0,0 +1,60 @@
+use crate::dive;
+
+/// Id of the virtual root whose children are the second and minute hands, with the children of
+/// branch `id` being `2 * id` and `2 * id + 1`.
+pub const ROOT_ID: u64 = 1;
+
+// Branch jitter settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct JitterConfig {
+    pub enabled: bool,
+    /// Every seed gives a different tree, which is the same each time.
+    pub seed: u64,
+    /// Largest angle a branch is turned by, in degrees.
+    pub amount: f32,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            amount: 10.0,
+        }
+    }
+}
+
+impl JitterConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Jitter branches");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.amount, 0.0..=45.0).text("jitter °"));
+            ui.horizontal(|ui| {
+                ui.label("Seed:");
+                // TOML integers are signed
+                ui.add(egui::DragValue::new(&mut self.seed).range(0..=i64::MAX as u64));
+                if ui.button("🎲").on_hover_text("New seed").clicked() {
+                    self.seed = dive::random_seed();
+                }
+            });
+        });
+    }
+
+    /// Angle in radians the branch with `id` is turned by.
+    pub fn angle(&self, id: u64) -> f64 {
+        if !self.enabled {
+            return 0.0;
+        }
+        let random = dive::splitmix64(self.seed ^ dive::splitmix64(id));
+        // Uniform in -1..1
+        let unit = (random >> 11) as f64 / (1u64 << 52) as f64 - 1.0;
+        unit * f64::from(self.amount).to_radians()
+    }
+}
+
+/// Id of the `index`th child of branch `id`.
+pub fn child_id(id: u64, index: usize) -> u64 {
+    // Wraps beyond 63 levels, which are never drawn
+    id.wrapping_mul(2).wrapping_add(index as u64)
+}
@@ -0,0 +1,125 @@
+use egui::{Color32, Pos2, Rect, Shape, Stroke, Vec2};
+
+// Jointed limb settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct JointsConfig {
+    pub enabled: bool,
+    /// Straight segments every hand and branch is made of.
+    pub segments: usize,
+    /// Angle a limb turns by from its start to its end, in degrees.
+    pub curvature: f32,
+}
+
+impl Default for JointsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            segments: 4,
+            curvature: 30.0,
+        }
+    }
+}
+
+impl JointsConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Jointed limbs");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.segments, 2..=12).text("segments"));
+            ui.add(egui::Slider::new(&mut self.curvature, -180.0..=180.0).text("curvature °"));
+        });
+    }
+
+    pub fn bends(&self) -> bool {
+        self.enabled && self.segments > 1
+    }
+
+    /// Bounds of the limb from `line[0]` to `line[1]`, including the bulge of its curve.
+    pub fn bounds(&self, line: [Pos2; 2]) -> Rect {
+        let rect = Rect::from_two_pos(line[0], line[1]);
+        if !self.bends() {
+            return rect;
+        }
+        // Sagitta of the arc through the joints
+        let turn = self.curvature.to_radians().abs().min(std::f32::consts::PI);
+        rect.expand(0.5 * line[0].distance(line[1]) * (turn / 4.0).tan())
+    }
+
+    /// Emits the limb from `line[0]` to `line[1]` as a single line, or as segments bending
+    /// progressively along an arc that still ends at `line[1]`. Filled limbs are polygons tapering
+    /// from the first to the second of `widths`, while lines are drawn with the second.
+    pub fn emit(
+        &self,
+        shapes: &mut Vec<Shape>,
+        line: [Pos2; 2],
+        widths: [f32; 2],
+        color: Color32,
+        fill: bool,
+    ) {
+        if !self.bends() {
+            push_limb(shapes, line, widths, color, fill);
+            return;
+        }
+        let turn = self.curvature.to_radians();
+        let count = self.segments as f32;
+        let angle = |index: usize| turn * ((index as f32 + 0.5) / count - 0.5);
+
+        // Unit segments at the joint angles end at `sum`, so dividing the chord by it as complex
+        // numbers gives the segment at angle zero, which the joint angles turn into the others
+        let sum =
+            (0..self.segments).fold(Vec2::ZERO, |sum, index| sum + Vec2::angled(angle(index)));
+        let chord = line[1] - line[0];
+        let step = Vec2::new(
+            chord.x * sum.x + chord.y * sum.y,
+            chord.y * sum.x - chord.x * sum.y,
+        ) / sum.length_sq().max(f32::EPSILON);
+        let width = |index: usize| egui::lerp(widths[0]..=widths[1], index as f32 / count);
+
+        let mut start = line[0];
+        for index in 0..self.segments {
+            let dir = Vec2::angled(angle(index));
+            let end = if index + 1 == self.segments {
+                line[1]
+            } else {
+                start
+                    + Vec2::new(
+                        step.x * dir.x - step.y * dir.y,
+                        step.x * dir.y + step.y * dir.x,
+                    )
+            };
+            let segment_widths = if fill {
+                [width(index), width(index + 1)]
+            } else {
+                [widths[1]; 2]
+            };
+            push_limb(shapes, [start, end], segment_widths, color, fill);
+            start = end;
+        }
+    }
+}
+
+fn push_limb(
+    shapes: &mut Vec<Shape>,
+    line: [Pos2; 2],
+    widths: [f32; 2],
+    color: Color32,
+    fill: bool,
+) {
+    if !fill {
+        shapes.push(Shape::line_segment(line, Stroke::new(widths[1], color)));
+        return;
+    }
+    // Quad across the ends of the limb, which is convex for any widths
+    let normal = (line[1] - line[0]).normalized().rot90();
+    let [start, end] = widths.map(|width| normal * width / 2.0);
+    shapes.push(Shape::convex_polygon(
+        vec![
+            line[0] + start,
+            line[1] + end,
+            line[1] - end,
+            line[0] - start,
+        ],
+        color,
+        Stroke::NONE,
+    ));
+}
@@ -0,0 +1,43 @@
+use std::{
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// Set for the app process started by the supervisor.
+const SUPERVISED_ENV: &str = "FRACTAL_CLOCK_SUPERVISED";
+const MIN_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+/// Runs at least this long reset the restart delay, so only crash loops are slowed down.
+const STABLE_RUN: Duration = Duration::from_secs(120);
+
+/// Whether this process was started by [`supervise`].
+pub fn is_supervised() -> bool {
+    std::env::var_os(SUPERVISED_ENV).is_some()
+}
+
+/// Runs the app with the same arguments in a child process, restarting it whenever it crashes, so
+/// a wall mounted display recovers on its own. Returns once the app exits cleanly.
+///
+/// This runs before logging is set up, as the log file belongs to the app process.
+pub fn supervise() -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let mut delay = MIN_RESTART_DELAY;
+
+    loop {
+        let started = Instant::now();
+        let status = Command::new(&exe)
+            .args(std::env::args_os().skip(1))
+            .env(SUPERVISED_ENV, "1")
+            .status()?;
+        if status.success() {
+            return Ok(());
+        }
+
+        if started.elapsed() >= STABLE_RUN {
+            delay = MIN_RESTART_DELAY;
+        }
+        eprintln!("Fractal clock exited with {status}, restarting in {delay:?}");
+        std::thread::sleep(delay);
+        delay = (delay * 2).min(MAX_RESTART_DELAY);
+    }
+}
@@ -0,0 +1,486 @@
+use crate::{
+    autosave::Autosave,
+    cli::Args,
+    file_drop::Dropped,
+    monitors::{MonitorAwareApp, MonitorLayout},
+    palette::PaletteAction,
+    storage::SettingsStore,
+    window_geometry::{DEFAULT_WINDOW_SIZE, WindowGeometry},
+    window_stacking::WindowStacking,
+    x11_hints::X11Hints,
+};
+
+mod about;
+mod alarms;
+mod allocations;
+#[cfg(target_os = "android")]
+mod android;
+#[cfg(feature = "export")]
+mod ascii_art;
+#[cfg(feature = "audio")]
+mod audio;
+mod autosave;
+mod autostart;
+#[cfg(feature = "export")]
+mod background;
+mod bookmarks;
+#[cfg(feature = "export")]
+mod burst;
+mod calendar;
+mod chat;
+mod cli;
+#[cfg(feature = "export")]
+mod clipboard;
+mod color_space;
+mod color_vision;
+mod complications;
+mod compute;
+mod dive;
+mod effects;
+mod file_drop;
+mod fractal_clock;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+#[cfg(feature = "export")]
+mod headless;
+mod heatmap;
+mod history;
+#[cfg(feature = "hotkey")]
+mod hotkey;
+mod idle;
+mod instanced;
+mod jitter;
+mod joints;
+mod kiosk;
+mod location;
+mod lock;
+mod logging;
+mod metrics;
+mod migration;
+mod monitors;
+mod morph;
+mod night_mode;
+#[cfg(feature = "notifications")]
+mod notifications;
+mod ntp;
+mod oled;
+#[cfg(feature = "export")]
+mod output;
+mod palette;
+mod playback;
+mod post_processing;
+#[cfg(feature = "export")]
+mod poster;
+mod power;
+mod precision;
+mod profiler;
+mod profiles;
+mod prometheus;
+mod readout;
+mod refinement;
+mod remote;
+mod render_scale;
+#[cfg(feature = "export")]
+mod search;
+mod settings_sync;
+mod shaders;
+mod shadow;
+mod shape_pool;
+// Public for the rotor benchmark
+#[doc(hidden)]
+pub mod simd;
+mod slideshow;
+mod stereo;
+mod stopwatch;
+mod storage;
+mod sun;
+mod three_d;
+mod time_system;
+#[cfg(feature = "export")]
+mod timelapse;
+mod timeline;
+mod toasts;
+#[cfg(feature = "tray")]
+mod tray;
+#[cfg(feature = "tui")]
+mod tui;
+mod watchdog;
+mod weather;
+mod widget;
+mod widget_window;
+mod wind;
+mod window_geometry;
+mod window_stacking;
+mod worker;
+mod world_map;
+mod x11_hints;
+
+pub use allocations::CountingAllocator;
+pub use fractal_clock::{FractalClock, FractalClockConfig, FractalClockConfigBuilder};
+pub use widget::{FractalClockWidget, show};
+
+use tracing::level_filters::LevelFilter;
+use winit::event_loop::EventLoop;
+
+/// Runs the desktop app with the command line arguments.
+pub fn run() -> eframe::Result {
+    let args = Args::parse();
+    // Logging to stderr would garble the terminal, so it is not set up
+    if args.tui {
+        #[cfg(feature = "tui")]
+        return tui::run(&args).map_err(|e| eframe::Error::AppCreation(Box::new(e)));
+        #[cfg(not(feature = "tui"))]
+        {
+            eprintln!("--tui requires building with `--features tui`");
+            std::process::exit(2);
+        }
+    }
+    if args.kiosk && !kiosk::is_supervised() {
+        return kiosk::supervise().map_err(|e| eframe::Error::AppCreation(Box::new(e)));
+    }
+    logging::init(args.log_level.unwrap_or(LevelFilter::INFO));
+    let monitor_layout = MonitorLayout::default();
+
+    let mut options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_transparent(true)
+            .with_inner_size(DEFAULT_WINDOW_SIZE)
+            .with_drag_and_drop(true)
+            .with_fullscreen(args.kiosk)
+            .with_decorations(!args.kiosk),
+        // Window geometry is persisted by `WrapApp` so it can be clamped to the monitor layout
+        persist_window: false,
+        ..Default::default()
+    };
+
+    if let Some(index) = args.monitor {
+        let layout = monitor_layout.clone();
+        options.window_builder = Some(Box::new(move |builder| {
+            match monitors::window_rect_on(&layout, index, DEFAULT_WINDOW_SIZE) {
+                Some(rect) => builder.with_position(rect.min).with_inner_size(rect.size()),
+                None => builder,
+            }
+        }));
+    }
+
+    let event_loop = EventLoop::<eframe::UserEvent>::with_user_event().build()?;
+    let layout = monitor_layout.clone();
+    let app = eframe::create_native(
+        "Fractal Clock",
+        options,
+        Box::new(move |cc| Ok(Box::new(WrapApp::new(cc, &args, layout)))),
+        &event_loop,
+    );
+    event_loop.run_app(&mut MonitorAwareApp::new(app, monitor_layout))?;
+
+    Ok(())
+}
+
+struct WrapApp {
+    clock: FractalClock,
+    store: SettingsStore,
+    autosave: Autosave,
+    geometry: WindowGeometry,
+    monitor_layout: MonitorLayout,
+    #[cfg(feature = "tray")]
+    tray: Option<tray::Tray>,
+    #[cfg(feature = "hotkey")]
+    hotkey: hotkey::GlobalHotkey,
+    /// Hidden from the tray or with the global hotkey.
+    #[cfg(any(feature = "tray", feature = "hotkey"))]
+    hidden: bool,
+    /// Settings only come from the config file and are never saved.
+    kiosk: bool,
+    /// Settings are hidden, input is ignored and nothing is saved until unlocked.
+    locked: bool,
+    /// Stacking the window was last applied with.
+    applied_stacking: Option<(WindowStacking, bool)>,
+    /// X11 hints last set on the window.
+    applied_x11_hints: Option<X11Hints>,
+    #[cfg(target_os = "android")]
+    focused: bool,
+}
+
+impl WrapApp {
+    fn new(cc: &eframe::CreationContext<'_>, args: &Args, monitor_layout: MonitorLayout) -> Self {
+        about::set_renderer(cc);
+        let mut store = SettingsStore::open(args.config.clone());
+        store.watch(&cc.egui_ctx);
+        // In kiosk mode, settings left in the eframe storage by interactive sessions are ignored
+        let eframe_storage = cc.storage.filter(|_| !args.kiosk);
+        let mut clock: FractalClock = store
+            .get(eframe_storage, "fractal_clock")
+            .unwrap_or_default();
+        let geometry: WindowGeometry = store
+            .get(eframe_storage, "window_geometry")
+            .unwrap_or_default();
+        if args.monitor.is_none() && !args.kiosk {
+            geometry.restore(&cc.egui_ctx, &monitor_layout);
+        }
+        if args.start_fullscreen {
+            clock.fullscreen = true;
+        }
+        if let Some(path) = &args.metrics_out {
+            clock.start_metrics(path.clone());
+        }
+        if args.start_minimized {
+            cc.egui_ctx
+                .send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+        // Larger targets for fingers on phones and tablets
+        #[cfg(target_os = "android")]
+        cc.egui_ctx.style_mut(|style| {
+            style.spacing.interact_size.y = 40.0;
+            style.spacing.button_padding = egui::vec2(12.0, 8.0);
+            style.spacing.item_spacing.y = 10.0;
+        });
+
+        #[cfg(feature = "tray")]
+        let tray = tray::Tray::new(
+            &cc.egui_ctx,
+            clock.profiles.names().map(str::to_owned).collect(),
+        )
+        .inspect_err(|e| {
+            tracing::error!("Failed to create the tray icon: {e}");
+            toasts::error(format!("Failed to create the tray icon: {e}"));
+        })
+        .ok();
+
+        Self {
+            clock,
+            store,
+            autosave: if args.kiosk {
+                Autosave::disabled()
+            } else {
+                Autosave::start()
+            },
+            geometry,
+            monitor_layout,
+            #[cfg(feature = "tray")]
+            tray,
+            #[cfg(feature = "hotkey")]
+            hotkey: hotkey::GlobalHotkey::new(&cc.egui_ctx),
+            #[cfg(any(feature = "tray", feature = "hotkey"))]
+            hidden: false,
+            kiosk: args.kiosk,
+            locked: args.lock,
+            applied_stacking: None,
+            applied_x11_hints: None,
+            #[cfg(target_os = "android")]
+            focused: true,
+        }
+    }
+
+    fn persist(&mut self, mut storage: Option<&mut dyn eframe::Storage>) {
+        if self.kiosk || self.locked {
+            return;
+        }
+        self.store
+            .set(storage.as_deref_mut(), "fractal_clock", &self.clock);
+        self.store.set(storage, "window_geometry", &self.geometry);
+        self.store.flush();
+    }
+
+    /// Saves the settings when the app goes to the background, where Android may kill it without
+    /// calling `on_exit`.
+    #[cfg(target_os = "android")]
+    fn handle_lifecycle(&mut self, ctx: &egui::Context) {
+        let focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+        if self.focused && !focused {
+            self.persist(None);
+            self.autosave.finish();
+        }
+        self.focused = focused;
+    }
+
+    /// Applies dropped settings, palettes and background images.
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        for file in ctx.input(|i| i.raw.dropped_files.clone()) {
+            let name = file
+                .path
+                .as_deref()
+                .and_then(std::path::Path::file_name)
+                .map_or(file.name.clone(), |name| {
+                    name.to_string_lossy().into_owned()
+                });
+            match file_drop::read(&file) {
+                Ok(Dropped::Settings(clock)) => {
+                    self.clock = *clock;
+                    toasts::info(format!("Applied the settings from {name}"));
+                }
+                Ok(Dropped::Preset(config)) => {
+                    self.clock.apply_preset(*config);
+                    toasts::info(format!("Applied the preset {name}"));
+                }
+                Ok(Dropped::Palette(colors)) if colors.len() >= 2 => {
+                    self.clock.apply_palette(PaletteAction::Gradient(colors));
+                    toasts::info(format!("Applied the palette from {name}"));
+                }
+                Ok(Dropped::Palette(_)) => {
+                    toasts::error(format!("{name} has fewer than two colors for a gradient"));
+                }
+                #[cfg(feature = "export")]
+                Ok(Dropped::Background(path)) => self.clock.background_image = Some(path),
+                Err(e) => {
+                    tracing::error!("Failed to open the dropped {name}: {e}");
+                    toasts::error(format!("Failed to open {name}: {e}"));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "tray")]
+    fn handle_tray(&mut self, ctx: &egui::Context) {
+        use tray::TrayAction;
+
+        let Some(tray) = &mut self.tray else {
+            return;
+        };
+        tray.update_profile_names(self.clock.profiles.names());
+
+        let actions: Vec<TrayAction> = tray.actions().collect();
+        for action in actions {
+            // Drained while locked, so the actions are not replayed once unlocked
+            if self.locked {
+                continue;
+            }
+            match action {
+                TrayAction::ToggleWindow => self.set_hidden(ctx, !self.hidden),
+                TrayAction::TogglePause => self.clock.toggle_pause(),
+                TrayAction::ToggleFullscreen => self.clock.fullscreen = !self.clock.fullscreen,
+                TrayAction::ToggleWidget => {
+                    self.clock.widget_window.enabled = !self.clock.widget_window.enabled;
+                }
+                TrayAction::SwitchProfile(index) => self.clock.switch_profile(index),
+                TrayAction::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            }
+        }
+
+        let minimized = ctx.input(|i| i.viewport().minimized).unwrap_or(false);
+        if minimized && self.clock.minimize_to_tray && !self.hidden {
+            self.set_hidden(ctx, true);
+        }
+    }
+
+    #[cfg(feature = "hotkey")]
+    fn handle_hotkey(&mut self, ctx: &egui::Context) {
+        use hotkey::HotkeyAction;
+
+        let action = self.hotkey.update(&self.clock.hotkey);
+        if self.locked {
+            return;
+        }
+        match action {
+            Some(HotkeyAction::ToggleWindow) => self.set_hidden(ctx, !self.hidden),
+            Some(HotkeyAction::ToggleFullscreen) => self.clock.fullscreen = !self.clock.fullscreen,
+            None => {}
+        }
+    }
+
+    #[cfg(any(feature = "tray", feature = "hotkey"))]
+    fn set_hidden(&mut self, ctx: &egui::Context, hidden: bool) {
+        self.hidden = hidden;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(!hidden));
+        if !hidden {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+    }
+}
+
+impl eframe::App for WrapApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        ctx.set_visuals(egui::Visuals::dark());
+        if self.store.reload()
+            && let Some(clock) = self.store.overlay("fractal_clock", &self.clock)
+        {
+            self.clock = clock;
+            toasts::info("Reloaded settings from the config file");
+        }
+        // Locked from the settings, which are saved one last time
+        if self.clock.locked && !self.locked {
+            let storage = frame.storage_mut();
+            self.persist(storage.map(|storage| storage as &mut dyn eframe::Storage));
+            self.locked = true;
+        }
+        if self.locked && ctx.input_mut(|i| i.consume_shortcut(&lock::UNLOCK_SHORTCUT)) {
+            self.locked = false;
+            toasts::info("Unlocked the clock");
+        }
+        // Also after the config file is reloaded
+        self.clock.locked = self.locked;
+        if !self.locked {
+            if let Some(clock) = self.autosave.restore_ui(ctx) {
+                self.clock = clock;
+            }
+            self.autosave.update(&self.clock);
+        }
+        self.clock.update(ctx);
+        if !self.kiosk
+            && !self.locked
+            && let Some(clock) = self.clock.update_sync(ctx)
+        {
+            self.clock = clock;
+            toasts::info("Applied the settings synced from another machine");
+        }
+        ctx.input(|i| self.geometry.track(i.viewport(), &self.monitor_layout));
+        #[cfg(feature = "tray")]
+        self.handle_tray(ctx);
+        #[cfg(feature = "hotkey")]
+        self.handle_hotkey(ctx);
+        #[cfg(target_os = "android")]
+        self.handle_lifecycle(ctx);
+        // Kiosk settings only come from the config file
+        if !self.kiosk && !self.locked {
+            self.handle_dropped_files(ctx);
+        }
+
+        // The widget window paints its own shape
+        let panel_frame = if self.clock.transparent_background || self.clock.widget_window.enabled {
+            egui::Frame {
+                fill: egui::Color32::TRANSPARENT,
+                inner_margin: egui::Margin::ZERO,
+                ..Default::default()
+            }
+        } else {
+            let frame = egui::Frame::dark_canvas(&ctx.style());
+            frame.fill(frame.fill.gamma_multiply(self.clock.window_opacity))
+        };
+
+        if self.kiosk {
+            // Also after the config file is reloaded
+            self.clock.kiosk = true;
+            self.clock.fullscreen = true;
+            ctx.set_cursor_icon(egui::CursorIcon::None);
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(self.clock.fullscreen));
+        self.clock.window_stacking.apply(
+            ctx,
+            self.clock.widget_window.enabled,
+            &mut self.applied_stacking,
+        );
+        self.clock
+            .x11_hints
+            .apply(frame, &mut self.applied_x11_hints);
+
+        egui::CentralPanel::default()
+            .frame(panel_frame)
+            .show(ctx, |ui| {
+                self.clock.ui(ui);
+            });
+        if !self.kiosk && !self.locked {
+            file_drop::paint_overlay(ctx);
+        }
+        toasts::show(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.persist(Some(storage));
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // `save` is only called when eframe has a storage, so the config file is written here too
+        self.persist(None);
+        self.autosave.finish();
+    }
+}
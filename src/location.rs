@@ -0,0 +1,39 @@
+/// Place on Earth that weather and sun times are looked up for.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct Location {
+    /// Degrees north.
+    pub latitude: f64,
+    /// Degrees east.
+    pub longitude: f64,
+}
+
+impl Default for Location {
+    fn default() -> Self {
+        // Greenwich
+        Self {
+            latitude: 51.48,
+            longitude: 0.0,
+        }
+    }
+}
+
+impl Location {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Location:");
+            ui.add(
+                egui::DragValue::new(&mut self.latitude)
+                    .range(-90.0..=90.0)
+                    .speed(0.1)
+                    .suffix("° N"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.longitude)
+                    .range(-180.0..=180.0)
+                    .speed(0.1)
+                    .suffix("° E"),
+            );
+        });
+    }
+}
@@ -0,0 +1,55 @@
+use egui::{Key, KeyboardShortcut, Modifiers, Ui};
+
+/// The only input a locked clock reacts to.
+pub const UNLOCK_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(
+    Modifiers::COMMAND
+        .plus(Modifiers::ALT)
+        .plus(Modifiers::SHIFT),
+    Key::L,
+);
+
+/// Button locking the clock for public displays, after a confirmation.
+#[derive(Default, PartialEq)]
+pub struct LockButton {
+    confirming: bool,
+}
+
+impl LockButton {
+    /// Returns whether locking was confirmed.
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        if ui
+            .button("🔒 Lock")
+            .on_hover_text("Hides the settings and ignores input until unlocked")
+            .clicked()
+        {
+            self.confirming = true;
+        }
+        if !self.confirming {
+            return false;
+        }
+
+        let shortcut = ui.ctx().format_shortcut(&UNLOCK_SHORTCUT);
+        let modal = egui::Modal::new(ui.id().with("lock")).show(ui.ctx(), |ui| {
+            ui.heading("Lock the clock?");
+            ui.label(format!(
+                "The settings are hidden, input is ignored and changes are not saved until \
+                 {shortcut} is pressed."
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("🔒 Lock").clicked() {
+                    Some(true)
+                } else if ui.button("Cancel").clicked() {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+            .inner
+        });
+        let confirmed = modal.inner == Some(true);
+        if modal.inner.is_some() || modal.should_close() {
+            self.confirming = false;
+        }
+        confirmed
+    }
+}
@@ -0,0 +1,197 @@
+use crate::storage;
+use chrono::{DateTime, Local};
+use egui::{ComboBox, RichText, ScrollArea};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Write as _},
+    fs::File,
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+};
+use tracing::{Event, Level, Subscriber, field::Field, level_filters::LevelFilter};
+use tracing_subscriber::{Layer, field::Visit, filter::Targets, fmt, layer::Context, prelude::*};
+
+/// Entries kept for the log viewer, older ones are dropped.
+const MAX_ENTRIES: usize = 1000;
+
+static ENTRIES: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+struct LogEntry {
+    time: DateTime<Local>,
+    level: Level,
+    target: String,
+    message: String,
+}
+
+/// Logs to stderr, to `fractal_clock.log` in the platform data directory and to the in-app viewer.
+///
+/// `level` applies to the clock itself, dependencies only log warnings and errors unless the level
+/// is lower than that.
+pub fn init(level: LevelFilter) {
+    let filter = Targets::new()
+        .with_default(level.min(LevelFilter::WARN))
+        .with_target(env!("CARGO_CRATE_NAME"), level);
+
+    let file_layer =
+        open_log_file().map(|file| fmt::layer().with_ansi(false).with_writer(Mutex::new(file)));
+
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .with(ViewerLayer)
+        .try_init();
+    if let Err(e) = result {
+        eprintln!("Failed to initialize logging: {e}");
+    }
+}
+
+fn open_log_file() -> Option<File> {
+    let path = storage::data_dir()?.join("fractal_clock.log");
+    let file = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| File::create(&path));
+
+    match file {
+        Ok(file) => {
+            let _ = LOG_FILE.set(path);
+            Some(file)
+        }
+        Err(e) => {
+            eprintln!("Not logging to {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Collects events for the log viewer.
+struct ViewerLayer;
+
+impl<S: Subscriber> Layer<S> for ViewerLayer {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+
+        let Ok(mut entries) = ENTRIES.lock() else {
+            return;
+        };
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry {
+            time: Local::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_owned(),
+            message: message.0,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.0, "{value:?}");
+            }
+            // Metadata of records forwarded from the `log` crate
+            name if name.starts_with("log.") => {}
+            name => {
+                let _ = write!(self.0, " {name}={value:?}");
+            }
+        }
+    }
+}
+
+/// Window listing the most recent log entries.
+#[derive(PartialEq)]
+pub struct LogViewer {
+    pub open: bool,
+    max_level: Level,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            max_level: Level::INFO,
+        }
+    }
+}
+
+impl LogViewer {
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size([600.0, 300.0])
+            .show(ctx, |ui| self.ui(ui));
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ComboBox::from_label("level")
+                .selected_text(self.max_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [
+                        Level::ERROR,
+                        Level::WARN,
+                        Level::INFO,
+                        Level::DEBUG,
+                        Level::TRACE,
+                    ] {
+                        ui.selectable_value(&mut self.max_level, level, level.as_str());
+                    }
+                });
+            if ui.button("Clear").clicked()
+                && let Ok(mut entries) = ENTRIES.lock()
+            {
+                entries.clear();
+            }
+        });
+        if let Some(path) = LOG_FILE.get() {
+            ui.label(format!("Also written to {}", path.display()));
+        }
+        ui.separator();
+
+        // Format outside the lock, as egui may log while laying out
+        let lines: Vec<_> = ENTRIES.lock().map_or_else(
+            |_| Vec::new(),
+            |entries| {
+                entries
+                    .iter()
+                    .filter(|entry| entry.level <= self.max_level)
+                    .map(|entry| {
+                        let line = format!(
+                            "{} {:>5} {}: {}",
+                            entry.time.format("%H:%M:%S%.3f"),
+                            entry.level,
+                            entry.target,
+                            entry.message
+                        );
+                        (entry.level, line)
+                    })
+                    .collect()
+            },
+        );
+
+        ScrollArea::vertical()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for (level, line) in lines {
+                    let color = match level {
+                        Level::ERROR => ui.visuals().error_fg_color,
+                        Level::WARN => ui.visuals().warn_fg_color,
+                        _ => ui.visuals().text_color(),
+                    };
+                    ui.label(RichText::new(line).monospace().color(color));
+                }
+            });
+    }
+}
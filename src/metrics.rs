@@ -0,0 +1,85 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const HEADER: &str = "timestamp,depth,line_count,paint_ms,culled_lines,fps,allocations";
+
+/// Measurements of a painted frame.
+pub struct FrameMetrics {
+    pub depth: usize,
+    pub line_count: usize,
+    pub paint_time: Duration,
+    pub culled_lines: usize,
+    pub fps: f32,
+    /// Allocations of the UI thread while painting, if the global allocator counts them.
+    pub allocations: Option<u64>,
+}
+
+/// Per-frame metrics written to a CSV file while recording, for analyzing performance offline.
+#[derive(Default)]
+pub struct MetricsLog {
+    recording: Option<(PathBuf, BufWriter<File>)>,
+}
+
+impl PartialEq for MetricsLog {
+    fn eq(&self, other: &Self) -> bool {
+        self.path() == other.path()
+    }
+}
+
+impl MetricsLog {
+    /// Starts recording into `path`, replacing any file there.
+    pub fn start(&mut self, path: PathBuf) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(&path)?);
+        writeln!(writer, "{HEADER}")?;
+        self.recording = Some((path, writer));
+        Ok(())
+    }
+
+    /// Stops recording, returning the file the metrics were written to.
+    pub fn stop(&mut self) -> Option<PathBuf> {
+        let (path, mut writer) = self.recording.take()?;
+        if let Err(e) = writer.flush() {
+            tracing::error!("Failed to write metrics to {}: {e}", path.display());
+        }
+        Some(path)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.recording.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    pub fn record(&mut self, frame: &FrameMetrics) {
+        let Some((path, writer)) = &mut self.recording else {
+            return;
+        };
+        let result = writeln!(
+            writer,
+            "{},{},{},{:.3},{},{:.1},{}",
+            chrono::Local::now().to_rfc3339(),
+            frame.depth,
+            frame.line_count,
+            frame.paint_time.as_secs_f64() * 1000.0,
+            frame.culled_lines,
+            frame.fps,
+            frame
+                .allocations
+                .map(|count| count.to_string())
+                .unwrap_or_default()
+        );
+        if let Err(e) = result {
+            tracing::error!("Failed to write metrics to {}: {e}", path.display());
+            self.recording = None;
+        }
+    }
+}
+
+/// File in the downloads or home directory named after the current time.
+pub fn default_path() -> Option<PathBuf> {
+    let dir = dirs::download_dir().or_else(dirs::home_dir)?;
+    let name = chrono::Local::now().format("fractal_clock_metrics_%Y-%m-%d_%H-%M-%S");
+    Some(dir.join(format!("{name}.csv")))
+}
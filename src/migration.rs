@@ -0,0 +1,87 @@
+use toml::{Table, Value};
+
+/// Version written into every persisted value. Bump it and append a migration when a persisted
+/// field is renamed, moved or changes meaning.
+pub const CURRENT_VERSION: usize = 1;
+
+const VERSION_KEY: &str = "version";
+
+type Migration = fn(&mut Table);
+
+/// `MIGRATIONS[n]` upgrades a version `n` value to version `n + 1`, whatever key it is stored under.
+const MIGRATIONS: [Migration; CURRENT_VERSION] = [v0_to_v1];
+
+/// Version 0 is every config saved before versioning existed; its layout is still current.
+fn v0_to_v1(_: &mut Table) {}
+
+/// Upgrades a persisted value to the current layout.
+pub fn migrate(value: &mut Value) {
+    let Some(table) = value.as_table_mut() else {
+        return;
+    };
+    let version = table
+        .get(VERSION_KEY)
+        .and_then(Value::as_integer)
+        .map_or(0, |version| version.max(0) as usize);
+
+    for migration in MIGRATIONS.iter().skip(version) {
+        migration(table);
+    }
+    stamp(value);
+}
+
+/// Marks a value as having the current layout.
+pub fn stamp(value: &mut Value) {
+    if let Some(table) = value.as_table_mut() {
+        table.insert(
+            VERSION_KEY.to_owned(),
+            Value::Integer(CURRENT_VERSION as i64),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractal_clock::FractalClockConfig;
+
+    #[test]
+    fn unversioned_values_are_stamped() {
+        let mut value = Value::Table(toml::toml! { depth = 12 });
+        migrate(&mut value);
+        assert_eq!(
+            value.get(VERSION_KEY).and_then(Value::as_integer),
+            Some(CURRENT_VERSION as i64)
+        );
+        assert_eq!(value.get("depth").and_then(Value::as_integer), Some(12));
+    }
+
+    #[test]
+    fn current_values_are_unchanged() {
+        let mut value = Value::Table(toml::toml! { depth = 12 });
+        stamp(&mut value);
+        let stamped = value.clone();
+        migrate(&mut value);
+        assert_eq!(value, stamped);
+    }
+
+    #[test]
+    fn other_values_are_ignored() {
+        let mut value = Value::Integer(3);
+        migrate(&mut value);
+        assert_eq!(value, Value::Integer(3));
+    }
+
+    #[test]
+    fn stamped_settings_read_back() {
+        let config = FractalClockConfig::builder()
+            .depth(5)
+            .rainbow_mode(false)
+            .build();
+        let mut value = Value::try_from(&config).unwrap();
+        stamp(&mut value);
+        migrate(&mut value);
+        let read: FractalClockConfig = value.try_into().unwrap();
+        assert!(read == config);
+    }
+}
@@ -0,0 +1,95 @@
+use eframe::{EframeWinitApplication, UserEvent};
+use egui::{Pos2, Rect, Vec2};
+use std::sync::{Arc, Mutex};
+use winit::{
+    application::ApplicationHandler,
+    event::{DeviceEvent, DeviceId, StartCause, WindowEvent},
+    event_loop::ActiveEventLoop,
+    window::WindowId,
+};
+
+/// Monitor rectangles in logical points, filled in once the event loop is running.
+pub type MonitorLayout = Arc<Mutex<Vec<Rect>>>;
+
+/// Centers `size` on the given monitor, shrinking it to fit if needed.
+pub fn window_rect_on(layout: &MonitorLayout, index: usize, size: Vec2) -> Option<Rect> {
+    let monitor = *layout.lock().ok()?.get(index)?;
+    Some(Rect::from_center_size(
+        monitor.center(),
+        size.min(monitor.size()),
+    ))
+}
+
+/// Wraps the eframe application to record the monitor layout before the window is created.
+pub struct MonitorAwareApp<'a> {
+    app: EframeWinitApplication<'a>,
+    layout: MonitorLayout,
+}
+
+impl<'a> MonitorAwareApp<'a> {
+    pub fn new(app: EframeWinitApplication<'a>, layout: MonitorLayout) -> Self {
+        Self { app, layout }
+    }
+}
+
+impl ApplicationHandler<UserEvent> for MonitorAwareApp<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if let Ok(mut layout) = self.layout.lock() {
+            *layout = event_loop
+                .available_monitors()
+                .map(|monitor| {
+                    let scale = monitor.scale_factor();
+                    let position = monitor.position().to_logical::<f32>(scale);
+                    let size = monitor.size().to_logical::<f32>(scale);
+                    Rect::from_min_size(
+                        Pos2::new(position.x, position.y),
+                        Vec2::new(size.width, size.height),
+                    )
+                })
+                .collect();
+        }
+        self.app.resumed(event_loop);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        self.app.window_event(event_loop, window_id, event);
+    }
+
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        self.app.new_events(event_loop, cause);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
+        self.app.user_event(event_loop, event);
+    }
+
+    fn device_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        self.app.device_event(event_loop, device_id, event);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.about_to_wait(event_loop);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.suspended(event_loop);
+    }
+
+    fn exiting(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.exiting(event_loop);
+    }
+
+    fn memory_warning(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.memory_warning(event_loop);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::fractal_clock::FractalClockConfig;
+#[cfg(feature = "export")]
+use crate::timelapse::ClipRender;
+use egui::{Color32, epaint::Hsva};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MorphAction {
+    SetA,
+    SetB,
+    Swap,
+    #[cfg(feature = "export")]
+    RenderGif,
+    #[cfg(feature = "export")]
+    RenderVideo,
+}
+
+// Morph settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct MorphConfig {
+    pub enabled: bool,
+    pub a: Option<FractalClockConfig>,
+    pub b: Option<FractalClockConfig>,
+    /// Real seconds of a loop from A to B and back.
+    pub duration_seconds: f32,
+    /// Width and height of the rendered frames in pixels.
+    pub size: u32,
+    pub fps: u32,
+}
+
+impl Default for MorphConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            a: None,
+            b: None,
+            duration_seconds: 20.0,
+            size: 720,
+            fps: 30,
+        }
+    }
+}
+
+impl MorphConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<MorphAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            for (label, config, set) in [
+                ("A", &self.a, MorphAction::SetA),
+                ("B", &self.b, MorphAction::SetB),
+            ] {
+                let text = if config.is_some() {
+                    format!("Replace {label}")
+                } else {
+                    format!("Set {label}")
+                };
+                if ui
+                    .button(text)
+                    .on_hover_text(format!("Uses the current settings as {label}"))
+                    .clicked()
+                {
+                    action = Some(set);
+                }
+            }
+            if ui.button("⇄ Swap").clicked() {
+                action = Some(MorphAction::Swap);
+            }
+        });
+        if !self.ready() {
+            self.enabled = false;
+        }
+        ui.add_enabled_ui(self.ready(), |ui| {
+            ui.checkbox(&mut self.enabled, "Play the morph")
+                .on_hover_text("Drives the settings, which cannot be edited while it plays");
+            ui.add(
+                egui::Slider::new(&mut self.duration_seconds, 1.0..=600.0)
+                    .logarithmic(true)
+                    .suffix(" s")
+                    .text("per loop"),
+            );
+        });
+        action
+    }
+
+    #[cfg(feature = "export")]
+    pub fn render_ui(&mut self, ui: &mut egui::Ui, render: &ClipRender) -> Option<MorphAction> {
+        ui.add_enabled_ui(self.ready(), |ui| {
+            ui.add(egui::Slider::new(&mut self.size, 128..=2160).text("frame size"));
+            ui.add(egui::Slider::new(&mut self.fps, 1..=60).text("FPS"));
+            if render.is_rendering() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Rendering the loop…");
+                });
+                return None;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Render GIF").clicked() {
+                    Some(MorphAction::RenderGif)
+                } else if ui
+                    .button("Render video")
+                    .on_hover_text("Requires ffmpeg on the PATH")
+                    .clicked()
+                {
+                    Some(MorphAction::RenderVideo)
+                } else {
+                    None
+                }
+            })
+            .inner
+        })
+        .inner
+    }
+
+    fn ready(&self) -> bool {
+        self.a.is_some() && self.b.is_some()
+    }
+
+    /// Settings at `seconds` of real time, when the morph plays.
+    pub fn config(&self, seconds: f64) -> Option<FractalClockConfig> {
+        if !self.enabled {
+            return None;
+        }
+        let duration = f64::from(self.duration_seconds.max(1.0));
+        self.at((seconds.rem_euclid(duration) / duration) as f32)
+    }
+
+    /// Settings at `phase` through the loop, from 0 to 1.
+    pub fn at(&self, phase: f32) -> Option<FractalClockConfig> {
+        let (a, b) = (self.a.as_ref()?, self.b.as_ref()?);
+        // There and back again, so the loop has no seam
+        let t = 1.0 - (2.0 * phase - 1.0).abs();
+        Some(FractalClockConfig::morph(a, b, smoothstep(t)))
+    }
+
+    #[cfg(feature = "export")]
+    pub fn frame_count(&self) -> u32 {
+        ((self.duration_seconds * self.fps as f32).round() as u32).max(1)
+    }
+}
+
+pub fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+pub fn lerp_usize(a: usize, b: usize, t: f32) -> usize {
+    lerp(a as f32, b as f32, t).round() as usize
+}
+
+pub fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    a.lerp_to_gamma(b, t)
+}
+
+/// Turns the hue the short way around the color wheel.
+pub fn lerp_hsva(a: Hsva, b: Hsva, t: f32) -> Hsva {
+    let turn = (b.h - a.h + 0.5).rem_euclid(1.0) - 0.5;
+    Hsva::new(
+        (a.h + turn * t).rem_euclid(1.0),
+        lerp(a.s, b.s, t),
+        lerp(a.v, b.v, t),
+        lerp(a.a, b.a, t),
+    )
+}
@@ -0,0 +1,66 @@
+use chrono::{NaiveTime, Timelike};
+use egui::Color32;
+
+const MINUTES_PER_DAY: f32 = 24.0 * 60.0;
+
+// Scheduled dimming settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct NightMode {
+    pub enabled: bool,
+    pub start_hour: u32,
+    pub end_hour: u32,
+    pub brightness: f32,
+    pub ramp_minutes: f32,
+}
+
+impl Default for NightMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_hour: 22,
+            end_hour: 7,
+            brightness: 0.2,
+            ramp_minutes: 30.0,
+        }
+    }
+}
+
+impl NightMode {
+    /// Brightness factor for the given time of day, ramping smoothly in and out of the schedule.
+    pub fn brightness_at(&self, time: NaiveTime) -> f32 {
+        if !self.enabled {
+            return 1.0;
+        }
+
+        let now = time.num_seconds_from_midnight() as f32 / 60.0;
+        let start = (self.start_hour * 60) as f32;
+        let duration = ((self.end_hour * 60) as f32 - start).rem_euclid(MINUTES_PER_DAY);
+        let elapsed = (now - start).rem_euclid(MINUTES_PER_DAY);
+        if duration == 0.0 || elapsed >= duration {
+            return 1.0;
+        }
+
+        let ramp = self.ramp_minutes.max(f32::EPSILON);
+        let weight = (elapsed / ramp).min((duration - elapsed) / ramp).min(1.0);
+        egui::lerp(1.0..=self.brightness, weight)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Dim at night");
+        ui.add(egui::Slider::new(&mut self.start_hour, 0..=23).text("start hour"));
+        ui.add(egui::Slider::new(&mut self.end_hour, 0..=23).text("end hour"));
+        ui.add(egui::Slider::new(&mut self.brightness, 0.0..=1.0).text("brightness"));
+        ui.add(egui::Slider::new(&mut self.ramp_minutes, 0.0..=120.0).text("ramp minutes"));
+    }
+}
+
+/// Scales the color channels while keeping alpha, so dimmed lines stay opaque.
+pub fn dim(color: Color32, brightness: f32) -> Color32 {
+    if brightness >= 1.0 {
+        return color;
+    }
+    let [r, g, b, a] = color.to_array();
+    let [r, g, b] = [r, g, b].map(|c| (c as f32 * brightness).round() as u8);
+    Color32::from_rgba_premultiplied(r, g, b, a)
+}
@@ -0,0 +1,37 @@
+use crate::alarms::{Reply, Response};
+use notify_rust::{Notification, Timeout};
+use std::sync::mpsc::Sender;
+
+/// Shows a desktop notification for the alarm `label` on a background thread, which waits for
+/// its Snooze or Dismiss action and sends it back with `id`.
+pub fn alarm(ctx: &egui::Context, label: &str, id: u64, responses: Sender<Reply>) {
+    let ctx = ctx.clone();
+    let label = label.to_owned();
+    std::thread::spawn(move || {
+        let handle = Notification::new()
+            .appname("Fractal Clock")
+            .summary("Fractal Clock")
+            .body(&label)
+            .action("snooze", "Snooze")
+            .action("dismiss", "Dismiss")
+            .timeout(Timeout::Never)
+            .show();
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                tracing::warn!("Failed to send a notification: {e}");
+                return;
+            }
+        };
+        handle.wait_for_action(|action| {
+            let response = match action {
+                "snooze" => Response::Snooze,
+                "dismiss" | "__closed" => Response::Dismiss,
+                // Clicking the notification leaves the alarm ringing in the window
+                _ => return,
+            };
+            let _ = responses.send((id, response));
+            ctx.request_repaint();
+        });
+    });
+}
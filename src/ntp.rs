@@ -0,0 +1,172 @@
+use chrono::TimeDelta;
+use std::{
+    net::UdpSocket,
+    sync::mpsc::{Receiver, TryRecvError, channel},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Failed synchronizations are retried sooner than the interval, but not too often.
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+/// Seconds from the NTP epoch, 1900-01-01, to the Unix epoch.
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+
+// NTP synchronization settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct NtpConfig {
+    pub enabled: bool,
+    pub server: String,
+    pub interval_minutes: u32,
+    /// Shows the network time instead of the system time.
+    pub correct: bool,
+}
+
+impl Default for NtpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: "pool.ntp.org".to_owned(),
+            interval_minutes: 60,
+            correct: true,
+        }
+    }
+}
+
+impl NtpConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, ntp: &Ntp) {
+        ui.checkbox(&mut self.enabled, "Synchronize with a time server");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Server:");
+                ui.text_edit_singleline(&mut self.server);
+            });
+            ui.add(
+                egui::Slider::new(&mut self.interval_minutes, 1..=1440).text("interval minutes"),
+            );
+            ui.checkbox(&mut self.correct, "Correct the displayed time");
+        });
+        if let Some(error) = &ntp.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.interval_minutes.max(1)) * 60)
+    }
+}
+
+/// Offset of the system clock from a time server, measured on a background thread.
+#[derive(Default)]
+pub struct Ntp {
+    /// Network time minus system time.
+    offset: Option<TimeDelta>,
+    error: Option<String>,
+    pending: Option<Receiver<Result<TimeDelta, String>>>,
+    next_sync: Option<Instant>,
+    /// Server of the latest request, which is repeated right away when it changes.
+    requested: Option<String>,
+}
+
+impl PartialEq for Ntp {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset && self.error == other.error
+    }
+}
+
+impl Ntp {
+    /// Measured drift of the system clock, positive if it is behind.
+    pub fn offset(&self) -> Option<TimeDelta> {
+        self.offset
+    }
+
+    /// Added to the system time to show the network time.
+    pub fn correction(&self, config: &NtpConfig) -> TimeDelta {
+        match self.offset {
+            Some(offset) if config.enabled && config.correct => offset,
+            _ => TimeDelta::zero(),
+        }
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context, config: &NtpConfig) {
+        if !config.enabled {
+            return;
+        }
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(result) => {
+                    self.pending = None;
+                    let delay = match result {
+                        Ok(offset) => {
+                            self.offset = Some(offset);
+                            self.error = None;
+                            config.interval()
+                        }
+                        Err(error) => {
+                            tracing::warn!("Failed to synchronize with {}: {error}", config.server);
+                            self.error = Some(error);
+                            RETRY_DELAY.min(config.interval())
+                        }
+                    };
+                    self.next_sync = Some(Instant::now() + delay);
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        let changed = self.requested.as_ref() != Some(&config.server);
+        let due = self.next_sync.is_none_or(|next| Instant::now() >= next);
+        if changed || due {
+            let (sender, receiver) = channel();
+            let server = config.server.clone();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send(measure_offset(&server).map_err(|e| e.to_string()));
+                ctx.request_repaint();
+            });
+            self.pending = Some(receiver);
+            self.requested = Some(config.server.clone());
+        }
+    }
+}
+
+/// Measures the clock offset with a single SNTP request.
+fn measure_offset(server: &str) -> std::io::Result<TimeDelta> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    socket.connect((server, 123))?;
+
+    // Version 3, client mode
+    let mut packet = [0u8; 48];
+    packet[0] = 0x1B;
+    let sent = unix_seconds(SystemTime::now());
+    socket.send(&packet)?;
+    let received = socket.recv(&mut packet)?;
+    let arrived = unix_seconds(SystemTime::now());
+    if received < packet.len() || packet[0] & 0x07 != 4 {
+        return Err(std::io::Error::other(
+            "invalid response from the time server",
+        ));
+    }
+
+    let timestamp = |offset: usize| {
+        let seconds = u32::from_be_bytes(packet[offset..offset + 4].try_into().unwrap_or_default());
+        let fraction = u32::from_be_bytes(
+            packet[offset + 4..offset + 8]
+                .try_into()
+                .unwrap_or_default(),
+        );
+        f64::from(seconds) + f64::from(fraction) / 2f64.powi(32) - NTP_UNIX_OFFSET
+    };
+    let (server_received, server_sent) = (timestamp(32), timestamp(40));
+    // Assumes the request and the response took equally long
+    let offset = ((server_received - sent) + (server_sent - arrived)) / 2.0;
+    Ok(TimeDelta::microseconds((offset * 1e6) as i64))
+}
+
+fn unix_seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
@@ -0,0 +1,63 @@
+use egui::{Vec2, vec2};
+use std::f64::consts::TAU;
+
+/// Ratio between the horizontal and vertical drift periods, so the center traces a slow Lissajous path
+/// instead of a straight line.
+const VERTICAL_PERIOD_RATIO: f64 = 1.31;
+
+// Burn-in protection settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct OledProtection {
+    pub enabled: bool,
+    #[serde(alias = "shift_pixels")]
+    pub shift_points: f32,
+    pub period_minutes: f32,
+    pub overlay_dim: f32,
+}
+
+impl Default for OledProtection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shift_points: 8.0,
+            period_minutes: 10.0,
+            overlay_dim: 0.5,
+        }
+    }
+}
+
+impl OledProtection {
+    fn period_seconds(&self) -> f64 {
+        f64::from(self.period_minutes.max(0.1)) * 60.0
+    }
+
+    /// Offset of the clock center at `time` seconds since startup.
+    pub fn center_offset(&self, time: f64) -> Vec2 {
+        if !self.enabled {
+            return Vec2::ZERO;
+        }
+        let phase = TAU * time / self.period_seconds();
+        let x = phase.sin();
+        let y = (phase / VERTICAL_PERIOD_RATIO).sin();
+        vec2(x as f32, y as f32) * self.shift_points
+    }
+
+    /// Opacity of static overlay elements, dimmed on every other period.
+    pub fn overlay_opacity(&self, time: f64) -> f32 {
+        let dimmed = (time / self.period_seconds()) as u64 % 2 == 1;
+        if self.enabled && dimmed {
+            self.overlay_dim
+        } else {
+            1.0
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Burn-in protection");
+        ui.add(egui::Slider::new(&mut self.shift_points, 0.0..=32.0).text("max shift (pt)"))
+            .on_hover_text("In points, so the shift covers the same distance at any DPI");
+        ui.add(egui::Slider::new(&mut self.period_minutes, 1.0..=60.0).text("period minutes"));
+        ui.add(egui::Slider::new(&mut self.overlay_dim, 0.1..=1.0).text("overlay opacity"));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::toasts;
+use egui::{ColorImage, UserData, ViewportCommand};
+use libloading::Library;
+use std::{
+    ffi::{CString, c_char, c_void},
+    ptr,
+};
+
+const NDI_FOURCC_RGBA: u32 = u32::from_le_bytes(*b"RGBA");
+const NDI_FRAME_FORMAT_PROGRESSIVE: i32 = 1;
+const NDI_SEND_TIMECODE_SYNTHESIZE: i64 = i64::MAX;
+
+#[cfg(target_os = "windows")]
+const NDI_LIBRARY_NAMES: &[&str] = &["Processing.NDI.Lib.x64.dll"];
+#[cfg(target_os = "macos")]
+const NDI_LIBRARY_NAMES: &[&str] = &["libndi.dylib", "/usr/local/lib/libndi.dylib"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const NDI_LIBRARY_NAMES: &[&str] = &["libndi.so.6", "libndi.so.5", "libndi.so"];
+
+#[repr(C)]
+struct NdiSendCreate {
+    p_ndi_name: *const c_char,
+    p_groups: *const c_char,
+    clock_video: bool,
+    clock_audio: bool,
+}
+
+#[repr(C)]
+struct NdiVideoFrameV2 {
+    xres: i32,
+    yres: i32,
+    fourcc: u32,
+    frame_rate_n: i32,
+    frame_rate_d: i32,
+    picture_aspect_ratio: f32,
+    frame_format_type: i32,
+    timecode: i64,
+    p_data: *const u8,
+    line_stride_in_bytes: i32,
+    p_metadata: *const c_char,
+    timestamp: i64,
+}
+
+type NdiInitialize = unsafe extern "C" fn() -> bool;
+type NdiDestroy = unsafe extern "C" fn();
+type NdiSendCreateFn = unsafe extern "C" fn(*const NdiSendCreate) -> *mut c_void;
+type NdiSendDestroy = unsafe extern "C" fn(*mut c_void);
+type NdiSendVideoV2 = unsafe extern "C" fn(*mut c_void, *const NdiVideoFrameV2);
+
+/// Marks screenshots requested for the stream, so other screenshot users are left alone.
+struct NdiFrameRequest;
+
+// Output settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct OutputConfig {
+    pub ndi_enabled: bool,
+    pub stream_name: String,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            ndi_enabled: false,
+            stream_name: "Fractal Clock".to_owned(),
+        }
+    }
+}
+
+/// Publishes rendered frames as an NDI source, using the NDI runtime installed on the system.
+struct NdiSender {
+    instance: *mut c_void,
+    send_video: NdiSendVideoV2,
+    send_destroy: NdiSendDestroy,
+    destroy: NdiDestroy,
+    stream_name: String,
+    pixels: Vec<u8>,
+    _library: Library,
+}
+
+impl NdiSender {
+    fn new(stream_name: &str) -> Result<Self, String> {
+        let library = NDI_LIBRARY_NAMES
+            .iter()
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .ok_or("NDI runtime not found, install it from ndi.video")?;
+
+        unsafe {
+            let initialize = *library
+                .get::<NdiInitialize>(b"NDIlib_initialize\0")
+                .map_err(|e| e.to_string())?;
+            let destroy = *library
+                .get::<NdiDestroy>(b"NDIlib_destroy\0")
+                .map_err(|e| e.to_string())?;
+            let send_create = *library
+                .get::<NdiSendCreateFn>(b"NDIlib_send_create\0")
+                .map_err(|e| e.to_string())?;
+            let send_destroy = *library
+                .get::<NdiSendDestroy>(b"NDIlib_send_destroy\0")
+                .map_err(|e| e.to_string())?;
+            let send_video = *library
+                .get::<NdiSendVideoV2>(b"NDIlib_send_send_video_v2\0")
+                .map_err(|e| e.to_string())?;
+
+            if !initialize() {
+                return Err("NDI is not supported on this CPU".to_owned());
+            }
+
+            let name = CString::new(stream_name).map_err(|e| e.to_string())?;
+            let instance = send_create(&NdiSendCreate {
+                p_ndi_name: name.as_ptr(),
+                p_groups: ptr::null(),
+                clock_video: false,
+                clock_audio: false,
+            });
+            if instance.is_null() {
+                destroy();
+                return Err("Failed to create NDI sender".to_owned());
+            }
+
+            Ok(Self {
+                instance,
+                send_video,
+                send_destroy,
+                destroy,
+                stream_name: stream_name.to_owned(),
+                pixels: Vec::new(),
+                _library: library,
+            })
+        }
+    }
+
+    fn send(&mut self, image: &ColorImage) {
+        let [width, height] = image.size;
+        self.pixels.clear();
+        self.pixels.extend(
+            image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_srgba_unmultiplied()),
+        );
+
+        let frame = NdiVideoFrameV2 {
+            xres: width as i32,
+            yres: height as i32,
+            fourcc: NDI_FOURCC_RGBA,
+            frame_rate_n: 60_000,
+            frame_rate_d: 1_000,
+            picture_aspect_ratio: 0.0,
+            frame_format_type: NDI_FRAME_FORMAT_PROGRESSIVE,
+            timecode: NDI_SEND_TIMECODE_SYNTHESIZE,
+            p_data: self.pixels.as_ptr(),
+            line_stride_in_bytes: (width * 4) as i32,
+            p_metadata: ptr::null(),
+            timestamp: 0,
+        };
+
+        unsafe { (self.send_video)(self.instance, &frame) };
+    }
+}
+
+impl Drop for NdiSender {
+    fn drop(&mut self) {
+        unsafe {
+            (self.send_destroy)(self.instance);
+            (self.destroy)();
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FrameOutput {
+    sender: Option<NdiSender>,
+    pub error: Option<String>,
+}
+
+impl PartialEq for FrameOutput {
+    fn eq(&self, other: &Self) -> bool {
+        self.sender.is_some() == other.sender.is_some() && self.error == other.error
+    }
+}
+
+impl FrameOutput {
+    pub fn is_streaming(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context, config: &OutputConfig) {
+        if !config.ndi_enabled {
+            self.sender = None;
+            self.error = None;
+            return;
+        }
+
+        if self
+            .sender
+            .as_ref()
+            .is_some_and(|sender| sender.stream_name != config.stream_name)
+        {
+            self.sender = None;
+        }
+
+        if self.sender.is_none() && self.error.is_none() {
+            match NdiSender::new(&config.stream_name) {
+                Ok(sender) => {
+                    tracing::info!("Streaming NDI source \"{}\"", config.stream_name);
+                    toasts::success(format!("Streaming as \"{}\"", config.stream_name));
+                    self.sender = Some(sender);
+                }
+                Err(error) => {
+                    tracing::error!("Failed to start NDI stream: {error}");
+                    toasts::error(format!("Failed to start NDI stream: {error}"));
+                    self.error = Some(error);
+                }
+            }
+        }
+
+        let Some(sender) = &mut self.sender else {
+            return;
+        };
+
+        ctx.input(|i| {
+            for event in &i.raw.events {
+                if let egui::Event::Screenshot {
+                    user_data, image, ..
+                } = event
+                    && user_data
+                        .data
+                        .as_ref()
+                        .is_some_and(|data| data.is::<NdiFrameRequest>())
+                {
+                    sender.send(image);
+                }
+            }
+        });
+
+        ctx.send_viewport_cmd(ViewportCommand::Screenshot(UserData::new(NdiFrameRequest)));
+        ctx.request_repaint();
+    }
+}
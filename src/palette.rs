@@ -0,0 +1,147 @@
+use egui::{Color32, Sense, Ui, vec2};
+use std::path::Path;
+
+const SWATCH_SIZE: f32 = 20.0;
+
+#[derive(Clone, PartialEq)]
+pub enum PaletteAction {
+    /// Colors the gradient runs through, from the first to the last.
+    Gradient(Vec<Color32>),
+    /// Colors for the hands and the branches.
+    Theme { hand: Color32, branch: Color32 },
+}
+
+/// Imports the colors of a GIMP palette, a coolors.co URL or a list of hex codes.
+#[derive(Default, PartialEq)]
+pub struct PaletteImport {
+    input: String,
+    colors: Vec<Color32>,
+    error: Option<String>,
+}
+
+impl PaletteImport {
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<PaletteAction> {
+        ui.add(
+            egui::TextEdit::multiline(&mut self.input)
+                .desired_rows(2)
+                .hint_text("#ff0000 #00ff00, a coolors.co URL, a .gpl file or its contents"),
+        );
+        if ui.button("Import").clicked() {
+            match parse(self.input.trim()) {
+                Ok(colors) => {
+                    self.colors = colors;
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.colors.clear();
+                    self.error = Some(e);
+                }
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        if self.colors.is_empty() {
+            return None;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for &color in &self.colors {
+                let (rect, response) =
+                    ui.allocate_exact_size(vec2(SWATCH_SIZE, SWATCH_SIZE), Sense::hover());
+                ui.painter().rect_filled(rect, 2.0, color);
+                response.on_hover_text(hex(color));
+            }
+        });
+        let mut action = None;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.colors.len() >= 2, egui::Button::new("Use as gradient"))
+                .on_hover_text("The gradient runs through the colors in order")
+                .clicked()
+            {
+                action = Some(PaletteAction::Gradient(self.colors.clone()));
+            }
+            if ui
+                .button("Use as theme")
+                .on_hover_text("The first color for the hands and the second for the branches")
+                .clicked()
+            {
+                let hand = self.colors[0];
+                let branch = self.colors.get(1).copied().unwrap_or(hand);
+                action = Some(PaletteAction::Theme { hand, branch });
+            }
+        });
+        action
+    }
+}
+
+/// Colors of a GIMP palette or the path of one, a coolors.co URL or a list of hex codes.
+pub fn parse(input: &str) -> Result<Vec<Color32>, String> {
+    let colors = if input.starts_with("GIMP Palette") {
+        parse_gpl(input)?
+    } else if input.to_lowercase().ends_with(".gpl") {
+        let contents = std::fs::read_to_string(Path::new(input))
+            .map_err(|e| format!("Failed to read {input}: {e}"))?;
+        parse_gpl(&contents)?
+    } else if input.contains("coolors.co") {
+        // The colors are the last part of the path, e.g. `coolors.co/palette/264653-2a9d8f`
+        let path = input.split(['?', '#']).next().unwrap_or_default();
+        let last = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        last.split('-').map(parse_hex).collect::<Result<_, _>>()?
+    } else {
+        input
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+            .filter(|code| !code.is_empty())
+            .map(parse_hex)
+            .collect::<Result<_, _>>()?
+    };
+    if colors.is_empty() {
+        return Err("No colors found".to_owned());
+    }
+    Ok(colors)
+}
+
+/// Colors of a GIMP palette, whose lines after the header are red, green and blue from 0 to
+/// 255 followed by an optional name.
+fn parse_gpl(contents: &str) -> Result<Vec<Color32>, String> {
+    contents
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        // Skips comments and the `Name:`, `Columns:` and `Channels:` headers
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|line| {
+            let channels: Vec<u8> = line
+                .split_whitespace()
+                .take(3)
+                .map(|channel| channel.parse().map_err(|_| format!("Not a color: {line}")))
+                .collect::<Result<_, _>>()?;
+            match channels[..] {
+                [r, g, b] => Ok(Color32::from_rgb(r, g, b)),
+                _ => Err(format!("Not a color: {line}")),
+            }
+        })
+        .collect()
+}
+
+/// Parses `#rrggbb` or `#rgb`, with or without the `#`.
+fn parse_hex(code: &str) -> Result<Color32, String> {
+    let digits = code.trim().trim_start_matches('#');
+    let digits = match digits.len() {
+        3 => digits.chars().flat_map(|c| [c, c]).collect(),
+        6 => digits.to_owned(),
+        _ => return Err(format!("Not a hex color: {code}")),
+    };
+    let value = u32::from_str_radix(&digits, 16).map_err(|_| format!("Not a hex color: {code}"))?;
+    let [_, r, g, b] = value.to_be_bytes();
+    Ok(Color32::from_rgb(r, g, b))
+}
+
+fn hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Local, TimeDelta, TimeZone as _};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+// Compressed-time playback settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PlaybackConfig {
+    pub enabled: bool,
+    /// Real seconds a whole day of hand motion is played in.
+    pub duration_seconds: f32,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_seconds: 60.0,
+        }
+    }
+}
+
+impl PlaybackConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, shown: &DateTime<Local>) {
+        ui.checkbox(&mut self.enabled, "Play a day in")
+            .on_hover_text("Loops through the whole day, to watch all its shapes");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.duration_seconds, 10.0..=3600.0)
+                    .logarithmic(true)
+                    .suffix(" s")
+                    .text("per day"),
+            );
+        });
+        if self.enabled {
+            ui.weak(format!("Showing {}", shown.format("%H:%M:%S")));
+        }
+    }
+
+    /// Time of the day shown at the real time `now`, looping from midnight to midnight of its day
+    /// every `duration_seconds`, or `now` itself when disabled.
+    pub fn time(&self, now: DateTime<Local>) -> DateTime<Local> {
+        if !self.enabled {
+            return now;
+        }
+        let duration = f64::from(self.duration_seconds.max(1.0));
+        // The wall clock keeps the position in the loop without any state
+        let real_seconds = now.timestamp_micros() as f64 / 1e6;
+        let fraction = real_seconds.rem_euclid(duration) / duration;
+        let Some(midnight) = now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        else {
+            return now;
+        };
+        midnight + TimeDelta::microseconds((fraction * SECONDS_PER_DAY * 1e6) as i64)
+    }
+}
@@ -0,0 +1,222 @@
+use crate::effects::{Effect, Language};
+use egui::{Slider, Ui};
+use std::sync::Arc;
+
+const VIGNETTE: &str = "
+void main() {
+    float aspect = resolution.x / resolution.y;
+    float dist = length((v_uv - 0.5) * vec2(aspect, 1.0));
+    float shade = 1.0 - params.x * smoothstep(params.y, params.y + 0.5, dist);
+    out_color = texture(u_frame, v_uv) * shade;
+}
+";
+
+const RGB_FRINGE: &str = "
+void main() {
+    vec2 shift = (v_uv - 0.5) * 2.0 * params.x / resolution;
+    vec4 red = texture(u_frame, v_uv + shift);
+    vec4 green = texture(u_frame, v_uv);
+    vec4 blue = texture(u_frame, v_uv - shift);
+    out_color = vec4(red.r, green.g, blue.b, max(max(red.a, green.a), blue.a));
+}
+";
+
+const FILM_GRAIN: &str = "
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+    vec2 cell = floor(v_uv * resolution / params.y);
+    // A new pattern 24 times a second, like film
+    float frame = mod(floor(time * 24.0), 97.0);
+    float noise = hash(cell + frame * 13.0) - 0.5;
+    vec4 color = texture(u_frame, v_uv);
+    out_color = vec4(clamp(color.rgb + noise * params.x * color.a, 0.0, color.a), color.a);
+}
+";
+
+const CRT: &str = "
+void main() {
+    vec2 centered = v_uv * 2.0 - 1.0;
+    centered += centered * centered.yx * centered.yx * params.x;
+    vec2 uv = centered * 0.5 + 0.5;
+    if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+        out_color = vec4(0.0);
+        return;
+    }
+    float line = 0.5 + 0.5 * sin(uv.y * resolution.y * 3.14159);
+    out_color = texture(u_frame, uv) * (1.0 - params.y * line);
+}
+";
+
+/// A built-in effect with its parameters.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum PostEffectKind {
+    /// Darkens towards the corners.
+    Vignette {
+        strength: f32,
+        /// Distance from the center where the darkening starts, in heights of the frame.
+        radius: f32,
+    },
+    /// Splits the red and blue channels apart towards the edges, like a cheap lens.
+    RgbFringe {
+        /// Pixels the channels are apart at the edges.
+        offset: f32,
+    },
+    /// Flickering noise over the lines.
+    FilmGrain { amount: f32, size: f32 },
+    /// Bulges the frame like a tube screen and darkens every other row of pixels.
+    Crt { curvature: f32, scanlines: f32 },
+}
+
+impl PostEffectKind {
+    const ALL: [Self; 4] = [
+        Self::Vignette {
+            strength: 0.6,
+            radius: 0.4,
+        },
+        Self::RgbFringe { offset: 4.0 },
+        Self::FilmGrain {
+            amount: 0.15,
+            size: 1.5,
+        },
+        Self::Crt {
+            curvature: 0.15,
+            scanlines: 0.3,
+        },
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Vignette { .. } => "Vignette",
+            Self::RgbFringe { .. } => "RGB fringe",
+            Self::FilmGrain { .. } => "Film grain",
+            Self::Crt { .. } => "CRT",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::Vignette { .. } => VIGNETTE,
+            Self::RgbFringe { .. } => RGB_FRINGE,
+            Self::FilmGrain { .. } => FILM_GRAIN,
+            Self::Crt { .. } => CRT,
+        }
+    }
+
+    fn params(self) -> [f32; 4] {
+        match self {
+            Self::Vignette { strength, radius } => [strength, radius, 0.0, 0.0],
+            Self::RgbFringe { offset } => [offset, 0.0, 0.0, 0.0],
+            Self::FilmGrain { amount, size } => [amount, size, 0.0, 0.0],
+            Self::Crt {
+                curvature,
+                scanlines,
+            } => [curvature, scanlines, 0.0, 0.0],
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        match self {
+            Self::Vignette { strength, radius } => {
+                ui.add(Slider::new(strength, 0.0..=1.0).text("strength"));
+                ui.add(Slider::new(radius, 0.0..=1.0).text("radius"));
+            }
+            Self::RgbFringe { offset } => {
+                ui.add(Slider::new(offset, 0.0..=20.0).suffix(" px").text("offset"));
+            }
+            Self::FilmGrain { amount, size } => {
+                ui.add(Slider::new(amount, 0.0..=0.5).text("amount"));
+                ui.add(
+                    Slider::new(size, 1.0..=4.0)
+                        .suffix(" px")
+                        .text("grain size"),
+                );
+            }
+            Self::Crt {
+                curvature,
+                scanlines,
+            } => {
+                ui.add(Slider::new(curvature, 0.0..=0.5).text("curvature"));
+                ui.add(Slider::new(scanlines, 0.0..=1.0).text("scanlines"));
+            }
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct PostEffect {
+    pub enabled: bool,
+    pub kind: PostEffectKind,
+}
+
+// Post-processing settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PostProcessingConfig {
+    /// Applied to the frame in this order.
+    pub effects: Vec<PostEffect>,
+}
+
+impl Default for PostProcessingConfig {
+    fn default() -> Self {
+        Self {
+            effects: PostEffectKind::ALL
+                .map(|kind| PostEffect {
+                    enabled: false,
+                    kind,
+                })
+                .into(),
+        }
+    }
+}
+
+impl PostProcessingConfig {
+    /// The enabled effects in order.
+    pub fn active(&self) -> impl Iterator<Item = Arc<Effect>> + '_ {
+        self.effects
+            .iter()
+            .filter(|effect| effect.enabled)
+            .map(|effect| {
+                Arc::new(Effect {
+                    name: effect.kind.label().to_owned(),
+                    language: Language::Glsl,
+                    source: effect.kind.source().into(),
+                    params: effect.kind.params(),
+                })
+            })
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let count = self.effects.len();
+        let mut moved = None;
+        for (index, effect) in self.effects.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut effect.enabled, effect.kind.label());
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("⬆").small())
+                        .on_hover_text("Apply earlier")
+                        .clicked()
+                    {
+                        moved = Some((index, index - 1));
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("⬇").small())
+                        .on_hover_text("Apply later")
+                        .clicked()
+                    {
+                        moved = Some((index, index + 1));
+                    }
+                });
+                if effect.enabled {
+                    ui.indent("params", |ui| effect.kind.ui(ui));
+                }
+            });
+        }
+        if let Some((from, to)) = moved {
+            self.effects.swap(from, to);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use egui::{Color32, Pos2};
+use image::RgbaImage;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{Receiver, TryRecvError, channel},
+};
+
+// Poster export settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PosterConfig {
+    pub columns: u32,
+    pub rows: u32,
+    /// Width and height of every clock in pixels.
+    pub cell_size: u32,
+    /// Writes the time under every clock.
+    pub captions: bool,
+}
+
+impl Default for PosterConfig {
+    fn default() -> Self {
+        Self {
+            columns: 6,
+            rows: 4,
+            cell_size: 400,
+            captions: true,
+        }
+    }
+}
+
+impl PosterConfig {
+    /// Returns whether the export was requested.
+    pub fn ui(&mut self, ui: &mut egui::Ui, poster: &Poster) -> bool {
+        ui.add(egui::Slider::new(&mut self.columns, 1..=12).text("columns"));
+        ui.add(egui::Slider::new(&mut self.rows, 1..=12).text("rows"));
+        ui.add(egui::Slider::new(&mut self.cell_size, 100..=1000).text("clock size"));
+        ui.checkbox(&mut self.captions, "Captions");
+        let size = self.image_size();
+        ui.weak(format!(
+            "{} clocks every {} in a {}×{} image",
+            self.count(),
+            format_interval(self.interval_seconds()),
+            size[0],
+            size[1]
+        ));
+        if poster.is_exporting() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Rendering the poster…");
+            });
+            false
+        } else {
+            ui.button("🖼 Save poster").clicked()
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.columns.max(1) * self.rows.max(1)
+    }
+
+    /// Seconds between the clocks, spreading them evenly over the day from midnight.
+    pub fn interval_seconds(&self) -> u32 {
+        86_400 / self.count()
+    }
+
+    fn caption_height(&self) -> u32 {
+        if self.captions { self.cell_size / 8 } else { 0 }
+    }
+
+    pub fn image_size(&self) -> [u32; 2] {
+        [
+            self.columns.max(1) * self.cell_size,
+            self.rows.max(1) * (self.cell_size + self.caption_height()),
+        ]
+    }
+
+    /// Copies the clock of the `index`th cell into the poster, with its `caption` in
+    /// `caption_color`.
+    pub fn place(
+        &self,
+        poster: &mut RgbaImage,
+        index: u32,
+        cell: &RgbaImage,
+        caption: &str,
+        caption_color: Color32,
+    ) {
+        let row_height = self.cell_size + self.caption_height();
+        let x = index % self.columns.max(1) * self.cell_size;
+        let y = index / self.columns.max(1) * row_height;
+        image::imageops::overlay(poster, cell, x.into(), y.into());
+        if self.captions {
+            let center = Pos2::new(
+                (x + self.cell_size / 2) as f32,
+                (y + self.cell_size + self.caption_height() / 2) as f32,
+            );
+            let size = self.caption_height() as f32 * 0.6;
+            crate::headless::draw_text(poster, caption, center, size, caption_color);
+        }
+    }
+}
+
+fn format_interval(seconds: u32) -> String {
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (hours, 0, 0) => format!("{hours} h"),
+        (0, minutes, 0) => format!("{minutes} min"),
+        (hours, minutes, seconds) => format!("{hours}:{minutes:02}:{seconds:02}"),
+    }
+}
+
+/// Renders the poster, or another image, on a background thread and saves it as a PNG file.
+#[derive(Default)]
+pub struct Poster {
+    pending: Option<Receiver<Result<PathBuf, String>>>,
+}
+
+impl PartialEq for Poster {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_exporting() == other.is_exporting()
+    }
+}
+
+impl Poster {
+    pub fn is_exporting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Runs `render` on a background thread and saves the image it returns in the downloads
+    /// directory, named after `kind`.
+    pub fn export(
+        &mut self,
+        ctx: &egui::Context,
+        kind: &'static str,
+        render: impl FnOnce() -> RgbaImage + Send + 'static,
+    ) {
+        let (sender, receiver) = channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(save(&render(), kind));
+            ctx.request_repaint();
+        });
+        self.pending = Some(receiver);
+    }
+
+    /// The saved file or the error, once the export finished.
+    pub fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        let result = match self.pending.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("Rendering stopped unexpectedly".to_owned()),
+        };
+        self.pending = None;
+        Some(result)
+    }
+}
+
+fn save(image: &RgbaImage, kind: &str) -> Result<PathBuf, String> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or("No downloads or home directory")?;
+    let name = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let path = dir.join(format!("fractal_clock_{kind}_{name}.png"));
+    image.save(&path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
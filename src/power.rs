@@ -0,0 +1,97 @@
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// Reduced quality profile used while running on battery
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct BatteryProfile {
+    pub enabled: bool,
+    pub max_depth: usize,
+    pub fps_cap: u32,
+    /// Turns off the shadow, heatmap, blend modes, shader effects and post-processing.
+    pub effects_off: bool,
+}
+
+impl Default for BatteryProfile {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_depth: 10,
+            fps_cap: 30,
+            effects_off: true,
+        }
+    }
+}
+
+/// Tracks whether the system is running on battery, polling the platform occasionally.
+#[derive(Default, PartialEq)]
+pub struct PowerMonitor {
+    on_battery: bool,
+    last_poll: Option<Instant>,
+}
+
+impl PowerMonitor {
+    pub fn on_battery(&self) -> bool {
+        self.on_battery
+    }
+
+    pub fn update(&mut self) {
+        if self
+            .last_poll
+            .is_some_and(|last_poll| last_poll.elapsed() < POLL_INTERVAL)
+        {
+            return;
+        }
+        self.last_poll = Some(Instant::now());
+        self.on_battery = on_battery().unwrap_or(false);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn on_battery() -> Option<bool> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut discharging = false;
+
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).unwrap_or_default();
+        // Batteries of wireless mice, keyboards and gamepads do not power the system
+        if read("scope").trim() == "Device" {
+            continue;
+        }
+        match read("type").trim() {
+            "Mains" | "USB" if read("online").trim() == "1" => return Some(false),
+            "Battery" if read("status").trim() == "Discharging" => discharging = true,
+            _ => {}
+        }
+    }
+
+    Some(discharging)
+}
+
+#[cfg(target_os = "windows")]
+fn on_battery() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // 0 is offline, 1 online and 255 unknown
+    Some(status.ACLineStatus == 0)
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn on_battery() -> Option<bool> {
+    None
+}
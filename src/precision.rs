@@ -0,0 +1,159 @@
+use egui::{Pos2, Rect, Vec2, emath::RectTransform};
+use std::ops::{Add, Mul, Sub};
+
+/// Floating point type the branches are generated with.
+pub trait Real:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const INFINITY: Self;
+    /// Whether branches grow faster in SIMD batches than a node at a time, see `simd::grow`.
+    const BATCHED: bool;
+
+    fn from_f64(value: f64) -> Self;
+    fn to_f32(self) -> f32;
+}
+
+impl Real for f32 {
+    const ZERO: Self = 0.0;
+    const INFINITY: Self = f32::INFINITY;
+    const BATCHED: bool = true;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+impl Real for f64 {
+    const ZERO: Self = 0.0;
+    const INFINITY: Self = f64::INFINITY;
+    // Half as many lanes fit a register, which does not make up for splitting the nodes
+    const BATCHED: bool = false;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+/// A 2D vector as a complex number, so rotating and scaling a branch is a single multiplication.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T: Real> Complex<T> {
+    pub const ZERO: Self = Self {
+        re: T::ZERO,
+        im: T::ZERO,
+    };
+
+    pub fn from_polar(length: f64, angle: f64) -> Self {
+        Self {
+            re: T::from_f64(length * angle.cos()),
+            im: T::from_f64(length * angle.sin()),
+        }
+    }
+
+    pub fn from_vec2(vec: Vec2) -> Self {
+        Self {
+            re: T::from_f64(vec.x.into()),
+            im: T::from_f64(vec.y.into()),
+        }
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.re.to_f32(), self.im.to_f32())
+    }
+
+    pub fn length_sq(self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+impl<T: Real> Add for Complex<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl<T: Real> Sub for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
+
+impl<T: Real> Mul for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+/// Maps the fractal space to the screen, subtracting the view center before converting to `f32`,
+/// so precision is only lost relative to the size of the view rather than of the whole tree.
+pub struct ScreenTransform<T> {
+    from_center: Complex<T>,
+    to_center: Pos2,
+    scale: Vec2,
+    visible_min: Complex<T>,
+    visible_max: Complex<T>,
+}
+
+impl<T: Real> ScreenTransform<T> {
+    /// `visible` is the part of the screen branches are culled to.
+    pub fn new(to_screen: &RectTransform, visible: Rect) -> Self {
+        let visible = to_screen.inverse().transform_rect(visible);
+        Self {
+            from_center: Complex::from_vec2(to_screen.from().center().to_vec2()),
+            to_center: to_screen.to().center(),
+            scale: to_screen.scale(),
+            visible_min: Complex::from_vec2(visible.min.to_vec2()),
+            visible_max: Complex::from_vec2(visible.max.to_vec2()),
+        }
+    }
+
+    pub fn to_screen(&self, pos: Complex<T>) -> Pos2 {
+        self.to_center + (pos - self.from_center).to_vec2() * self.scale
+    }
+
+    /// Squared distance from `pos` to the visible part of the fractal space.
+    pub fn distance_sq_to_visible(&self, pos: Complex<T>) -> T {
+        let outside = |value: T, min: T, max: T| {
+            if value < min {
+                min - value
+            } else if value > max {
+                value - max
+            } else {
+                T::ZERO
+            }
+        };
+        Complex {
+            re: outside(pos.re, self.visible_min.re, self.visible_max.re),
+            im: outside(pos.im, self.visible_min.im, self.visible_max.im),
+        }
+        .length_sq()
+    }
+}
@@ -0,0 +1,344 @@
+use crate::toasts;
+use egui::{Align2, Color32, FontId, Rect, Sense, Stroke, Ui, pos2, vec2};
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+/// Frames kept for the profiler window and the saved profile.
+const MAX_FRAMES: usize = 300;
+const ROW_HEIGHT: f32 = 18.0;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDING: Mutex<Recording> = Mutex::new(Recording {
+    frames: VecDeque::new(),
+    threads: Vec::new(),
+});
+
+thread_local! {
+    /// Scopes open on this thread.
+    static DEPTH: Cell<u8> = const { Cell::new(0) };
+    /// Index of this thread in `Recording::threads`, assigned on its first scope.
+    static THREAD: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// A timed part of a frame.
+#[derive(Clone, Copy)]
+struct Span {
+    name: &'static str,
+    thread: u32,
+    /// Scopes the span is nested in.
+    depth: u8,
+    /// Seconds since the start of the frame.
+    start: f64,
+    end: f64,
+}
+
+struct Frame {
+    start: Instant,
+    /// Seconds until the next frame started, or `None` while this is the current frame.
+    duration: Option<f64>,
+    spans: Vec<Span>,
+}
+
+struct Recording {
+    frames: VecDeque<Frame>,
+    /// Names of the threads that recorded spans.
+    threads: Vec<String>,
+}
+
+/// Times the code until the end of its scope while the profiler records.
+pub struct Scope {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+/// Starts a span named `name`, which ends when the returned guard is dropped.
+pub fn scope(name: &'static str) -> Scope {
+    let start = ENABLED.load(Ordering::Relaxed).then(|| {
+        DEPTH.with(|depth| depth.set(depth.get().saturating_add(1)));
+        Instant::now()
+    });
+    Scope { name, start }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let end = Instant::now();
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get().saturating_sub(1));
+            depth.get()
+        });
+        let Ok(mut recording) = RECORDING.lock() else {
+            return;
+        };
+        let thread = THREAD.with(|thread| {
+            *thread.get().get_or_insert_with(|| {
+                let name = std::thread::current()
+                    .name()
+                    .unwrap_or("unnamed")
+                    .to_owned();
+                recording.threads.push(name);
+                recording.threads.len() as u32 - 1
+            })
+        });
+        if let Some(frame) = recording.frames.back_mut() {
+            let seconds =
+                |instant: Instant| instant.saturating_duration_since(frame.start).as_secs_f64();
+            frame.spans.push(Span {
+                name: self.name,
+                thread,
+                depth,
+                start: seconds(start),
+                end: seconds(end),
+            });
+        }
+    }
+}
+
+/// Ends the current frame and starts the next one, while the profiler records.
+pub fn new_frame() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(mut recording) = RECORDING.lock() else {
+        return;
+    };
+    let now = Instant::now();
+    if let Some(frame) = recording.frames.back_mut() {
+        frame.duration = Some((now - frame.start).as_secs_f64());
+    }
+    recording.frames.push_back(Frame {
+        start: now,
+        duration: None,
+        spans: Vec::new(),
+    });
+    while recording.frames.len() > MAX_FRAMES {
+        recording.frames.pop_front();
+    }
+}
+
+/// Window with the frame times and a flame graph of the selected frame, which records while
+/// open.
+#[derive(Default, PartialEq)]
+pub struct ProfilerWindow {
+    pub open: bool,
+    paused: bool,
+    /// Index of the selected frame, or the latest complete one if `None`.
+    selected: Option<usize>,
+}
+
+impl ProfilerWindow {
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let recording = self.open && !self.paused;
+        if ENABLED.swap(recording, Ordering::Relaxed) != recording && recording {
+            // Frames recorded before a pause would show a gap as one long frame
+            if let Ok(mut recording) = RECORDING.lock() {
+                recording.frames.clear();
+            }
+            self.selected = None;
+        }
+        let mut open = self.open;
+        egui::Window::new("Profiler")
+            .open(&mut open)
+            .default_size([700.0, 300.0])
+            .show(ctx, |ui| self.ui(ui));
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.paused, "⏸ Pause");
+            if ui.button("💾 Save").clicked() {
+                match save() {
+                    Ok(path) => toasts::success(format!("Saved the profile to {}", path.display())),
+                    Err(e) => toasts::error(format!("Failed to save the profile: {e}")),
+                }
+            }
+        })
+        .response
+        .on_hover_text(
+            "Saved as a Chrome trace, which chrome://tracing, Perfetto and speedscope open. Frames \
+             of the clock's own drawing are shown; egui tessellates and renders after them.",
+        );
+        let Ok(recording) = RECORDING.lock() else {
+            return;
+        };
+        let complete = recording
+            .frames
+            .iter()
+            .filter(|frame| frame.duration.is_some())
+            .count();
+        if complete == 0 {
+            ui.label("Recording…");
+            return;
+        }
+        let selected = self.selected.filter(|&index| index < complete);
+        let selected = selected.unwrap_or(complete - 1);
+        if let Some(index) = frame_chart(ui, &recording.frames, complete, selected) {
+            self.selected = Some(index);
+            self.paused = true;
+        }
+        ui.separator();
+        let frame = &recording.frames[selected];
+        ui.label(format!(
+            "Frame {} of {complete}: {:.2} ms",
+            selected + 1,
+            frame.duration.unwrap_or_default() * 1000.0
+        ));
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            flame_graph(ui, frame, &recording.threads);
+        });
+    }
+}
+
+/// Bars of the frame times, returning the clicked frame.
+fn frame_chart(
+    ui: &mut Ui,
+    frames: &VecDeque<Frame>,
+    complete: usize,
+    selected: usize,
+) -> Option<usize> {
+    let size = vec2(ui.available_width(), 60.0);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let painter = ui.painter_at(rect);
+    let longest = frames
+        .iter()
+        .filter_map(|frame| frame.duration)
+        .fold(1.0 / 60.0, f64::max);
+    let width = rect.width() / MAX_FRAMES as f32;
+    for (index, frame) in frames.iter().take(complete).enumerate() {
+        let height = (frame.duration.unwrap_or_default() / longest) as f32 * rect.height();
+        let left = rect.left() + index as f32 * width;
+        let bar = Rect::from_min_max(
+            pos2(left, rect.bottom() - height),
+            pos2(left + width.max(1.0), rect.bottom()),
+        );
+        let color = if index == selected {
+            ui.visuals().selection.bg_fill
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        painter.rect_filled(bar, 0.0, color);
+    }
+    response
+        .interact_pointer_pos()
+        .filter(|_| response.clicked())
+        .map(|pos| (((pos.x - rect.left()) / width) as usize).min(complete - 1))
+}
+
+/// Spans of `frame` as nested bars along the frame's time, a group of rows per thread.
+fn flame_graph(ui: &mut Ui, frame: &Frame, threads: &[String]) {
+    let duration = frame.duration.unwrap_or_default().max(1e-6);
+    for (thread, name) in threads.iter().enumerate() {
+        let spans: Vec<_> = frame
+            .spans
+            .iter()
+            .filter(|span| span.thread as usize == thread)
+            .collect();
+        if spans.is_empty() {
+            continue;
+        }
+        ui.label(name);
+        let rows = spans.iter().map(|span| span.depth).max().unwrap_or(0) + 1;
+        let size = vec2(ui.available_width(), f32::from(rows) * ROW_HEIGHT);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        let painter = ui.painter_at(rect);
+        let x = |seconds: f64| rect.left() + (seconds / duration) as f32 * rect.width();
+        for span in spans {
+            let bar = Rect::from_min_max(
+                pos2(
+                    x(span.start),
+                    rect.top() + f32::from(span.depth) * ROW_HEIGHT,
+                ),
+                pos2(
+                    x(span.end).max(x(span.start) + 1.0),
+                    rect.top() + f32::from(span.depth + 1) * ROW_HEIGHT - 1.0,
+                ),
+            );
+            painter.rect(
+                bar,
+                2.0,
+                color(span.name),
+                Stroke::NONE,
+                egui::StrokeKind::Inside,
+            );
+            let label = format!("{} {:.2} ms", span.name, (span.end - span.start) * 1000.0);
+            if bar.width() > 40.0 {
+                painter.with_clip_rect(bar).text(
+                    bar.left_center() + vec2(4.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    &label,
+                    FontId::proportional(11.0),
+                    Color32::BLACK,
+                );
+            }
+            if response.hover_pos().is_some_and(|pos| bar.contains(pos)) {
+                response.clone().on_hover_text_at_pointer(label);
+            }
+        }
+    }
+}
+
+/// A light color of its own for every span name.
+fn color(name: &str) -> Color32 {
+    let hash = name.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte.into())
+    });
+    egui::ecolor::Hsva::new((hash % 360) as f32 / 360.0, 0.45, 0.95, 1.0).into()
+}
+
+/// Writes the recorded frames as Chrome trace events to a file in the downloads or home
+/// directory, returning its path.
+fn save() -> std::io::Result<PathBuf> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| std::io::Error::other("no downloads or home directory"))?;
+    let name = chrono::Local::now().format("fractal_clock_profile_%Y-%m-%d_%H-%M-%S");
+    let path = dir.join(format!("{name}.json"));
+
+    let recording = RECORDING
+        .lock()
+        .map_err(|_| std::io::Error::other("the profiler panicked"))?;
+    let Some(first) = recording.frames.front().map(|frame| frame.start) else {
+        return Err(std::io::Error::other("no frames were recorded"));
+    };
+    let mut events: Vec<_> = recording
+        .threads
+        .iter()
+        .enumerate()
+        .map(|(thread, name)| {
+            serde_json::json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 1,
+                "tid": thread,
+                "args": { "name": name },
+            })
+        })
+        .collect();
+    for frame in &recording.frames {
+        let offset = (frame.start - first).as_secs_f64();
+        events.extend(frame.spans.iter().map(|span| {
+            serde_json::json!({
+                "name": span.name,
+                "ph": "X",
+                "pid": 1,
+                "tid": span.thread,
+                "ts": (offset + span.start) * 1e6,
+                "dur": (span.end - span.start) * 1e6,
+            })
+        }));
+    }
+    std::fs::write(&path, serde_json::to_vec(&events)?)?;
+    Ok(path)
+}
@@ -0,0 +1,109 @@
+use crate::{
+    complications::Complications, fractal_clock::FractalClockConfig, readout::ReadoutConfig,
+    window_stacking::WindowStacking,
+};
+use egui::{Key, Ui};
+
+/// Number of profiles, one per shortcut.
+pub const PROFILE_COUNT: usize = 3;
+/// Switch to the profiles with Ctrl (Cmd on macOS).
+pub const PROFILE_KEYS: [Key; PROFILE_COUNT] = [Key::Num1, Key::Num2, Key::Num3];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProfileAction {
+    Save(usize),
+    Switch(usize),
+}
+
+/// Everything switched with a profile.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct ProfileState {
+    pub config: FractalClockConfig,
+    pub fullscreen: bool,
+    pub widget_window: bool,
+    #[serde(alias = "window_layer")]
+    pub window_stacking: WindowStacking,
+    pub fps_cap: Option<u32>,
+    pub readout: ReadoutConfig,
+    /// Complications shown around the clock.
+    pub complications: Complications,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct SessionProfile {
+    pub name: String,
+    /// Empty until the profile is first saved.
+    pub state: Option<ProfileState>,
+}
+
+// Session profile settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SessionProfiles {
+    pub profiles: [SessionProfile; PROFILE_COUNT],
+    /// Profile last switched to or saved.
+    pub active: Option<usize>,
+}
+
+impl Default for SessionProfiles {
+    fn default() -> Self {
+        Self {
+            profiles: ["Work", "Presentation", "Night"].map(|name| SessionProfile {
+                name: name.to_owned(),
+                state: None,
+            }),
+            active: None,
+        }
+    }
+}
+
+impl SessionProfiles {
+    /// Copy without the secrets of the complications kept in the profiles.
+    pub fn redacted(&self) -> Self {
+        let mut profiles = self.clone();
+        for state in profiles
+            .profiles
+            .iter_mut()
+            .filter_map(|profile| profile.state.as_mut())
+        {
+            state.complications = state.complications.redacted();
+        }
+        profiles
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<ProfileAction> {
+        let mut action = None;
+        for (index, profile) in self.profiles.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    let active = self.active == Some(index);
+                    ui.label(if active { "●" } else { "○" });
+                    ui.add(egui::TextEdit::singleline(&mut profile.name).desired_width(110.0));
+                    if ui
+                        .add_enabled(profile.state.is_some(), egui::Button::new("Switch"))
+                        .on_hover_text(format!("Ctrl+{}", index + 1))
+                        .clicked()
+                    {
+                        action = Some(ProfileAction::Switch(index));
+                    }
+                    if ui
+                        .button("Save")
+                        .on_hover_text(
+                            "Keeps the current settings, window mode, FPS cap, readout and \
+                             complications in the profile",
+                        )
+                        .clicked()
+                    {
+                        action = Some(ProfileAction::Save(index));
+                    }
+                });
+            });
+        }
+        action
+    }
+
+    #[cfg(feature = "tray")]
+    pub fn names(&self) -> impl Iterator<Item = &str> + Clone {
+        self.profiles.iter().map(|profile| profile.name.as_str())
+    }
+}
@@ -0,0 +1,268 @@
+use crate::metrics::FrameMetrics;
+use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    net::{TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How often the listener checks whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// Prometheus endpoint settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PrometheusConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Accepts scrapes from other machines instead of only this one.
+    pub public: bool,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9184,
+            public: false,
+        }
+    }
+}
+
+impl PrometheusConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, server: &PrometheusServer) {
+        ui.checkbox(&mut self.enabled, "Serve Prometheus metrics");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Port:");
+                ui.add(egui::DragValue::new(&mut self.port).range(1..=u16::MAX));
+            });
+            ui.checkbox(&mut self.public, "Reachable from the network");
+        });
+        if let Some(error) = &server.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if let Some(running) = &server.running {
+            ui.weak(format!("Serving http://{}/metrics", running.address));
+        }
+    }
+
+    fn address(&self) -> String {
+        let host = if self.public { "0.0.0.0" } else { "127.0.0.1" };
+        format!("{host}:{}", self.port)
+    }
+}
+
+/// Latest frame, read by the listener thread for every scrape.
+struct Snapshot {
+    started: Instant,
+    frames: u64,
+    last_frame: Option<SystemTime>,
+    paint_seconds: f64,
+    fps: f32,
+    line_count: usize,
+    culled_lines: usize,
+}
+
+struct Running {
+    address: String,
+    snapshot: Arc<Mutex<Snapshot>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Serves the frame metrics over HTTP for scraping by Prometheus, so dashboards can alert on
+/// a slow or hung clock. The server runs on its own thread and keeps answering if the UI hangs,
+/// with the time of the last frame showing it.
+#[derive(Default)]
+pub struct PrometheusServer {
+    running: Option<Running>,
+    error: Option<String>,
+    /// Address of the latest attempt to start, which is not retried until it changes.
+    requested: Option<String>,
+}
+
+impl PartialEq for PrometheusServer {
+    fn eq(&self, other: &Self) -> bool {
+        self.requested == other.requested && self.error == other.error
+    }
+}
+
+impl PrometheusServer {
+    /// Starts, restarts or stops the server to match `config`.
+    pub fn update(&mut self, config: &PrometheusConfig) {
+        if !config.enabled {
+            self.running = None;
+            self.error = None;
+            self.requested = None;
+            return;
+        }
+        let address = config.address();
+        if self.requested.as_ref() == Some(&address) {
+            return;
+        }
+        self.running = None;
+        self.requested = Some(address.clone());
+        match start(&address) {
+            Ok(running) => {
+                tracing::info!("Serving Prometheus metrics on {address}");
+                self.running = Some(running);
+                self.error = None;
+            }
+            Err(e) => {
+                tracing::error!("Failed to serve Prometheus metrics on {address}: {e}");
+                self.error = Some(format!("Failed to listen on {address}: {e}"));
+            }
+        }
+    }
+
+    pub fn record(&self, frame: &FrameMetrics) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        if let Ok(mut snapshot) = running.snapshot.lock() {
+            snapshot.frames += 1;
+            snapshot.last_frame = Some(SystemTime::now());
+            snapshot.paint_seconds = frame.paint_time.as_secs_f64();
+            snapshot.fps = frame.fps;
+            snapshot.line_count = frame.line_count;
+            snapshot.culled_lines = frame.culled_lines;
+        }
+    }
+}
+
+fn start(address: &str) -> std::io::Result<Running> {
+    let listener = TcpListener::bind(address)?;
+    // Polled, so the thread notices when the server is stopped
+    listener.set_nonblocking(true)?;
+    let snapshot = Arc::new(Mutex::new(Snapshot {
+        started: Instant::now(),
+        frames: 0,
+        last_frame: None,
+        paint_seconds: 0.0,
+        fps: 0.0,
+        line_count: 0,
+        culled_lines: 0,
+    }));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let thread_snapshot = snapshot.clone();
+    let thread_stop = stop.clone();
+    std::thread::spawn(move || {
+        while !thread_stop.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = respond(stream, &thread_snapshot) {
+                        tracing::debug!("Failed to answer a metrics request: {e}");
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to accept a metrics request: {e}");
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+    });
+
+    Ok(Running {
+        address: address.to_owned(),
+        snapshot,
+        stop,
+    })
+}
+
+fn respond(mut stream: TcpStream, snapshot: &Mutex<Snapshot>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, body) = if path == "/metrics" {
+        let body = snapshot
+            .lock()
+            .map(|snapshot| exposition(&snapshot))
+            .unwrap_or_default();
+        ("200 OK", body)
+    } else {
+        (
+            "404 Not Found",
+            "Metrics are served at /metrics\n".to_owned(),
+        )
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Metrics in the Prometheus text format.
+fn exposition(snapshot: &Snapshot) -> String {
+    let last_frame = snapshot
+        .last_frame
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0.0, |time| time.as_secs_f64());
+    let metrics: [(&str, &str, &str, String); 7] = [
+        (
+            "fractal_clock_uptime_seconds",
+            "gauge",
+            "Seconds since the metrics server started.",
+            snapshot.started.elapsed().as_secs_f64().to_string(),
+        ),
+        (
+            "fractal_clock_frames_total",
+            "counter",
+            "Frames painted.",
+            snapshot.frames.to_string(),
+        ),
+        (
+            "fractal_clock_last_frame_timestamp_seconds",
+            "gauge",
+            "Unix time of the last painted frame, which stops advancing when the clock hangs.",
+            last_frame.to_string(),
+        ),
+        (
+            "fractal_clock_paint_seconds",
+            "gauge",
+            "Time taken to paint the last frame.",
+            snapshot.paint_seconds.to_string(),
+        ),
+        (
+            "fractal_clock_fps",
+            "gauge",
+            "Frames per second at the last frame.",
+            snapshot.fps.to_string(),
+        ),
+        (
+            "fractal_clock_lines",
+            "gauge",
+            "Lines painted in the last frame.",
+            snapshot.line_count.to_string(),
+        ),
+        (
+            "fractal_clock_culled_lines",
+            "gauge",
+            "Lines skipped in the last frame for being outside the window.",
+            snapshot.culled_lines.to_string(),
+        ),
+    ];
+    metrics
+        .iter()
+        .map(|(name, kind, help, value)| {
+            format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+        })
+        .collect()
+}
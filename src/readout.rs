@@ -0,0 +1,122 @@
+use chrono::{
+    DateTime, Local,
+    format::{Item, StrftimeItems},
+};
+use std::fmt::Write as _;
+
+const PLACEHOLDERS: [(&str, &str); 6] = [
+    ("time", "the time in the selected time system"),
+    ("line_count", "lines painted in the last frame"),
+    ("fps", "frames per second"),
+    ("preset", "the render profile"),
+    ("depth", "levels of branches drawn"),
+    ("zoom", "magnification"),
+];
+
+// Digital readout settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ReadoutConfig {
+    /// strftime format with `{placeholder}`s, where `{{` and `}}` are literal braces.
+    pub template: String,
+}
+
+impl Default for ReadoutConfig {
+    fn default() -> Self {
+        Self {
+            template: "{time}".to_owned(),
+        }
+    }
+}
+
+impl ReadoutConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let help = PLACEHOLDERS
+            .iter()
+            .map(|(name, description)| format!("{{{name}}}: {description}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.horizontal(|ui| {
+            ui.label("Template:");
+            ui.text_edit_singleline(&mut self.template)
+                .on_hover_text(format!("strftime codes such as %H:%M:%S, and\n{help}"));
+        });
+        if let Err(e) = parse(&self.template) {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+    }
+}
+
+/// Values of the placeholders for one frame.
+pub struct Values<'a> {
+    pub time: &'a DateTime<Local>,
+    /// The time in the selected time system.
+    pub system_time: String,
+    pub line_count: usize,
+    pub fps: f32,
+    pub preset: &'a str,
+    pub depth: usize,
+    pub zoom: f32,
+}
+
+enum Piece<'a> {
+    Strftime(String),
+    Placeholder(&'a str),
+}
+
+/// Splits `template` into strftime runs and placeholders, checking both.
+fn parse(template: &str) -> Result<Vec<Piece<'_>>, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        literal.push_str(&rest[..index]);
+        let brace = &rest[index..];
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            // Doubled for strftime too, which passes braces through
+            literal.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+        if brace.starts_with('}') {
+            return Err("Unmatched }, write }} for a brace".to_owned());
+        }
+        let end = brace.find('}').ok_or("Unclosed {, write {{ for a brace")?;
+        let name = &brace[1..end];
+        if !PLACEHOLDERS.iter().any(|(known, _)| *known == name) {
+            return Err(format!("Unknown placeholder {{{name}}}"));
+        }
+        pieces.push(Piece::Strftime(std::mem::take(&mut literal)));
+        pieces.push(Piece::Placeholder(name));
+        rest = &brace[end + 1..];
+    }
+    literal.push_str(rest);
+    pieces.push(Piece::Strftime(literal));
+
+    for piece in &pieces {
+        if let Piece::Strftime(format) = piece
+            && StrftimeItems::new(format).any(|item| item == Item::Error)
+        {
+            return Err(format!("Invalid strftime code in \"{format}\""));
+        }
+    }
+    Ok(pieces)
+}
+
+/// The readout for `values`, or why `template` is invalid.
+pub fn render(template: &str, values: &Values<'_>) -> Result<String, String> {
+    let mut text = String::new();
+    for piece in parse(template)? {
+        let _ = match piece {
+            Piece::Strftime(format) => write!(text, "{}", values.time.format(&format)),
+            Piece::Placeholder("time") => write!(text, "{}", values.system_time),
+            Piece::Placeholder("line_count") => write!(text, "{}", values.line_count),
+            Piece::Placeholder("fps") => write!(text, "{:.0}", values.fps),
+            Piece::Placeholder("preset") => write!(text, "{}", values.preset),
+            Piece::Placeholder("depth") => write!(text, "{}", values.depth),
+            Piece::Placeholder("zoom") => write!(text, "{:.2}", values.zoom),
+            Piece::Placeholder(_) => Ok(()),
+        };
+    }
+    Ok(text)
+}
@@ -0,0 +1,75 @@
+// Progressive refinement settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct RefinementConfig {
+    /// Draws fewer levels while dragging, scrolling or zooming, to stay responsive.
+    pub enabled: bool,
+    /// Levels of branches drawn while interacting.
+    pub interaction_depth: usize,
+    /// Frames each level is shown before the next one is added back once input stops.
+    pub frames_per_level: u32,
+}
+
+impl Default for RefinementConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interaction_depth: 6,
+            frames_per_level: 2,
+        }
+    }
+}
+
+impl RefinementConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Fewer branches while interacting")
+            .on_hover_text("Refines back to the full depth over a few frames once input stops");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.interaction_depth, 1..=12).text("levels"));
+            ui.add(egui::Slider::new(&mut self.frames_per_level, 1..=10).text("frames per level"));
+        });
+    }
+}
+
+/// Caps the depth while the user interacts, then lifts the cap a level at a time.
+#[derive(Default, PartialEq)]
+pub struct Governor {
+    /// The current cap, until it reaches the full depth.
+    cap: Option<usize>,
+    frames: u32,
+}
+
+impl Governor {
+    /// The depth to draw this frame of `depth` levels, keeping the frames coming while refining.
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        config: &RefinementConfig,
+        depth: usize,
+    ) -> usize {
+        if !config.enabled {
+            self.cap = None;
+            return depth;
+        }
+        let interacting = ctx.dragged_id().is_some()
+            || ctx.input(|i| {
+                i.smooth_scroll_delta != egui::Vec2::ZERO
+                    || i.zoom_delta() != 1.0
+                    || i.multi_touch().is_some()
+            });
+        if interacting {
+            self.cap = Some(config.interaction_depth);
+            self.frames = 0;
+        } else if let Some(cap) = self.cap {
+            self.frames += 1;
+            if self.frames >= config.frames_per_level {
+                self.frames = 0;
+                self.cap = Some(cap + 1).filter(|&cap| cap < depth);
+            }
+        }
+        if self.cap.is_some() {
+            ctx.request_repaint();
+        }
+        self.cap.map_or(depth, |cap| cap.min(depth))
+    }
+}
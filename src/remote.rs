@@ -0,0 +1,672 @@
+use crate::{about, fractal_clock::FractalClockConfig, timeline::Parameter};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read as _, Write as _},
+    net::{IpAddr, Shutdown, TcpListener, TcpStream},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender, channel},
+    },
+    time::{Duration, Instant},
+};
+
+/// Version of the protocol, sent to the remote when it connects.
+const PROTOCOL_VERSION: u32 = 1;
+/// Service type the clock is advertised as over mDNS.
+#[cfg(feature = "network")]
+const SERVICE_TYPE: &str = "_fractal-clock._tcp.local.";
+/// How often the listener checks whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time a remote has to say hello, so connections that never do don't take up the slots.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time a welcomed remote may stay silent before it is disconnected.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Remotes connected at once at most.
+const MAX_CONNECTIONS: usize = 8;
+/// Bytes of a request at most, which no valid request comes close to.
+const MAX_REQUEST: u64 = 4096;
+/// Wrong PINs an address may send before it is locked out.
+const FREE_ATTEMPTS: u32 = 3;
+/// Lockout after the free attempts, doubled with every further wrong PIN.
+const LOCKOUT: Duration = Duration::from_secs(30);
+const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+/// Wait before answering a wrong PIN, which slows guessing over parallel connections.
+const WRONG_PIN_DELAY: Duration = Duration::from_secs(1);
+
+// Remote control settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Name the remotes list the clock under.
+    pub name: String,
+    /// Asked of every remote when not empty.
+    pub pin: String,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9185,
+            name: "Fractal Clock".to_owned(),
+            pin: String::new(),
+        }
+    }
+}
+
+impl RemoteConfig {
+    /// Copy without the PIN.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        about::redact(&mut config.pin);
+        config
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, server: &RemoteServer) {
+        ui.checkbox(&mut self.enabled, "Allow remote control")
+            .on_hover_text("Lets companion apps on the local network adjust the clock");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut self.name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Port:");
+                ui.add(egui::DragValue::new(&mut self.port).range(1..=u16::MAX));
+            });
+            ui.horizontal(|ui| {
+                ui.label("PIN:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.pin)
+                        .password(true)
+                        .hint_text("none")
+                        .desired_width(80.0),
+                );
+            });
+            ui.weak("The PIN is sent unencrypted, so only trust it on your own network");
+        });
+        if let Some(error) = &server.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if let Some(running) = &server.running {
+            let remotes = running.connections.lock().map_or(0, |c| c.len());
+            ui.weak(format!(
+                "Listening on port {}, {remotes} connected",
+                self.port
+            ));
+            #[cfg(not(feature = "network"))]
+            ui.weak("Built without the network feature, so remotes need the address");
+        }
+    }
+}
+
+/// A change asked for by a remote, applied on the UI thread.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Change {
+    Parameter(Parameter, f32),
+    RainbowMode(bool),
+}
+
+impl Change {
+    pub fn apply(self, config: &mut FractalClockConfig) {
+        match self {
+            Self::Parameter(parameter, value) => parameter.set(config, value),
+            Self::RainbowMode(enabled) => config.set_rainbow_mode(enabled),
+        }
+    }
+}
+
+/// A line of JSON sent by a remote.
+#[derive(serde::Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Request {
+    /// First request of every connection.
+    Hello {
+        #[serde(default)]
+        pin: String,
+    },
+    Get,
+    Set {
+        parameter: Parameter,
+        value: f32,
+    },
+    Rainbow {
+        enabled: bool,
+    },
+}
+
+/// A line of JSON answering a request.
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Response<'a> {
+    Welcome {
+        version: u32,
+        name: &'a str,
+        parameters: Vec<ParameterInfo>,
+    },
+    State(State),
+    Ok,
+    Error {
+        message: String,
+    },
+}
+
+#[derive(serde::Serialize)]
+struct ParameterInfo {
+    parameter: Parameter,
+    label: &'static str,
+    min: f32,
+    max: f32,
+}
+
+#[derive(serde::Serialize, Clone, Copy)]
+struct ParameterValue {
+    parameter: Parameter,
+    value: f32,
+}
+
+/// Latest settings, read by the connection threads for every `get`.
+#[derive(serde::Serialize, Clone)]
+struct State {
+    parameters: Vec<ParameterValue>,
+    rainbow_mode: bool,
+}
+
+impl State {
+    fn new(config: &FractalClockConfig) -> Self {
+        Self {
+            parameters: Parameter::ALL
+                .into_iter()
+                .map(|parameter| ParameterValue {
+                    parameter,
+                    value: parameter.get(config),
+                })
+                .collect(),
+            rainbow_mode: config.rainbow_mode(),
+        }
+    }
+}
+
+/// Wrong PINs by the address of the remote, which is locked out for longer and longer after a
+/// few of them.
+#[derive(Default)]
+struct Attempts {
+    /// Number of wrong PINs in a row and the time of the last one.
+    failures: HashMap<IpAddr, (u32, Instant)>,
+}
+
+impl Attempts {
+    /// Time left before `address` may try another PIN.
+    fn locked_out(&self, address: IpAddr) -> Option<Duration> {
+        let (failures, last) = self.failures.get(&address)?;
+        lockout(*failures)?.checked_sub(last.elapsed())
+    }
+
+    fn fail(&mut self, address: IpAddr) {
+        self.prune(Instant::now());
+        let (failures, last) = self.failures.entry(address).or_insert((0, Instant::now()));
+        *failures += 1;
+        *last = Instant::now();
+    }
+
+    fn succeed(&mut self, address: IpAddr) {
+        self.failures.remove(&address);
+    }
+
+    /// Forgets addresses without a wrong PIN in the longest lockout, which has expired by `now`.
+    fn prune(&mut self, now: Instant) {
+        self.failures
+            .retain(|_, (_, last)| now.duration_since(*last) < MAX_LOCKOUT);
+    }
+}
+
+/// Lockout after `failures` wrong PINs in a row.
+fn lockout(failures: u32) -> Option<Duration> {
+    let doublings = failures.checked_sub(FREE_ATTEMPTS)?;
+    Some(
+        LOCKOUT
+            .saturating_mul(2u32.saturating_pow(doublings))
+            .min(MAX_LOCKOUT),
+    )
+}
+
+/// What the connection threads share with the server.
+struct Shared {
+    name: String,
+    pin: String,
+    attempts: Mutex<Attempts>,
+    state: Mutex<State>,
+    changes: Sender<Change>,
+    ctx: egui::Context,
+}
+
+struct Running {
+    shared: Arc<Shared>,
+    changes: Receiver<Change>,
+    /// Open connections, shut down with the server.
+    connections: Arc<Mutex<Vec<TcpStream>>>,
+    stop: Arc<AtomicBool>,
+    #[cfg(feature = "network")]
+    mdns: Option<mdns_sd::ServiceDaemon>,
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Ok(connections) = self.connections.lock() {
+            for stream in connections.iter() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+        #[cfg(feature = "network")]
+        if let Some(mdns) = &self.mdns {
+            let _ = mdns.shutdown();
+        }
+    }
+}
+
+/// Lets companion apps on the local network find the clock over mDNS and adjust its settings
+/// live, with newline-delimited JSON over TCP. Every connection is served on its own thread,
+/// sending the changes to the UI thread to apply.
+#[derive(Default)]
+pub struct RemoteServer {
+    running: Option<Running>,
+    error: Option<String>,
+    /// Settings of the latest attempt to start, which is not retried until they change.
+    requested: Option<RemoteConfig>,
+}
+
+impl PartialEq for RemoteServer {
+    fn eq(&self, other: &Self) -> bool {
+        self.requested == other.requested && self.error == other.error
+    }
+}
+
+impl RemoteServer {
+    /// Starts, restarts or stops the server to match `config`.
+    pub fn update(&mut self, ctx: &egui::Context, config: &RemoteConfig) {
+        if !config.enabled {
+            self.running = None;
+            self.error = None;
+            self.requested = None;
+            return;
+        }
+        if self.requested.as_ref() == Some(config) {
+            return;
+        }
+        self.running = None;
+        self.requested = Some(config.clone());
+        match start(ctx, config) {
+            Ok(running) => {
+                tracing::info!("Listening for remotes on port {}", config.port);
+                self.running = Some(running);
+                self.error = None;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for remotes on port {}: {e}", config.port);
+                self.error = Some(format!("Failed to listen on port {}: {e}", config.port));
+            }
+        }
+    }
+
+    /// Changes asked for since the last call.
+    pub fn changes(&self) -> Vec<Change> {
+        self.running
+            .as_ref()
+            .map(|running| running.changes.try_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Makes `config` what the remotes get.
+    pub fn publish(&self, config: &FractalClockConfig) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        if let Ok(mut state) = running.shared.state.lock() {
+            *state = State::new(config);
+        }
+    }
+}
+
+fn start(ctx: &egui::Context, config: &RemoteConfig) -> std::io::Result<Running> {
+    let listener = TcpListener::bind(("0.0.0.0", config.port))?;
+    // Polled, so the thread notices when the server is stopped
+    listener.set_nonblocking(true)?;
+    let (sender, changes) = channel();
+    let shared = Arc::new(Shared {
+        name: config.name.trim().to_owned(),
+        pin: config.pin.clone(),
+        attempts: Mutex::new(Attempts::default()),
+        state: Mutex::new(State {
+            parameters: Vec::new(),
+            rainbow_mode: false,
+        }),
+        changes: sender,
+        ctx: ctx.clone(),
+    });
+    let connections = Arc::new(Mutex::new(Vec::new()));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let thread_shared = shared.clone();
+    let thread_connections = connections.clone();
+    let thread_stop = stop.clone();
+    std::thread::spawn(move || {
+        while !thread_stop.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, address)) => {
+                    if let Err(e) = accept(stream, &thread_shared, &thread_connections) {
+                        tracing::debug!("Failed to accept remote {address}: {e}");
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to accept a remote: {e}");
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+    });
+
+    Ok(Running {
+        #[cfg(feature = "network")]
+        mdns: advertise(config),
+        shared,
+        changes,
+        connections,
+        stop,
+    })
+}
+
+/// Serves `stream` on its own thread until the remote disconnects.
+fn accept(
+    stream: TcpStream,
+    shared: &Arc<Shared>,
+    connections: &Arc<Mutex<Vec<TcpStream>>>,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    stream.set_read_timeout(Some(HELLO_TIMEOUT))?;
+    let address = stream.peer_addr()?;
+    {
+        let Ok(mut connections) = connections.lock() else {
+            return Ok(());
+        };
+        if connections.len() >= MAX_CONNECTIONS {
+            return stream.shutdown(Shutdown::Both);
+        }
+        connections.push(stream.try_clone()?);
+    }
+    tracing::info!("Remote {address} connected");
+    shared.ctx.request_repaint();
+
+    let shared = shared.clone();
+    let connections = connections.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = serve(&stream, address.ip(), &shared) {
+            tracing::debug!("Remote {address} failed: {e}");
+        }
+        tracing::info!("Remote {address} disconnected");
+        if let Ok(mut connections) = connections.lock() {
+            connections.retain(|other| other.peer_addr().is_ok_and(|other| other != address));
+        }
+        shared.ctx.request_repaint();
+    });
+    Ok(())
+}
+
+fn serve(stream: &TcpStream, address: IpAddr, shared: &Shared) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut writer = stream;
+    let mut welcomed = false;
+    loop {
+        let request = match read_request(&mut reader)? {
+            Incoming::Closed => return Ok(()),
+            Incoming::Blank => continue,
+            Incoming::TooLong => return respond(&mut writer, &error("Request too long")),
+            Incoming::Request(request) => request,
+        };
+        let response = match request {
+            Err(e) => error(e),
+            Ok(Request::Hello { pin }) => {
+                let Ok(mut attempts) = shared.attempts.lock() else {
+                    return Ok(());
+                };
+                if let Some(left) = attempts.locked_out(address) {
+                    let message =
+                        format!("Too many wrong PINs, try again in {} s", left.as_secs() + 1);
+                    return respond(&mut writer, &error(message));
+                }
+                if pin != shared.pin {
+                    attempts.fail(address);
+                    drop(attempts);
+                    tracing::warn!("Remote {address} sent a wrong PIN");
+                    std::thread::sleep(WRONG_PIN_DELAY);
+                    return respond(&mut writer, &error("Wrong PIN"));
+                }
+                attempts.succeed(address);
+                drop(attempts);
+                if !welcomed {
+                    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+                }
+                welcomed = true;
+                Response::Welcome {
+                    version: PROTOCOL_VERSION,
+                    name: &shared.name,
+                    parameters: Parameter::ALL
+                        .into_iter()
+                        .map(|parameter| ParameterInfo {
+                            parameter,
+                            label: parameter.label(),
+                            min: *parameter.range().start(),
+                            max: *parameter.range().end(),
+                        })
+                        .collect(),
+                }
+            }
+            Ok(_) if !welcomed => error("Say hello first"),
+            Ok(Request::Get) => match shared.state.lock() {
+                Ok(state) => Response::State(state.clone()),
+                Err(_) => error("The clock stopped"),
+            },
+            Ok(Request::Set { parameter, value }) => {
+                send(shared, Change::Parameter(parameter, value))
+            }
+            Ok(Request::Rainbow { enabled }) => send(shared, Change::RainbowMode(enabled)),
+        };
+        respond(&mut writer, &response)?;
+    }
+}
+
+enum Incoming {
+    Closed,
+    Blank,
+    TooLong,
+    Request(Result<Request, String>),
+}
+
+/// Reads the next line of `reader`, up to `MAX_REQUEST` bytes.
+fn read_request(reader: &mut impl BufRead) -> std::io::Result<Incoming> {
+    let mut line = String::new();
+    if reader.take(MAX_REQUEST).read_line(&mut line)? == 0 {
+        return Ok(Incoming::Closed);
+    }
+    if !line.ends_with('\n') && line.len() as u64 >= MAX_REQUEST {
+        return Ok(Incoming::TooLong);
+    }
+    if line.trim().is_empty() {
+        return Ok(Incoming::Blank);
+    }
+    Ok(Incoming::Request(
+        serde_json::from_str(&line).map_err(|e| format!("Invalid request: {e}")),
+    ))
+}
+
+fn send(shared: &Shared, change: Change) -> Response<'static> {
+    if shared.changes.send(change).is_ok() {
+        shared.ctx.request_repaint();
+        Response::Ok
+    } else {
+        error("The clock stopped listening")
+    }
+}
+
+fn error(message: impl Into<String>) -> Response<'static> {
+    Response::Error {
+        message: message.into(),
+    }
+}
+
+fn respond(writer: &mut &TcpStream, response: &Response) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(response).map_err(std::io::Error::other)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
+
+/// Advertises the server on the local network, which the remote works without by entering the
+/// address.
+#[cfg(feature = "network")]
+fn advertise(config: &RemoteConfig) -> Option<mdns_sd::ServiceDaemon> {
+    let name = config.name.trim();
+    let host: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let version = PROTOCOL_VERSION.to_string();
+    let pin = if config.pin.is_empty() { "no" } else { "yes" };
+    let properties = [("version", version.as_str()), ("pin", pin)];
+    let result = mdns_sd::ServiceDaemon::new().and_then(|daemon| {
+        let service = mdns_sd::ServiceInfo::new(
+            SERVICE_TYPE,
+            name,
+            &format!("{host}.local."),
+            (),
+            config.port,
+            &properties[..],
+        )?
+        .enable_addr_auto();
+        daemon.register(service)?;
+        Ok(daemon)
+    });
+    match result {
+        Ok(daemon) => Some(daemon),
+        Err(e) => {
+            tracing::warn!("Not advertising the remote control over mDNS: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read(input: &str) -> Incoming {
+        read_request(&mut Cursor::new(input)).unwrap()
+    }
+
+    fn request(input: &str) -> Result<Request, String> {
+        match read(input) {
+            Incoming::Request(request) => request,
+            _ => panic!("not a request: {input}"),
+        }
+    }
+
+    #[test]
+    fn reads_requests() {
+        assert!(matches!(
+            request("{\"command\":\"hello\",\"pin\":\"1234\"}\n"),
+            Ok(Request::Hello { pin }) if pin == "1234"
+        ));
+        assert!(matches!(
+            request("{\"command\":\"hello\"}\n"),
+            Ok(Request::Hello { pin }) if pin.is_empty()
+        ));
+        assert!(matches!(request("{\"command\":\"get\"}"), Ok(Request::Get)));
+        assert!(matches!(
+            request("{\"command\":\"set\",\"parameter\":\"Depth\",\"value\":18}\n"),
+            Ok(Request::Set { parameter: Parameter::Depth, value }) if value == 18.0
+        ));
+        assert!(matches!(
+            request("{\"command\":\"rainbow\",\"enabled\":false}\n"),
+            Ok(Request::Rainbow { enabled: false })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        for input in [
+            "garbage\n",
+            "{\"command\":\"reboot\"}\n",
+            "{\"command\":\"set\",\"parameter\":\"depth\",\"value\":18}\n",
+            "{\"command\":\"set\",\"parameter\":\"Depth\",\"value\":\"18\"}\n",
+            "{\"command\":\"set\",\"parameter\":\"Depth\"}\n",
+            "{\"command\":\"rainbow\"}\n",
+        ] {
+            assert!(request(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn limits_request_length() {
+        let padding = " ".repeat(MAX_REQUEST as usize);
+        assert!(matches!(
+            read(&format!("{{\"command\":\"get\"}}{padding}\n")),
+            Incoming::TooLong
+        ));
+        let fits = " ".repeat(MAX_REQUEST as usize - 20);
+        assert!(matches!(
+            read(&format!("{{\"command\":\"get\"}}{fits}\n")),
+            Incoming::Request(Ok(Request::Get))
+        ));
+        assert!(matches!(read(""), Incoming::Closed));
+        assert!(matches!(read("  \n"), Incoming::Blank));
+    }
+
+    #[test]
+    fn locks_out_after_wrong_pins() {
+        let address = IpAddr::from([192, 168, 1, 2]);
+        let other = IpAddr::from([192, 168, 1, 3]);
+        let mut attempts = Attempts::default();
+        for _ in 0..FREE_ATTEMPTS {
+            assert!(attempts.locked_out(address).is_none());
+            attempts.fail(address);
+        }
+        assert!(attempts.locked_out(address).is_some());
+        assert!(attempts.locked_out(other).is_none());
+        attempts.succeed(address);
+        assert!(attempts.locked_out(address).is_none());
+    }
+
+    #[test]
+    fn forgets_old_wrong_pins() {
+        let address = IpAddr::from([192, 168, 1, 2]);
+        let other = IpAddr::from([192, 168, 1, 3]);
+        let mut attempts = Attempts::default();
+        attempts.fail(address);
+        let later = Instant::now() + MAX_LOCKOUT;
+        attempts.failures.insert(other, (1, later));
+        attempts.prune(later);
+        assert!(!attempts.failures.contains_key(&address));
+        assert!(attempts.failures.contains_key(&other));
+    }
+
+    #[test]
+    fn lockout_doubles_up_to_the_limit() {
+        assert_eq!(lockout(FREE_ATTEMPTS - 1), None);
+        assert_eq!(lockout(FREE_ATTEMPTS), Some(LOCKOUT));
+        assert_eq!(lockout(FREE_ATTEMPTS + 1), Some(LOCKOUT * 2));
+        assert_eq!(lockout(u32::MAX), Some(MAX_LOCKOUT));
+    }
+}
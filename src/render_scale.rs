@@ -0,0 +1,493 @@
+use crate::{
+    effects::{self, Chain, Effect, SharedErrors},
+    profiler,
+};
+use eframe::{
+    egui_glow::{self, ShaderVersion},
+    glow::{self, HasContext as _},
+};
+use egui::{
+    Id, Mesh, PaintCallback, PaintCallbackInfo, Painter, Shape,
+    epaint::{ClippedShape, Primitive, Tessellator, Vertex},
+};
+use std::{
+    mem::{offset_of, size_of},
+    ops::RangeInclusive,
+    sync::{Arc, Mutex},
+};
+
+pub const RENDER_SCALE_RANGE: RangeInclusive<f32> = 0.5..=4.0;
+
+/// How overlapping lines combine.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum BlendMode {
+    /// Lines are painted over each other.
+    Normal,
+    /// Light adds up where lines overlap, saturating towards white in the dense center.
+    Additive,
+    /// Lines darken each other and the background like layers of tinted glass.
+    Multiply,
+}
+
+impl BlendMode {
+    pub const ALL: [Self; 3] = [Self::Normal, Self::Additive, Self::Multiply];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Additive => "Additive",
+            Self::Multiply => "Multiply",
+        }
+    }
+}
+
+const LINE_VERTEX_SHADER: &str = r#"
+uniform vec2 u_screen_size;
+uniform vec2 u_origin;
+I vec2 a_pos;
+I vec4 a_srgba;
+O vec4 v_rgba;
+
+void main() {
+    vec2 pos = a_pos - u_origin;
+    gl_Position = vec4(2.0 * pos.x / u_screen_size.x - 1.0, 1.0 - 2.0 * pos.y / u_screen_size.y, 0.0, 1.0);
+    v_rgba = a_srgba;
+}
+"#;
+
+const LINE_FRAGMENT_SHADER: &str = r#"
+I vec4 v_rgba;
+
+void main() {
+    FRAG_COLOR = v_rgba;
+}
+"#;
+
+const COMPOSITE_VERTEX_SHADER: &str = r#"
+O vec2 v_uv;
+
+void main() {
+    vec2 corner = vec2(float(gl_VertexID % 2), float(gl_VertexID / 2));
+    v_uv = corner;
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+const COMPOSITE_FRAGMENT_SHADER: &str = r#"
+uniform sampler2D u_texture;
+I vec2 v_uv;
+
+void main() {
+    FRAG_COLOR = TEXTURE(u_texture, v_uv);
+}
+"#;
+
+/// GL objects shared between frames, kept in the egui context so they outlive the clock settings.
+type SharedTarget = Arc<Mutex<Option<Target>>>;
+
+/// Paints `shapes` into an offscreen texture at `scale` times the window resolution, which is then
+/// scaled to the window, supersampling above 1 and rendering fewer pixels below. The lines are
+/// combined with each other and the window by `blend`, after the texture is drawn through
+/// `effects` with `time` in seconds.
+pub fn paint_scaled(
+    painter: &Painter,
+    shapes: impl Iterator<Item = Shape>,
+    scale: f32,
+    blend: BlendMode,
+    effects: Vec<Arc<Effect>>,
+    time: f32,
+) {
+    let ctx = painter.ctx();
+    let rect = painter.clip_rect();
+    let shapes = shapes
+        .map(|shape| ClippedShape {
+            clip_rect: rect,
+            shape,
+        })
+        .collect();
+    // Tessellated here rather than by `Context::tessellate`, which expects fonts at the scale
+    let _scope = profiler::scope("tessellate");
+    let (font_tex_size, prepared_discs) = ctx.fonts(|fonts| {
+        let atlas = fonts.texture_atlas();
+        let atlas = atlas.lock();
+        (atlas.size(), atlas.prepared_discs())
+    });
+    let meshes: Vec<Mesh> = Tessellator::new(
+        ctx.pixels_per_point() * scale,
+        ctx.tessellation_options(|options| *options),
+        font_tex_size,
+        prepared_discs,
+    )
+    .tessellate_shapes(shapes)
+    .into_iter()
+    .filter_map(|primitive| match primitive.primitive {
+        Primitive::Mesh(mesh) => Some(mesh),
+        Primitive::Callback(_) => None,
+    })
+    .collect();
+
+    let target = ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<SharedTarget>(Id::new("render_scale"))
+            .clone()
+    });
+    let errors = effects::errors(ctx);
+    let callback = egui_glow::CallbackFn::new(move |info, painter| {
+        let Ok(mut target) = target.lock() else {
+            return;
+        };
+        let gl = painter.gl();
+        if target.is_none() {
+            match unsafe { Target::new(gl) } {
+                Ok(created) => *target = Some(created),
+                Err(e) => {
+                    tracing::error!("Render scale unavailable: {e}");
+                    return;
+                }
+            }
+        }
+        if let Some(target) = target.as_mut() {
+            let max_side = painter.max_texture_side() as f32;
+            let effects = (!effects.is_empty()).then_some((effects.as_slice(), &errors, time));
+            unsafe {
+                target.paint(gl, &info, &meshes, scale, max_side, blend);
+                target.composite(gl, &info, blend, effects);
+            }
+        }
+    });
+
+    painter.add(PaintCallback {
+        rect,
+        callback: Arc::new(callback),
+    });
+}
+
+struct Target {
+    framebuffer: glow::Framebuffer,
+    texture: glow::Texture,
+    size: [i32; 2],
+    line_program: glow::Program,
+    composite_program: glow::Program,
+    vertex_array: glow::VertexArray,
+    vertex_buffer: glow::Buffer,
+    index_buffer: glow::Buffer,
+    /// Created when effects are first applied.
+    effects: Option<Chain>,
+}
+
+impl Target {
+    unsafe fn new(gl: &glow::Context) -> Result<Self, String> {
+        let version = ShaderVersion::get(gl);
+        // The composite pass generates its quad from `gl_VertexID`
+        if !version.is_new_shader_interface() {
+            return Err("OpenGL 3.0 or OpenGL ES 3.0 is required".to_owned());
+        }
+        unsafe {
+            let line_program = link_program(gl, version, LINE_VERTEX_SHADER, LINE_FRAGMENT_SHADER)?;
+            let composite_program = link_program(
+                gl,
+                version,
+                COMPOSITE_VERTEX_SHADER,
+                COMPOSITE_FRAGMENT_SHADER,
+            )?;
+
+            let vertex_array = gl.create_vertex_array()?;
+            let vertex_buffer = gl.create_buffer()?;
+            let index_buffer = gl.create_buffer()?;
+
+            gl.bind_vertex_array(Some(vertex_array));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
+            let stride = size_of::<Vertex>() as i32;
+            if let Some(pos) = gl.get_attrib_location(line_program, "a_pos") {
+                gl.enable_vertex_attrib_array(pos);
+                gl.vertex_attrib_pointer_f32(
+                    pos,
+                    2,
+                    glow::FLOAT,
+                    false,
+                    stride,
+                    offset_of!(Vertex, pos) as i32,
+                );
+            }
+            if let Some(color) = gl.get_attrib_location(line_program, "a_srgba") {
+                gl.enable_vertex_attrib_array(color);
+                gl.vertex_attrib_pointer_f32(
+                    color,
+                    4,
+                    glow::UNSIGNED_BYTE,
+                    true,
+                    stride,
+                    offset_of!(Vertex, color) as i32,
+                );
+            }
+            gl.bind_vertex_array(None);
+
+            let texture = gl.create_texture()?;
+            let framebuffer = gl.create_framebuffer()?;
+
+            Ok(Self {
+                framebuffer,
+                texture,
+                size: [0, 0],
+                line_program,
+                composite_program,
+                vertex_array,
+                vertex_buffer,
+                index_buffer,
+                effects: None,
+            })
+        }
+    }
+
+    unsafe fn resize(&mut self, gl: &glow::Context, size: [i32; 2]) {
+        if self.size == size {
+            return;
+        }
+        self.size = size;
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                size[0],
+                size[1],
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(None),
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                glow::LINEAR_MIPMAP_LINEAR as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MAG_FILTER,
+                glow::LINEAR as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(self.texture),
+                0,
+            );
+        }
+    }
+
+    unsafe fn paint(
+        &mut self,
+        gl: &glow::Context,
+        info: &PaintCallbackInfo,
+        meshes: &[Mesh],
+        scale: f32,
+        max_side: f32,
+        blend: BlendMode,
+    ) {
+        let viewport = info.viewport_in_pixels();
+        let size = [viewport.width_px, viewport.height_px]
+            .map(|side| (side as f32 * scale).round().clamp(1.0, max_side) as i32);
+        let rect = info.viewport;
+
+        unsafe {
+            self.resize(gl, size);
+
+            // Draw the lines into the texture
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.framebuffer));
+            gl.viewport(0, 0, size[0], size[1]);
+            gl.disable(glow::SCISSOR_TEST);
+            // Multiplying starts from white, which leaves the background unchanged
+            if blend == BlendMode::Multiply {
+                gl.clear_color(1.0, 1.0, 1.0, 1.0);
+            } else {
+                gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            }
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            // The colors are premultiplied by alpha
+            match blend {
+                BlendMode::Normal => gl.blend_func_separate(
+                    glow::ONE,
+                    glow::ONE_MINUS_SRC_ALPHA,
+                    glow::ONE_MINUS_DST_ALPHA,
+                    glow::ONE,
+                ),
+                BlendMode::Additive => gl.blend_func(glow::ONE, glow::ONE),
+                BlendMode::Multiply => gl.blend_func_separate(
+                    glow::DST_COLOR,
+                    glow::ONE_MINUS_SRC_ALPHA,
+                    glow::ZERO,
+                    glow::ONE,
+                ),
+            }
+
+            gl.use_program(Some(self.line_program));
+            let screen_size = gl.get_uniform_location(self.line_program, "u_screen_size");
+            gl.uniform_2_f32(screen_size.as_ref(), rect.width(), rect.height());
+            let origin = gl.get_uniform_location(self.line_program, "u_origin");
+            gl.uniform_2_f32(origin.as_ref(), rect.left(), rect.top());
+
+            gl.bind_vertex_array(Some(self.vertex_array));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vertex_buffer));
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.index_buffer));
+            for mesh in meshes {
+                gl.buffer_data_u8_slice(
+                    glow::ARRAY_BUFFER,
+                    bytemuck::cast_slice(&mesh.vertices),
+                    glow::STREAM_DRAW,
+                );
+                gl.buffer_data_u8_slice(
+                    glow::ELEMENT_ARRAY_BUFFER,
+                    bytemuck::cast_slice(&mesh.indices),
+                    glow::STREAM_DRAW,
+                );
+                gl.draw_elements(
+                    glow::TRIANGLES,
+                    mesh.indices.len() as i32,
+                    glow::UNSIGNED_INT,
+                    0,
+                );
+            }
+
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            gl.generate_mipmap(glow::TEXTURE_2D);
+
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    /// Composites the texture over the window, through `effects` at `time` if any compile.
+    unsafe fn composite(
+        &mut self,
+        gl: &glow::Context,
+        info: &PaintCallbackInfo,
+        blend: BlendMode,
+        effects: Option<(&[Arc<Effect>], &SharedErrors, f32)>,
+    ) {
+        let viewport = info.viewport_in_pixels();
+        unsafe {
+            let to_screen = || {
+                gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                gl.viewport(
+                    viewport.left_px,
+                    viewport.from_bottom_px,
+                    viewport.width_px,
+                    viewport.height_px,
+                );
+                if blend == BlendMode::Multiply {
+                    gl.blend_func_separate(glow::DST_COLOR, glow::ZERO, glow::ZERO, glow::ONE);
+                } else {
+                    gl.blend_func_separate(
+                        glow::ONE,
+                        glow::ONE_MINUS_SRC_ALPHA,
+                        glow::ONE_MINUS_DST_ALPHA,
+                        glow::ONE,
+                    );
+                }
+            };
+            if let Some((effects, errors, time)) = effects {
+                if self.effects.is_none() {
+                    match Chain::new(gl, errors.clone()) {
+                        Ok(chain) => self.effects = Some(chain),
+                        Err(e) => tracing::error!("Shader effects unavailable: {e}"),
+                    }
+                }
+                if let Some(chain) = &mut self.effects
+                    && chain.apply(gl, effects, self.texture, self.size, time, to_screen)
+                {
+                    gl.bind_texture(glow::TEXTURE_2D, None);
+                    return;
+                }
+            }
+            to_screen();
+            gl.use_program(Some(self.composite_program));
+            gl.bind_vertex_array(Some(self.vertex_array));
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            let sampler = gl.get_uniform_location(self.composite_program, "u_texture");
+            gl.uniform_1_i32(sampler.as_ref(), 0);
+            gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+
+            gl.bind_vertex_array(None);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+    }
+}
+
+pub unsafe fn link_program(
+    gl: &glow::Context,
+    version: ShaderVersion,
+    vertex_source: &str,
+    fragment_source: &str,
+) -> Result<glow::Program, String> {
+    let preamble = "#define I in\n#define O out\n";
+    let fragment_preamble = "#define I in\n#define TEXTURE texture\nout vec4 out_color;\n#define FRAG_COLOR out_color\n";
+    let precision = if version.is_embedded() {
+        "precision mediump float;\n"
+    } else {
+        ""
+    };
+
+    unsafe {
+        let program = gl.create_program()?;
+        let shaders = [
+            (
+                glow::VERTEX_SHADER,
+                format!(
+                    "{}\n{preamble}{vertex_source}",
+                    version.version_declaration()
+                ),
+            ),
+            (
+                glow::FRAGMENT_SHADER,
+                format!(
+                    "{}\n{precision}{fragment_preamble}{fragment_source}",
+                    version.version_declaration()
+                ),
+            ),
+        ]
+        .map(|(kind, source)| {
+            let shader = gl.create_shader(kind)?;
+            gl.shader_source(shader, &source);
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                return Err(gl.get_shader_info_log(shader));
+            }
+            gl.attach_shader(program, shader);
+            Ok(shader)
+        });
+
+        let mut result = Ok(program);
+        for shader in &shaders {
+            if let Err(e) = shader {
+                result = Err(e.clone());
+            }
+        }
+        if result.is_ok() {
+            gl.link_program(program);
+            if !gl.get_program_link_status(program) {
+                result = Err(gl.get_program_info_log(program));
+            }
+        }
+        for shader in shaders.into_iter().flatten() {
+            gl.detach_shader(program, shader);
+            gl.delete_shader(shader);
+        }
+        if result.is_err() {
+            gl.delete_program(program);
+        }
+        result
+    }
+}
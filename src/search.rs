@@ -0,0 +1,215 @@
+use chrono::NaiveTime;
+use egui::{ColorImage, Rect, Shape, TextureHandle, TextureOptions};
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+
+/// Side of the grid the spread of the branches is measured on.
+const SPREAD_CELLS: usize = 32;
+pub const THUMBNAIL_SIZE: u32 = 96;
+
+/// A time found by the search, with its score and thumbnail.
+pub struct Candidate {
+    pub time: NaiveTime,
+    pub score: f32,
+    pub thumbnail: ColorImage,
+}
+
+pub enum SearchMessage {
+    Progress(f32),
+    Done(Vec<Candidate>),
+}
+
+pub enum SearchAction {
+    Start,
+    Jump(NaiveTime),
+    Bookmark(NaiveTime),
+}
+
+// Beautiful time search settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Minutes between the times scored.
+    pub step_minutes: u32,
+    /// Number of best times kept.
+    pub count: usize,
+    /// Minutes the kept times are apart at least, so they are not all the same shape.
+    pub separation_minutes: u32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            step_minutes: 1,
+            count: 6,
+            separation_minutes: 15,
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, search: &Search) -> Option<SearchAction> {
+        let mut action = None;
+        ui.add_enabled_ui(search.progress.is_none(), |ui| {
+            ui.add(egui::Slider::new(&mut self.step_minutes, 1..=60).text("step minutes"));
+            ui.add(egui::Slider::new(&mut self.count, 1..=24).text("results"));
+            ui.add(egui::Slider::new(&mut self.separation_minutes, 0..=120).text("minutes apart"));
+            if ui
+                .button("🔍 Find beautiful times")
+                .on_hover_text("Scores the day by the symmetry and spread of the branches")
+                .clicked()
+            {
+                action = Some(SearchAction::Start);
+            }
+        });
+        if let Some(progress) = search.progress {
+            ui.add(egui::ProgressBar::new(progress).show_percentage());
+        }
+
+        egui::Grid::new("search_results").show(ui, |ui| {
+            for (index, (time, score, texture)) in search.results.iter().enumerate() {
+                ui.vertical(|ui| {
+                    let image = egui::Image::new(texture)
+                        .fit_to_exact_size(egui::Vec2::splat(THUMBNAIL_SIZE as f32 * 0.75))
+                        .sense(egui::Sense::click());
+                    if ui
+                        .add(image)
+                        .on_hover_text(format!("Score {score:.2}"))
+                        .clicked()
+                    {
+                        action = Some(SearchAction::Jump(*time));
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(time.format("%H:%M").to_string());
+                        if ui.small_button("🔖").on_hover_text("Bookmark").clicked() {
+                            action = Some(SearchAction::Bookmark(*time));
+                        }
+                    });
+                });
+                if index % 3 == 2 {
+                    ui.end_row();
+                }
+            }
+        });
+        action
+    }
+}
+
+/// Searches the day for the times the clock looks best on a background thread, showing the best
+/// ones as thumbnails.
+#[derive(Default)]
+pub struct Search {
+    receiver: Option<Receiver<SearchMessage>>,
+    progress: Option<f32>,
+    results: Vec<(NaiveTime, f32, TextureHandle)>,
+}
+
+impl PartialEq for Search {
+    fn eq(&self, other: &Self) -> bool {
+        self.progress == other.progress && self.results.len() == other.results.len()
+    }
+}
+
+impl Search {
+    /// Runs `search` on a background thread, which reports its progress through the sender.
+    pub fn start(
+        &mut self,
+        ctx: &egui::Context,
+        search: impl FnOnce(&dyn Fn(SearchMessage)) + Send + 'static,
+    ) {
+        let (sender, receiver) = channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            search(&|message| {
+                let _ = sender.send(message);
+                ctx.request_repaint();
+            });
+        });
+        self.receiver = Some(receiver);
+        self.progress = Some(0.0);
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(SearchMessage::Progress(progress)) => self.progress = Some(progress),
+                Ok(SearchMessage::Done(candidates)) => {
+                    self.results = candidates
+                        .into_iter()
+                        .map(|candidate| {
+                            let name = format!("search_{}", candidate.time);
+                            let texture =
+                                ctx.load_texture(name, candidate.thumbnail, TextureOptions::LINEAR);
+                            (candidate.time, candidate.score, texture)
+                        })
+                        .collect();
+                    self.stop();
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    tracing::error!("The search for beautiful times stopped unexpectedly");
+                    self.stop();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        self.receiver = None;
+        self.progress = None;
+    }
+}
+
+/// Keeps the `count` best scored times at least `separation` seconds apart, best first.
+pub fn best(
+    mut scored: Vec<(NaiveTime, f32)>,
+    count: usize,
+    separation: i64,
+) -> Vec<(NaiveTime, f32)> {
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut kept: Vec<(NaiveTime, f32)> = Vec::with_capacity(count);
+    for (time, score) in scored {
+        if kept.len() == count {
+            break;
+        }
+        let apart = kept.iter().all(|&(other, _)| {
+            let seconds = (time - other).num_seconds().abs();
+            // Measured both ways around midnight
+            seconds.min(86_400 - seconds) >= separation
+        });
+        if apart {
+            kept.push((time, score));
+        }
+    }
+    kept
+}
+
+/// Share of the cells of `rect` the lines are centered in, relative to the most they could fill,
+/// which is low when the branches pile up on each other.
+pub fn spread(shapes: &[Shape], rect: Rect) -> f32 {
+    let mut cells = vec![false; SPREAD_CELLS * SPREAD_CELLS];
+    let mut lines = 0;
+    for shape in shapes {
+        let Shape::LineSegment { points, .. } = shape else {
+            continue;
+        };
+        lines += 1;
+        let offset = (points[0].lerp(points[1], 0.5) - rect.min) / rect.size();
+        if (0.0..1.0).contains(&offset.x) && (0.0..1.0).contains(&offset.y) {
+            let cell = |f: f32| (f * SPREAD_CELLS as f32) as usize;
+            cells[cell(offset.y) * SPREAD_CELLS + cell(offset.x)] = true;
+        }
+    }
+    let filled = cells.iter().filter(|&&filled| filled).count();
+    filled as f32 / lines.min(cells.len()).max(1) as f32
+}
+
+/// Image of the thumbnail from the headless renderer.
+pub fn thumbnail(image: &image::RgbaImage) -> ColorImage {
+    let size = [image.width() as usize, image.height() as usize];
+    ColorImage::from_rgba_unmultiplied(size, image.as_raw())
+}
@@ -0,0 +1,361 @@
+use crate::{about, storage};
+use chrono::{DateTime, Local, Utc};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::mpsc::{Receiver, TryRecvError, channel},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Failed syncs are retried sooner than the sync interval, but not too often.
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+/// How often the settings are compared with the last seen ones, to stamp local changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Settings shared between the machines. The others, like the window, outputs, servers and local
+/// paths, belong to each machine.
+const SYNCED: [&str; 4] = ["config", "profiles", "complications", "readout"];
+
+// Settings sync
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    /// URL of the synced file, e.g. on a WebDAV share, read with GET and written with PUT.
+    pub url: String,
+    /// Sent as a bearer token when not empty.
+    pub token: String,
+    pub interval_minutes: u32,
+    /// When the synced settings last changed on this machine, so the newer copy wins.
+    pub modified: Option<DateTime<Utc>>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            token: String::new(),
+            interval_minutes: 5,
+            modified: None,
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Copy without the token.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        about::redact(&mut config.token);
+        config
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, sync: &mut SettingsSync) {
+        ui.checkbox(&mut self.enabled, "Sync settings and presets")
+            .on_hover_text(
+                "Keeps the settings and presets of several machines identical through a shared \
+             file, where the most recently changed copy wins",
+            );
+        ui.horizontal(|ui| {
+            ui.label("URL:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.url)
+                    .hint_text("https://dav.example.com/fractal_clock.json"),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Token:");
+            ui.add(egui::TextEdit::singleline(&mut self.token).password(true));
+        });
+        ui.add(
+            egui::Slider::new(&mut self.interval_minutes, 1..=60)
+                .logarithmic(true)
+                .text("minutes between syncs"),
+        );
+        if !cfg!(feature = "network") {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Syncing requires building with `--features network`",
+            );
+        }
+        ui.horizontal(|ui| {
+            if sync.pending.is_some() {
+                ui.spinner();
+                ui.label("Syncing…");
+            } else if ui
+                .add_enabled(self.enabled, egui::Button::new("Sync now"))
+                .clicked()
+            {
+                sync.next_sync = None;
+            }
+        });
+        match &sync.status {
+            Some(Ok(status)) => {
+                ui.weak(status);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            None => {}
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.interval_minutes.max(1)) * 60)
+    }
+}
+
+/// The synced file.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Bundle {
+    modified: DateTime<Utc>,
+    /// The clock's settings shared between the machines.
+    settings: Value,
+    /// Contents of the files in the presets folder, by file name.
+    presets: BTreeMap<String, String>,
+}
+
+enum Outcome {
+    Uploaded(DateTime<Utc>),
+    Downloaded(Bundle),
+    UpToDate,
+}
+
+/// Uploads and downloads the settings on a background thread.
+#[derive(Default)]
+pub struct SettingsSync {
+    /// Synced settings when last checked, to notice local changes.
+    last_seen: Option<Value>,
+    next_check: Option<Instant>,
+    next_sync: Option<Instant>,
+    pending: Option<Receiver<Result<Outcome, String>>>,
+    status: Option<Result<String, String>>,
+}
+
+impl PartialEq for SettingsSync {
+    fn eq(&self, other: &Self) -> bool {
+        self.status == other.status
+    }
+}
+
+impl SettingsSync {
+    /// Whether the settings are due to be compared with the last seen ones.
+    pub fn check_due(&self) -> bool {
+        self.next_check.is_none_or(|next| Instant::now() >= next)
+    }
+
+    /// Forgets the last seen settings while something other than the user changes them, so they
+    /// are seen anew once it stops.
+    pub fn pause(&mut self) {
+        self.last_seen = None;
+    }
+
+    /// Stamps `config.modified` when the synced `settings` changed since last seen.
+    pub fn observe(&mut self, config: &mut SyncConfig, settings: Value) {
+        self.next_check = Some(Instant::now() + CHECK_INTERVAL);
+        if self
+            .last_seen
+            .as_ref()
+            .is_some_and(|seen| *seen != settings)
+        {
+            config.modified = Some(Utc::now());
+        }
+        self.last_seen = Some(settings);
+    }
+
+    /// Syncs when due, returning the settings of a newer copy from another machine.
+    pub fn update(&mut self, ctx: &egui::Context, config: &mut SyncConfig) -> Option<Value> {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(result) => {
+                    self.pending = None;
+                    return self.finish(config, result);
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        let due = self.next_sync.is_none_or(|next| Instant::now() >= next);
+        if config.enabled
+            && due
+            && !config.url.is_empty()
+            && let Some(settings) = self.last_seen.clone()
+        {
+            let (sender, receiver) = channel();
+            let url = config.url.clone();
+            let token = config.token.clone();
+            let modified = config.modified;
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send(sync(&url, &token, modified, settings));
+                ctx.request_repaint();
+            });
+            self.pending = Some(receiver);
+        }
+        None
+    }
+
+    fn finish(
+        &mut self,
+        config: &mut SyncConfig,
+        result: Result<Outcome, String>,
+    ) -> Option<Value> {
+        let now = Local::now().format("%H:%M");
+        let mut downloaded = None;
+        self.status = Some(match result {
+            Ok(Outcome::Uploaded(modified)) => {
+                config.modified = Some(modified);
+                Ok(format!("Uploaded at {now}"))
+            }
+            Ok(Outcome::Downloaded(bundle)) => {
+                config.modified = Some(bundle.modified);
+                write_presets(&bundle.presets);
+                // The downloaded settings are not a local change
+                self.last_seen = None;
+                downloaded = Some(bundle.settings);
+                Ok(format!("Downloaded at {now}"))
+            }
+            Ok(Outcome::UpToDate) => Ok(format!("Up to date at {now}")),
+            Err(error) => {
+                tracing::warn!("Failed to sync the settings: {error}");
+                Err(error)
+            }
+        });
+        let delay = if self.status.as_ref().is_some_and(Result::is_err) {
+            RETRY_DELAY.min(config.interval())
+        } else {
+            config.interval()
+        };
+        self.next_sync = Some(Instant::now() + delay);
+        downloaded
+    }
+}
+
+/// The settings shared between the machines out of all of the clock's `settings`.
+pub fn synced(settings: &Value) -> Value {
+    let synced = SYNCED
+        .iter()
+        .filter_map(|key| Some((key.to_string(), settings.get(key)?.clone())))
+        .collect();
+    Value::Object(synced)
+}
+
+/// Downloads the remote copy if it is newer, and uploads the local one otherwise.
+fn sync(
+    url: &str,
+    token: &str,
+    modified: Option<DateTime<Utc>>,
+    settings: Value,
+) -> Result<Outcome, String> {
+    let remote = download(url, token)?;
+    match (remote, modified) {
+        (Some(remote), Some(modified)) if remote.modified == modified => Ok(Outcome::UpToDate),
+        // Never changed here, so any remote copy is newer
+        (Some(remote), None) => Ok(Outcome::Downloaded(remote)),
+        (Some(remote), Some(modified)) if remote.modified > modified => {
+            Ok(Outcome::Downloaded(remote))
+        }
+        _ => {
+            let bundle = Bundle {
+                modified: modified.unwrap_or_else(Utc::now),
+                settings,
+                presets: read_presets(),
+            };
+            upload(url, token, &bundle)?;
+            Ok(Outcome::Uploaded(bundle.modified))
+        }
+    }
+}
+
+fn read_presets() -> BTreeMap<String, String> {
+    let Some(entries) = storage::presets_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return BTreeMap::new();
+    };
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            let contents = std::fs::read_to_string(&path).ok()?;
+            Some((name, contents))
+        })
+        .collect()
+}
+
+/// Writes the downloaded presets over the local ones with the same names.
+fn write_presets(presets: &BTreeMap<String, String>) {
+    let Some(dir) = storage::presets_dir() else {
+        return;
+    };
+    if !presets.is_empty()
+        && let Err(e) = std::fs::create_dir_all(&dir)
+    {
+        tracing::warn!("Failed to create {}: {e}", dir.display());
+        return;
+    }
+    for (name, contents) in presets {
+        // Only plain file names, so a remote copy cannot write elsewhere
+        let Some(file_name) = Path::new(name)
+            .file_name()
+            .filter(|file| *file == name.as_str())
+        else {
+            continue;
+        };
+        if let Err(e) = std::fs::write(dir.join(file_name), contents) {
+            tracing::warn!("Failed to write preset {name}: {e}");
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+fn download(url: &str, token: &str) -> Result<Option<Bundle>, String> {
+    let mut response = authorize(ureq::get(url), token)
+        .config()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .call()
+        .map_err(|e| e.to_string())?;
+    match response.status().as_u16() {
+        // Nothing was synced yet
+        404 => Ok(None),
+        200..=299 => response
+            .body_mut()
+            .read_json()
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        status => Err(format!("The server answered with status {status}")),
+    }
+}
+
+#[cfg(feature = "network")]
+fn upload(url: &str, token: &str, bundle: &Bundle) -> Result<(), String> {
+    authorize(ureq::put(url), token)
+        .config()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .send_json(bundle)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "network")]
+fn authorize<B>(request: ureq::RequestBuilder<B>, token: &str) -> ureq::RequestBuilder<B> {
+    if token.is_empty() {
+        request
+    } else {
+        request.header("Authorization", format!("Bearer {token}"))
+    }
+}
+
+#[cfg(not(feature = "network"))]
+fn download(_url: &str, _token: &str) -> Result<Option<Bundle>, String> {
+    Err("Built without the network feature".to_owned())
+}
+
+#[cfg(not(feature = "network"))]
+fn upload(_url: &str, _token: &str, _bundle: &Bundle) -> Result<(), String> {
+    Err("Built without the network feature".to_owned())
+}
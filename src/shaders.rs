@@ -0,0 +1,284 @@
+use crate::{
+    effects::{self, Effect, Language, SharedErrors},
+    storage, toasts,
+};
+use egui::{RichText, Ui};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        mpsc::{Receiver, channel},
+    },
+};
+
+/// Shaders written by "Add examples", which show the inputs every effect gets.
+const EXAMPLES: [(&str, &str); 3] = [
+    (
+        "glow.wgsl",
+        r#"// Blurs the bright lines into a soft halo around them.
+struct Globals {
+    // Size of the frame in pixels
+    resolution: vec2<f32>,
+    // Seconds since the app started
+    time: f32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var frame: texture_2d<f32>;
+@group(0) @binding(2) var frame_sampler: sampler;
+
+@fragment
+fn main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let pixel = 1.0 / globals.resolution;
+    var halo = vec4<f32>(0.0);
+    for (var x = -3; x <= 3; x++) {
+        for (var y = -3; y <= 3; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * pixel * 2.0;
+            halo += textureSample(frame, frame_sampler, uv + offset);
+        }
+    }
+    let color = textureSample(frame, frame_sampler, uv);
+    return min(color + halo / 49.0 * 0.8, vec4<f32>(1.0));
+}
+"#,
+    ),
+    (
+        "chromatic_aberration.wgsl",
+        r#"// Splits the colors towards the edges, like a cheap lens.
+struct Globals {
+    resolution: vec2<f32>,
+    time: f32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var frame: texture_2d<f32>;
+@group(0) @binding(2) var frame_sampler: sampler;
+
+@fragment
+fn main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let shift = (uv - 0.5) * 0.01;
+    let red = textureSample(frame, frame_sampler, uv + shift);
+    let green = textureSample(frame, frame_sampler, uv);
+    let blue = textureSample(frame, frame_sampler, uv - shift);
+    return vec4<f32>(red.r, green.g, blue.b, max(max(red.a, green.a), blue.a));
+}
+"#,
+    ),
+    (
+        "vignette.wgsl",
+        r#"// Darkens the corners, breathing slowly with the time.
+struct Globals {
+    resolution: vec2<f32>,
+    time: f32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var frame: texture_2d<f32>;
+@group(0) @binding(2) var frame_sampler: sampler;
+
+@fragment
+fn main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let aspect = globals.resolution.x / globals.resolution.y;
+    let distance = length((uv - 0.5) * vec2<f32>(aspect, 1.0));
+    let radius = 0.75 + 0.05 * sin(globals.time * 0.5);
+    let shade = smoothstep(radius, radius - 0.45, distance);
+    return textureSample(frame, frame_sampler, uv) * shade;
+}
+"#,
+    ),
+];
+
+// Shader effects settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct ShaderEffectsConfig {
+    pub enabled: bool,
+    /// File names of the shaders that are skipped, so new shaders are applied when added.
+    disabled: Vec<String>,
+}
+
+impl ShaderEffectsConfig {
+    /// Effects to draw the frame through, in the order of their file names.
+    pub fn active(&self, library: &ShaderLibrary) -> Vec<Arc<Effect>> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        library
+            .shaders
+            .iter()
+            .filter(|effect| !self.disabled.contains(&effect.name))
+            .cloned()
+            .collect()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, library: &ShaderLibrary) {
+        ui.checkbox(&mut self.enabled, "Apply shader effects").on_hover_text(
+            "Draws the clock through the WGSL fragment shaders in the shaders directory, in the \
+             order of their file names. Saved shaders are reloaded right away.",
+        );
+        if !cfg!(feature = "shaders") {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Shader effects require building with `--features shaders`",
+            );
+        }
+        let Some(dir) = storage::shaders_dir() else {
+            ui.label("No config directory for shaders");
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(format!("Directory: {}", dir.display()));
+            if ui.button("Add examples").clicked() {
+                match add_examples(&dir) {
+                    Ok(count) => toasts::success(format!("Added {count} example shaders")),
+                    Err(e) => toasts::error(format!("Failed to add the example shaders: {e}")),
+                }
+            }
+        });
+        if library.shaders.is_empty() {
+            ui.label("No .wgsl files yet");
+        }
+        let errors = library.errors();
+        for effect in &library.shaders {
+            let mut enabled = !self.disabled.contains(&effect.name);
+            if ui.checkbox(&mut enabled, &effect.name).changed() {
+                if enabled {
+                    self.disabled.retain(|name| *name != effect.name);
+                } else {
+                    self.disabled.push(effect.name.clone());
+                }
+            }
+            if enabled
+                && self.enabled
+                && let Some(error) = errors.get(&effect.name)
+            {
+                ui.label(
+                    RichText::new(error)
+                        .monospace()
+                        .color(ui.visuals().error_fg_color),
+                );
+            }
+        }
+    }
+}
+
+/// WGSL shaders loaded from the shaders directory, reloaded when its files change.
+#[derive(Default)]
+pub struct ShaderLibrary {
+    shaders: Vec<Arc<Effect>>,
+    watcher: Option<(RecommendedWatcher, Receiver<()>)>,
+    loaded: bool,
+    /// Whether watching was tried, once the directory exists.
+    watched: bool,
+    errors: Option<SharedErrors>,
+}
+
+impl PartialEq for ShaderLibrary {
+    fn eq(&self, other: &Self) -> bool {
+        self.shaders == other.shaders
+    }
+}
+
+impl ShaderLibrary {
+    /// Reads the shaders when first called and again when the directory changed.
+    pub fn update(&mut self, ctx: &egui::Context) {
+        if self.errors.is_none() {
+            self.errors = Some(effects::errors(ctx));
+        }
+        let Some(dir) = storage::shaders_dir() else {
+            return;
+        };
+        let mut changed = !self.loaded;
+        if let Some((_, changes)) = &self.watcher {
+            changed |= changes.try_iter().count() > 0;
+        } else if !self.watched && dir.is_dir() {
+            // The directory is only created with the examples
+            self.watched = true;
+            self.watcher = watch(ctx, &dir);
+            changed = true;
+        }
+        if changed {
+            self.loaded = true;
+            self.shaders = read_shaders(&dir);
+        }
+    }
+
+    /// Errors of the shaders that failed to compile when last drawn, by file name.
+    fn errors(&self) -> BTreeMap<String, String> {
+        self.errors
+            .as_ref()
+            .and_then(|errors| errors.lock().ok().map(|errors| errors.clone()))
+            .unwrap_or_default()
+    }
+}
+
+fn read_shaders(dir: &Path) -> Vec<Arc<Effect>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "wgsl")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            match std::fs::read_to_string(&path) {
+                Ok(wgsl) => Some(Arc::new(Effect {
+                    name,
+                    language: Language::Wgsl,
+                    source: wgsl.into(),
+                    params: [0.0; 4],
+                })),
+                Err(e) => {
+                    tracing::warn!("Failed to read shader {}: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Watches `dir` for saved shaders, repainting `ctx` when they change.
+fn watch(ctx: &egui::Context, dir: &Path) -> Option<(RecommendedWatcher, Receiver<()>)> {
+    let (sender, changes) = channel();
+    let ctx = ctx.clone();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|event| !event.kind.is_access()) {
+            let _ = sender.send(());
+            ctx.request_repaint();
+        }
+    })
+    .and_then(|mut watcher| {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => Some((watcher, changes)),
+        Err(e) => {
+            tracing::warn!("Not watching shaders {}: {e}", dir.display());
+            None
+        }
+    }
+}
+
+/// Writes the example shaders that are not in `dir` yet, returning how many were written.
+fn add_examples(dir: &Path) -> std::io::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for (name, wgsl) in EXAMPLES {
+        let path = dir.join(name);
+        if !path.exists() {
+            std::fs::write(path, wgsl)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
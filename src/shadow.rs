@@ -0,0 +1,69 @@
+use egui::{Color32, Shape, Stroke, Vec2, vec2};
+
+// Drop shadow settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ShadowConfig {
+    pub enabled: bool,
+    /// Shift of the shadow in points, which outlines the clock when zero.
+    pub offset: Vec2,
+    pub color: Color32,
+    /// Added to the width of the shadow's lines, in points.
+    pub extra_width: f32,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            offset: vec2(3.0, 3.0),
+            color: Color32::from_black_alpha(160),
+            extra_width: 0.0,
+        }
+    }
+}
+
+impl ShadowConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "Shadow");
+            ui.color_edit_button_srgba(&mut self.color);
+        });
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Offset:");
+                ui.add(egui::DragValue::new(&mut self.offset.x).range(-50.0..=50.0));
+                ui.add(egui::DragValue::new(&mut self.offset.y).range(-50.0..=50.0));
+            });
+            ui.add(egui::Slider::new(&mut self.extra_width, 0.0..=10.0).text("outline width"))
+                .on_hover_text("With no offset, the shadow outlines the lines");
+        });
+    }
+
+    /// Puts a shifted and recolored copy of the lines and polygons of `shapes` beneath them.
+    pub fn apply(&self, shapes: &mut Vec<Shape>) {
+        if !self.enabled {
+            return;
+        }
+        let shadows: Vec<_> = shapes
+            .iter()
+            .filter_map(|shape| {
+                let mut shadow = match shape {
+                    Shape::LineSegment { points, stroke } => Shape::line_segment(
+                        *points,
+                        Stroke::new(stroke.width + self.extra_width, self.color),
+                    ),
+                    Shape::Path(path) => Shape::convex_polygon(
+                        path.points.clone(),
+                        self.color,
+                        Stroke::new(self.extra_width, self.color),
+                    ),
+                    _ => return None,
+                };
+                shadow.translate(self.offset);
+                Some(shadow)
+            })
+            .collect();
+        shapes.splice(0..0, shadows);
+    }
+}
@@ -0,0 +1,47 @@
+use egui::Shape;
+use std::sync::{Arc, Mutex};
+
+/// Buffers of at most this many frames are kept.
+const MAX_FREE: usize = 4;
+
+/// Shape buffers of retired frames, kept for the next frames instead of being freed and grown
+/// again from empty. Clones share the buffers, so the background generation thread can take
+/// the buffers the UI thread gives back.
+#[derive(Clone, Default)]
+pub struct ShapePool {
+    free: Arc<Mutex<Vec<Vec<Shape>>>>,
+}
+
+impl ShapePool {
+    /// An empty buffer for at least `capacity` shapes, the largest kept one if any.
+    pub fn take(&self, capacity: usize) -> Vec<Shape> {
+        let mut shapes = self
+            .free
+            .lock()
+            .ok()
+            .and_then(|mut free| free.pop())
+            .unwrap_or_default();
+        reserve(&mut shapes, capacity);
+        shapes
+    }
+
+    /// Keeps the buffer of `shapes` for a later `take`, dropping the shapes themselves.
+    pub fn give(&self, mut shapes: Vec<Shape>) {
+        shapes.clear();
+        if let Ok(mut free) = self.free.lock()
+            && free.len() < MAX_FREE
+        {
+            free.push(shapes);
+            // The largest buffer is taken first
+            free.sort_by_key(Vec::capacity);
+        }
+    }
+}
+
+/// Grows `buffer` while empty to hold `capacity` items in one allocation, which copies nothing,
+/// so filling it does not reallocate and copy the items halfway through a frame.
+pub fn reserve<T>(buffer: &mut Vec<T>, capacity: usize) {
+    if buffer.is_empty() && buffer.capacity() < capacity {
+        *buffer = Vec::with_capacity(capacity);
+    }
+}
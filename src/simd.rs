@@ -0,0 +1,176 @@
+pub use crate::precision::{Complex, Real};
+
+/// Nodes grown together, as many `f32` as fit a 256-bit register.
+pub const LANES: usize = 8;
+
+/// A node that children grow from.
+pub trait Parent<T> {
+    fn pos(&self) -> Complex<T>;
+    fn dir(&self) -> Complex<T>;
+}
+
+impl<T: Real> Parent<T> for [Complex<T>; 2] {
+    fn pos(&self) -> Complex<T> {
+        self[0]
+    }
+
+    fn dir(&self) -> Complex<T> {
+        self[1]
+    }
+}
+
+/// The widest instruction set the batches run with on this CPU.
+pub fn instruction_set() -> &'static str {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx") {
+        return "AVX";
+    }
+    if cfg!(target_feature = "sse2") {
+        "SSE2"
+    } else if cfg!(target_feature = "neon") {
+        "NEON"
+    } else {
+        "scalar"
+    }
+}
+
+/// Replaces `children` with the position and direction of the child of every parent that
+/// `rotor` turns and scales it into, in batches of `LANES` nodes if `T::BATCHED`.
+pub fn grow<T: Real, P: Parent<T>>(
+    rotor: Complex<T>,
+    parents: &[P],
+    children: &mut Vec<[Complex<T>; 2]>,
+) {
+    if !T::BATCHED {
+        return grow_scalar(rotor, parents, children);
+    }
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx") {
+        // SAFETY: the CPU supports AVX
+        return unsafe { grow_avx(rotor, parents, children) };
+    }
+    grow_lanes(rotor, parents, children);
+}
+
+/// Like `grow`, a node at a time, which the benchmark compares the batches to.
+pub fn grow_scalar<T: Real, P: Parent<T>>(
+    rotor: Complex<T>,
+    parents: &[P],
+    children: &mut Vec<[Complex<T>; 2]>,
+) {
+    children.clear();
+    grow_scalar_into(rotor, parents, children);
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx")]
+fn grow_avx<T: Real, P: Parent<T>>(
+    rotor: Complex<T>,
+    parents: &[P],
+    children: &mut Vec<[Complex<T>; 2]>,
+) {
+    grow_lanes(rotor, parents, children);
+}
+
+/// Splits the nodes into arrays of their coordinates, which the compiler turns into vector
+/// instructions of the features enabled where this is inlined.
+#[inline(always)]
+fn grow_lanes<T: Real, P: Parent<T>>(
+    rotor: Complex<T>,
+    parents: &[P],
+    children: &mut Vec<[Complex<T>; 2]>,
+) {
+    children.clear();
+    children.reserve(parents.len());
+    let mut batches = parents.chunks_exact(LANES);
+    let mut outputs = children.spare_capacity_mut()[..parents.len()].chunks_exact_mut(LANES);
+    for (batch, output) in (&mut batches).zip(&mut outputs) {
+        let pos_re: [T; LANES] = std::array::from_fn(|i| batch[i].pos().re);
+        let pos_im: [T; LANES] = std::array::from_fn(|i| batch[i].pos().im);
+        let dir_re: [T; LANES] = std::array::from_fn(|i| batch[i].dir().re);
+        let dir_im: [T; LANES] = std::array::from_fn(|i| batch[i].dir().im);
+        let re: [T; LANES] = std::array::from_fn(|i| rotor.re * dir_re[i] - rotor.im * dir_im[i]);
+        let im: [T; LANES] = std::array::from_fn(|i| rotor.re * dir_im[i] + rotor.im * dir_re[i]);
+        for (i, child) in output.iter_mut().enumerate() {
+            child.write([
+                Complex {
+                    re: pos_re[i] + re[i],
+                    im: pos_im[i] + im[i],
+                },
+                Complex {
+                    re: re[i],
+                    im: im[i],
+                },
+            ]);
+        }
+    }
+    for (parent, child) in batches.remainder().iter().zip(outputs.into_remainder()) {
+        let dir = rotor * parent.dir();
+        child.write([parent.pos() + dir, dir]);
+    }
+    // SAFETY: every child up to the length of the parents was written above
+    unsafe { children.set_len(parents.len()) };
+}
+
+fn grow_scalar_into<T: Real, P: Parent<T>>(
+    rotor: Complex<T>,
+    parents: &[P],
+    children: &mut Vec<[Complex<T>; 2]>,
+) {
+    children.extend(parents.iter().map(|parent| {
+        let dir = rotor * parent.dir();
+        [parent.pos() + dir, dir]
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parents(len: usize) -> Vec<[Complex<f32>; 2]> {
+        (0..len)
+            .map(|i| {
+                let i = i as f32;
+                [
+                    Complex {
+                        re: i,
+                        im: -0.5 * i,
+                    },
+                    Complex {
+                        re: (0.3 * i).cos(),
+                        im: (0.3 * i).sin(),
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batches_match_scalar() {
+        let rotor = Complex { re: 0.6, im: -0.45 };
+        for len in [0, 1, LANES - 1, LANES, LANES * 3 + 5] {
+            let parents = parents(len);
+            let mut expected = Vec::new();
+            grow_scalar(rotor, &parents, &mut expected);
+            assert_eq!(expected.len(), len);
+
+            // Stale children from the last level are replaced
+            let mut children = parents.clone();
+            children.extend(parents.clone());
+            grow(rotor, &parents, &mut children);
+            assert!(children == expected, "grow with {len} parents");
+
+            let mut children = Vec::new();
+            grow_lanes(rotor, &parents, &mut children);
+            assert!(children == expected, "grow_lanes with {len} parents");
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            if std::arch::is_x86_feature_detected!("avx") {
+                let mut children = Vec::new();
+                // SAFETY: the CPU supports AVX
+                unsafe { grow_avx(rotor, &parents, &mut children) };
+                assert!(children == expected, "grow_avx with {len} parents");
+            }
+        }
+    }
+}
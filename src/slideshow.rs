@@ -0,0 +1,290 @@
+use crate::{file_drop, fractal_clock::FractalClockConfig, morph, storage, toasts};
+use egui::{Slider, Ui};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, channel},
+};
+
+// Slideshow settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SlideshowConfig {
+    pub enabled: bool,
+    /// Folder of the config files to show, the presets folder when empty.
+    pub folder: String,
+    /// Real seconds each slide is shown.
+    pub interval_seconds: f32,
+    /// Real seconds of morphing into the next slide.
+    pub transition_seconds: f32,
+}
+
+impl Default for SlideshowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: String::new(),
+            interval_seconds: 60.0,
+            transition_seconds: 3.0,
+        }
+    }
+}
+
+impl SlideshowConfig {
+    fn dir(&self) -> Option<PathBuf> {
+        let folder = self.folder.trim();
+        if folder.is_empty() {
+            storage::presets_dir()
+        } else {
+            Some(PathBuf::from(folder))
+        }
+    }
+
+    /// Returns whether the current settings should be saved as a preset.
+    pub fn ui(&mut self, ui: &mut Ui, slideshow: &mut Slideshow) -> bool {
+        ui.horizontal(|ui| {
+            ui.label("Folder:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.folder)
+                    .hint_text("presets")
+                    .desired_width(160.0),
+            )
+            .on_hover_text("Config files in this folder are shown in the order of their names");
+        });
+        if let Some(dir) = &slideshow.dir {
+            ui.weak(format!(
+                "{} slides in {}",
+                slideshow.slides.len(),
+                dir.display()
+            ));
+        }
+        if slideshow.slides.is_empty() {
+            self.enabled = false;
+        }
+        ui.add_enabled_ui(!slideshow.slides.is_empty(), |ui| {
+            ui.checkbox(&mut self.enabled, "Play the slideshow")
+                .on_hover_text("Drives the settings, which cannot be edited while it plays");
+        });
+        ui.add(
+            Slider::new(&mut self.interval_seconds, 5.0..=3600.0)
+                .logarithmic(true)
+                .suffix(" s")
+                .text("per slide"),
+        );
+        ui.add(
+            Slider::new(&mut self.transition_seconds, 0.0..=30.0)
+                .suffix(" s")
+                .text("transition"),
+        );
+        if self.enabled
+            && let Some(slide) = slideshow
+                .current
+                .and_then(|index| slideshow.slides.get(index))
+        {
+            ui.label(format!("Showing {}", slide.name));
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut slideshow.name)
+                    .hint_text("Preset name")
+                    .desired_width(120.0),
+            );
+            ui.add_enabled(
+                !slideshow.name.trim().is_empty(),
+                egui::Button::new("Save preset"),
+            )
+            .on_hover_text("Saves the current settings into the folder as a slide")
+            .clicked()
+        })
+        .inner
+    }
+}
+
+struct Slide {
+    /// File name without the extension.
+    name: String,
+    config: FractalClockConfig,
+}
+
+/// Settings read from the config files in the slideshow folder, read again when the folder
+/// changes.
+#[derive(Default)]
+pub struct Slideshow {
+    slides: Vec<Slide>,
+    /// Folder the slides were read from.
+    dir: Option<PathBuf>,
+    watcher: Option<(RecommendedWatcher, Receiver<()>)>,
+    /// App time in seconds the slideshow started playing.
+    started: Option<f64>,
+    /// Index of the shown slide.
+    current: Option<usize>,
+    /// Name the next preset is saved with.
+    name: String,
+}
+
+impl PartialEq for Slideshow {
+    fn eq(&self, other: &Self) -> bool {
+        self.dir == other.dir
+            && self
+                .slides
+                .iter()
+                .map(|slide| &slide.name)
+                .eq(other.slides.iter().map(|slide| &slide.name))
+    }
+}
+
+impl Slideshow {
+    /// Reads the slides when the folder was changed in the settings or on disk.
+    pub fn update(&mut self, ctx: &egui::Context, settings: &SlideshowConfig) {
+        let dir = settings.dir();
+        let mut changed = dir != self.dir;
+        if changed {
+            self.dir = dir;
+            self.watcher = self
+                .dir
+                .as_deref()
+                .filter(|dir| dir.is_dir())
+                .and_then(|dir| watch(ctx, dir));
+        } else if let Some((_, changes)) = &self.watcher {
+            changed = changes.try_iter().count() > 0;
+        }
+        if changed {
+            self.slides = self.dir.as_deref().map(read_slides).unwrap_or_default();
+        }
+    }
+
+    /// Settings at `seconds` of app time while the slideshow plays, morphing from the previous
+    /// slide at the start of each.
+    pub fn config(
+        &mut self,
+        settings: &SlideshowConfig,
+        seconds: f64,
+    ) -> Option<FractalClockConfig> {
+        if !settings.enabled || self.slides.is_empty() {
+            self.started = None;
+            self.current = None;
+            return None;
+        }
+        let elapsed = seconds - *self.started.get_or_insert(seconds);
+        let interval = f64::from(settings.interval_seconds.max(1.0));
+        let step = (elapsed / interval) as usize;
+        let count = self.slides.len();
+        let index = step % count;
+        self.current = Some(index);
+        let config = &self.slides[index].config;
+
+        let into = (elapsed % interval) as f32;
+        let transition = settings.transition_seconds.min(settings.interval_seconds);
+        if step > 0 && count > 1 && into < transition {
+            let previous = &self.slides[(step - 1) % count].config;
+            let t = morph::smoothstep(into / transition);
+            return Some(FractalClockConfig::morph(previous, config, t));
+        }
+        Some(config.clone())
+    }
+
+    /// Saves `config` into the slideshow folder under the entered name.
+    pub fn save_preset(&mut self, config: &FractalClockConfig) {
+        let name: String = self
+            .name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || " -_".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let Some(dir) = self.dir.clone() else {
+            toasts::error("No folder for presets");
+            return;
+        };
+        let path = dir.join(format!("{name}.toml"));
+        let result = toml::to_string_pretty(config)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                std::fs::write(&path, contents).map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => {
+                toasts::success(format!("Saved preset {name}"));
+                self.name.clear();
+                // Watches the folder in case it was just created
+                self.dir = None;
+            }
+            Err(e) => {
+                tracing::error!("Failed to save preset {}: {e}", path.display());
+                toasts::error(format!("Failed to save preset {name}: {e}"));
+            }
+        }
+    }
+}
+
+fn read_slides(dir: &Path) -> Vec<Slide> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml" || extension == "json")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| match read_slide(&path) {
+            Ok(config) => Some(Slide {
+                name: path.file_stem()?.to_string_lossy().into_owned(),
+                config,
+            }),
+            Err(e) => {
+                tracing::warn!("Skipping slide {}: {e}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+/// Reads a preset, the clock's settings or a whole config file.
+fn read_slide(path: &Path) -> Result<FractalClockConfig, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let json = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    let value = file_drop::parse_value(&text, json)?;
+    // The clock's settings keep these next to the window and export settings
+    let value = match value.get("config") {
+        Some(config) => config.clone(),
+        None => value,
+    };
+    value.try_into().map_err(|e: toml::de::Error| e.to_string())
+}
+
+/// Watches `dir` for added, changed and removed slides, repainting `ctx` when they change.
+fn watch(ctx: &egui::Context, dir: &Path) -> Option<(RecommendedWatcher, Receiver<()>)> {
+    let (sender, changes) = channel();
+    let ctx = ctx.clone();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|event| !event.kind.is_access()) {
+            let _ = sender.send(());
+            ctx.request_repaint();
+        }
+    })
+    .and_then(|mut watcher| {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => Some((watcher, changes)),
+        Err(e) => {
+            tracing::warn!("Not watching slides {}: {e}", dir.display());
+            None
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use egui::{Color32, Rect, emath::RectTransform};
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum StereoMode {
+    Off,
+    /// Red for the left eye and cyan for the right, overlaid for red/cyan glasses.
+    Anaglyph,
+    /// Each eye squeezed into half of the window, for 3D displays in half side-by-side mode.
+    SideBySide,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+// Stereo 3D settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct StereoConfig {
+    pub mode: StereoMode,
+    /// Horizontal offset in points between the eyes for the deepest branches.
+    pub parallax: f32,
+}
+
+impl Default for StereoConfig {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Off,
+            parallax: 12.0,
+        }
+    }
+}
+
+impl StereoConfig {
+    pub fn eyes(&self) -> &'static [Option<Eye>] {
+        match self.mode {
+            StereoMode::Off => &[None],
+            StereoMode::Anaglyph | StereoMode::SideBySide => &[Some(Eye::Left), Some(Eye::Right)],
+        }
+    }
+
+    /// Horizontal offset of branches `depth` levels deep out of `levels`, pushing deeper branches
+    /// behind the screen.
+    pub fn offset(&self, eye: Option<Eye>, depth: usize, levels: usize) -> f32 {
+        let mut shift = 0.5 * self.parallax * depth as f32 / levels.max(1) as f32;
+        if self.mode == StereoMode::SideBySide {
+            // The display stretches each half back to the full width
+            shift /= 2.0;
+        }
+        match eye {
+            None => 0.0,
+            Some(Eye::Left) => -shift,
+            Some(Eye::Right) => shift,
+        }
+    }
+
+    /// Color of a line seen by `eye`. Anaglyph colors are additive, so both eyes overlap to the
+    /// original color.
+    pub fn tint(&self, eye: Option<Eye>, color: Color32) -> Color32 {
+        let [r, g, b, _] = color.to_array();
+        match (self.mode, eye) {
+            (StereoMode::Anaglyph, Some(Eye::Left)) => Color32::from_rgba_premultiplied(r, 0, 0, 0),
+            (StereoMode::Anaglyph, Some(Eye::Right)) => {
+                Color32::from_rgba_premultiplied(0, g, b, 0)
+            }
+            _ => color,
+        }
+    }
+
+    /// Part of `window` shown to `eye`.
+    pub fn eye_window(&self, eye: Option<Eye>, window: Rect) -> Rect {
+        let (left, right) = window.split_left_right_at_fraction(0.5);
+        match (self.mode, eye) {
+            (StereoMode::SideBySide, Some(Eye::Left)) => left,
+            (StereoMode::SideBySide, Some(Eye::Right)) => right,
+            _ => window,
+        }
+    }
+
+    /// Squeezes `rect` of `window` into the part shown to `eye`.
+    pub fn eye_rect(&self, eye: Option<Eye>, rect: Rect, window: Rect) -> Rect {
+        let eye_window = self.eye_window(eye, window);
+        let scale = eye_window.width() / window.width().max(1.0);
+        Rect::from_x_y_ranges(
+            eye_window.left() + (rect.left() - window.left()) * scale
+                ..=eye_window.left() + (rect.right() - window.left()) * scale,
+            rect.y_range(),
+        )
+    }
+
+    /// Transform of the fractal space to the part of `window` shown to `eye`.
+    pub fn eye_transform(
+        &self,
+        eye: Option<Eye>,
+        to_screen: &RectTransform,
+        window: Rect,
+    ) -> RectTransform {
+        RectTransform::from_to(
+            *to_screen.from(),
+            self.eye_rect(eye, *to_screen.to(), window),
+        )
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, StereoMode::Off, "Off");
+            ui.radio_value(&mut self.mode, StereoMode::Anaglyph, "Anaglyph");
+            ui.radio_value(&mut self.mode, StereoMode::SideBySide, "Side by side");
+        });
+        ui.add(egui::Slider::new(&mut self.parallax, 0.0..=50.0).text("parallax (px)"));
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Local, TimeDelta, TimeZone as _};
+use std::time::{Duration, Instant};
+
+/// Laps drawn as ghosts at most, the latest ones.
+pub const MAX_GHOSTS: usize = 12;
+
+// Stopwatch settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct StopwatchConfig {
+    /// Shows the elapsed time on the hands instead of the time of day.
+    pub enabled: bool,
+    /// Opacity of the frozen hands of every lap.
+    pub ghost_opacity: f32,
+    /// Draws a few levels of branches with the ghost hands.
+    pub ghost_fractal: bool,
+    pub ghost_depth: usize,
+}
+
+impl Default for StopwatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ghost_opacity: 0.25,
+            ghost_fractal: false,
+            ghost_depth: 4,
+        }
+    }
+}
+
+impl StopwatchConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, stopwatch: &mut Stopwatch) {
+        ui.checkbox(&mut self.enabled, "Stopwatch mode")
+            .on_hover_text("The hands show the elapsed time, from 12 o'clock");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.monospace(format_duration(stopwatch.elapsed()));
+                if stopwatch.is_running() {
+                    if ui.button("⏸ Stop").clicked() {
+                        stopwatch.stop();
+                    }
+                    if ui.button("⏱ Lap").clicked() {
+                        stopwatch.lap();
+                    }
+                } else if ui.button("▶ Start").clicked() {
+                    stopwatch.start();
+                }
+                if ui.button("⟲ Reset").clicked() {
+                    *stopwatch = Stopwatch::default();
+                }
+            });
+            let mut previous = Duration::ZERO;
+            for (index, &lap) in stopwatch.laps.iter().enumerate() {
+                ui.weak(format!(
+                    "Lap {}: {} (+{})",
+                    index + 1,
+                    format_duration(lap),
+                    format_duration(lap - previous)
+                ));
+                previous = lap;
+            }
+            ui.add(egui::Slider::new(&mut self.ghost_opacity, 0.0..=1.0).text("ghost opacity"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.ghost_fractal, "Ghost branches");
+                ui.add_enabled(
+                    self.ghost_fractal,
+                    egui::Slider::new(&mut self.ghost_depth, 1..=8).text("levels"),
+                );
+            });
+        });
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let centiseconds = duration.as_millis() / 10;
+    let (hours, minutes) = (centiseconds / 360_000, centiseconds / 6000 % 60);
+    let (seconds, centiseconds) = (centiseconds / 100 % 60, centiseconds % 100);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}.{centiseconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}.{centiseconds:02}")
+    }
+}
+
+/// Elapsed time and the laps recorded since the last reset.
+#[derive(Default, PartialEq)]
+pub struct Stopwatch {
+    started: Option<Instant>,
+    /// Time elapsed before the latest start.
+    banked: Duration,
+    /// Elapsed time at every lap.
+    pub laps: Vec<Duration>,
+}
+
+impl Stopwatch {
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.banked += started.elapsed();
+        }
+    }
+
+    pub fn lap(&mut self) {
+        self.laps.push(self.elapsed());
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.banked
+            + self
+                .started
+                .map_or(Duration::ZERO, |started| started.elapsed())
+    }
+
+    /// Time of the day `now` is on that shows `elapsed` on the hands, counted from midnight.
+    pub fn time(elapsed: Duration, now: DateTime<Local>) -> DateTime<Local> {
+        now.date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .and_then(|midnight| Some(midnight + TimeDelta::from_std(elapsed).ok()?))
+            .unwrap_or(now)
+    }
+}
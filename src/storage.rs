@@ -0,0 +1,377 @@
+use crate::{migration, toasts};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        OnceLock,
+        mpsc::{Receiver, channel},
+    },
+};
+
+/// Directory used instead of the platform config and data directories, where there are none.
+static APP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keeps all files in `dir`, like the app's internal storage on Android.
+#[cfg(target_os = "android")]
+pub fn set_app_dir(dir: PathBuf) {
+    let _ = APP_DIR.set(dir);
+}
+
+/// `fractal_clock.toml` in the platform config directory, e.g. `~/.config/fractal_clock/` on Linux.
+pub fn default_config_path() -> Option<PathBuf> {
+    app_dir(dirs::config_dir()).map(|dir| dir.join("fractal_clock.toml"))
+}
+
+/// Directory of the WGSL effect shaders, e.g. `~/.config/fractal_clock/shaders/` on Linux.
+pub fn shaders_dir() -> Option<PathBuf> {
+    app_dir(dirs::config_dir()).map(|dir| dir.join("shaders"))
+}
+
+/// Directory of the saved presets shown by the slideshow, e.g. `~/.config/fractal_clock/presets/`
+/// on Linux.
+pub fn presets_dir() -> Option<PathBuf> {
+    app_dir(dirs::config_dir()).map(|dir| dir.join("presets"))
+}
+
+/// Directory for the log and autosave files, e.g. `~/.local/share/fractal_clock/` on Linux.
+pub fn data_dir() -> Option<PathBuf> {
+    app_dir(dirs::data_local_dir())
+}
+
+fn app_dir(platform_dir: Option<PathBuf>) -> Option<PathBuf> {
+    APP_DIR
+        .get()
+        .cloned()
+        .or_else(|| Some(platform_dir?.join("fractal_clock")))
+}
+
+/// Settings storage combining the eframe storage with a human-editable TOML file.
+///
+/// Values set in the file are laid over the ones in the eframe storage, so the file may only set a
+/// few of them and the rest keep what was changed in the app. A file given explicitly with
+/// `--config` is also written back on save, while the one in the platform config directory is only
+/// read, so it can be managed in dotfiles. Values are always mirrored in `table`, so the app keeps
+/// working for the session even when no storage is available at all.
+pub struct SettingsStore {
+    file: Option<PathBuf>,
+    writable: bool,
+    table: toml::Table,
+    last_written: String,
+    watcher: Option<(RecommendedWatcher, Receiver<()>)>,
+}
+
+impl SettingsStore {
+    pub fn open(explicit_file: Option<PathBuf>) -> Self {
+        let writable = explicit_file.is_some();
+        let file = explicit_file
+            .map(|path| std::path::absolute(&path).unwrap_or(path))
+            .or_else(default_config_path);
+        let table = file.as_deref().and_then(read_table).unwrap_or_default();
+
+        Self {
+            file,
+            writable,
+            table,
+            last_written: String::new(),
+            watcher: None,
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        eframe_storage: Option<&dyn eframe::Storage>,
+        key: &str,
+    ) -> Option<T> {
+        let from_file = self.table.contains_key(key);
+        let stored = eframe_storage
+            .and_then(|storage| storage.get_string(key))
+            .and_then(|text| text.parse().ok())
+            .map(toml::Value::Table);
+        // The file may only set a few values, which are laid over the stored ones
+        let value = match (stored, self.table.get(key)) {
+            (Some(mut stored), Some(file)) => {
+                migration::migrate(&mut stored);
+                let mut file = file.clone();
+                migration::migrate(&mut file);
+                merge(&mut stored, file);
+                Some(stored)
+            }
+            (stored, file) => file.cloned().or(stored),
+        };
+
+        match value {
+            Some(mut value) => {
+                migration::migrate(&mut value);
+                match value.clone().try_into() {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        // Kept, as the defaults used instead are saved over them
+                        let backup = if from_file {
+                            self.back_up_file()
+                        } else {
+                            back_up_value(key, &value)
+                        };
+                        let kept = backup
+                            .map(|path| format!(", kept in {}", path.display()))
+                            .unwrap_or_default();
+                        tracing::error!("Failed to read the saved {key}{kept}: {e}");
+                        toasts::error(format!("Failed to read the saved {key}{kept}: {e}"));
+                        None
+                    }
+                }
+            }
+            // Settings saved before they were versioned are stored as RON
+            #[cfg(feature = "persistence")]
+            None => eframe::get_value(eframe_storage?, key),
+            #[cfg(not(feature = "persistence"))]
+            None => None,
+        }
+    }
+
+    /// `current` with the values of the config file laid over it, after the file was reloaded.
+    pub fn overlay<T: Serialize + DeserializeOwned>(&self, key: &str, current: &T) -> Option<T> {
+        let mut file = self.table.get(key)?.clone();
+        migration::migrate(&mut file);
+        let result = toml::Value::try_from(current)
+            .map_err(|e| e.to_string())
+            .and_then(|mut value| {
+                migration::stamp(&mut value);
+                merge(&mut value, file);
+                value.try_into().map_err(|e: toml::de::Error| e.to_string())
+            });
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Ignoring the reloaded {key}: {e}");
+                toasts::warning(format!("Ignoring the reloaded {key}: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Copies the config file next to it as `*.bak`.
+    fn back_up_file(&self) -> Option<PathBuf> {
+        let path = self.file.as_ref()?;
+        let mut backup = path.clone().into_os_string();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        match std::fs::copy(path, &backup) {
+            Ok(_) => Some(backup),
+            Err(e) => {
+                tracing::warn!("Failed to back up {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    pub fn set<T: Serialize>(
+        &mut self,
+        eframe_storage: Option<&mut (dyn eframe::Storage + '_)>,
+        key: &str,
+        value: &T,
+    ) {
+        let mut value = match toml::Value::try_from(value) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::error!("Failed to serialize {key}: {e}");
+                toasts::error(format!("Failed to save {key}: {e}"));
+                return;
+            }
+        };
+        migration::stamp(&mut value);
+
+        if let Some(storage) = eframe_storage
+            && let Ok(text) = toml::to_string(&value)
+        {
+            storage.set_string(key, text);
+        }
+        self.table.insert(key.to_owned(), value);
+    }
+
+    /// Writes the config file, if one was given with `--config`.
+    pub fn flush(&mut self) {
+        let Some(path) = self.file.as_ref().filter(|_| self.writable) else {
+            return;
+        };
+        let result = toml::to_string_pretty(&self.table)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                std::fs::write(path, &contents).map_err(|e| e.to_string())?;
+                self.last_written = contents;
+                Ok(())
+            });
+        if let Err(e) = result {
+            tracing::error!("Failed to write config {}: {e}", path.display());
+            toasts::error(format!("Failed to write config {}: {e}", path.display()));
+        }
+    }
+
+    /// Watches the config file for external edits, repainting `ctx` when it changes.
+    pub fn watch(&mut self, ctx: &egui::Context) {
+        let Some(dir) = self.file.as_deref().and_then(Path::parent) else {
+            return;
+        };
+        // Created on the first run, so a config file added later is still picked up
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            tracing::debug!("Not watching config {}: {e}", dir.display());
+            return;
+        }
+
+        let (sender, changes) = channel();
+        let file = self.file.clone();
+        let ctx = ctx.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let touches_file = event.paths.iter().any(|path| Some(path) == file.as_ref());
+            if touches_file && (event.kind.is_create() || event.kind.is_modify()) {
+                let _ = sender.send(());
+                ctx.request_repaint();
+            }
+        })
+        .and_then(|mut watcher| {
+            // Watch the directory, as editors often replace the file instead of writing to it
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+
+        match watcher {
+            Ok(watcher) => {
+                tracing::debug!("Watching config {}", dir.display());
+                self.watcher = Some((watcher, changes));
+            }
+            Err(e) => tracing::warn!("Not watching config {}: {e}", dir.display()),
+        }
+    }
+
+    /// Re-reads the config file if it changed on disk since the last call.
+    pub fn reload(&mut self) -> bool {
+        let Some((_, changes)) = &self.watcher else {
+            return false;
+        };
+        if changes.try_iter().count() == 0 {
+            return false;
+        }
+
+        let Some(path) = &self.file else {
+            return false;
+        };
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        if contents == self.last_written {
+            return false;
+        }
+
+        match contents.parse() {
+            Ok(table) => {
+                tracing::info!("Reloaded config {}", path.display());
+                self.table = table;
+                self.last_written = contents;
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring invalid config {}: {e}", path.display());
+                toasts::warning(format!("Ignoring invalid config {}: {e}", path.display()));
+                false
+            }
+        }
+    }
+}
+
+/// Lays `overlay` over `base`, replacing its values with the ones set in `overlay` and keeping
+/// the rest, table by table.
+fn merge(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Writes a value from the eframe storage to `<key>.toml.bak` in the data directory.
+fn back_up_value(key: &str, value: &toml::Value) -> Option<PathBuf> {
+    let dir = data_dir()?;
+    let path = dir.join(format!("{key}.toml.bak"));
+    let result = toml::to_string_pretty(value)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            std::fs::write(&path, contents).map_err(|e| e.to_string())
+        });
+    match result {
+        Ok(()) => Some(path),
+        Err(e) => {
+            tracing::warn!("Failed to back up {key} to {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn read_table(path: &Path) -> Option<toml::Table> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .parse()
+            .inspect_err(|e| tracing::warn!("Ignoring invalid config {}: {e}", path.display()))
+            .ok(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            tracing::warn!("Failed to read config {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keeps_values_missing_from_the_overlay() {
+        let mut base = toml::Value::Table(toml::toml! {
+            fullscreen = false
+            [config]
+            depth = 15
+            zoom = 1.0
+        });
+        let overlay = toml::Value::Table(toml::toml! {
+            [config]
+            depth = 12
+        });
+        merge(&mut base, overlay);
+        assert_eq!(
+            base,
+            toml::Value::Table(toml::toml! {
+                fullscreen = false
+                [config]
+                depth = 12
+                zoom = 1.0
+            })
+        );
+    }
+
+    #[test]
+    fn merge_replaces_other_values() {
+        let mut base = toml::Value::Table(toml::toml! {
+            stops = [1, 2, 3]
+            color = { r = 1 }
+        });
+        let overlay = toml::Value::Table(toml::toml! {
+            stops = [4]
+            color = "red"
+            added = true
+        });
+        merge(&mut base, overlay.clone());
+        assert_eq!(base, overlay);
+    }
+}
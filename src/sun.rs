@@ -0,0 +1,160 @@
+use crate::{location::Location, time_system::TimeSystem};
+use chrono::{DateTime, Local, NaiveDate, TimeZone as _};
+use egui::{Color32, Painter, Stroke, emath::RectTransform, pos2};
+use std::f64::consts::TAU;
+
+/// Julian day of the J2000 epoch, 2000-01-01 12:00.
+const J2000: f64 = 2_451_545.0;
+/// Julian day of the Unix epoch.
+const UNIX_EPOCH: f64 = 2_440_587.5;
+/// Tilt of the Earth's axis in degrees.
+const OBLIQUITY: f64 = 23.4397;
+/// Altitude of the sun's center at sunrise, accounting for refraction and its radius.
+const HORIZON: f64 = -0.833;
+/// Markers span this fraction of the hour hand's length, centered on its tip.
+const MARKER_LENGTH: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SunTimes {
+    Rises {
+        sunrise: DateTime<Local>,
+        sunset: DateTime<Local>,
+    },
+    /// The sun stays up all day.
+    MidnightSun,
+    /// The sun stays down all day.
+    PolarNight,
+}
+
+impl SunTimes {
+    /// Sunrise and sunset on `date` at `location`, with the sunrise equation, which is accurate
+    /// to about a minute outside the polar regions.
+    pub fn compute(date: NaiveDate, location: Location) -> Self {
+        let days = (date - NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default()).num_days();
+        let solar_noon = days as f64 - location.longitude / 360.0;
+        let anomaly = (357.5291 + 0.985_600_28 * solar_noon)
+            .rem_euclid(360.0)
+            .to_radians();
+        let center =
+            1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+        let ecliptic_longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+            .rem_euclid(360.0)
+            .to_radians();
+        let transit =
+            J2000 + solar_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+        let declination = (ecliptic_longitude.sin() * OBLIQUITY.to_radians().sin()).asin();
+
+        let latitude = location.latitude.to_radians();
+        let cos_hour_angle = (HORIZON.to_radians().sin() - latitude.sin() * declination.sin())
+            / (latitude.cos() * declination.cos());
+        if cos_hour_angle > 1.0 {
+            return Self::PolarNight;
+        }
+        if cos_hour_angle < -1.0 {
+            return Self::MidnightSun;
+        }
+        let half_day = cos_hour_angle.acos() / TAU;
+        match (
+            local_time(transit - half_day),
+            local_time(transit + half_day),
+        ) {
+            (Some(sunrise), Some(sunset)) => Self::Rises { sunrise, sunset },
+            _ => Self::PolarNight,
+        }
+    }
+}
+
+fn local_time(julian_day: f64) -> Option<DateTime<Local>> {
+    let millis = ((julian_day - UNIX_EPOCH) * 86_400_000.0) as i64;
+    Local.timestamp_millis_opt(millis).single()
+}
+
+// Sunrise and sunset marker settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SunMarkers {
+    pub enabled: bool,
+    pub sunrise_color: Color32,
+    pub sunset_color: Color32,
+}
+
+impl Default for SunMarkers {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sunrise_color: Color32::from_rgb(255, 180, 60),
+            sunset_color: Color32::from_rgb(120, 90, 220),
+        }
+    }
+}
+
+impl SunMarkers {
+    pub fn ui(&mut self, ui: &mut egui::Ui, sun: &Sun) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "Sunrise and sunset");
+            ui.color_edit_button_srgba(&mut self.sunrise_color);
+            ui.color_edit_button_srgba(&mut self.sunset_color);
+        });
+        if self.enabled {
+            let text = match sun.times {
+                Some((_, _, SunTimes::Rises { sunrise, sunset })) => format!(
+                    "Sunrise {}, sunset {}",
+                    sunrise.format("%H:%M"),
+                    sunset.format("%H:%M")
+                ),
+                Some((_, _, SunTimes::MidnightSun)) => "Midnight sun".to_owned(),
+                Some((_, _, SunTimes::PolarNight)) => "Polar night".to_owned(),
+                None => String::new(),
+            };
+            ui.weak(text);
+        }
+    }
+
+    /// Paints ticks across the circle of the hour hand's tip where it points at sunrise and
+    /// sunset.
+    pub fn paint(
+        &self,
+        painter: &Painter,
+        to_screen: &RectTransform,
+        times: SunTimes,
+        hand: f32,
+        system: TimeSystem,
+        location: Location,
+    ) {
+        let SunTimes::Rises { sunrise, sunset } = times else {
+            return;
+        };
+        for (time, color) in [(sunrise, self.sunrise_color), (sunset, self.sunset_color)] {
+            let [_, _, turns] = system.hand_turns(&time, location);
+            let angle = std::f32::consts::TAU * turns as f32 - std::f32::consts::FRAC_PI_2;
+            let dir = egui::Vec2::angled(angle);
+            let [inner, outer] = [1.0 - MARKER_LENGTH / 2.0, 1.0 + MARKER_LENGTH / 2.0]
+                .map(|factor| to_screen * (pos2(0.0, 0.0) + dir * hand * factor));
+            painter.line_segment([inner, outer], Stroke::new(3.0, color));
+        }
+    }
+}
+
+/// Sun times of the current day, recomputed after midnight or when the location changes.
+#[derive(Default, PartialEq)]
+pub struct Sun {
+    times: Option<(NaiveDate, Location, SunTimes)>,
+}
+
+impl Sun {
+    pub fn times(&mut self, now: &DateTime<Local>, location: Location) -> SunTimes {
+        let date = now.date_naive();
+        match self.times {
+            Some((computed_date, computed_location, times))
+                if computed_date == date && computed_location == location =>
+            {
+                times
+            }
+            _ => {
+                let times = SunTimes::compute(date, location);
+                self.times = Some((date, location, times));
+                times
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use egui::{Pos2, Response, pos2};
+use std::ops::{Add, Mul};
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+/// A rotation in 3D, the counterpart of the 2D rotors branches are rotated with.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Quat {
+    w: f32,
+    v: Vec3,
+}
+
+impl Quat {
+    /// Rotation by `angle` radians around the unit vector `axis`.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Self {
+            w: cos,
+            v: axis * sin,
+        }
+    }
+
+    pub fn rotate(self, vec: Vec3) -> Vec3 {
+        // v' = v + 2w (q × v) + 2 q × (q × v)
+        let t = self.v.cross(vec) * 2.0;
+        vec + t * self.w + self.v.cross(t)
+    }
+
+    /// Keeps the rotation from drifting away from unit length after many multiplications.
+    fn normalized(self) -> Self {
+        let length =
+            (self.w * self.w + self.v.x * self.v.x + self.v.y * self.v.y + self.v.z * self.v.z)
+                .sqrt()
+                .max(f32::EPSILON);
+        Self {
+            w: self.w / length,
+            v: self.v * (1.0 / length),
+        }
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+
+    /// Rotation by `other` followed by `self`.
+    fn mul(self, other: Self) -> Self {
+        Self {
+            w: self.w * other.w
+                - (self.v.x * other.v.x + self.v.y * other.v.y + self.v.z * other.v.z),
+            v: other.v * self.w + self.v * other.w + self.v.cross(other.v),
+        }
+        .normalized()
+    }
+}
+
+// 3D mode settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ThreeDConfig {
+    pub enabled: bool,
+    /// Angle in radians the branches roll out of the plane of their parent, in opposite directions
+    /// for the second and minute branches.
+    pub tilt: f32,
+    /// Camera rotation around the vertical axis, in radians.
+    pub yaw: f32,
+    /// Camera rotation around the horizontal axis, in radians.
+    pub pitch: f32,
+    /// Distance of the camera from the center, in radii of the tree.
+    pub distance: f32,
+}
+
+impl Default for ThreeDConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tilt: 0.5,
+            yaw: 0.4,
+            pitch: -0.3,
+            distance: 3.0,
+        }
+    }
+}
+
+impl ThreeDConfig {
+    /// Rotations of a branch into its two children, bending by `angles` within the plane rolled
+    /// by the tilt.
+    pub fn rotors(&self, angles: [f32; 2]) -> [Quat; 2] {
+        let [second, minute] = angles;
+        [(second, self.tilt), (minute, -self.tilt)].map(|(angle, tilt)| {
+            Quat::from_axis_angle(Vec3::X, tilt) * Quat::from_axis_angle(Vec3::Z, angle)
+        })
+    }
+
+    pub fn camera(&self, radius: f32) -> Camera {
+        Camera {
+            rotation: Quat::from_axis_angle(Vec3::X, self.pitch)
+                * Quat::from_axis_angle(Vec3::Y, self.yaw),
+            distance: self.distance.max(1.0) * radius.max(f32::EPSILON),
+        }
+    }
+
+    /// Orbits the camera around the clock while dragging the canvas.
+    pub fn orbit(&mut self, canvas: &Response) {
+        if canvas.dragged() {
+            let delta = canvas.drag_delta() * 0.01;
+            self.yaw -= delta.x;
+            self.pitch = (self.pitch + delta.y).clamp(-1.55, 1.55);
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "3D mode (experimental)")
+            .on_hover_text("Drag to orbit the camera");
+        ui.horizontal(|ui| {
+            ui.drag_angle(&mut self.tilt);
+            ui.label("tilt");
+        });
+        ui.add(egui::Slider::new(&mut self.distance, 1.5..=10.0).text("camera distance"));
+        if ui.button("Reset camera").clicked() {
+            let default = Self::default();
+            self.yaw = default.yaw;
+            self.pitch = default.pitch;
+            self.distance = default.distance;
+        }
+    }
+}
+
+/// Perspective camera looking at the center of the clock.
+pub struct Camera {
+    rotation: Quat,
+    distance: f32,
+}
+
+/// A point as seen by the camera.
+pub struct Projected {
+    /// Position in the 2D fractal space, matching the classic mode in the plane of the clock.
+    pub pos: Pos2,
+    /// Distance behind the plane through the center, used to draw far lines first.
+    pub depth: f32,
+    /// Perspective scale, applied to line widths.
+    pub scale: f32,
+}
+
+impl Camera {
+    /// Projects `point`, or `None` if it is behind the camera.
+    pub fn project(&self, point: Vec3) -> Option<Projected> {
+        let view = self.rotation.rotate(point);
+        let z = self.distance + view.z;
+        if z < self.distance * 0.01 {
+            return None;
+        }
+        let scale = self.distance / z;
+        Some(Projected {
+            pos: pos2(view.x * scale, view.y * scale),
+            depth: view.z,
+            scale,
+        })
+    }
+}
+
+/// A branch of the 3D tree, whose children are rotated relative to `orientation`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Branch {
+    /// End of the branch, where its children grow from.
+    pub end: Vec3,
+    pub orientation: Quat,
+    pub length: f32,
+}
+
+impl Branch {
+    pub fn dir(&self) -> Vec3 {
+        self.orientation.rotate(Vec3::X) * self.length
+    }
+}
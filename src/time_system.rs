@@ -0,0 +1,148 @@
+use crate::location::Location;
+use chrono::{DateTime, Local, Timelike as _};
+
+/// Julian day of the J2000 epoch, 2000-01-01 12:00.
+const J2000: f64 = 2_451_545.0;
+/// Julian day of the Unix epoch.
+const UNIX_EPOCH: f64 = 2_440_587.5;
+/// Terrestrial time minus UTC in seconds, for the Mars time equations.
+const TT_OFFSET: f64 = 69.184;
+
+/// How the time of day is divided up into the turns of the hands.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum TimeSystem {
+    Standard,
+    /// Standard hands, with the hours, minutes and seconds shown in binary.
+    Binary,
+    /// The day split into 16 hex hours of 256 hex minutes of 16 hex seconds.
+    Hexadecimal,
+    /// French revolutionary time, with 10 hours of 100 minutes of 100 seconds a day.
+    Decimal,
+    /// Local sidereal time, following the stars, with a day about 4 minutes shorter.
+    Sidereal,
+    /// Coordinated Mars Time, the mean solar time at Mars' prime meridian, with a day of about
+    /// 24 hours and 40 minutes.
+    Mars,
+    /// Swatch Internet Time, with the day in Biel (UTC+1) split into 1000 beats.
+    Beat,
+    /// Standard hands, with the seconds since the Unix epoch shown.
+    Unix,
+}
+
+impl TimeSystem {
+    pub const ALL: [Self; 8] = [
+        Self::Standard,
+        Self::Binary,
+        Self::Hexadecimal,
+        Self::Decimal,
+        Self::Sidereal,
+        Self::Mars,
+        Self::Beat,
+        Self::Unix,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Binary => "Binary",
+            Self::Hexadecimal => "Hexadecimal",
+            Self::Decimal => "Decimal",
+            Self::Sidereal => "Sidereal",
+            Self::Mars => "Mars (MTC)",
+            Self::Beat => "Internet Time (.beat)",
+            Self::Unix => "Unix time",
+        }
+    }
+
+    /// Fraction of the day elapsed, where the day is a sidereal day or a Mars sol for those
+    /// systems. Sidereal time depends on the longitude of `location`.
+    fn day_fraction(self, time: &DateTime<Local>, location: Location) -> f64 {
+        match self {
+            Self::Standard | Self::Binary | Self::Hexadecimal | Self::Decimal | Self::Unix => {
+                let seconds =
+                    time.num_seconds_from_midnight() as f64 + time.nanosecond() as f64 / 1e9;
+                seconds / 86_400.0
+            }
+            Self::Sidereal => {
+                // Greenwich mean sidereal time in hours, shifted by an hour every 15°
+                let days = julian_day(time) - J2000;
+                let hours = 18.697_374_558 + 24.065_709_824_419_08 * days;
+                (hours / 24.0 + location.longitude / 360.0).rem_euclid(1.0)
+            }
+            Self::Beat => {
+                let seconds = time.timestamp_millis() as f64 / 1000.0 + 3600.0;
+                (seconds / 86_400.0).rem_euclid(1.0)
+            }
+            Self::Mars => {
+                // Mars sol date
+                let sols = (julian_day(time) + TT_OFFSET / 86_400.0 - 2_405_522.002_877_9)
+                    / 1.027_491_251_7;
+                sols.rem_euclid(1.0)
+            }
+        }
+    }
+
+    /// Fractions of a turn of the second, minute and hour hands.
+    pub fn hand_turns(self, time: &DateTime<Local>, location: Location) -> [f64; 3] {
+        let day = self.day_fraction(time, location);
+        let turns = match self {
+            Self::Standard | Self::Binary | Self::Unix => [day * 1440.0, day * 24.0, day * 2.0],
+            // The hour hand goes around once a day, on a dial of 16 or 10 hours
+            Self::Hexadecimal => [day * 4096.0, day * 16.0, day],
+            // For beats, a turn of the second hand per beat and of the minute hand per 100 beats
+            Self::Decimal | Self::Beat => [day * 1000.0, day * 10.0, day],
+            // A dial of 24 of the longer or shorter hours, as they drift from the solar day
+            Self::Sidereal | Self::Mars => [day * 1440.0, day * 24.0, day],
+        };
+        turns.map(f64::fract)
+    }
+
+    /// Digital time in this system.
+    pub fn format(self, time: &DateTime<Local>, location: Location) -> String {
+        let day = self.day_fraction(time, location);
+        match self {
+            Self::Standard => time.format("%H:%M:%S%.3f").to_string(),
+            Self::Binary => format!(
+                "{:05b}:{:06b}:{:06b}",
+                time.hour(),
+                time.minute(),
+                time.second()
+            ),
+            Self::Hexadecimal => {
+                let units = (day * 65_536.0) as u32;
+                format!(
+                    "{:X}_{:02X}_{:X}",
+                    units >> 12,
+                    (units >> 4) & 0xFF,
+                    units & 0xF
+                )
+            }
+            Self::Decimal => {
+                let seconds = (day * 100_000.0) as u32;
+                format!(
+                    "{}:{:02}:{:02}",
+                    seconds / 10_000,
+                    seconds / 100 % 100,
+                    seconds % 100
+                )
+            }
+            Self::Beat => format!("@{:06.2}", day * 1000.0),
+            Self::Unix => format!("{:.3}", time.timestamp_millis() as f64 / 1000.0),
+            Self::Sidereal | Self::Mars => {
+                let seconds = (day * 86_400.0) as u32;
+                let name = if self == Self::Sidereal { "LST" } else { "MTC" };
+                format!(
+                    "{name} {:02}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60
+                )
+            }
+        }
+    }
+}
+
+fn julian_day(time: &DateTime<Local>) -> f64 {
+    let millis = time.timestamp_millis();
+    millis as f64 / 86_400_000.0 + UNIX_EPOCH
+}
@@ -0,0 +1,313 @@
+use image::{
+    Delay, Frame, RgbaImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{Receiver, TryRecvError, channel},
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TimelapseAction {
+    Start,
+    Stop,
+    AssembleGif,
+    AssembleVideo,
+}
+
+// Timelapse settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct TimelapseConfig {
+    /// Real seconds between captured frames.
+    pub interval_seconds: u32,
+    /// Width and height of the frames in pixels.
+    pub size: u32,
+    /// Frames per second of the assembled GIF or video.
+    pub fps: u32,
+}
+
+impl Default for TimelapseConfig {
+    fn default() -> Self {
+        Self {
+            interval_seconds: 60,
+            size: 720,
+            fps: 24,
+        }
+    }
+}
+
+impl TimelapseConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, timelapse: &Timelapse) -> Option<TimelapseAction> {
+        let recording = timelapse.recording.as_ref();
+        ui.add_enabled_ui(recording.is_none(), |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.interval_seconds, 1..=3600)
+                    .logarithmic(true)
+                    .text("seconds per frame"),
+            );
+            ui.add(egui::Slider::new(&mut self.size, 128..=2160).text("frame size"));
+        });
+        ui.add(egui::Slider::new(&mut self.fps, 1..=60).text("playback FPS"));
+
+        let mut action = None;
+        if let Some(recording) = recording {
+            ui.label(format!(
+                "Captured {} frames into {}",
+                recording.frames,
+                recording.dir.display()
+            ));
+            if ui.button("⏹ Stop").clicked() {
+                action = Some(TimelapseAction::Stop);
+            }
+        } else if ui.button("⏺ Start timelapse").clicked() {
+            action = Some(TimelapseAction::Start);
+        }
+
+        if timelapse.assembling.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Assembling…");
+            });
+        } else if let Some(dir) = &timelapse.finished {
+            ui.weak(format!("Frames in {}", dir.display()));
+            ui.horizontal(|ui| {
+                if ui.button("Make GIF").clicked() {
+                    action = Some(TimelapseAction::AssembleGif);
+                }
+                if ui
+                    .button("Make video")
+                    .on_hover_text("Requires ffmpeg on the PATH")
+                    .clicked()
+                {
+                    action = Some(TimelapseAction::AssembleVideo);
+                }
+            });
+        }
+        action
+    }
+}
+
+struct Recording {
+    dir: PathBuf,
+    frames: usize,
+    next: Instant,
+}
+
+/// Captures a frame of the clock every few real seconds into a directory of PNG files, which can
+/// then be assembled into a sped-up GIF or video of a whole day.
+#[derive(Default)]
+pub struct Timelapse {
+    recording: Option<Recording>,
+    /// Directory of the last recording.
+    finished: Option<PathBuf>,
+    assembling: Option<Receiver<Result<PathBuf, String>>>,
+}
+
+impl PartialEq for Timelapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.recording.as_ref().map(|recording| &recording.dir)
+            == other.recording.as_ref().map(|recording| &recording.dir)
+            && self.finished == other.finished
+    }
+}
+
+impl Timelapse {
+    /// Starts capturing into a new directory in the downloads or home directory.
+    pub fn start(&mut self) -> std::io::Result<()> {
+        let dir = frames_dir("timelapse")?;
+        tracing::info!("Recording a timelapse into {}", dir.display());
+        self.recording = Some(Recording {
+            dir,
+            frames: 0,
+            next: Instant::now(),
+        });
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(recording) = self.recording.take()
+            && recording.frames > 0
+        {
+            self.finished = Some(recording.dir);
+        }
+    }
+
+    /// Whether the next frame should be captured now, otherwise scheduling a repaint for it.
+    pub fn due(&self, ctx: &egui::Context) -> bool {
+        let Some(recording) = &self.recording else {
+            return false;
+        };
+        let remaining = recording.next.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        ctx.request_repaint_after(remaining);
+        false
+    }
+
+    /// Writes the captured `image` on a background thread and schedules the next frame.
+    pub fn save(&mut self, image: RgbaImage, config: &TimelapseConfig) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        let path = frame_path(&recording.dir, recording.frames);
+        std::thread::spawn(move || {
+            if let Err(e) = image.save(&path) {
+                tracing::error!("Failed to save timelapse frame {}: {e}", path.display());
+            }
+        });
+        recording.frames += 1;
+        // Skips the frames missed while the app was suspended instead of catching up
+        let interval = Duration::from_secs(config.interval_seconds.max(1).into());
+        recording.next = (recording.next + interval).max(Instant::now());
+    }
+
+    /// Assembles the frames of the last recording on a background thread.
+    pub fn assemble(&mut self, ctx: &egui::Context, video: bool, fps: u32) {
+        let Some(dir) = self.finished.clone() else {
+            return;
+        };
+        let (sender, receiver) = channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = if video {
+                assemble_video(&dir, fps)
+            } else {
+                assemble_gif(&dir, fps)
+            };
+            let _ = sender.send(result);
+            ctx.request_repaint();
+        });
+        self.assembling = Some(receiver);
+    }
+
+    /// The result of assembling, once it finished.
+    pub fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        let receiver = self.assembling.as_ref()?;
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.assembling = None;
+                Some(result)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.assembling = None;
+                Some(Err("Assembling stopped unexpectedly".to_owned()))
+            }
+        }
+    }
+}
+
+/// Renders the frames of a clip on a background thread, which assembles them into a GIF or video.
+#[derive(Default)]
+pub struct ClipRender {
+    pending: Option<Receiver<Result<PathBuf, String>>>,
+}
+
+impl PartialEq for ClipRender {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_rendering() == other.is_rendering()
+    }
+}
+
+impl ClipRender {
+    pub fn is_rendering(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Runs `render` on a background thread, which returns the assembled file.
+    pub fn start(
+        &mut self,
+        ctx: &egui::Context,
+        render: impl FnOnce() -> Result<PathBuf, String> + Send + 'static,
+    ) {
+        let (sender, receiver) = channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(render());
+            ctx.request_repaint();
+        });
+        self.pending = Some(receiver);
+    }
+
+    /// The assembled file or the error, once rendering finished.
+    pub fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        let result = match self.pending.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("Rendering stopped unexpectedly".to_owned()),
+        };
+        self.pending = None;
+        Some(result)
+    }
+}
+
+/// Creates a new directory for frames in the downloads or home directory, named after `kind` and
+/// the current time.
+pub fn frames_dir(kind: &str) -> std::io::Result<PathBuf> {
+    let parent = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| std::io::Error::other("no downloads or home directory"))?;
+    let time = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let dir = parent.join(format!("fractal_clock_{kind}_{time}"));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+pub fn frame_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("frame_{index:05}.png"))
+}
+
+/// Assembles the numbered frames in `dir` into a GIF next to it.
+pub fn assemble_gif(dir: &Path, fps: u32) -> Result<PathBuf, String> {
+    let path = dir.with_extension("gif");
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut encoder = GifEncoder::new_with_speed(file, 10);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| e.to_string())?;
+    let delay = Delay::from_numer_denom_ms(1000, fps.max(1));
+    for index in 0.. {
+        let frame = frame_path(dir, index);
+        if !frame.exists() {
+            break;
+        }
+        let image = image::open(&frame).map_err(|e| e.to_string())?;
+        encoder
+            .encode_frame(Frame::from_parts(image.into_rgba8(), 0, 0, delay))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(path)
+}
+
+/// Assembles the numbered frames in `dir` into an MP4 video next to it with ffmpeg.
+pub fn assemble_video(dir: &Path, fps: u32) -> Result<PathBuf, String> {
+    let path = dir.with_extension("mp4");
+    let output = Command::new("ffmpeg")
+        .arg("-y")
+        .args(["-framerate", &fps.max(1).to_string()])
+        .arg("-i")
+        .arg(dir.join("frame_%05d.png"))
+        // Most players only decode 4:2:0, which needs even dimensions
+        .args([
+            "-pix_fmt",
+            "yuv420p",
+            "-vf",
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+        ])
+        .arg(&path)
+        .output()
+        .map_err(|e| format!("Failed to run ffmpeg: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg failed: {}",
+            stderr.lines().last().unwrap_or_default()
+        ));
+    }
+    Ok(path)
+}
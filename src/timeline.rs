@@ -0,0 +1,454 @@
+#[cfg(feature = "export")]
+use crate::timelapse::ClipRender;
+use crate::{
+    fractal_clock::{
+        BRANCH_SCALE_RANGE, DEPTH_RANGE, FACTOR_RANGE, FractalClockConfig, GRAVITY_RANGE,
+        SPIRAL_RANGE, START_LINE_WIDTH_RANGE, ZOOM_RANGE,
+    },
+    render_scale::RENDER_SCALE_RANGE,
+};
+use egui::{Color32, Sense, Shape, Stroke, pos2, vec2};
+use std::ops::RangeInclusive;
+
+const STRIP_HEIGHT: f32 = 18.0;
+const KEYFRAME_RADIUS: f32 = 5.0;
+
+/// A setting that can be keyframed.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum Parameter {
+    Zoom,
+    RenderScale,
+    StartLineWidth,
+    Gravity,
+    Spiral,
+    Depth,
+    LengthFactor,
+    SecondScale,
+    MinuteScale,
+    LuminanceFactor,
+    WidthFactor,
+}
+
+impl Parameter {
+    pub const ALL: [Self; 11] = [
+        Self::Zoom,
+        Self::RenderScale,
+        Self::StartLineWidth,
+        Self::Gravity,
+        Self::Spiral,
+        Self::Depth,
+        Self::LengthFactor,
+        Self::SecondScale,
+        Self::MinuteScale,
+        Self::LuminanceFactor,
+        Self::WidthFactor,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Zoom => "Zoom",
+            Self::RenderScale => "Render scale",
+            Self::StartLineWidth => "Line width",
+            Self::Gravity => "Gravity",
+            Self::Spiral => "Spiral",
+            Self::Depth => "Depth",
+            Self::LengthFactor => "Length factor",
+            Self::SecondScale => "Second branch scale",
+            Self::MinuteScale => "Minute branch scale",
+            Self::LuminanceFactor => "Luminance factor",
+            Self::WidthFactor => "Width factor",
+        }
+    }
+
+    pub fn range(self) -> RangeInclusive<f32> {
+        match self {
+            Self::Zoom => ZOOM_RANGE,
+            Self::RenderScale => RENDER_SCALE_RANGE,
+            Self::StartLineWidth => START_LINE_WIDTH_RANGE,
+            Self::Gravity => GRAVITY_RANGE,
+            Self::Spiral => SPIRAL_RANGE,
+            Self::Depth => *DEPTH_RANGE.start() as f32..=*DEPTH_RANGE.end() as f32,
+            Self::LengthFactor | Self::LuminanceFactor | Self::WidthFactor => FACTOR_RANGE,
+            Self::SecondScale | Self::MinuteScale => BRANCH_SCALE_RANGE,
+        }
+    }
+
+    pub fn get(self, config: &FractalClockConfig) -> f32 {
+        match self {
+            Self::Zoom => config.zoom(),
+            Self::RenderScale => config.render_scale(),
+            Self::StartLineWidth => config.start_line_width(),
+            Self::Gravity => config.gravity(),
+            Self::Spiral => config.spiral(),
+            Self::Depth => config.depth() as f32,
+            Self::LengthFactor => config.length_factor(),
+            Self::SecondScale => config.second_scale(),
+            Self::MinuteScale => config.minute_scale(),
+            Self::LuminanceFactor => config.luminance_factor(),
+            Self::WidthFactor => config.width_factor(),
+        }
+    }
+
+    pub fn set(self, config: &mut FractalClockConfig, value: f32) {
+        match self {
+            Self::Zoom => config.set_zoom(value),
+            Self::RenderScale => config.set_render_scale(value),
+            Self::StartLineWidth => config.set_start_line_width(value),
+            Self::Gravity => config.set_gravity(value),
+            Self::Spiral => config.set_spiral(value),
+            Self::Depth => config.set_depth(value.round().max(0.0) as usize),
+            Self::LengthFactor => config.set_length_factor(value),
+            Self::SecondScale => config.set_second_scale(value),
+            Self::MinuteScale => config.set_minute_scale(value),
+            Self::LuminanceFactor => config.set_luminance_factor(value),
+            Self::WidthFactor => config.set_width_factor(value),
+        }
+    }
+}
+
+/// How the value moves from a keyframe to the next.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Keeps the value until the next keyframe.
+    Hold,
+}
+
+impl Easing {
+    const ALL: [Self; 5] = [
+        Self::Linear,
+        Self::EaseIn,
+        Self::EaseOut,
+        Self::EaseInOut,
+        Self::Hold,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::EaseIn => "Ease in",
+            Self::EaseOut => "Ease out",
+            Self::EaseInOut => "Ease in/out",
+            Self::Hold => "Hold",
+        }
+    }
+
+    /// Fraction of the way to the next value at `t` of the time to it.
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Self::Hold => 0.0,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct Keyframe {
+    pub seconds: f32,
+    pub value: f32,
+    /// Easing towards the next keyframe.
+    pub easing: Easing,
+}
+
+/// The keyframes of one parameter, sorted by time.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct Track {
+    pub parameter: Parameter,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Value at `seconds`, holding the first and last keyframes before and after them.
+    fn value_at(&self, seconds: f32) -> Option<f32> {
+        let next = self.keyframes.partition_point(|key| key.seconds <= seconds);
+        let Some(previous) = next.checked_sub(1).map(|index| &self.keyframes[index]) else {
+            return self.keyframes.first().map(|key| key.value);
+        };
+        let Some(next) = self.keyframes.get(next) else {
+            return Some(previous.value);
+        };
+        let span = (next.seconds - previous.seconds).max(f32::EPSILON);
+        let t = previous.easing.apply((seconds - previous.seconds) / span);
+        Some(previous.value + (next.value - previous.value) * t)
+    }
+
+    /// Adds a keyframe at `seconds`, replacing one at the same time.
+    fn insert(&mut self, seconds: f32, value: f32) {
+        let index = self.keyframes.partition_point(|key| key.seconds < seconds);
+        match self.keyframes.get_mut(index) {
+            Some(key) if (key.seconds - seconds).abs() < 0.01 => key.value = value,
+            _ => self.keyframes.insert(
+                index,
+                Keyframe {
+                    seconds,
+                    value,
+                    easing: Easing::EaseInOut,
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "export")]
+#[derive(Clone, Copy, PartialEq)]
+pub enum TimelineAction {
+    RenderGif,
+    RenderVideo,
+}
+
+// Keyframe timeline settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct TimelineConfig {
+    pub duration_seconds: f32,
+    /// Starts over at the end when previewing.
+    pub looping: bool,
+    pub tracks: Vec<Track>,
+    /// Width and height of the rendered frames in pixels.
+    pub size: u32,
+    pub fps: u32,
+}
+
+impl Default for TimelineConfig {
+    fn default() -> Self {
+        Self {
+            duration_seconds: 10.0,
+            looping: true,
+            tracks: Vec::new(),
+            size: 720,
+            fps: 30,
+        }
+    }
+}
+
+impl TimelineConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, timeline: &mut Timeline, config: &FractalClockConfig) {
+        ui.add(
+            egui::Slider::new(&mut self.duration_seconds, 1.0..=600.0)
+                .logarithmic(true)
+                .suffix(" s")
+                .text("duration"),
+        );
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut timeline.previewing, "Preview")
+                .on_hover_text("Drives the keyframed settings from the playhead");
+            let label = if timeline.playing { "⏸" } else { "▶" };
+            if ui.button(label).clicked() {
+                timeline.playing = !timeline.playing;
+                timeline.previewing |= timeline.playing;
+            }
+            if ui.button("⏮").clicked() {
+                timeline.playhead = 0.0;
+            }
+            ui.checkbox(&mut self.looping, "Loop");
+        });
+        ui.add(
+            egui::Slider::new(&mut timeline.playhead, 0.0..=self.duration_seconds)
+                .suffix(" s")
+                .text("playhead"),
+        );
+
+        let mut removed = None;
+        for (index, track) in self.tracks.iter_mut().enumerate() {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.strong(track.parameter.label());
+                if ui
+                    .button("＋ Key")
+                    .on_hover_text("Keys the current value at the playhead")
+                    .clicked()
+                {
+                    track.insert(timeline.playhead, track.parameter.get(config));
+                }
+                if ui.button("🗑").on_hover_text("Remove the track").clicked() {
+                    removed = Some(index);
+                }
+            });
+            strip_ui(ui, track, &mut timeline.playhead, self.duration_seconds);
+            keyframes_ui(ui, track, self.duration_seconds);
+        }
+        if let Some(index) = removed {
+            self.tracks.remove(index);
+        }
+
+        ui.separator();
+        let mut added = None;
+        egui::ComboBox::from_id_salt("timeline_add_track")
+            .selected_text("Add track")
+            .show_ui(ui, |ui| {
+                for parameter in Parameter::ALL {
+                    let used = self.tracks.iter().any(|track| track.parameter == parameter);
+                    if ui
+                        .add_enabled(!used, egui::Button::new(parameter.label()))
+                        .clicked()
+                    {
+                        added = Some(parameter);
+                    }
+                }
+            });
+        if let Some(parameter) = added {
+            let mut track = Track {
+                parameter,
+                keyframes: Vec::new(),
+            };
+            track.insert(timeline.playhead, parameter.get(config));
+            self.tracks.push(track);
+        }
+    }
+
+    #[cfg(feature = "export")]
+    pub fn render_ui(&mut self, ui: &mut egui::Ui, render: &ClipRender) -> Option<TimelineAction> {
+        ui.add_enabled_ui(!self.tracks.is_empty(), |ui| {
+            ui.add(egui::Slider::new(&mut self.size, 128..=2160).text("frame size"));
+            ui.add(egui::Slider::new(&mut self.fps, 1..=60).text("FPS"));
+            if render.is_rendering() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Rendering the timeline…");
+                });
+                return None;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Render GIF").clicked() {
+                    Some(TimelineAction::RenderGif)
+                } else if ui
+                    .button("Render video")
+                    .on_hover_text("Requires ffmpeg on the PATH")
+                    .clicked()
+                {
+                    Some(TimelineAction::RenderVideo)
+                } else {
+                    None
+                }
+            })
+            .inner
+        })
+        .inner
+    }
+
+    /// Sets the keyframed settings of `config` to their values at `seconds`.
+    pub fn apply(&self, seconds: f32, config: &mut FractalClockConfig) {
+        for track in &self.tracks {
+            if let Some(value) = track.value_at(seconds) {
+                track.parameter.set(config, value);
+            }
+        }
+    }
+
+    #[cfg(feature = "export")]
+    pub fn frame_count(&self) -> u32 {
+        ((self.duration_seconds * self.fps as f32).round() as u32).max(1)
+    }
+}
+
+/// The keyframes of `track` over the duration, moving the playhead when clicked or dragged.
+fn strip_ui(ui: &mut egui::Ui, track: &Track, playhead: &mut f32, duration: f32) {
+    let size = vec2(ui.available_width(), STRIP_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+    let x = |seconds: f32| rect.left() + rect.width() * (seconds / duration).clamp(0.0, 1.0);
+    if let Some(pointer) = response.interact_pointer_pos() {
+        *playhead = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0) * duration;
+    }
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    for key in &track.keyframes {
+        let center = pos2(x(key.seconds), rect.center().y);
+        let diamond = [
+            center - vec2(0.0, KEYFRAME_RADIUS),
+            center + vec2(KEYFRAME_RADIUS, 0.0),
+            center + vec2(0.0, KEYFRAME_RADIUS),
+            center - vec2(KEYFRAME_RADIUS, 0.0),
+        ];
+        painter.add(Shape::convex_polygon(
+            diamond.to_vec(),
+            visuals.selection.bg_fill,
+            Stroke::NONE,
+        ));
+    }
+    painter.vline(
+        x(*playhead),
+        rect.y_range(),
+        Stroke::new(1.5, Color32::from_rgb(230, 80, 60)),
+    );
+}
+
+fn keyframes_ui(ui: &mut egui::Ui, track: &mut Track, duration: f32) {
+    let range = track.parameter.range();
+    let mut removed = None;
+    let mut moved = false;
+    for (index, key) in track.keyframes.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            moved |= ui
+                .add(
+                    egui::DragValue::new(&mut key.seconds)
+                        .range(0.0..=duration)
+                        .speed(0.05)
+                        .suffix(" s"),
+                )
+                .changed();
+            ui.add(
+                egui::DragValue::new(&mut key.value)
+                    .range(range.clone())
+                    .speed(0.01),
+            );
+            egui::ComboBox::from_id_salt((track.parameter.label(), index))
+                .selected_text(key.easing.label())
+                .show_ui(ui, |ui| {
+                    for easing in Easing::ALL {
+                        ui.selectable_value(&mut key.easing, easing, easing.label());
+                    }
+                });
+            if ui.small_button("✖").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        track.keyframes.remove(index);
+    }
+    if moved {
+        track
+            .keyframes
+            .sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+    }
+}
+
+/// Playhead of the timeline preview.
+#[derive(Default, PartialEq)]
+pub struct Timeline {
+    pub previewing: bool,
+    pub playing: bool,
+    pub playhead: f32,
+}
+
+impl Timeline {
+    /// Moves the playhead by the time since the last frame while playing, returning whether the
+    /// settings follow the timeline.
+    pub fn advance(&mut self, ctx: &egui::Context, config: &TimelineConfig) -> bool {
+        if !self.previewing || config.tracks.is_empty() {
+            self.playing = false;
+            return false;
+        }
+        if self.playing {
+            self.playhead += ctx.input(|i| i.stable_dt);
+            if self.playhead > config.duration_seconds {
+                if config.looping {
+                    self.playhead %= config.duration_seconds.max(f32::EPSILON);
+                } else {
+                    self.playhead = config.duration_seconds;
+                    self.playing = false;
+                }
+            }
+            ctx.request_repaint();
+        }
+        true
+    }
+}
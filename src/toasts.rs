@@ -0,0 +1,135 @@
+use egui::{Align2, Color32, Frame, Id, Order, RichText, Sense, Vec2};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const INFO_DURATION: Duration = Duration::from_secs(4);
+const ERROR_DURATION: Duration = Duration::from_secs(8);
+const MAX_TOASTS: usize = 5;
+
+static TOASTS: Mutex<Vec<Toast>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Copy, PartialEq)]
+enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+struct Toast {
+    kind: ToastKind,
+    text: String,
+    shown_at: Option<Instant>,
+}
+
+/// Queues a short notification, shown in the bottom right corner of the window.
+pub fn info(text: impl Into<String>) {
+    push(ToastKind::Info, text.into());
+}
+
+pub fn success(text: impl Into<String>) {
+    push(ToastKind::Success, text.into());
+}
+
+pub fn warning(text: impl Into<String>) {
+    push(ToastKind::Warning, text.into());
+}
+
+pub fn error(text: impl Into<String>) {
+    push(ToastKind::Error, text.into());
+}
+
+fn push(kind: ToastKind, text: String) {
+    let Ok(mut toasts) = TOASTS.lock() else {
+        return;
+    };
+    // The same problem reported every frame is shown once
+    if toasts
+        .iter()
+        .any(|toast| toast.kind == kind && toast.text == text)
+    {
+        return;
+    }
+    if toasts.len() == MAX_TOASTS {
+        toasts.remove(0);
+    }
+    toasts.push(Toast {
+        kind,
+        text,
+        shown_at: None,
+    });
+}
+
+/// Draws the queued toasts, dropping them once expired or clicked.
+pub fn show(ctx: &egui::Context) {
+    let Ok(mut toasts) = TOASTS.lock() else {
+        return;
+    };
+    let now = Instant::now();
+    toasts.retain(|toast| {
+        toast
+            .shown_at
+            .is_none_or(|shown_at| now - shown_at < toast.duration())
+    });
+    if toasts.is_empty() {
+        return;
+    }
+
+    let mut dismissed = None;
+    egui::Area::new(Id::new("toasts"))
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-10.0, -10.0))
+        .show(ctx, |ui| {
+            ui.set_max_width(320.0);
+            for (index, toast) in toasts.iter_mut().enumerate() {
+                let shown_at = *toast.shown_at.get_or_insert(now);
+                let response = Frame::popup(ui.style())
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(RichText::new(toast.icon()).color(toast.color(ui)));
+                            ui.label(&toast.text);
+                        });
+                    })
+                    .response
+                    .interact(Sense::click())
+                    .on_hover_text("Click to dismiss");
+                if response.clicked() {
+                    dismissed = Some(index);
+                }
+                ctx.request_repaint_after(toast.duration().saturating_sub(now - shown_at));
+            }
+        });
+
+    if let Some(index) = dismissed {
+        toasts.remove(index);
+    }
+}
+
+impl Toast {
+    fn duration(&self) -> Duration {
+        match self.kind {
+            ToastKind::Info | ToastKind::Success => INFO_DURATION,
+            ToastKind::Warning | ToastKind::Error => ERROR_DURATION,
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self.kind {
+            ToastKind::Info => "ℹ",
+            ToastKind::Success => "✔",
+            ToastKind::Warning => "⚠",
+            ToastKind::Error => "❌",
+        }
+    }
+
+    fn color(&self, ui: &egui::Ui) -> Color32 {
+        match self.kind {
+            ToastKind::Info => ui.visuals().text_color(),
+            ToastKind::Success => Color32::from_rgb(115, 186, 37),
+            ToastKind::Warning => ui.visuals().warn_fg_color,
+            ToastKind::Error => ui.visuals().error_fg_color,
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::mpsc::{Receiver, Sender, channel};
+#[cfg(target_os = "linux")]
+use std::time::Duration;
+use tray_icon::{
+    Icon, TrayIcon, TrayIconBuilder,
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+};
+
+const ICON_SIZE: u32 = 32;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrayAction {
+    ToggleWindow,
+    TogglePause,
+    ToggleFullscreen,
+    ToggleWidget,
+    /// Switches to the session profile with this index.
+    SwitchProfile(usize),
+    Quit,
+}
+
+impl TrayAction {
+    const ALL: [Self; 8] = [
+        Self::ToggleWindow,
+        Self::TogglePause,
+        Self::ToggleFullscreen,
+        Self::ToggleWidget,
+        Self::SwitchProfile(0),
+        Self::SwitchProfile(1),
+        Self::SwitchProfile(2),
+        Self::Quit,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::ToggleWindow => "toggle_window",
+            Self::TogglePause => "toggle_pause",
+            Self::ToggleFullscreen => "toggle_fullscreen",
+            Self::ToggleWidget => "toggle_widget",
+            Self::SwitchProfile(index) => ["profile_1", "profile_2", "profile_3"][index],
+            Self::Quit => "quit",
+        }
+    }
+
+    fn label(self, profile_names: &[String]) -> String {
+        match self {
+            Self::ToggleWindow => "Show / Hide".to_owned(),
+            Self::TogglePause => "Pause / Resume".to_owned(),
+            Self::ToggleFullscreen => "Fullscreen".to_owned(),
+            Self::ToggleWidget => "Widget mode".to_owned(),
+            Self::SwitchProfile(index) => match profile_names.get(index) {
+                Some(name) => format!("{name} profile"),
+                None => format!("Profile {}", index + 1),
+            },
+            Self::Quit => "Quit".to_owned(),
+        }
+    }
+}
+
+/// How often the GTK thread checks for renamed profiles.
+#[cfg(target_os = "linux")]
+const RENAME_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct Tray {
+    actions: Receiver<TrayAction>,
+    /// Profile names shown in the menu.
+    profile_names: Vec<String>,
+    /// New profile names for the GTK thread, which owns the menu.
+    #[cfg(target_os = "linux")]
+    renames: Sender<Vec<String>>,
+    #[cfg(not(target_os = "linux"))]
+    menu: TrayMenu,
+}
+
+impl Tray {
+    /// Builds the tray menu, relabeled as the session profiles are renamed.
+    pub fn new(ctx: &egui::Context, profile_names: Vec<String>) -> Result<Self, String> {
+        let (sender, actions) = channel();
+        forward_menu_events(ctx.clone(), sender);
+
+        // On Linux the tray is driven by GTK, which needs its own event loop
+        #[cfg(target_os = "linux")]
+        let renames = {
+            let (ready_sender, ready) = channel();
+            let (renames, renamed) = channel::<Vec<String>>();
+            let names = profile_names.clone();
+            std::thread::spawn(move || {
+                let menu = gtk::init()
+                    .map_err(|e| format!("Failed to initialize GTK: {e}"))
+                    .and_then(|()| TrayMenu::new(&names));
+                let menu = match menu {
+                    Ok(menu) => {
+                        let _ = ready_sender.send(Ok(()));
+                        menu
+                    }
+                    Err(e) => {
+                        let _ = ready_sender.send(Err(e));
+                        return;
+                    }
+                };
+                gtk::glib::timeout_add_local(RENAME_INTERVAL, move || {
+                    if let Some(names) = renamed.try_iter().last() {
+                        menu.relabel(&names);
+                    }
+                    gtk::glib::ControlFlow::Continue
+                });
+                gtk::main();
+            });
+            ready
+                .recv()
+                .map_err(|_| "The tray thread stopped".to_owned())??;
+            renames
+        };
+
+        Ok(Self {
+            actions,
+            #[cfg(target_os = "linux")]
+            renames,
+            #[cfg(not(target_os = "linux"))]
+            menu: TrayMenu::new(&profile_names)?,
+            profile_names,
+        })
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = TrayAction> + '_ {
+        self.actions.try_iter()
+    }
+
+    /// Relabels the profiles in the menu when they were renamed.
+    pub fn update_profile_names<'a>(&mut self, names: impl Iterator<Item = &'a str> + Clone) {
+        if self
+            .profile_names
+            .iter()
+            .map(String::as_str)
+            .eq(names.clone())
+        {
+            return;
+        }
+        self.profile_names = names.map(str::to_owned).collect();
+        #[cfg(target_os = "linux")]
+        let _ = self.renames.send(self.profile_names.clone());
+        #[cfg(not(target_os = "linux"))]
+        self.menu.relabel(&self.profile_names);
+    }
+}
+
+/// The tray icon with its menu, kept alive for as long as the tray is shown.
+struct TrayMenu {
+    _icon: TrayIcon,
+    profiles: Vec<MenuItem>,
+}
+
+impl TrayMenu {
+    fn new(profile_names: &[String]) -> Result<Self, String> {
+        let menu = Menu::new();
+        let mut profiles = Vec::new();
+        for action in TrayAction::ALL {
+            if matches!(action, TrayAction::SwitchProfile(0) | TrayAction::Quit) {
+                menu.append(&PredefinedMenuItem::separator())
+                    .map_err(|e| e.to_string())?;
+            }
+            let item = MenuItem::with_id(action.id(), action.label(profile_names), true, None);
+            menu.append(&item).map_err(|e| e.to_string())?;
+            if let TrayAction::SwitchProfile(_) = action {
+                profiles.push(item);
+            }
+        }
+
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip("Fractal Clock")
+            .with_icon(clock_icon()?)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            _icon: icon,
+            profiles,
+        })
+    }
+
+    fn relabel(&self, profile_names: &[String]) {
+        for (index, item) in self.profiles.iter().enumerate() {
+            item.set_text(TrayAction::SwitchProfile(index).label(profile_names));
+        }
+    }
+}
+
+fn forward_menu_events(ctx: egui::Context, sender: Sender<TrayAction>) {
+    MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+        if let Some(action) = TrayAction::ALL
+            .into_iter()
+            .find(|action| event.id == action.id())
+        {
+            let _ = sender.send(action);
+            ctx.request_repaint();
+        }
+    }));
+}
+
+fn clock_icon() -> Result<Icon, String> {
+    let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+    let rgba = (0..ICON_SIZE * ICON_SIZE)
+        .flat_map(|i| {
+            let (x, y) = ((i % ICON_SIZE) as f32, (i / ICON_SIZE) as f32);
+            let distance = (x - center).hypot(y - center);
+            let on_ring = (distance - center + 2.0).abs() < 1.5;
+            let on_hand = ((x - center).abs() < 1.5 && y <= center && distance < center - 4.0)
+                || ((y - center).abs() < 1.5 && x >= center && distance < center - 8.0);
+            if on_ring || on_hand {
+                [115, 186, 37, 255]
+            } else {
+                [0, 0, 0, 0]
+            }
+        })
+        .collect();
+
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).map_err(|e| e.to_string())
+}
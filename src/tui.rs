@@ -0,0 +1,95 @@
+use crate::{cli::Args, fractal_clock::FractalClock, storage::SettingsStore};
+use egui::{Rect, Shape, pos2};
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    style::Color,
+    symbols::Marker,
+    widgets::canvas::{Canvas, Line},
+};
+use std::time::Duration;
+
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs the clock in the terminal, with the settings from the config file.
+pub fn run(args: &Args) -> std::io::Result<()> {
+    let store = SettingsStore::open(args.config.clone());
+    let clock: FractalClock = store.get(None, "fractal_clock").unwrap_or_default();
+
+    let terminal = ratatui::try_init()?;
+    let result = run_loop(terminal, clock);
+    ratatui::restore();
+    result
+}
+
+fn run_loop(mut terminal: DefaultTerminal, mut clock: FractalClock) -> std::io::Result<()> {
+    let mut marker = Marker::Braille;
+    loop {
+        terminal.draw(|frame| {
+            let area = frame.area();
+            // Dots per cell, which are about square as cells are twice as high as wide
+            let (dots_x, dots_y) = match marker {
+                Marker::Braille => (2.0, 4.0),
+                _ => (1.0, 2.0),
+            };
+            let size = [
+                f64::from(area.width) * dots_x,
+                f64::from(area.height) * dots_y,
+            ];
+            let rect =
+                Rect::from_min_size(pos2(0.0, 0.0), egui::vec2(size[0] as f32, size[1] as f32));
+
+            let lines: Vec<_> = clock
+                .lines(rect)
+                .filter_map(|shape| match shape {
+                    Shape::LineSegment { points, stroke } => {
+                        let [r, g, b, _] = stroke.color.to_srgba_unmultiplied();
+                        // The canvas' y axis points up
+                        Some(Line {
+                            x1: f64::from(points[0].x),
+                            y1: size[1] - f64::from(points[0].y),
+                            x2: f64::from(points[1].x),
+                            y2: size[1] - f64::from(points[1].y),
+                            color: Color::Rgb(r, g, b),
+                        })
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            let canvas = Canvas::default()
+                .marker(marker)
+                .x_bounds([0.0, size[0]])
+                .y_bounds([0.0, size[1]])
+                .paint(|ctx| {
+                    for line in &lines {
+                        ctx.draw(line);
+                    }
+                });
+            frame.render_widget(canvas, area);
+        })?;
+
+        if !event::poll(FRAME_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char(' ') => clock.toggle_pause(),
+            // Switch between braille dots and the coarser but bolder block characters
+            KeyCode::Char('b') => {
+                marker = match marker {
+                    Marker::Braille => Marker::HalfBlock,
+                    _ => Marker::Braille,
+                };
+            }
+            _ => {}
+        }
+    }
+}
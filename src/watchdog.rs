@@ -0,0 +1,15 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// Runs `f`, catching a panic so the app can disable what caused it and keep running, and
+/// returning the panic message instead.
+///
+/// The state `f` mutated may be left half updated, so callers reset what the panic may have broken.
+pub fn catch<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned())
+    })
+}
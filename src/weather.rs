@@ -0,0 +1,293 @@
+use crate::{about, location::Location};
+use serde_json::Value;
+use std::{
+    sync::mpsc::{Receiver, TryRecvError, channel},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Failed requests are retried sooner than the refresh interval, but not too often.
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum WeatherProvider {
+    /// Free and without an API key.
+    OpenMeteo,
+    OpenWeatherMap,
+}
+
+impl WeatherProvider {
+    const ALL: [Self; 2] = [Self::OpenMeteo, Self::OpenWeatherMap];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::OpenMeteo => "Open-Meteo",
+            Self::OpenWeatherMap => "OpenWeatherMap",
+        }
+    }
+
+    /// URL template, where `{lat}`, `{lon}` and `{key}` are replaced.
+    fn default_url(self) -> &'static str {
+        match self {
+            Self::OpenMeteo => {
+                "https://api.open-meteo.com/v1/forecast?latitude={lat}&longitude={lon}\
+                 &current=temperature_2m,weather_code"
+            }
+            Self::OpenWeatherMap => {
+                "https://api.openweathermap.org/data/2.5/weather?lat={lat}&lon={lon}\
+                 &units=metric&appid={key}"
+            }
+        }
+    }
+
+    fn parse(self, json: &Value) -> Option<Reading> {
+        let (temperature, condition) = match self {
+            Self::OpenMeteo => {
+                let current = json.get("current")?;
+                (
+                    current.get("temperature_2m")?.as_f64()?,
+                    Condition::from_wmo(current.get("weather_code")?.as_u64()?),
+                )
+            }
+            Self::OpenWeatherMap => (
+                json.pointer("/main/temp")?.as_f64()?,
+                Condition::from_openweathermap(json.pointer("/weather/0/id")?.as_u64()?),
+            ),
+        };
+        Some(Reading {
+            celsius: temperature as f32,
+            condition,
+            fetched: Instant::now(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Condition {
+    Clear,
+    Cloudy,
+    Fog,
+    Rain,
+    Snow,
+    Thunderstorm,
+}
+
+impl Condition {
+    /// From a WMO weather interpretation code.
+    fn from_wmo(code: u64) -> Self {
+        match code {
+            0 | 1 => Self::Clear,
+            45 | 48 => Self::Fog,
+            51..=67 | 80..=82 => Self::Rain,
+            71..=77 | 85 | 86 => Self::Snow,
+            95..=99 => Self::Thunderstorm,
+            _ => Self::Cloudy,
+        }
+    }
+
+    fn from_openweathermap(id: u64) -> Self {
+        match id {
+            200..=299 => Self::Thunderstorm,
+            300..=599 => Self::Rain,
+            600..=699 => Self::Snow,
+            700..=799 => Self::Fog,
+            800 | 801 => Self::Clear,
+            _ => Self::Cloudy,
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Clear => "☀",
+            Self::Cloudy => "☁",
+            Self::Fog => "🌫",
+            Self::Rain => "🌧",
+            Self::Snow => "❄",
+            Self::Thunderstorm => "⚡",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub celsius: f32,
+    pub condition: Condition,
+    fetched: Instant,
+}
+
+// Weather provider settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct WeatherConfig {
+    pub provider: WeatherProvider,
+    /// Overrides the provider's URL template when not empty.
+    pub url: String,
+    pub api_key: String,
+    pub refresh_minutes: u32,
+    pub fahrenheit: bool,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            provider: WeatherProvider::OpenMeteo,
+            url: String::new(),
+            api_key: String::new(),
+            refresh_minutes: 30,
+            fahrenheit: false,
+        }
+    }
+}
+
+impl WeatherConfig {
+    /// Copy without the API key.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        about::redact(&mut config.api_key);
+        config
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, weather: &Weather) {
+        egui::ComboBox::from_label("Provider")
+            .selected_text(self.provider.label())
+            .show_ui(ui, |ui| {
+                for provider in WeatherProvider::ALL {
+                    ui.selectable_value(&mut self.provider, provider, provider.label());
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label("URL:");
+            ui.add(egui::TextEdit::singleline(&mut self.url).hint_text("provider default"))
+                .on_hover_text("{lat}, {lon} and {key} are replaced");
+        });
+        ui.horizontal(|ui| {
+            ui.label("API key:");
+            ui.add(egui::TextEdit::singleline(&mut self.api_key).password(true));
+        });
+        ui.add(egui::Slider::new(&mut self.refresh_minutes, 10..=180).text("refresh minutes"));
+        ui.checkbox(&mut self.fahrenheit, "Fahrenheit");
+        if let Some(error) = &weather.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.refresh_minutes.max(1)) * 60)
+    }
+
+    fn url(&self, location: Location) -> String {
+        let template = if self.url.is_empty() {
+            self.provider.default_url()
+        } else {
+            &self.url
+        };
+        template
+            .replace("{lat}", &location.latitude.to_string())
+            .replace("{lon}", &location.longitude.to_string())
+            .replace("{key}", &self.api_key)
+    }
+
+    /// Temperature with its unit.
+    pub fn format(&self, reading: &Reading) -> String {
+        if self.fahrenheit {
+            format!("{:.0}°F", reading.celsius * 9.0 / 5.0 + 32.0)
+        } else {
+            format!("{:.0}°C", reading.celsius)
+        }
+    }
+}
+
+/// Latest weather, fetched on a background thread.
+#[derive(Default)]
+pub struct Weather {
+    reading: Option<Reading>,
+    error: Option<String>,
+    pending: Option<Receiver<Result<Reading, String>>>,
+    next_fetch: Option<Instant>,
+    /// Settings of the latest request, which is repeated right away when they change.
+    requested: Option<(WeatherConfig, Location)>,
+}
+
+impl PartialEq for Weather {
+    fn eq(&self, other: &Self) -> bool {
+        self.reading == other.reading && self.error == other.error
+    }
+}
+
+impl Weather {
+    /// The latest reading, and whether it is older than two refresh intervals, e.g. when offline.
+    pub fn reading(&self, config: &WeatherConfig) -> Option<(&Reading, bool)> {
+        let reading = self.reading.as_ref()?;
+        let stale = reading.fetched.elapsed() > config.refresh_interval() * 2;
+        Some((reading, stale))
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context, config: &WeatherConfig, location: Location) {
+        if let Some(pending) = &self.pending {
+            match pending.try_recv() {
+                Ok(result) => {
+                    self.pending = None;
+                    let delay = match result {
+                        Ok(reading) => {
+                            self.reading = Some(reading);
+                            self.error = None;
+                            config.refresh_interval()
+                        }
+                        Err(error) => {
+                            tracing::warn!("Failed to fetch the weather: {error}");
+                            self.error = Some(error);
+                            RETRY_DELAY.min(config.refresh_interval())
+                        }
+                    };
+                    self.next_fetch = Some(Instant::now() + delay);
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        let changed = self.requested.as_ref() != Some(&(config.clone(), location));
+        let due = self.next_fetch.is_none_or(|next| Instant::now() >= next);
+        if changed || due {
+            self.fetch(ctx, config, location);
+        }
+    }
+
+    fn fetch(&mut self, ctx: &egui::Context, config: &WeatherConfig, location: Location) {
+        let (sender, receiver) = channel();
+        let url = config.url(location);
+        let provider = config.provider;
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(request(&url, provider));
+            ctx.request_repaint();
+        });
+        self.pending = Some(receiver);
+        self.requested = Some((config.clone(), location));
+    }
+}
+
+fn request(url: &str, provider: WeatherProvider) -> Result<Reading, String> {
+    provider
+        .parse(&download(url)?)
+        .ok_or_else(|| "Unexpected response from the weather provider".to_owned())
+}
+
+#[cfg(feature = "network")]
+fn download(url: &str) -> Result<Value, String> {
+    ureq::get(url)
+        .config()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .call()
+        .map_err(|e| e.to_string())?
+        .body_mut()
+        .read_json()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "network"))]
+fn download(_url: &str) -> Result<Value, String> {
+    Err("Built without the network feature".to_owned())
+}
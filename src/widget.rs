@@ -0,0 +1,87 @@
+use crate::fractal_clock::{FractalClock, FractalClockConfig};
+use chrono::{DateTime, Local};
+use egui::{Id, Response, Sense, Ui, Vec2};
+use std::{cell::RefCell, collections::HashMap};
+
+thread_local! {
+    /// Clocks of the embedded widgets with the pass they were last shown in. They hold the
+    /// drawing buffers reused across frames and cannot be sent to other threads, so they are kept
+    /// here instead of the egui memory.
+    static CLOCKS: RefCell<HashMap<Id, (u64, FractalClock)>> = RefCell::default();
+}
+
+/// Fractal clock embedded in another egui app, drawing `config` at the current or a given time.
+///
+/// ```no_run
+/// # fn example(ui: &mut egui::Ui, config: &mut fractal_clock::FractalClockConfig) {
+/// fractal_clock::FractalClockWidget::new(config)
+///     .desired_size(egui::vec2(200.0, 200.0))
+///     .show(ui);
+/// # }
+/// ```
+pub struct FractalClockWidget<'a> {
+    config: &'a mut FractalClockConfig,
+    time: Option<DateTime<Local>>,
+    desired_size: Option<Vec2>,
+    id_salt: Id,
+}
+
+impl<'a> FractalClockWidget<'a> {
+    pub fn new(config: &'a mut FractalClockConfig) -> Self {
+        Self {
+            config,
+            time: None,
+            desired_size: None,
+            id_salt: Id::new("fractal_clock"),
+        }
+    }
+
+    /// Shows `time` instead of the current time, which also stops the widget from repainting
+    /// every frame.
+    pub fn time(mut self, time: DateTime<Local>) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Size of the clock, defaulting to all the available space.
+    pub fn desired_size(mut self, size: Vec2) -> Self {
+        self.desired_size = Some(size);
+        self
+    }
+
+    /// Tells apart several clocks in the same `Ui`, which keep their own drawing buffers.
+    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
+        self.id_salt = Id::new(id_salt);
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) -> Response {
+        let size = self
+            .desired_size
+            .unwrap_or_else(|| ui.available_size_before_wrap());
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+        let time = self.time.unwrap_or_else(|| {
+            ui.ctx().request_repaint();
+            Local::now()
+        });
+
+        let id = ui.id().with(self.id_salt);
+        let pass = ui.ctx().cumulative_pass_nr();
+        CLOCKS.with_borrow_mut(|clocks| {
+            // Forget the clocks no longer shown
+            clocks.retain(|_, (shown, _)| *shown + 1 >= pass);
+            let (shown, clock) = clocks.entry(id).or_default();
+            *shown = pass;
+            clock.paint_with(&ui.painter_at(rect), self.config, time);
+        });
+        response
+    }
+}
+
+/// Shows a fractal clock drawing `config` at `time` in the available space of `ui`.
+pub fn show(ui: &mut Ui, config: &mut FractalClockConfig, time: DateTime<Local>) -> Response {
+    FractalClockWidget::new(config).time(time).show(ui)
+}
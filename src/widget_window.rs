@@ -0,0 +1,132 @@
+use egui::{
+    Color32, CursorIcon, Painter, Rect, ResizeDirection, Response, Sense, Shape, Stroke, Ui,
+    ViewportCommand, vec2,
+};
+
+/// Size of the resize handle in the bottom right corner.
+const RESIZE_HANDLE_SIZE: f32 = 14.0;
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum WidgetShape {
+    Rounded,
+    /// Masked to the clock's bounding circle.
+    Circle,
+}
+
+// Frameless widget window settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct WidgetWindow {
+    /// Frameless window, moved by dragging the clock.
+    pub enabled: bool,
+    pub shape: WidgetShape,
+    pub corner_radius: f32,
+}
+
+impl Default for WidgetWindow {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shape: WidgetShape::Rounded,
+            corner_radius: 16.0,
+        }
+    }
+}
+
+impl WidgetWindow {
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, "Frameless widget window");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Shape:");
+                ui.radio_value(&mut self.shape, WidgetShape::Rounded, "Rounded");
+                ui.radio_value(&mut self.shape, WidgetShape::Circle, "Circle");
+            });
+            if self.shape == WidgetShape::Rounded {
+                ui.add(
+                    egui::Slider::new(&mut self.corner_radius, 0.0..=64.0).text("corner radius"),
+                );
+            }
+        });
+    }
+
+    fn circle(&self) -> bool {
+        self.enabled && self.shape == WidgetShape::Circle
+    }
+
+    /// Fills the window, or only its shape in widget mode.
+    pub fn paint_background(&self, painter: &Painter, window: Rect, fill: Color32) {
+        if self.circle() {
+            painter.circle_filled(window.center(), window.size().min_elem() / 2.0, fill);
+        } else {
+            let radius = if self.enabled {
+                self.corner_radius
+            } else {
+                0.0
+            };
+            painter.rect_filled(window, radius, fill);
+        }
+    }
+
+    /// Drops the line segments and polygons outside of the window's circle.
+    pub fn mask(&self, window: Rect, shapes: &mut Vec<Shape>) {
+        if !self.circle() {
+            return;
+        }
+        let center = window.center();
+        let radius_sq = (window.size().min_elem() / 2.0).powi(2);
+        shapes.retain(|shape| match shape {
+            Shape::LineSegment { points, .. } => points
+                .iter()
+                .all(|point| point.distance_sq(center) <= radius_sq),
+            Shape::Path(path) => path
+                .points
+                .iter()
+                .all(|point| point.distance_sq(center) <= radius_sq),
+            _ => true,
+        });
+    }
+
+    /// Moves the window when the canvas is dragged, unless `can_move` is false as the drag pans
+    /// the view, and shows a resize handle.
+    pub fn interact(&self, ui: &Ui, canvas: &Response, can_move: bool) {
+        if !self.enabled {
+            return;
+        }
+        if can_move && canvas.drag_started_by(egui::PointerButton::Primary) {
+            ui.ctx().send_viewport_cmd(ViewportCommand::StartDrag);
+        }
+
+        let window = canvas.rect;
+        let handle = Rect::from_min_max(
+            window.max - vec2(RESIZE_HANDLE_SIZE, RESIZE_HANDLE_SIZE),
+            window.max,
+        );
+        let response = ui
+            .interact(handle, ui.id().with("resize_handle"), Sense::drag())
+            .on_hover_cursor(CursorIcon::ResizeSouthEast);
+        if response.drag_started() {
+            ui.ctx()
+                .send_viewport_cmd(ViewportCommand::BeginResize(ResizeDirection::SouthEast));
+        }
+
+        // A few diagonal lines, brighter while hovered
+        let color = if response.hovered() {
+            ui.visuals().strong_text_color()
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        let inset = if self.circle() {
+            0.0
+        } else {
+            self.corner_radius / 4.0
+        };
+        let corner = handle.max - vec2(inset, inset);
+        for offset in [4.0, 8.0, 12.0] {
+            ui.painter().line_segment(
+                [corner - vec2(offset, 0.0), corner - vec2(0.0, offset)],
+                Stroke::new(1.0, color),
+            );
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::dive::splitmix64;
+
+/// Sways per second of the trunk, with deeper and thinner branches fluttering faster.
+const SWAY_FREQUENCY: f64 = 0.4;
+const FLUTTER_PER_DEPTH: f64 = 0.08;
+/// Gusts come and go over a few seconds.
+const GUST_FREQUENCY: f64 = 0.15;
+
+// Wind settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct WindConfig {
+    pub enabled: bool,
+    /// Largest angle a branch sways by, in degrees.
+    pub strength: f32,
+    /// How much gusts vary the strength over time, from steady to stormy.
+    pub gustiness: f32,
+}
+
+impl Default for WindConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strength: 4.0,
+            gustiness: 0.5,
+        }
+    }
+}
+
+impl WindConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Wind");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.strength, 0.0..=30.0).text("wind strength °"));
+            ui.add(egui::Slider::new(&mut self.gustiness, 0.0..=1.0).text("gustiness"));
+        });
+    }
+
+    /// Angle in radians the branch with `id` at `depth` is swayed by at `time` in seconds.
+    pub fn angle(&self, id: u64, depth: usize, time: f64) -> f64 {
+        if !self.enabled {
+            return 0.0;
+        }
+        // Shared by the whole tree, so gusts move all branches together
+        let gust = 1.0 + f64::from(self.gustiness) * 2.0 * noise(time * GUST_FREQUENCY, 0);
+        let frequency = SWAY_FREQUENCY * (1.0 + FLUTTER_PER_DEPTH * depth as f64);
+        let sway = noise(time * frequency, splitmix64(id));
+        f64::from(self.strength).to_radians() * sway * gust.max(0.0)
+    }
+}
+
+/// Smooth 1D gradient noise in about -1..1, differing for every `seed`.
+fn noise(x: f64, seed: u64) -> f64 {
+    let cell = x.floor();
+    let t = x - cell;
+    let gradient = |cell: f64| {
+        let hash = splitmix64(seed ^ (cell as i64 as u64));
+        (hash >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    };
+    let (left, right) = (gradient(cell) * t, gradient(cell + 1.0) * (t - 1.0));
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    // Gradient noise peaks at half the gradient
+    2.0 * (left + (right - left) * fade)
+}
@@ -0,0 +1,80 @@
+use crate::monitors::MonitorLayout;
+use egui::{Pos2, Rect, Vec2, ViewportCommand, ViewportInfo};
+
+pub const DEFAULT_WINDOW_SIZE: Vec2 = Vec2::new(1920.0, 1080.0);
+
+/// Size, position and state of the window outside fullscreen, restored on the next launch.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+#[serde(default)]
+pub struct WindowGeometry {
+    outer_position: Option<Pos2>,
+    inner_size: Vec2,
+    maximized: bool,
+    monitor: Option<usize>,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            outer_position: None,
+            inner_size: DEFAULT_WINDOW_SIZE,
+            maximized: false,
+            monitor: None,
+        }
+    }
+}
+
+impl WindowGeometry {
+    /// Records the current window state, keeping the last normal geometry while fullscreen or maximized.
+    pub fn track(&mut self, viewport: &ViewportInfo, layout: &MonitorLayout) {
+        if viewport.fullscreen == Some(true) || viewport.minimized == Some(true) {
+            return;
+        }
+
+        self.maximized = viewport.maximized == Some(true);
+        if self.maximized {
+            return;
+        }
+
+        if let (Some(outer), Some(inner)) = (viewport.outer_rect, viewport.inner_rect) {
+            self.outer_position = Some(outer.min);
+            self.inner_size = inner.size();
+            self.monitor = layout.lock().ok().and_then(|monitors| {
+                monitors
+                    .iter()
+                    .position(|monitor| monitor.contains(outer.center()))
+            });
+        }
+    }
+
+    /// Moves the window back to the saved geometry, clamped to the monitors currently connected.
+    pub fn restore(&self, ctx: &egui::Context, layout: &MonitorLayout) {
+        let monitors = layout.lock().map(|m| m.clone()).unwrap_or_default();
+        let monitor = self
+            .monitor
+            .and_then(|index| monitors.get(index))
+            .or_else(|| monitors.first());
+
+        let mut size = self.inner_size;
+        if let Some(monitor) = monitor {
+            size = size.min(monitor.size());
+        }
+        ctx.send_viewport_cmd(ViewportCommand::InnerSize(size));
+
+        if let Some(position) = self.outer_position {
+            let window = Rect::from_min_size(position, size);
+            let visible = monitors
+                .iter()
+                .any(|monitor| monitor.intersects(window.shrink(size.min_elem() / 4.0)));
+            let position = match monitor {
+                Some(monitor) if !visible => monitor.center() - size / 2.0,
+                _ => position,
+            };
+            ctx.send_viewport_cmd(ViewportCommand::OuterPosition(position));
+        }
+
+        if self.maximized {
+            ctx.send_viewport_cmd(ViewportCommand::Maximized(true));
+        }
+    }
+}
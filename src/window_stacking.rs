@@ -0,0 +1,67 @@
+use egui::{ViewportCommand, WindowLevel};
+
+/// Where the window is stacked relative to other windows.
+///
+/// This only asks the window manager to keep the window on top or at the bottom. It is not a
+/// Wayland layer-shell surface, so there are no anchors or exclusive zones.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Default)]
+pub enum WindowStacking {
+    #[default]
+    Normal,
+    /// Above other windows, like a desktop widget.
+    #[serde(alias = "Overlay")]
+    AlwaysOnTop,
+    /// Below other windows and without decorations, like a wallpaper.
+    #[serde(alias = "Background")]
+    AlwaysOnBottom,
+}
+
+impl WindowStacking {
+    const ALL: [Self; 3] = [Self::Normal, Self::AlwaysOnTop, Self::AlwaysOnBottom];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::AlwaysOnTop => "Always on top",
+            Self::AlwaysOnBottom => "Always on bottom",
+        }
+    }
+
+    fn level(self) -> WindowLevel {
+        match self {
+            Self::Normal => WindowLevel::Normal,
+            Self::AlwaysOnTop => WindowLevel::AlwaysOnTop,
+            Self::AlwaysOnBottom => WindowLevel::AlwaysOnBottom,
+        }
+    }
+
+    /// Restacks the window if the stacking or `frameless` changed since they were last applied.
+    pub fn apply(self, ctx: &egui::Context, frameless: bool, applied: &mut Option<(Self, bool)>) {
+        if *applied == Some((self, frameless)) {
+            return;
+        }
+        ctx.send_viewport_cmd(ViewportCommand::WindowLevel(self.level()));
+        ctx.send_viewport_cmd(ViewportCommand::Decorations(
+            self != Self::AlwaysOnBottom && !frameless,
+        ));
+        *applied = Some((self, frameless));
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Stacking:");
+            for stacking in Self::ALL {
+                ui.radio_value(self, stacking, stacking.label());
+            }
+        });
+        // winit, which eframe draws with, cannot create layer-shell surfaces, and Wayland
+        // compositors do not let clients restack their own windows
+        if *self != Self::Normal && std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "On Wayland, keep the window above or below others with a window rule of \
+                 the compositor, or run the clock under XWayland (WAYLAND_DISPLAY unset).",
+            );
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use egui::{Pos2, Shape};
+use std::{
+    sync::mpsc::{Receiver, Sender, TryRecvError, channel},
+    time::{Duration, Instant},
+};
+
+/// Runs jobs on a background thread one at a time, so the UI thread only hands over the latest
+/// job and picks up results without waiting.
+pub struct Worker<J, R> {
+    jobs: Sender<J>,
+    results: Receiver<R>,
+    busy: bool,
+}
+
+impl<J: Send + 'static, R: Send + 'static> Worker<J, R> {
+    /// Starts the thread, which creates its job handler with `make` and stops once the worker is
+    /// dropped. The handler may hold state that cannot be sent between threads.
+    pub fn spawn<F>(ctx: &egui::Context, make: impl FnOnce() -> F + Send + 'static) -> Self
+    where
+        F: FnMut(J) -> R,
+    {
+        let (jobs, job_receiver) = channel::<J>();
+        let (result_sender, results) = channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let mut handle = make();
+            for job in job_receiver {
+                if result_sender.send(handle(job)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+        Self {
+            jobs,
+            results,
+            busy: false,
+        }
+    }
+
+    /// Hands `job` to the thread unless it is still busy with the previous one, returning
+    /// `false` if the thread stopped, e.g. after a panic.
+    pub fn offer(&mut self, job: impl FnOnce() -> J) -> bool {
+        if self.busy {
+            return true;
+        }
+        self.busy = self.jobs.send(job()).is_ok();
+        self.busy
+    }
+
+    pub fn try_recv(&mut self) -> Option<R> {
+        match self.results.try_recv() {
+            Ok(result) => {
+                self.busy = false;
+                Some(result)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.busy = false;
+                None
+            }
+        }
+    }
+}
+
+/// The two latest results of a worker, for easing from one to the next while the following one
+/// is being generated.
+pub struct Interpolation<R> {
+    previous: Option<R>,
+    latest: Option<R>,
+    arrived: Instant,
+    /// Time between the two latest results, which the easing takes.
+    interval: Duration,
+}
+
+impl<R> Default for Interpolation<R> {
+    fn default() -> Self {
+        Self {
+            previous: None,
+            latest: None,
+            arrived: Instant::now(),
+            interval: Duration::ZERO,
+        }
+    }
+}
+
+impl<R> Interpolation<R> {
+    /// Keeps `result` as the latest one, returning the previous one it replaces.
+    pub fn push(&mut self, result: R) -> Option<R> {
+        let now = Instant::now();
+        self.interval = now - self.arrived;
+        self.arrived = now;
+        std::mem::replace(&mut self.previous, self.latest.replace(result))
+    }
+
+    pub fn latest(&self) -> Option<&R> {
+        self.latest.as_ref()
+    }
+
+    /// The previous result with the fraction of the way to the latest one, or `None` once the
+    /// latest result is reached.
+    pub fn progress(&self) -> Option<(&R, f32)> {
+        let previous = self.previous.as_ref()?;
+        let t = self.arrived.elapsed().as_secs_f32() / self.interval.as_secs_f32().max(1e-3);
+        (t < 1.0).then_some((previous, t))
+    }
+}
+
+/// Eases every line of `from` towards the line at the same index of `to` by `t`, which only
+/// matches up when both frames have the same lines, e.g. when none entered or left the window.
+/// Returns `false` without touching `out` otherwise.
+pub fn interpolate_shapes(from: &[Shape], to: &[Shape], t: f32, out: &mut Vec<Shape>) -> bool {
+    if from.len() != to.len() {
+        return false;
+    }
+    let lerp = |a: &[Pos2; 2], b: &[Pos2; 2]| [0, 1].map(|i| a[i].lerp(b[i], t));
+    let start = out.len();
+    for (from, to) in from.iter().zip(to) {
+        match (from, to) {
+            (Shape::LineSegment { points: a, .. }, Shape::LineSegment { points: b, stroke }) => {
+                out.push(Shape::line_segment(lerp(a, b), *stroke))
+            }
+            (Shape::Path(a), Shape::Path(b)) if a.points.len() == b.points.len() => {
+                let mut path = b.clone();
+                for (point, from) in path.points.iter_mut().zip(&a.points) {
+                    *point = from.lerp(*point, t);
+                }
+                out.push(Shape::Path(path));
+            }
+            _ => {
+                out.truncate(start);
+                return false;
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,429 @@
+use chrono::{DateTime, Local, Offset as _, TimeDelta, TimeZone as _};
+use chrono_tz::Tz;
+use egui::{Color32, Painter, Pos2, Rect, Shape, Stroke, Vec2, pos2};
+
+/// Spacing of the parallels and meridians in degrees.
+const GRATICULE_STEP: i32 = 30;
+const LABEL_SIZE: f32 = 12.0;
+
+/// Cities offered in the settings, with their time zone, latitude and longitude.
+const CITIES: [(&str, Tz, f64, f64); 24] = [
+    ("Anchorage", Tz::America__Anchorage, 61.2, -149.9),
+    ("Auckland", Tz::Pacific__Auckland, -36.8, 174.8),
+    ("Bangkok", Tz::Asia__Bangkok, 13.8, 100.5),
+    ("Berlin", Tz::Europe__Berlin, 52.5, 13.4),
+    (
+        "Buenos Aires",
+        Tz::America__Argentina__Buenos_Aires,
+        -34.6,
+        -58.4,
+    ),
+    ("Cairo", Tz::Africa__Cairo, 30.0, 31.2),
+    ("Chicago", Tz::America__Chicago, 41.9, -87.6),
+    ("Delhi", Tz::Asia__Kolkata, 28.6, 77.2),
+    ("Dubai", Tz::Asia__Dubai, 25.3, 55.3),
+    ("Honolulu", Tz::Pacific__Honolulu, 21.3, -157.9),
+    ("Istanbul", Tz::Europe__Istanbul, 41.0, 29.0),
+    ("Johannesburg", Tz::Africa__Johannesburg, -26.2, 28.0),
+    ("Lagos", Tz::Africa__Lagos, 6.5, 3.4),
+    ("London", Tz::Europe__London, 51.5, -0.1),
+    ("Los Angeles", Tz::America__Los_Angeles, 34.1, -118.2),
+    ("Mexico City", Tz::America__Mexico_City, 19.4, -99.1),
+    ("Moscow", Tz::Europe__Moscow, 55.8, 37.6),
+    ("New York", Tz::America__New_York, 40.7, -74.0),
+    ("Paris", Tz::Europe__Paris, 48.9, 2.4),
+    ("São Paulo", Tz::America__Sao_Paulo, -23.6, -46.6),
+    ("Shanghai", Tz::Asia__Shanghai, 31.2, 121.5),
+    ("Singapore", Tz::Asia__Singapore, 1.3, 103.8),
+    ("Sydney", Tz::Australia__Sydney, -33.9, 151.2),
+    ("Tokyo", Tz::Asia__Tokyo, 35.7, 139.7),
+];
+
+/// Coastlines of the continents and largest islands as longitude and latitude, simplified to a
+/// few points each for the stylized map.
+const COASTLINES: [&[(f32, f32)]; 10] = [
+    // North America
+    &[
+        (-168.0, 66.0),
+        (-162.0, 70.0),
+        (-140.0, 70.0),
+        (-125.0, 70.0),
+        (-95.0, 72.0),
+        (-80.0, 73.0),
+        (-62.0, 66.0),
+        (-55.0, 52.0),
+        (-66.0, 44.0),
+        (-70.0, 42.0),
+        (-76.0, 35.0),
+        (-81.0, 31.0),
+        (-80.0, 25.0),
+        (-82.0, 28.0),
+        (-90.0, 30.0),
+        (-97.0, 27.0),
+        (-97.0, 21.0),
+        (-92.0, 18.0),
+        (-87.0, 21.0),
+        (-88.0, 15.0),
+        (-83.0, 10.0),
+        (-78.0, 8.0),
+        (-80.0, 7.0),
+        (-86.0, 12.0),
+        (-92.0, 14.0),
+        (-105.0, 20.0),
+        (-110.0, 24.0),
+        (-117.0, 32.0),
+        (-124.0, 40.0),
+        (-124.0, 48.0),
+        (-133.0, 56.0),
+        (-140.0, 60.0),
+        (-152.0, 60.0),
+        (-165.0, 54.0),
+        (-158.0, 58.0),
+        (-166.0, 62.0),
+    ],
+    // South America
+    &[
+        (-77.0, 8.0),
+        (-72.0, 12.0),
+        (-62.0, 10.0),
+        (-52.0, 5.0),
+        (-35.0, -5.0),
+        (-39.0, -15.0),
+        (-48.0, -26.0),
+        (-58.0, -35.0),
+        (-65.0, -41.0),
+        (-68.0, -55.0),
+        (-73.0, -50.0),
+        (-73.0, -40.0),
+        (-71.0, -30.0),
+        (-70.0, -18.0),
+        (-76.0, -14.0),
+        (-81.0, -6.0),
+        (-80.0, 0.0),
+        (-77.0, 4.0),
+    ],
+    // Africa
+    &[
+        (-17.0, 21.0),
+        (-10.0, 30.0),
+        (-6.0, 36.0),
+        (10.0, 37.0),
+        (20.0, 32.0),
+        (32.0, 31.0),
+        (43.0, 12.0),
+        (51.0, 11.0),
+        (40.0, -5.0),
+        (40.0, -15.0),
+        (35.0, -25.0),
+        (20.0, -35.0),
+        (15.0, -28.0),
+        (12.0, -15.0),
+        (9.0, -1.0),
+        (9.0, 4.0),
+        (-8.0, 4.0),
+        (-17.0, 14.0),
+    ],
+    // Eurasia
+    &[
+        (-10.0, 36.0),
+        (-9.0, 43.0),
+        (-2.0, 44.0),
+        (-5.0, 48.0),
+        (2.0, 51.0),
+        (8.0, 54.0),
+        (10.0, 58.0),
+        (5.0, 62.0),
+        (15.0, 69.0),
+        (28.0, 71.0),
+        (40.0, 67.0),
+        (60.0, 69.0),
+        (70.0, 73.0),
+        (100.0, 77.0),
+        (130.0, 71.0),
+        (160.0, 70.0),
+        (180.0, 67.0),
+        (170.0, 60.0),
+        (160.0, 58.0),
+        (157.0, 51.0),
+        (142.0, 54.0),
+        (140.0, 48.0),
+        (130.0, 43.0),
+        (129.0, 35.0),
+        (126.0, 35.0),
+        (121.0, 40.0),
+        (118.0, 38.0),
+        (122.0, 30.0),
+        (120.0, 23.0),
+        (110.0, 20.0),
+        (106.0, 10.0),
+        (100.0, 14.0),
+        (100.0, 3.0),
+        (104.0, 1.0),
+        (98.0, 8.0),
+        (94.0, 16.0),
+        (90.0, 22.0),
+        (80.0, 15.0),
+        (77.0, 8.0),
+        (73.0, 20.0),
+        (67.0, 25.0),
+        (57.0, 25.0),
+        (48.0, 30.0),
+        (56.0, 26.0),
+        (59.0, 22.0),
+        (52.0, 17.0),
+        (43.0, 13.0),
+        (39.0, 21.0),
+        (35.0, 28.0),
+        (34.0, 32.0),
+        (36.0, 36.0),
+        (27.0, 37.0),
+        (26.0, 41.0),
+        (23.0, 37.0),
+        (20.0, 40.0),
+        (16.0, 38.0),
+        (12.0, 44.0),
+        (9.0, 44.0),
+        (3.0, 43.0),
+        (-1.0, 37.0),
+        (-6.0, 36.0),
+    ],
+    // Australia
+    &[
+        (114.0, -22.0),
+        (114.0, -34.0),
+        (118.0, -35.0),
+        (124.0, -33.0),
+        (132.0, -32.0),
+        (138.0, -35.0),
+        (146.0, -39.0),
+        (150.0, -37.0),
+        (153.0, -28.0),
+        (146.0, -19.0),
+        (142.0, -11.0),
+        (136.0, -12.0),
+        (130.0, -12.0),
+        (122.0, -17.0),
+    ],
+    // Greenland
+    &[
+        (-73.0, 78.0),
+        (-60.0, 82.0),
+        (-30.0, 83.0),
+        (-20.0, 75.0),
+        (-22.0, 70.0),
+        (-43.0, 60.0),
+        (-50.0, 64.0),
+        (-55.0, 70.0),
+        (-68.0, 76.0),
+    ],
+    // Great Britain
+    &[
+        (-5.0, 50.0),
+        (1.0, 51.0),
+        (2.0, 53.0),
+        (-2.0, 56.0),
+        (-3.0, 59.0),
+        (-6.0, 57.0),
+        (-5.0, 54.0),
+        (-3.0, 53.0),
+        (-5.0, 52.0),
+    ],
+    // Japan
+    &[
+        (130.0, 31.0),
+        (135.0, 34.0),
+        (140.0, 35.0),
+        (142.0, 39.0),
+        (141.0, 43.0),
+        (145.0, 44.0),
+        (141.0, 45.0),
+        (139.0, 40.0),
+        (136.0, 36.0),
+        (131.0, 34.0),
+    ],
+    // Madagascar
+    &[
+        (44.0, -25.0),
+        (47.0, -25.0),
+        (50.0, -15.0),
+        (49.0, -12.0),
+        (44.0, -16.0),
+    ],
+    // New Zealand
+    &[
+        (172.0, -34.0),
+        (178.0, -38.0),
+        (174.0, -41.0),
+        (168.0, -46.0),
+        (172.0, -43.0),
+    ],
+];
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+pub struct City {
+    pub name: String,
+    pub timezone: Tz,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl City {
+    /// The time of day in the city at `now`, on the local clock the hands are computed from.
+    pub fn time(&self, now: &DateTime<Local>) -> DateTime<Local> {
+        let offset = self.timezone.offset_from_utc_datetime(&now.naive_utc());
+        let shift = offset.fix().local_minus_utc() - now.offset().local_minus_utc();
+        *now + TimeDelta::seconds(shift.into())
+    }
+}
+
+// World map settings
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct WorldMapConfig {
+    /// Shows small clocks pinned at the cities instead of the clock.
+    pub enabled: bool,
+    pub cities: Vec<City>,
+    /// Levels of branches drawn on every small clock.
+    pub depth: usize,
+    /// Width of the small clocks as a fraction of the map's height.
+    pub clock_size: f32,
+    pub map_color: Color32,
+    /// Names and times under the small clocks.
+    pub labels: bool,
+}
+
+impl Default for WorldMapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cities: ["London", "New York", "Tokyo", "Sydney"]
+                .into_iter()
+                .filter_map(preset)
+                .collect(),
+            depth: 5,
+            clock_size: 0.12,
+            map_color: Color32::from_gray(90),
+            labels: true,
+        }
+    }
+}
+
+fn preset(name: &str) -> Option<City> {
+    CITIES.iter().find(|(preset, ..)| *preset == name).map(
+        |&(name, timezone, latitude, longitude)| City {
+            name: name.to_owned(),
+            timezone,
+            latitude,
+            longitude,
+        },
+    )
+}
+
+impl WorldMapConfig {
+    pub fn ui(&mut self, ui: &mut egui::Ui, now: &DateTime<Local>) {
+        ui.checkbox(&mut self.enabled, "World map")
+            .on_hover_text("Small clocks in the time zones of the cities");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut self.depth, 1..=10).text("depth"));
+            ui.add(egui::Slider::new(&mut self.clock_size, 0.05..=0.4).text("clock size"));
+            ui.horizontal(|ui| {
+                ui.label("Map color:");
+                ui.color_edit_button_srgba(&mut self.map_color);
+                ui.checkbox(&mut self.labels, "Labels");
+            });
+            let mut removed = None;
+            for (index, city) in self.cities.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut city.name);
+                    ui.weak(format!(
+                        "{} {}",
+                        city.timezone.name(),
+                        city.time(now).format("%H:%M")
+                    ));
+                    if ui.small_button("✖").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                self.cities.remove(index);
+            }
+            egui::ComboBox::from_id_salt("world_map_add_city")
+                .selected_text("＋ Add city")
+                .show_ui(ui, |ui| {
+                    for (name, ..) in CITIES {
+                        if ui.selectable_label(false, name).clicked()
+                            && let Some(city) = preset(name)
+                        {
+                            self.cities.push(city);
+                        }
+                    }
+                });
+        });
+    }
+
+    /// Part of `window` the map fills, keeping the 2:1 aspect of the projection.
+    pub fn map_rect(window: Rect) -> Rect {
+        let size = window.size();
+        let size = if size.x > size.y * 2.0 {
+            Vec2::new(size.y * 2.0, size.y)
+        } else {
+            Vec2::new(size.x, size.x / 2.0)
+        };
+        Rect::from_center_size(window.center(), size)
+    }
+
+    /// Where the longitude and latitude lie on the equirectangular `map`.
+    pub fn project(map: Rect, longitude: f32, latitude: f32) -> Pos2 {
+        pos2(
+            map.left() + (longitude + 180.0) / 360.0 * map.width(),
+            map.top() + (90.0 - latitude) / 180.0 * map.height(),
+        )
+    }
+
+    /// Paints the graticule and coastlines on `map`.
+    pub fn paint_map(&self, painter: &Painter, map: Rect) {
+        let faint = Stroke::new(1.0, self.map_color.gamma_multiply(0.4));
+        for longitude in (-180..=180).step_by(GRATICULE_STEP as usize) {
+            let longitude = longitude as f32;
+            painter.line_segment(
+                [
+                    Self::project(map, longitude, 90.0),
+                    Self::project(map, longitude, -90.0),
+                ],
+                faint,
+            );
+        }
+        for latitude in (-90..=90).step_by(GRATICULE_STEP as usize) {
+            let latitude = latitude as f32;
+            painter.line_segment(
+                [
+                    Self::project(map, -180.0, latitude),
+                    Self::project(map, 180.0, latitude),
+                ],
+                faint,
+            );
+        }
+        let stroke = Stroke::new(1.5, self.map_color);
+        for coastline in COASTLINES {
+            let points = coastline
+                .iter()
+                .map(|&(longitude, latitude)| Self::project(map, longitude, latitude))
+                .collect();
+            painter.add(Shape::closed_line(points, stroke));
+        }
+    }
+
+    /// Paints the name and time of `city` under its clock at `rect`.
+    pub fn paint_label(&self, painter: &Painter, rect: Rect, city: &City, time: &DateTime<Local>) {
+        if self.labels {
+            painter.text(
+                rect.center_bottom(),
+                egui::Align2::CENTER_TOP,
+                format!("{} {}", city.name, time.format("%H:%M")),
+                egui::FontId::proportional(LABEL_SIZE),
+                self.map_color.lerp_to_gamma(Color32::WHITE, 0.5),
+            );
+        }
+    }
+}
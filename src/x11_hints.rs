@@ -0,0 +1,151 @@
+/// Window type the window manager is told the clock is, which decides how it is stacked,
+/// decorated and focused.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum X11WindowType {
+    Normal,
+    /// Panel-like window kept above others, e.g. when always on top.
+    Dock,
+    /// Drawn as the desktop background, e.g. when always on bottom.
+    Desktop,
+    /// Small tool window.
+    Utility,
+}
+
+impl X11WindowType {
+    const ALL: [Self; 4] = [Self::Normal, Self::Dock, Self::Desktop, Self::Utility];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Dock => "Dock",
+            Self::Desktop => "Desktop",
+            Self::Utility => "Utility",
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn atom_name(self) -> &'static str {
+        match self {
+            Self::Normal => "_NET_WM_WINDOW_TYPE_NORMAL",
+            Self::Dock => "_NET_WM_WINDOW_TYPE_DOCK",
+            Self::Desktop => "_NET_WM_WINDOW_TYPE_DESKTOP",
+            Self::Utility => "_NET_WM_WINDOW_TYPE_UTILITY",
+        }
+    }
+}
+
+// X11 window manager hints
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct X11Hints {
+    pub window_type: X11WindowType,
+    pub skip_taskbar: bool,
+    pub skip_pager: bool,
+}
+
+impl Default for X11Hints {
+    fn default() -> Self {
+        Self {
+            window_type: X11WindowType::Normal,
+            skip_taskbar: false,
+            skip_pager: false,
+        }
+    }
+}
+
+impl X11Hints {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_label("Window type")
+            .selected_text(self.window_type.label())
+            .show_ui(ui, |ui| {
+                for window_type in X11WindowType::ALL {
+                    ui.selectable_value(&mut self.window_type, window_type, window_type.label());
+                }
+            });
+        ui.checkbox(&mut self.skip_taskbar, "Hide from the taskbar");
+        ui.checkbox(&mut self.skip_pager, "Hide from the pager");
+    }
+
+    /// Sets the hints on the X11 window of `frame` if they changed since they were last applied.
+    /// Does nothing on other platforms and on Wayland.
+    pub fn apply(&self, frame: &eframe::Frame, applied: &mut Option<Self>) {
+        if applied.as_ref() == Some(self) {
+            return;
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(window) = x11_window(frame) {
+            let first = applied.is_none();
+            if let Err(e) = set_hints(window, self, first) {
+                tracing::warn!("Failed to set X11 window hints: {e}");
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = frame;
+        *applied = Some(self.clone());
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn x11_window(frame: &eframe::Frame) -> Option<u32> {
+    use winit::raw_window_handle::{HasWindowHandle as _, RawWindowHandle};
+
+    match frame.window_handle().ok()?.as_raw() {
+        RawWindowHandle::Xlib(handle) => u32::try_from(handle.window).ok(),
+        RawWindowHandle::Xcb(handle) => Some(handle.window.get()),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_hints(window: u32, hints: &X11Hints, first: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use x11rb::{
+        connection::Connection as _,
+        protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, PropMode},
+        wrapper::ConnectionExt as _,
+    };
+
+    let (connection, screen) = x11rb::connect(None)?;
+    let root = connection.setup().roots[screen].root;
+    let atom = |name: &str| -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(connection
+            .intern_atom(false, name.as_bytes())?
+            .reply()?
+            .atom)
+    };
+
+    connection.change_property32(
+        PropMode::REPLACE,
+        window,
+        atom("_NET_WM_WINDOW_TYPE")?,
+        AtomEnum::ATOM,
+        &[atom(hints.window_type.atom_name())?],
+    )?;
+    // Window managers read the type when a window is mapped, so it is mapped again
+    if !first {
+        connection.unmap_window(window)?;
+        connection.map_window(window)?;
+    }
+
+    // The state of a mapped window is changed by asking the window manager through the root window
+    let net_wm_state = atom("_NET_WM_STATE")?;
+    for (name, enabled) in [
+        ("_NET_WM_STATE_SKIP_TASKBAR", hints.skip_taskbar),
+        ("_NET_WM_STATE_SKIP_PAGER", hints.skip_pager),
+    ] {
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            net_wm_state,
+            // Remove is 0, add is 1, and 1 as the source means a normal application
+            [u32::from(enabled), atom(name)?, 0, 1, 0],
+        );
+        connection.send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+    }
+    connection.flush()?;
+    Ok(())
+}